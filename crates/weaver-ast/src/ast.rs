@@ -7,21 +7,29 @@ use std::str::FromStr;
 use derive_more::{Display, From as FromDerive};
 use serde::{Deserialize, Serialize};
 
+pub use alter::*;
 pub use create::*;
 pub use data_type::*;
+pub use delete::*;
+pub use drop::*;
 pub use expr::*;
 pub use from::*;
 pub use identifier::{Identifier, ResolvedColumnRef, UnresolvedColumnRef};
+pub use insert::*;
 
-pub use literal::Literal;
+pub use literal::{DecimalLiteral, Literal};
 pub use load::*;
 pub use select::*;
+pub use update::*;
 
 use crate::error::ParseQueryError;
 use crate::QueryParser;
 
+mod alter;
 mod create;
 mod data_type;
+mod delete;
+mod drop;
 mod expr;
 mod from;
 mod identifier;
@@ -29,6 +37,7 @@ mod insert;
 mod literal;
 mod load;
 mod select;
+mod update;
 pub mod visitor;
 
 /// The query type
@@ -39,6 +48,14 @@ pub enum Query {
     Select(Select),
     Create(Create),
     LoadData(LoadData),
+    Insert(Insert),
+    Update(Update),
+    Delete(Delete),
+    DropTable(DropTable),
+    DropSchema(DropSchema),
+    AlterTable(AlterTable),
+    /// Sets the default schema of the current connection
+    Use(Identifier),
     KillProcess(i64),
     #[serde(untagged)]
     QueryList(Vec<Query>),
@@ -85,6 +102,27 @@ impl Display for Query {
             Query::LoadData(load) => {
                 write!(f, "{load}")
             }
+            Query::Insert(insert) => {
+                write!(f, "{insert}")
+            }
+            Query::Update(update) => {
+                write!(f, "{update}")
+            }
+            Query::Delete(delete) => {
+                write!(f, "{delete}")
+            }
+            Query::DropTable(drop_table) => {
+                write!(f, "{drop_table}")
+            }
+            Query::DropSchema(drop_schema) => {
+                write!(f, "{drop_schema}")
+            }
+            Query::AlterTable(alter_table) => {
+                write!(f, "{alter_table}")
+            }
+            Query::Use(schema) => {
+                write!(f, "use {schema}")
+            }
             Query::KillProcess(pid) => {
                 write!(f, "kill {pid}")
             }
@@ -92,7 +130,7 @@ impl Display for Query {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum ResultColumn {
     #[serde(rename = "*")]
@@ -0,0 +1,63 @@
+//! The ALTER statements
+
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ast::{ColumnDefinition, Identifier};
+
+/// Alters the definition of an existing table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlterTable {
+    pub schema: Option<Identifier>,
+    pub name: Identifier,
+    /// The alterations, applied in order
+    pub actions: Vec<AlterTableAction>,
+}
+
+impl Display for AlterTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "alter table {schema}{name} {actions}",
+            schema = self
+                .schema
+                .as_ref()
+                .map(|i| format!("{}.", i))
+                .unwrap_or_default(),
+            name = self.name,
+            actions = self
+                .actions
+                .iter()
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+/// A single alteration of a table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AlterTableAction {
+    /// Adds a column after all existing columns
+    AddColumn(ColumnDefinition),
+    /// Drops a column, removing it from any keys that use it
+    DropColumn(Identifier),
+    /// Renames a column
+    RenameColumn { from: Identifier, to: Identifier },
+    /// Replaces the definition of a column with the same name
+    ModifyColumn(ColumnDefinition),
+}
+
+impl Display for AlterTableAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlterTableAction::AddColumn(column) => write!(f, "add column {column}"),
+            AlterTableAction::DropColumn(column) => write!(f, "drop column {column}"),
+            AlterTableAction::RenameColumn { from, to } => {
+                write!(f, "rename column {from} to {to}")
+            }
+            AlterTableAction::ModifyColumn(column) => write!(f, "modify column {column}"),
+        }
+    }
+}
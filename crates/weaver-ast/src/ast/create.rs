@@ -3,11 +3,67 @@ use std::fmt::{Display, Formatter};
 use derive_more::{Display as DisplayCustom, From};
 use serde::{Deserialize, Serialize};
 
-use crate::ast::{DataType, Identifier};
+use crate::ast::{DataType, Expr, Identifier};
 
 #[derive(Debug, Clone, Serialize, Deserialize, From, DisplayCustom)]
 pub enum Create {
     Table(CreateTable),
+    Schema(CreateSchema),
+    Index(CreateIndex),
+}
+
+/// Creates a new, empty schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSchema {
+    pub name: Identifier,
+}
+
+impl Display for CreateSchema {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "create schema `{}`", self.name)
+    }
+}
+
+/// Creates a secondary index on an existing table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateIndex {
+    pub name: Identifier,
+    pub unique: bool,
+    pub schema: Option<Identifier>,
+    pub table: Identifier,
+    pub columns: Vec<IndexColumn>,
+}
+
+/// A part of an index, either a column or an expression computed from the columns of a row
+#[derive(Debug, Clone, Serialize, Deserialize, DisplayCustom)]
+pub enum IndexColumn {
+    #[display("`{0}`", _0)]
+    Column(Identifier),
+    #[display("({0})", _0)]
+    Expr(Expr),
+}
+
+impl Display for CreateIndex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "create {unique}index `{name}` on {schema}`{table}` ({columns})",
+            unique = if self.unique { "unique " } else { "" },
+            name = self.name,
+            schema = self
+                .schema
+                .as_ref()
+                .map(|i| format!("`{}`.", i))
+                .unwrap_or_default(),
+            table = self.table,
+            columns = self
+                .columns
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -61,7 +117,11 @@ pub enum CreateDefinition {
 pub struct ColumnDefinition {
     pub id: Identifier,
     pub data_type: DataType,
+    /// The collation strings in the column are compared in
+    pub collation: Option<Identifier>,
     pub non_null: bool,
+    /// The value used when none is given on insert, evaluated for every inserted row
+    pub default: Option<Expr>,
     pub auto_increment: bool,
     pub unique: bool,
     pub key: bool,
@@ -72,10 +132,20 @@ impl Display for ColumnDefinition {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{id} {data_type}{non_null}{auto_increment}{unique}{primary}{key}",
+            "{id} {data_type}{collation}{non_null}{default}{auto_increment}{unique}{primary}{key}",
             id = self.id,
             data_type = self.data_type,
+            collation = self
+                .collation
+                .as_ref()
+                .map(|collation| format!(" collate {collation}"))
+                .unwrap_or_default(),
             non_null = if self.non_null { " non null" } else { "" },
+            default = self
+                .default
+                .as_ref()
+                .map(|default| format!(" default {default}"))
+                .unwrap_or_default(),
             auto_increment = if self.auto_increment {
                 " auto_increment"
             } else {
@@ -92,13 +162,149 @@ impl Display for ColumnDefinition {
     }
 }
 
+/// A table level constraint
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConstraintDefinition {
-    symbol: Option<Identifier>,
+    /// The name given by `constraint <symbol>`
+    pub symbol: Option<Identifier>,
+    pub constraint: Constraint,
+}
+
+impl ConstraintDefinition {
+    /// Gets the name of the key, foreign key or check created by this constraint, if one was given
+    pub fn key_name(&self) -> Option<&Identifier> {
+        match &self.constraint {
+            Constraint::PrimaryKey { .. } => None,
+            Constraint::Unique { name, .. }
+            | Constraint::Key { name, .. }
+            | Constraint::ForeignKey { name, .. } => name.as_ref().or(self.symbol.as_ref()),
+            Constraint::Check { .. } => self.symbol.as_ref(),
+        }
+    }
 }
 
 impl Display for ConstraintDefinition {
-    fn fmt(&self, _f: &mut Formatter<'_>) -> std::fmt::Result {
-        todo!()
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(symbol) = &self.symbol {
+            write!(f, "constraint {symbol} ")?;
+        }
+        write!(f, "{}", self.constraint)
     }
 }
+
+/// The kinds of table level constraints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Constraint {
+    PrimaryKey {
+        columns: Vec<Identifier>,
+    },
+    Unique {
+        name: Option<Identifier>,
+        columns: Vec<Identifier>,
+    },
+    /// A non-unique key
+    Key {
+        name: Option<Identifier>,
+        columns: Vec<Identifier>,
+    },
+    /// Requires the columns to match a row of another table
+    ForeignKey {
+        name: Option<Identifier>,
+        columns: Vec<Identifier>,
+        references: ForeignKeyReference,
+    },
+    /// Requires an expression to not be false for any row
+    Check {
+        expr: Expr,
+    },
+}
+
+impl Constraint {
+    /// Gets the columns this constraint applies to
+    pub fn columns(&self) -> &[Identifier] {
+        match self {
+            Constraint::PrimaryKey { columns }
+            | Constraint::Unique { columns, .. }
+            | Constraint::Key { columns, .. }
+            | Constraint::ForeignKey { columns, .. } => columns,
+            Constraint::Check { .. } => &[],
+        }
+    }
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (kind, name) = match self {
+            Constraint::PrimaryKey { .. } => ("primary key", None),
+            Constraint::Unique { name, .. } => ("unique key", name.as_ref()),
+            Constraint::Key { name, .. } => ("key", name.as_ref()),
+            Constraint::ForeignKey { name, .. } => ("foreign key", name.as_ref()),
+            Constraint::Check { expr } => return write!(f, "check ({expr})"),
+        };
+        write!(f, "{kind} ")?;
+        if let Some(name) = name {
+            write!(f, "{name} ")?;
+        }
+        write!(
+            f,
+            "({})",
+            self.columns()
+                .iter()
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        if let Constraint::ForeignKey { references, .. } = self {
+            write!(f, " {references}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The parent of a foreign key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForeignKeyReference {
+    pub schema: Option<Identifier>,
+    pub table: Identifier,
+    pub columns: Vec<Identifier>,
+    pub on_delete: ReferentialAction,
+    pub on_update: ReferentialAction,
+}
+
+impl Display for ForeignKeyReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "references {schema}{table} ({columns}) on delete {on_delete} on update {on_update}",
+            schema = self
+                .schema
+                .as_ref()
+                .map(|i| format!("{}.", i))
+                .unwrap_or_default(),
+            table = self.table,
+            columns = self
+                .columns
+                .iter()
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            on_delete = self.on_delete,
+            on_update = self.on_update,
+        )
+    }
+}
+
+/// What happens to the rows referencing a parent row when it is deleted or its key is updated
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, DisplayCustom)]
+pub enum ReferentialAction {
+    /// The parent row can not be changed while referenced
+    #[default]
+    #[display("restrict")]
+    Restrict,
+    /// Referencing rows are deleted or updated along with the parent
+    #[display("cascade")]
+    Cascade,
+    /// The referencing columns are set to null
+    #[display("set null")]
+    SetNull,
+}
@@ -2,31 +2,102 @@ use derive_more::{Display, From};
 use serde::{Deserialize, Serialize};
 
 /// Data type enum.
-#[derive(Copy, Debug, Clone, Eq, PartialEq, Serialize, Deserialize, From, Display)]
+#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, From, Display)]
 pub enum DataType {
     Int(IntType),
     Float(FloatType),
+    DecimalType(DecimalType),
     VarCharType(VarCharType),
     VarBinaryType(VarBinaryType),
     BooleanType(BooleanType),
+    DateType(DateType),
+    TimeType(TimeType),
+    TimestampType(TimestampType),
+    IntervalType(IntervalType),
+    JsonType(JsonType),
+    UuidType(UuidType),
 }
 
-#[derive(Copy, Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Display)]
+#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Display)]
 #[display("int")]
 pub struct IntType(pub u8);
 
-#[derive(Copy, Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Display)]
+#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Display)]
 #[display("float")]
 pub struct FloatType(pub u8);
 
-#[derive(Copy, Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Display)]
+/// A fixed point number with a precision (the total number of digits) and a scale (the number of
+/// digits after the decimal point)
+#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Display)]
+#[display("decimal({0}, {1})", _0, _1)]
+pub struct DecimalType(pub u8, pub u8);
+
+impl DecimalType {
+    /// The most digits a decimal can have
+    pub const MAX_PRECISION: u8 = 38;
+    /// The precision of a decimal declared without one
+    pub const DEFAULT_PRECISION: u8 = 10;
+
+    /// Creates a decimal type, checking that the precision is at most [`Self::MAX_PRECISION`] and
+    /// the scale is at most the precision
+    pub fn new(precision: i64, scale: i64) -> Option<Self> {
+        if !(1..=Self::MAX_PRECISION as i64).contains(&precision)
+            || !(0..=precision).contains(&scale)
+        {
+            return None;
+        }
+        Some(Self(precision as u8, scale as u8))
+    }
+}
+
+/// A string of at most the given number of bytes. `TEXT` is a varchar of the maximum width.
+#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Display)]
 #[display("varchar({0})", _0)]
-pub struct VarCharType(pub u8);
+pub struct VarCharType(pub u16);
 
-#[derive(Copy, Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Display)]
+impl VarCharType {
+    /// The widest a varchar can be
+    pub const MAX_WIDTH: u16 = u16::MAX;
+}
+
+/// A byte string of at most the given number of bytes. `BLOB` is a varbinary of the maximum width.
+#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Display)]
 #[display("varbinary({0})", _0)]
-pub struct VarBinaryType(pub u8);
+pub struct VarBinaryType(pub u16);
+
+impl VarBinaryType {
+    /// The widest a varbinary can be
+    pub const MAX_WIDTH: u16 = u16::MAX;
+}
 
-#[derive(Copy, Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Display)]
+#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Display)]
 #[display("boolean")]
 pub struct BooleanType;
+
+#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Display)]
+#[display("date")]
+pub struct DateType;
+
+#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Display)]
+#[display("time")]
+pub struct TimeType;
+
+#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Display)]
+#[display("timestamp{}", if *with_time_zone { " with time zone" } else { "" })]
+pub struct TimestampType {
+    pub with_time_zone: bool,
+}
+
+#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Display)]
+#[display("interval")]
+pub struct IntervalType;
+
+/// A json document
+#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Display)]
+#[display("json")]
+pub struct JsonType;
+
+/// A 128-bit universally unique identifier
+#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Display)]
+#[display("uuid")]
+pub struct UuidType;
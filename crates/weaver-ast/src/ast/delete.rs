@@ -0,0 +1,35 @@
+//! The DELETE statement
+
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ast::{Expr, Identifier};
+
+/// Deletes rows from a table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delete {
+    pub schema: Option<Identifier>,
+    pub table: Identifier,
+    /// Only rows matching this condition are deleted. If not present, all rows are deleted.
+    pub condition: Option<Expr>,
+}
+
+impl Display for Delete {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "delete from {schema}{table}",
+            schema = self
+                .schema
+                .as_ref()
+                .map(|i| format!("{}.", i))
+                .unwrap_or_default(),
+            table = self.table,
+        )?;
+        if let Some(condition) = &self.condition {
+            write!(f, " where {condition}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+//! The DROP statements
+
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ast::Identifier;
+
+/// Drops a table, deleting all of its data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DropTable {
+    pub schema: Option<Identifier>,
+    pub name: Identifier,
+    /// If set, dropping a table that does not exist is not an error
+    pub if_exists: bool,
+}
+
+impl Display for DropTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "drop table {if_exists}{schema}{name}",
+            if_exists = if self.if_exists { "if exists " } else { "" },
+            schema = self
+                .schema
+                .as_ref()
+                .map(|i| format!("{}.", i))
+                .unwrap_or_default(),
+            name = self.name,
+        )
+    }
+}
+
+/// Drops a schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DropSchema {
+    pub name: Identifier,
+    /// If set, all tables within the schema are dropped too. Otherwise only empty schemas can be
+    /// dropped.
+    pub cascade: bool,
+}
+
+impl Display for DropSchema {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "drop schema {}", self.name)?;
+        if self.cascade {
+            write!(f, " cascade")?;
+        }
+        Ok(())
+    }
+}
@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::ast::identifier::{ResolvedColumnRef, UnresolvedColumnRef};
 use crate::ast::literal::Binary;
-use crate::ast::{Identifier, Literal, ReferencesCols};
+use crate::ast::{DataType, Identifier, Literal, OrderBy, ReferencesCols, Select};
 
 /// A reference to a column, can either be in a resolved or unresolved state.
 #[derive(Debug, Eq, PartialEq, Hash, Clone, Serialize, Deserialize, Display, From)]
@@ -61,6 +61,52 @@ pub enum Expr {
         function: Identifier,
         args: FunctionArgs,
     },
+    /// Explicitly converts the value of an expression to another type
+    Cast {
+        expr: Box<Expr>,
+        data_type: DataType,
+    },
+    /// A function evaluated over the rows of a window around each row
+    WindowFunction {
+        function: Identifier,
+        args: FunctionArgs,
+        window: Box<Window>,
+    },
+    /// A `case` expression. Without an operand, the first branch whose condition is true is used,
+    /// otherwise the first branch whose condition equals the operand is used.
+    Case {
+        operand: Option<Box<Expr>>,
+        branches: Vec<(Expr, Expr)>,
+        otherwise: Option<Box<Expr>>,
+    },
+    /// Checks if an expression equals any expression in a list
+    InList {
+        expr: Box<Expr>,
+        list: Vec<Expr>,
+        negated: bool,
+    },
+    /// Checks if an expression is within an inclusive range
+    Between {
+        expr: Box<Expr>,
+        low: Box<Expr>,
+        high: Box<Expr>,
+        negated: bool,
+    },
+    /// Checks if an expression equals any value returned by a single column subquery
+    InSubQuery {
+        expr: Box<Expr>,
+        query: Box<Select>,
+        negated: bool,
+    },
+    /// Checks if a subquery returns any rows
+    Exists {
+        query: Box<Select>,
+        negated: bool,
+    },
+    /// The single value returned by a subquery, which is null if it returns no rows
+    SubQuery {
+        query: Box<Select>,
+    },
 }
 
 impl Display for Expr {
@@ -88,6 +134,72 @@ impl Display for Expr {
             Expr::FunctionCall { function, args } => {
                 write!(f, "{}({})", function, args)
             }
+            Expr::Cast { expr, data_type } => {
+                write!(f, "cast({expr} as {data_type})")
+            }
+            Expr::WindowFunction {
+                function,
+                args,
+                window,
+            } => {
+                write!(f, "{function}({args}) over ({window})")
+            }
+            Expr::Case {
+                operand,
+                branches,
+                otherwise,
+            } => {
+                write!(f, "case")?;
+                if let Some(operand) = operand {
+                    write!(f, " {operand}")?;
+                }
+                for (condition, result) in branches {
+                    write!(f, " when {condition} then {result}")?;
+                }
+                if let Some(otherwise) = otherwise {
+                    write!(f, " else {otherwise}")?;
+                }
+                write!(f, " end")
+            }
+            Expr::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                write!(
+                    f,
+                    "{expr} {}in ({})",
+                    if *negated { "not " } else { "" },
+                    list.iter()
+                        .map(|i| i.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }
+            Expr::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => {
+                let not = if *negated { "not " } else { "" };
+                write!(f, "{expr} {not}between {low} and {high}")
+            }
+            Expr::InSubQuery {
+                expr,
+                query,
+                negated,
+            } => {
+                let not = if *negated { "not " } else { "" };
+                write!(f, "{expr} {not}in ({query})")
+            }
+            Expr::Exists { query, negated } => {
+                let not = if *negated { "not " } else { "" };
+                write!(f, "{not}exists ({query})")
+            }
+            Expr::SubQuery { query } => {
+                write!(f, "({query})")
+            }
         }
     }
 }
@@ -116,45 +228,36 @@ impl Expr {
         }
     }
 
-    /// Reduces this expression, does nothing if not constant
+    /// Reduces this expression, does nothing if not constant. Operands of different types are
+    /// [promoted](Literal::promote) to a common type first. Expressions that can not be reduced,
+    /// such as those with incompatible operands or dividing by zero, are kept as is so that their
+    /// errors are reported when evaluated.
     pub fn reduce(&mut self) {
         if !self.is_const() {
             return;
         }
 
-        match self {
+        let reduced = match self {
             Expr::Unary { op, expr } => {
                 expr.reduce();
-                let expr = expr.literal().expect("is literal");
-                match op {
-                    UnaryOp::Not => match expr {
-                        Literal::Binary(binary) => {
-                            *self = Expr::Literal {
-                                literal: Literal::from(Binary::from(
-                                    binary.as_ref().iter().map(|b| !*b).collect::<Vec<_>>(),
-                                )),
-                            }
-                        }
-                        Literal::Integer(i) => {
-                            *self = Expr::Literal {
-                                literal: Literal::from(!*i),
-                            }
-                        }
-                        _other => panic!("can not bitwise negate {_other}"),
-                    },
-                    UnaryOp::Negate => match expr {
-                        Literal::Integer(i) => {
-                            *self = Expr::Literal {
-                                literal: Literal::from(-*i),
-                            }
-                        }
-                        Literal::Float(f) => {
-                            *self = Expr::Literal {
-                                literal: Literal::from(-*f),
-                            }
-                        }
-                        _other => panic!("can not negate {_other}"),
-                    },
+                match (op, expr.literal()) {
+                    (UnaryOp::Not, Some(Literal::Binary(binary))) => Some(Literal::from(
+                        Binary::from(binary.as_ref().iter().map(|b| !*b).collect::<Vec<_>>()),
+                    )),
+                    (UnaryOp::Not, Some(Literal::Integer(i))) => Some(Literal::from(!*i)),
+                    (UnaryOp::Negate, Some(Literal::Integer(i))) => {
+                        i.checked_neg().map(Literal::from)
+                    }
+                    (UnaryOp::Negate, Some(Literal::Float(f))) => Some(Literal::from(-*f)),
+                    (UnaryOp::Negate, Some(Literal::Decimal(decimal))) => {
+                        let negated = decimal.negated();
+                        // i64::MIN is only an integer once it's negated
+                        Some(match negated.as_str().parse::<i64>() {
+                            Ok(i) => Literal::from(i),
+                            Err(_) => Literal::from(negated),
+                        })
+                    }
+                    _ => None,
                 }
             }
             Expr::Binary {
@@ -164,78 +267,93 @@ impl Expr {
             } => {
                 l.reduce();
                 r.reduce();
-                let l = l.literal().expect("is literal");
-                let r = r.literal().expect("is literal");
-                let lit: Literal = match op {
-                    BinaryOp::Eq => (l == r).into(),
-                    BinaryOp::Neq => (l != r).into(),
-                    BinaryOp::Greater => (l > r).into(),
-                    BinaryOp::Less => (l < r).into(),
-                    BinaryOp::GreaterEq => (l >= r).into(),
-                    BinaryOp::LessEq => (l <= r).into(),
-                    BinaryOp::Plus => match (l, r) {
-                        (Literal::Integer(l), Literal::Integer(r)) => (l + r).into(),
-                        (Literal::Float(l), Literal::Float(r)) => (l + r).into(),
-                        (Literal::String(l), Literal::String(r)) => format!("{l}{r}").into(),
-                        (Literal::Binary(l), Literal::Binary(r)) => {
-                            Binary::from(l.iter().chain(r.iter()).copied().collect::<Vec<u8>>())
-                                .into()
-                        }
-                        _ => panic!("can not apply `+` to {l} and {r}"),
-                    },
-                    BinaryOp::Minus => match (l, r) {
-                        (Literal::Integer(l), Literal::Integer(r)) => (l - r).into(),
-                        (Literal::Float(l), Literal::Float(r)) => (l - r).into(),
-                        _ => panic!("can not apply `-` to {l} and {r}"),
-                    },
-                    BinaryOp::Multiply => match (l, r) {
-                        (Literal::Integer(l), Literal::Integer(r)) => (l * r).into(),
-                        (Literal::Float(l), Literal::Float(r)) => (l + r).into(),
-                        _ => panic!("can not apply `*` to {l} and {r}"),
-                    },
-                    BinaryOp::Divide => match (l, r) {
-                        (Literal::Integer(l), Literal::Integer(r)) => (l / r).into(),
-                        (Literal::Float(l), Literal::Float(r)) => (l / r).into(),
-                        _ => panic!("can not apply `/` to {l} and {r}"),
-                    },
-                    BinaryOp::And => {
-                        if let (Literal::Boolean(left), Literal::Boolean(right)) = (l, r) {
-                            (*left && *right).into()
-                        } else {
-                            panic!("can not apply `and` to {l} and {r}");
-                        }
-                    }
-                    BinaryOp::Or => {
-                        if let (Literal::Boolean(left), Literal::Boolean(right)) = (l, r) {
-                            (*left || *right).into()
-                        } else {
-                            panic!("can not apply `or` to {l} and {r}");
-                        }
-                    }
-                };
-                *self = Expr::Literal { literal: lit };
+                match (l.literal(), r.literal()) {
+                    (Some(l), Some(r)) => reduce_binary(op, l, r),
+                    _ => None,
+                }
             }
-            _ => {}
+            _ => None,
+        };
+        if let Some(literal) = reduced {
+            *self = Expr::Literal { literal };
         }
     }
 
-    /// Get this expression this in a series of post fix expressions
+    /// Gets the subqueries used within this expression, not including any subqueries nested within
+    /// them
+    pub fn sub_queries(&self) -> Vec<&Select> {
+        match self {
+            Expr::InSubQuery { expr, query, .. } => {
+                let mut ret = expr.sub_queries();
+                ret.push(query);
+                ret
+            }
+            Expr::Exists { query, .. } | Expr::SubQuery { query } => vec![query],
+            Expr::Unary { expr, .. } | Expr::Cast { expr, .. } => expr.sub_queries(),
+            Expr::Binary { left, right, .. } => {
+                let mut ret = left.sub_queries();
+                ret.extend(right.sub_queries());
+                ret
+            }
+            Expr::FunctionCall {
+                args: FunctionArgs::Params { exprs, .. },
+                ..
+            } => exprs.iter().flat_map(|expr| expr.sub_queries()).collect(),
+            Expr::Case {
+                operand,
+                branches,
+                otherwise,
+            } => operand
+                .iter()
+                .chain(otherwise)
+                .map(|expr| expr.as_ref())
+                .chain(
+                    branches
+                        .iter()
+                        .flat_map(|(condition, result)| [condition, result]),
+                )
+                .flat_map(|expr| expr.sub_queries())
+                .collect(),
+            Expr::InList { expr, list, .. } => {
+                let mut ret = expr.sub_queries();
+                ret.extend(list.iter().flat_map(|expr| expr.sub_queries()));
+                ret
+            }
+            Expr::Between {
+                expr, low, high, ..
+            } => [expr, low, high]
+                .into_iter()
+                .flat_map(|expr| expr.sub_queries())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Get this expression this in a series of post fix expressions. The operands of `case`, `in`
+    /// and `between` expressions are not included, as they are evaluated by the expression itself
     pub fn postfix(&self) -> Vec<&Expr> {
+        self.postfix_until(&|_| false)
+    }
+
+    /// Get this expression in a series of post fix expressions like [`Expr::postfix`], without the
+    /// operands of the expressions `evaluates_operands` is true for
+    pub fn postfix_until<F: Fn(&Expr) -> bool>(&self, evaluates_operands: &F) -> Vec<&Expr> {
         let mut ret = vec![];
         match self {
-            Expr::Unary { expr, .. } => {
-                ret.extend(expr.postfix());
+            _ if evaluates_operands(self) => {}
+            Expr::Unary { expr, .. } | Expr::Cast { expr, .. } => {
+                ret.extend(expr.postfix_until(evaluates_operands));
             }
             Expr::Binary { left, right, .. } => {
-                ret.extend(left.postfix());
-                ret.extend(right.postfix());
+                ret.extend(left.postfix_until(evaluates_operands));
+                ret.extend(right.postfix_until(evaluates_operands));
             }
             Expr::FunctionCall {
                 args: FunctionArgs::Params { exprs, .. },
                 ..
             } => {
                 for arg in exprs.iter().rev() {
-                    ret.extend(arg.postfix());
+                    ret.extend(arg.postfix_until(evaluates_operands));
                 }
             }
             _ => {}
@@ -245,11 +363,80 @@ impl Expr {
     }
 }
 
+/// Reduces a binary operation of two literals, if possible
+fn reduce_binary(op: &BinaryOp, l: &Literal, r: &Literal) -> Option<Literal> {
+    match op {
+        // patterns are only matched by the evaluator
+        BinaryOp::Like { .. } | BinaryOp::NotLike { .. } | BinaryOp::Regexp => None,
+        // json is only parsed by the evaluator
+        BinaryOp::JsonGet | BinaryOp::JsonGetText => None,
+        BinaryOp::And | BinaryOp::Or => match (l, r) {
+            (Literal::Boolean(l), Literal::Boolean(r)) => {
+                Some(Literal::from(if *op == BinaryOp::And {
+                    *l && *r
+                } else {
+                    *l || *r
+                }))
+            }
+            _ => None,
+        },
+        BinaryOp::Plus | BinaryOp::Minus | BinaryOp::Multiply | BinaryOp::Divide
+            if matches!((l, r), (Literal::Null, _) | (_, Literal::Null)) =>
+        {
+            Some(Literal::Null)
+        }
+        _ => {
+            let (l, r) = l.promote(r)?;
+            match op {
+                BinaryOp::Eq => Some((l == r).into()),
+                BinaryOp::Neq => Some((l != r).into()),
+                BinaryOp::Greater => Some((l > r).into()),
+                BinaryOp::Less => Some((l < r).into()),
+                BinaryOp::GreaterEq => Some((l >= r).into()),
+                BinaryOp::LessEq => Some((l <= r).into()),
+                BinaryOp::Plus => match (l, r) {
+                    (Literal::Integer(l), Literal::Integer(r)) => {
+                        l.checked_add(r).map(Literal::from)
+                    }
+                    (Literal::Float(l), Literal::Float(r)) => Some((l + r).into()),
+                    (Literal::String(l), Literal::String(r)) => Some(format!("{l}{r}").into()),
+                    (Literal::Binary(l), Literal::Binary(r)) => Some(
+                        Binary::from(l.iter().chain(r.iter()).copied().collect::<Vec<u8>>()).into(),
+                    ),
+                    _ => None,
+                },
+                BinaryOp::Minus => match (l, r) {
+                    (Literal::Integer(l), Literal::Integer(r)) => {
+                        l.checked_sub(r).map(Literal::from)
+                    }
+                    (Literal::Float(l), Literal::Float(r)) => Some((l - r).into()),
+                    _ => None,
+                },
+                BinaryOp::Multiply => match (l, r) {
+                    (Literal::Integer(l), Literal::Integer(r)) => {
+                        l.checked_mul(r).map(Literal::from)
+                    }
+                    (Literal::Float(l), Literal::Float(r)) => Some((l * r).into()),
+                    _ => None,
+                },
+                BinaryOp::Divide => match (l, r) {
+                    (Literal::Integer(l), Literal::Integer(r)) => {
+                        l.checked_div(r).map(Literal::from)
+                    }
+                    (Literal::Float(l), Literal::Float(r)) => Some((l / r).into()),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+    }
+}
+
 impl ReferencesCols for Expr {
     fn columns(&self) -> HashSet<ColumnRef> {
         match self {
             Expr::Column { column } => HashSet::from([column.clone()]),
-            Expr::Unary { op: _, expr } => expr.columns(),
+            Expr::Unary { op: _, expr } | Expr::Cast { expr, .. } => expr.columns(),
             Expr::Binary {
                 left: l,
                 op: _,
@@ -269,6 +456,45 @@ impl ReferencesCols for Expr {
                     .chain(ordered_by.iter().flatten())
                     .flat_map(|expr| expr.columns()),
             ),
+            Expr::WindowFunction { args, window, .. } => {
+                let args = match args {
+                    FunctionArgs::Params { exprs, .. } => exprs.as_slice(),
+                    FunctionArgs::Wildcard { .. } => &[],
+                };
+                args.iter()
+                    .chain(&window.partition_by)
+                    .chain(window.order_by.iter().map(|OrderBy(expr, _)| expr))
+                    .flat_map(|expr| expr.columns())
+                    .collect()
+            }
+            Expr::Case {
+                operand,
+                branches,
+                otherwise,
+            } => operand
+                .iter()
+                .chain(otherwise)
+                .map(|expr| expr.as_ref())
+                .chain(
+                    branches
+                        .iter()
+                        .flat_map(|(condition, result)| [condition, result]),
+                )
+                .flat_map(|expr| expr.columns())
+                .collect(),
+            Expr::InList { expr, list, .. } => expr
+                .columns()
+                .into_iter()
+                .chain(list.iter().flat_map(|expr| expr.columns()))
+                .collect(),
+            Expr::Between {
+                expr, low, high, ..
+            } => [expr, low, high]
+                .into_iter()
+                .flat_map(|expr| expr.columns())
+                .collect(),
+            // columns within a subquery are only in its own scope
+            Expr::InSubQuery { expr, .. } => expr.columns(),
             _ => HashSet::new(),
         }
     }
@@ -331,6 +557,86 @@ impl Display for FunctionArgs {
     }
 }
 
+/// The rows a window function is evaluated over. Rows are split into partitions, which are each
+/// ordered. The frame is the rows of a partition around the current row that are used.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Hash, Default)]
+pub struct Window {
+    pub partition_by: Vec<Expr>,
+    pub order_by: Vec<OrderBy>,
+    pub frame: Option<WindowFrame>,
+}
+
+impl Display for Window {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut clauses = vec![];
+        if !self.partition_by.is_empty() {
+            clauses.push(format!(
+                "partition by {}",
+                self.partition_by
+                    .iter()
+                    .map(|i| i.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !self.order_by.is_empty() {
+            clauses.push(format!(
+                "order by {}",
+                self.order_by
+                    .iter()
+                    .map(|i| i.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if let Some(frame) = &self.frame {
+            clauses.push(frame.to_string());
+        }
+        write!(f, "{}", clauses.join(" "))
+    }
+}
+
+/// The rows of a partition used by a window function, relative to the current row
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Hash)]
+pub struct WindowFrame {
+    pub units: FrameUnits,
+    pub start: FrameBound,
+    pub end: FrameBound,
+}
+
+impl Display for WindowFrame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} between {} and {}", self.units, self.start, self.end)
+    }
+}
+
+/// How the bounds of a frame are measured
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Display, Hash)]
+pub enum FrameUnits {
+    /// Bounds are a number of rows away from the current row
+    #[display("rows")]
+    Rows,
+    /// Bounds are a distance away from the value ordering the current row, and rows ordered by
+    /// the same value are always in the same frame
+    #[display("range")]
+    Range,
+}
+
+/// A bound of a window frame
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Display, Hash)]
+pub enum FrameBound {
+    #[display("unbounded preceding")]
+    UnboundedPreceding,
+    #[display("{_0} preceding")]
+    Preceding(u64),
+    #[display("current row")]
+    CurrentRow,
+    #[display("{_0} following")]
+    Following(u64),
+    #[display("unbounded following")]
+    UnboundedFollowing,
+}
+
 /// Operator for where clauses
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Display, Hash)]
 #[serde(rename_all = "camelCase")]
@@ -359,6 +665,20 @@ pub enum BinaryOp {
     And,
     #[display("or")]
     Or,
+    /// Matches a `like` pattern, where `escape` defaults to a backslash
+    #[display("like")]
+    Like { escape: Option<char> },
+    #[display("not like")]
+    NotLike { escape: Option<char> },
+    /// Matches a regular expression
+    #[display("regexp")]
+    Regexp,
+    /// Gets a member of a json object by key or an element of a json array by index
+    #[display("->")]
+    JsonGet,
+    /// Like [`JsonGet`](Self::JsonGet), but gives the member as text
+    #[display("->>")]
+    JsonGetText,
 }
 
 /// Operator for where clauses
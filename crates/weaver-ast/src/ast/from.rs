@@ -7,12 +7,12 @@ use crate::ast::select::Select;
 use crate::ast::{Expr, Identifier};
 
 /// The from clause
-#[derive(Debug, Clone, Serialize, Deserialize, Deref, AsRef, Display)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Hash, Deref, AsRef, Display)]
 #[display("from {_0}")]
 pub struct FromClause(pub TableOrSubQuery);
 
 /// A table or a subquery
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum TableOrSubQuery {
     #[serde(rename_all = "camelCase")]
@@ -71,7 +71,7 @@ impl Display for TableOrSubQuery {
 }
 
 /// The join clause is all joins
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Hash)]
 pub struct JoinClause {
     pub left: Box<TableOrSubQuery>,
     pub op: JoinOperator,
@@ -90,7 +90,7 @@ impl Display for JoinClause {
 }
 
 /// Join Constraint
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Hash)]
 pub struct JoinConstraint {
     pub on: Expr,
 }
@@ -102,7 +102,7 @@ impl Display for JoinConstraint {
 }
 
 /// The join operator
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum JoinOperator {
     Left,
@@ -111,6 +111,14 @@ pub enum JoinOperator {
     Inner,
     Cross,
     Outer,
+    /// Keeps the left rows with a match on the right side. Only created by the planner.
+    Semi,
+    /// Keeps the left rows without a match on the right side. Only created by the planner.
+    Anti,
+    /// Keeps the left rows without a match on the right side like `not in`, so left rows with a
+    /// null key are dropped and no rows are kept if the right side has a null key, unless the right
+    /// side is empty. Only created by the planner.
+    NullAwareAnti,
 }
 
 impl Display for JoinOperator {
@@ -122,6 +130,9 @@ impl Display for JoinOperator {
             JoinOperator::Inner => "inner join",
             JoinOperator::Cross => "cross join",
             JoinOperator::Outer => "outer join",
+            JoinOperator::Semi => "semi join",
+            JoinOperator::Anti => "anti join",
+            JoinOperator::NullAwareAnti => "null-aware anti join",
         };
         write!(f, "{s}")
     }
@@ -1 +1,80 @@
+//! The INSERT statement
 
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ast::{Expr, Identifier, Select};
+
+/// Inserts new rows into an existing table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Insert {
+    pub schema: Option<Identifier>,
+    pub table: Identifier,
+    /// The columns being inserted into. If empty, all columns are used in order.
+    pub columns: Vec<Identifier>,
+    pub source: InsertSource,
+}
+
+impl Display for Insert {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "insert into {schema}{table}",
+            schema = self
+                .schema
+                .as_ref()
+                .map(|i| format!("{}.", i))
+                .unwrap_or_default(),
+            table = self.table
+        )?;
+        if !self.columns.is_empty() {
+            write!(
+                f,
+                " ({})",
+                self.columns
+                    .iter()
+                    .map(|i| i.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+        write!(f, " {}", self.source)
+    }
+}
+
+/// Where the inserted rows come from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InsertSource {
+    /// Rows of expressions
+    Values(Vec<Vec<Expr>>),
+    /// The result of a select query
+    Select(Box<Select>),
+}
+
+impl Display for InsertSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InsertSource::Values(rows) => {
+                write!(
+                    f,
+                    "values {}",
+                    rows.iter()
+                        .map(|row| format!(
+                            "({})",
+                            row.iter()
+                                .map(|e| e.to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }
+            InsertSource::Select(select) => {
+                write!(f, "{select}")
+            }
+        }
+    }
+}
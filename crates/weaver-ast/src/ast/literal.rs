@@ -16,6 +16,7 @@ pub enum Literal {
     String(String),
     Integer(i64),
     Float(f64),
+    Decimal(DecimalLiteral),
     Boolean(bool),
     Null,
 }
@@ -28,6 +29,7 @@ impl PartialEq for Literal {
             (String(l), String(r)) => l == r,
             (Integer(l), Integer(r)) => l == r,
             (Float(l), Float(r)) => l == r,
+            (Decimal(l), Decimal(r)) => l == r,
             (Boolean(l), Boolean(r)) => l == r,
             (Null, Null) => false, // null is explicitly never equal
             _ => false,
@@ -37,6 +39,45 @@ impl PartialEq for Literal {
 
 impl Eq for Literal {}
 
+impl Literal {
+    /// Converts two literals to their narrowest common type. Booleans are promoted to integers,
+    /// integers to floats, and strings to binaries. Returns `None` if the literals have no common
+    /// type, or if either is null or a decimal, as decimals are only exact once read by the
+    /// evaluator.
+    pub fn promote(&self, other: &Literal) -> Option<(Literal, Literal)> {
+        use Literal::*;
+        // the position of a numeric type within the promotions
+        let rank = |literal: &Literal| match literal {
+            Boolean(_) => Some(0),
+            Integer(_) => Some(1),
+            Float(_) => Some(2),
+            _ => None,
+        };
+        let to_rank = |literal: &Literal, rank: u8| match (literal, rank) {
+            (Boolean(b), 1) => Integer(*b as i64),
+            (Boolean(b), 2) => Float(*b as i64 as f64),
+            (Integer(i), 2) => Float(*i as f64),
+            (literal, _) => literal.clone(),
+        };
+        let to_binary = |literal: &Literal| match literal {
+            String(s) => Binary(self::Binary::from(s.as_bytes().to_vec())),
+            literal => literal.clone(),
+        };
+
+        match (self, other) {
+            (Null, _) | (_, Null) | (Decimal(_), _) | (_, Decimal(_)) => None,
+            (l, r) if discriminant(l) == discriminant(r) => Some((l.clone(), r.clone())),
+            (String(_) | Binary(_), String(_) | Binary(_)) => {
+                Some((to_binary(self), to_binary(other)))
+            }
+            (l, r) => {
+                let rank = rank(l)?.max(rank(r)?);
+                Some((to_rank(l, rank), to_rank(r, rank)))
+            }
+        }
+    }
+}
+
 impl Hash for Literal {
     fn hash<H: Hasher>(&self, state: &mut H) {
         let disc = discriminant(self);
@@ -48,6 +89,7 @@ impl Hash for Literal {
             Literal::Float(_f) => {
                 unimplemented!("float hashing?")
             }
+            Literal::Decimal(decimal) => decimal.hash(state),
             Literal::Boolean(bool) => bool.hash(state),
             Null => {}
         }
@@ -69,6 +111,36 @@ impl PartialOrd for Literal {
     }
 }
 
+/// The digits of a fractional or large numeric literal as they were written, so that they can be
+/// read exactly. They are only rounded to a float when compared with or stored as one.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize, Display)]
+#[display("{decimal}")]
+pub struct DecimalLiteral {
+    decimal: String,
+}
+
+impl DecimalLiteral {
+    /// Creates a decimal literal from its digits, like `-12.50`
+    pub fn new(decimal: impl Into<String>) -> Self {
+        Self {
+            decimal: decimal.into(),
+        }
+    }
+
+    /// Gets the digits of this literal
+    pub fn as_str(&self) -> &str {
+        &self.decimal
+    }
+
+    /// Gets this literal with its sign flipped
+    pub fn negated(&self) -> Self {
+        match self.decimal.strip_prefix('-') {
+            Some(positive) => Self::new(positive),
+            None => Self::new(format!("-{}", self.decimal)),
+        }
+    }
+}
+
 #[derive(
     Debug,
     PartialOrd,
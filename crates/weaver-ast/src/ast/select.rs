@@ -2,14 +2,23 @@ use std::fmt::{Display, Formatter};
 
 use serde::{Deserialize, Serialize};
 
-use crate::ast::{Expr, FromClause, ResultColumn};
+use crate::ast::{Expr, FromClause, Identifier, ResultColumn};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Hash)]
 pub struct Select {
+    /// Common table expressions that this select can use like tables
+    pub with: Option<With>,
+    /// Whether duplicate rows are removed
+    pub distinct: bool,
     pub columns: Vec<ResultColumn>,
     pub from: Option<FromClause>,
     pub condition: Option<Expr>,
     pub group_by: Option<Vec<Expr>>,
+    /// Filters groups, so it may use aggregates
+    pub having: Option<Expr>,
+    /// Selects combined with this one, from left to right. The order and page of this select
+    /// apply to the whole compound select.
+    pub compound: Vec<(CompoundOperator, Select)>,
     pub order_by: Option<Vec<OrderBy>>,
     pub limit: Option<u64>,
     pub offset: Option<u64>,
@@ -17,9 +26,13 @@ pub struct Select {
 
 impl Display for Select {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(with) = &self.with {
+            write!(f, "{with} ")?;
+        }
         write!(
             f,
-            "select {}",
+            "select {}{}",
+            if self.distinct { "distinct " } else { "" },
             self.columns
                 .iter()
                 .map(|s| s.to_string())
@@ -30,7 +43,7 @@ impl Display for Select {
             write!(f, " {from}")?;
         }
         if let Some(condition) = &self.condition {
-            write!(f, " {condition}")?;
+            write!(f, " where {condition}")?;
         }
         if let Some(group_by) = &self.group_by {
             write!(
@@ -43,6 +56,12 @@ impl Display for Select {
                     .join(", ")
             )?;
         }
+        if let Some(having) = &self.having {
+            write!(f, " having {having}")?;
+        }
+        for (op, select) in &self.compound {
+            write!(f, " {op} {select}")?;
+        }
         if let Some(order_by) = &self.order_by {
             write!(
                 f,
@@ -64,7 +83,81 @@ impl Display for Select {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The common table expressions of a select
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Hash)]
+pub struct With {
+    /// Whether the tables can refer to themselves
+    pub recursive: bool,
+    pub tables: Vec<CommonTableExpr>,
+}
+
+impl Display for With {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "with {}{}",
+            if self.recursive { "recursive " } else { "" },
+            self.tables
+                .iter()
+                .map(|table| table.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+/// A named select that can be used like a table
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Hash)]
+pub struct CommonTableExpr {
+    pub name: Identifier,
+    /// Names the columns of the table instead of the columns of the select
+    pub columns: Option<Vec<Identifier>>,
+    pub select: Select,
+}
+
+impl Display for CommonTableExpr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(columns) = &self.columns {
+            write!(
+                f,
+                "({})",
+                columns
+                    .iter()
+                    .map(|column| column.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+        write!(f, " as ({})", self.select)
+    }
+}
+
+/// Combines the rows of two selects
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Hash)]
+pub enum CompoundOperator {
+    /// The distinct rows of both selects
+    Union,
+    /// Every row of both selects
+    UnionAll,
+    /// The distinct rows of the left select that are also in the right select
+    Intersect,
+    /// The distinct rows of the left select that are not in the right select
+    Except,
+}
+
+impl Display for CompoundOperator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompoundOperator::Union => write!(f, "union"),
+            CompoundOperator::UnionAll => write!(f, "union all"),
+            CompoundOperator::Intersect => write!(f, "intersect"),
+            CompoundOperator::Except => write!(f, "except"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Hash)]
 pub struct OrderBy(pub Expr, pub Option<OrderDirection>);
 
 impl Display for OrderBy {
@@ -73,7 +166,7 @@ impl Display for OrderBy {
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize, Hash)]
 pub enum OrderDirection {
     #[default]
     Asc,
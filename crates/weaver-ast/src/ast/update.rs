@@ -0,0 +1,43 @@
+//! The UPDATE statement
+
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ast::{Expr, Identifier};
+
+/// Updates existing rows within a table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Update {
+    pub schema: Option<Identifier>,
+    pub table: Identifier,
+    /// The columns being set, and the expressions used to compute their new values
+    pub set: Vec<(Identifier, Expr)>,
+    /// Only rows matching this condition are updated. If not present, all rows are updated.
+    pub condition: Option<Expr>,
+}
+
+impl Display for Update {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "update {schema}{table} set {set}",
+            schema = self
+                .schema
+                .as_ref()
+                .map(|i| format!("{}.", i))
+                .unwrap_or_default(),
+            table = self.table,
+            set = self
+                .set
+                .iter()
+                .map(|(column, expr)| format!("{column} = {expr}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        if let Some(condition) = &self.condition {
+            write!(f, " where {condition}")?;
+        }
+        Ok(())
+    }
+}
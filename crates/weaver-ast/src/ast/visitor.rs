@@ -2,9 +2,11 @@
 
 use crate::ast::select::Select;
 use crate::ast::{
-    ColumnDefinition, ColumnRef, Create, CreateDefinition, CreateTable, DataType, Expr, FromClause,
-    FunctionArgs, Identifier, JoinClause, JoinConstraint, Literal, LoadData, OrderBy, Query,
-    ResolvedColumnRef, ResultColumn, TableOrSubQuery, UnresolvedColumnRef,
+    AlterTable, AlterTableAction, ColumnDefinition, ColumnRef, Constraint, ConstraintDefinition,
+    Create, CreateDefinition, CreateIndex, CreateTable, DataType, Delete, DropSchema, DropTable,
+    Expr, ForeignKeyReference, FromClause, FunctionArgs, Identifier, IndexColumn, Insert,
+    InsertSource, JoinClause, JoinConstraint, Literal, LoadData, OrderBy, Query, ResolvedColumnRef,
+    ResultColumn, TableOrSubQuery, UnresolvedColumnRef, Update, Window,
 };
 
 /// Creates a mut visitor
@@ -47,6 +49,27 @@ visit_mut! {
                 Query::LoadData(load) => {
                     visitor.visit_load_data_mut(load)
                 }
+                Query::Insert(insert) => {
+                    visitor.visit_insert_mut(insert)
+                }
+                Query::Update(update) => {
+                    visitor.visit_update_mut(update)
+                }
+                Query::Delete(delete) => {
+                    visitor.visit_delete_mut(delete)
+                }
+                Query::DropTable(drop_table) => {
+                    visitor.visit_drop_table_mut(drop_table)
+                }
+                Query::DropSchema(drop_schema) => {
+                    visitor.visit_drop_schema_mut(drop_schema)
+                }
+                Query::AlterTable(alter_table) => {
+                    visitor.visit_alter_table_mut(alter_table)
+                }
+                Query::Use(schema) => {
+                    visitor.visit_identifier_mut(schema)
+                }
                 Query::KillProcess(_) => {
                     Ok(())
                 }
@@ -70,11 +93,124 @@ visit_mut! {
             visitor.visit_identifier_mut(column)
         })
     }
+    pub visit (visitor, insert: &mut Insert) -> Result<()> {
+        let Insert {
+            schema,
+            table,
+            columns,
+            source,
+        } = insert;
+
+        if let Some(schema) = schema {
+            visitor.visit_identifier_mut(schema)?;
+        }
+        visitor.visit_identifier_mut(table)?;
+        columns.iter_mut()
+            .try_for_each(|column| {
+            visitor.visit_identifier_mut(column)
+        })?;
+        visitor.visit_insert_source_mut(source)
+    }
+    pub visit (visitor, insert_source: &mut InsertSource) -> Result<()> {
+        match insert_source {
+            InsertSource::Values(rows) => {
+                rows.iter_mut()
+                    .flatten()
+                    .try_for_each(|expr| visitor.visit_expr_mut(expr))
+            }
+            InsertSource::Select(select) => {
+                visitor.visit_select_mut(select)
+            }
+        }
+    }
+    pub visit (visitor, update: &mut Update) -> Result<()> {
+        let Update {
+            schema,
+            table,
+            set,
+            condition,
+        } = update;
+
+        if let Some(schema) = schema {
+            visitor.visit_identifier_mut(schema)?;
+        }
+        visitor.visit_identifier_mut(table)?;
+        set.iter_mut()
+            .try_for_each(|(column, expr)| {
+            visitor.visit_identifier_mut(column)?;
+            visitor.visit_expr_mut(expr)
+        })?;
+        if let Some(condition) = condition {
+            visitor.visit_expr_mut(condition)?;
+        }
+        Ok(())
+    }
+    pub visit (visitor, delete: &mut Delete) -> Result<()> {
+        let Delete {
+            schema,
+            table,
+            condition,
+        } = delete;
+
+        if let Some(schema) = schema {
+            visitor.visit_identifier_mut(schema)?;
+        }
+        visitor.visit_identifier_mut(table)?;
+        if let Some(condition) = condition {
+            visitor.visit_expr_mut(condition)?;
+        }
+        Ok(())
+    }
+    pub visit (visitor, drop_table: &mut DropTable) -> Result<()> {
+        let DropTable { schema, name, .. } = drop_table;
+        if let Some(schema) = schema {
+            visitor.visit_identifier_mut(schema)?;
+        }
+        visitor.visit_identifier_mut(name)
+    }
+    pub visit (visitor, drop_schema: &mut DropSchema) -> Result<()> {
+        visitor.visit_identifier_mut(&mut drop_schema.name)
+    }
+    pub visit (visitor, alter_table: &mut AlterTable) -> Result<()> {
+        let AlterTable { schema, name, actions } = alter_table;
+        if let Some(schema) = schema {
+            visitor.visit_identifier_mut(schema)?;
+        }
+        visitor.visit_identifier_mut(name)?;
+        actions.iter_mut().try_for_each(|action| visitor.visit_alter_table_action_mut(action))
+    }
+    pub visit (visitor, alter_table_action: &mut AlterTableAction) -> Result<()> {
+        match alter_table_action {
+            AlterTableAction::AddColumn(column_def) | AlterTableAction::ModifyColumn(column_def) => {
+                visitor.visit_column_definition_mut(column_def)
+            }
+            AlterTableAction::DropColumn(column) => visitor.visit_identifier_mut(column),
+            AlterTableAction::RenameColumn { from, to } => {
+                visitor.visit_identifier_mut(from)?;
+                visitor.visit_identifier_mut(to)
+            }
+        }
+    }
     pub visit (visitor, create: &mut Create) -> Result<()> {
         match create {
             Create::Table(create_table) => {
                 visitor.visit_create_table_mut(create_table)
             }
+            Create::Schema(create_schema) => {
+                visitor.visit_identifier_mut(&mut create_schema.name)
+            }
+            Create::Index(create_index) => {
+                let CreateIndex { name, schema, table, columns, .. } = create_index;
+                visitor.visit_identifier_mut(name)?;
+                if let Some(schema) = schema {
+                    visitor.visit_identifier_mut(schema)?;
+                }
+                visitor.visit_identifier_mut(table)?;
+                columns.iter_mut().try_for_each(|column| match column {
+                    IndexColumn::Column(column) => visitor.visit_identifier_mut(column),
+                    IndexColumn::Expr(expr) => visitor.visit_expr_mut(expr),
+                })
+            }
         }
     }
     pub visit (visitor, create_table: &mut CreateTable) -> Result<()> {
@@ -97,19 +233,56 @@ visit_mut! {
     pub visit (visitor, create_def: &mut CreateDefinition) -> Result<()> {
         match create_def {
             CreateDefinition::Column(column_def) => { visitor.visit_column_definition_mut(column_def) }
-            CreateDefinition::Constraint(_) => {
-                todo!()
+            CreateDefinition::Constraint(constraint_def) => {
+                visitor.visit_constraint_definition_mut(constraint_def)
             }}
     }
+    pub visit (visitor, constraint_def: &mut ConstraintDefinition) -> Result<()> {
+        let ConstraintDefinition { symbol, constraint } = constraint_def;
+        if let Some(symbol) = symbol {
+            visitor.visit_identifier_mut(symbol)?;
+        }
+        match constraint {
+            Constraint::PrimaryKey { columns } => {
+                columns.iter_mut().try_for_each(|column| visitor.visit_identifier_mut(column))
+            }
+            Constraint::Unique { name, columns } | Constraint::Key { name, columns } => {
+                if let Some(name) = name {
+                    visitor.visit_identifier_mut(name)?;
+                }
+                columns.iter_mut().try_for_each(|column| visitor.visit_identifier_mut(column))
+            }
+            Constraint::ForeignKey { name, columns, references } => {
+                if let Some(name) = name {
+                    visitor.visit_identifier_mut(name)?;
+                }
+                columns.iter_mut().try_for_each(|column| visitor.visit_identifier_mut(column))?;
+                visitor.visit_foreign_key_reference_mut(references)
+            }
+            Constraint::Check { expr } => visitor.visit_expr_mut(expr),
+        }
+    }
+    pub visit (visitor, references: &mut ForeignKeyReference) -> Result<()> {
+        let ForeignKeyReference { schema, table, columns, .. } = references;
+        if let Some(schema) = schema {
+            visitor.visit_identifier_mut(schema)?;
+        }
+        visitor.visit_identifier_mut(table)?;
+        columns.iter_mut().try_for_each(|column| visitor.visit_identifier_mut(column))
+    }
     pub visit (visitor, column_def: &mut ColumnDefinition) -> Result<()> {
         let ColumnDefinition {
             id,
             data_type,
+            default,
             ..
         } = column_def;
 
         visitor.visit_identifier_mut(id)?;
         visitor.visit_data_type_mut(data_type)?;
+        if let Some(default) = default {
+            visitor.visit_expr_mut(default)?;
+        }
         Ok(())
     }
 
@@ -119,16 +292,26 @@ visit_mut! {
 
     pub visit (visitor, select: &mut Select) -> Result<()> {
         let Select {
+            with,
+            distinct: _,
             columns,
             from,
             condition,
             group_by,
+            having,
+            compound,
             order_by,
 
 
             ..
         } = select;
 
+        if let Some(with) = with {
+            with.tables.iter_mut().try_for_each(|table| {
+                visitor.visit_select_mut(&mut table.select)
+            })?;
+        }
+
         if let Some(from) = from {
             visitor.visit_from_clause_mut(from)?;
         }
@@ -146,6 +329,14 @@ visit_mut! {
             .try_for_each(|expr| visitor.visit_expr_mut(expr))?;
         }
 
+        if let Some(having) = having {
+            visitor.visit_expr_mut(having)?;
+        }
+
+        compound.iter_mut().try_for_each(|(_, select)| {
+            visitor.visit_select_mut(select)
+        })?;
+
         if let Some(order_by) = order_by {
             order_by.iter_mut()
             .try_for_each(|expr| visitor.visit_order_by_mut(expr))?;
@@ -248,6 +439,44 @@ visit_mut! {
                 visitor.visit_identifier_mut(function)?;
                 visitor.visit_function_args_mut(args)
             }
+            Expr::Cast{ expr, data_type } => {
+                visitor.visit_expr_mut(expr)?;
+                visitor.visit_data_type_mut(data_type)
+            }
+            Expr::WindowFunction{ function, args, window } => {
+                visitor.visit_identifier_mut(function)?;
+                visitor.visit_function_args_mut(args)?;
+                visitor.visit_window_mut(window)
+            }
+            Expr::Case{ operand, branches, otherwise } => {
+                if let Some(operand) = operand {
+                    visitor.visit_expr_mut(operand)?;
+                }
+                for (condition, result) in branches {
+                    visitor.visit_expr_mut(condition)?;
+                    visitor.visit_expr_mut(result)?;
+                }
+                if let Some(otherwise) = otherwise {
+                    visitor.visit_expr_mut(otherwise)?;
+                }
+                Ok(())
+            }
+            Expr::InList{ expr, list, .. } => {
+                visitor.visit_expr_mut(expr)?;
+                list.iter_mut().try_for_each(|expr| visitor.visit_expr_mut(expr))
+            }
+            Expr::Between{ expr, low, high, .. } => {
+                visitor.visit_expr_mut(expr)?;
+                visitor.visit_expr_mut(low)?;
+                visitor.visit_expr_mut(high)
+            }
+            Expr::InSubQuery{ expr, query, .. } => {
+                visitor.visit_expr_mut(expr)?;
+                visitor.visit_select_mut(query)
+            }
+            Expr::Exists{ query, .. } | Expr::SubQuery{ query } => {
+                visitor.visit_select_mut(query)
+            }
         }
     }
     pub visit (visitor, column: &mut ColumnRef) -> Result<()> {
@@ -265,6 +494,10 @@ visit_mut! {
     pub visit (_visitor, _literal: &mut Literal) -> Result<()> {
         Ok(())
     }
+    pub visit (visitor, window: &mut Window) -> Result<()> {
+        window.partition_by.iter_mut().try_for_each(|expr| visitor.visit_expr_mut(expr))?;
+        window.order_by.iter_mut().try_for_each(|order_by| visitor.visit_order_by_mut(order_by))
+    }
     pub visit (visitor, function_args: &mut FunctionArgs) -> Result<()> {
         match function_args {
             FunctionArgs::Params{ exprs,ordered_by, .. } => {
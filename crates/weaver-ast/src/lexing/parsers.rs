@@ -67,6 +67,25 @@ fn keyword(input: &str) -> IResult<&str, Token> {
             value(Token::Join, ignore_case("join")),
             value(Token::Left, ignore_case("left")),
             value(Token::Right, ignore_case("right")),
+            // must come before the int type
+            value(Token::Intersect, ignore_case("intersect")),
+            value(Token::Union, ignore_case("union")),
+            value(Token::All, ignore_case("all")),
+            value(Token::Except, ignore_case("except")),
+            value(Token::Distinct, ignore_case("distinct")),
+        )),
+        alt((
+            // must come before the int type
+            value(Token::IntervalType, ignore_case("interval")),
+            // must come before "time"
+            value(Token::TimestampTzType, ignore_case("timestamptz")),
+            value(Token::TimestampType, ignore_case("timestamp")),
+            value(Token::TimeType, ignore_case("time")),
+            value(Token::DateType, ignore_case("date")),
+            value(Token::Zone, ignore_case("zone")),
+            value(Token::Extract, ignore_case("extract")),
+            value(Token::JsonType, ignore_case("json")),
+            value(Token::UuidType, ignore_case("uuid")),
         )),
         alt((
             value(Token::Load, ignore_case("load")),
@@ -74,6 +93,7 @@ fn keyword(input: &str) -> IResult<&str, Token> {
             value(Token::Into, ignore_case("into")),
             value(Token::Fields, ignore_case("fields")),
             value(Token::Group, ignore_case("group")),
+            value(Token::Having, ignore_case("having")),
             value(Token::Order, ignore_case("order")),
             value(Token::Asc, ignore_case("asc")),
             value(Token::Desc, ignore_case("desc")),
@@ -87,6 +107,17 @@ fn keyword(input: &str) -> IResult<&str, Token> {
             value(Token::Offset, ignore_case("offset")),
             value(Token::MetaKill, ignore_case("kill")),
             value(Token::MetaShow, ignore_case("show")),
+            value(Token::Update, ignore_case("update")),
+            value(Token::Set, ignore_case("set")),
+            value(Token::Schema, ignore_case("schema")),
+            value(Token::If, ignore_case("if")),
+            value(Token::Exists, ignore_case("exists")),
+            value(Token::Cascade, ignore_case("cascade")),
+            value(Token::Use, ignore_case("use")),
+            value(Token::Default, ignore_case("default")),
+            value(Token::Check, ignore_case("check")),
+            value(Token::With, ignore_case("with")),
+            value(Token::Recursive, ignore_case("recursive")),
         )),
         alt((
             value(Token::Values, ignore_case("values")),
@@ -95,6 +126,9 @@ fn keyword(input: &str) -> IResult<&str, Token> {
             value(Token::Key, ignore_case("key")),
             value(Token::Unique, ignore_case("unique")),
             value(Token::Foreign, ignore_case("foreign")),
+            value(Token::Constraint, ignore_case("constraint")),
+            value(Token::References, ignore_case("references")),
+            value(Token::Restrict, ignore_case("restrict")),
             value(Token::AutoIncrement, ignore_case("auto_increment")),
             value(
                 Token::IntType,
@@ -114,6 +148,8 @@ fn keyword(input: &str) -> IResult<&str, Token> {
             ),
             value(Token::VarCharType, ignore_case("varchar")),
             value(Token::VarBinaryType, ignore_case("varbinary")),
+            value(Token::TextType, ignore_case("text")),
+            value(Token::BlobType, ignore_case("blob")),
         )),
         alt((
             value(Token::Outer, ignore_case("outer")),
@@ -127,6 +163,46 @@ fn keyword(input: &str) -> IResult<&str, Token> {
             value(Token::Not, ignore_case("not")),
             value(Token::Null, ignore_case("null")),
             value(Token::Is, ignore_case("is")),
+            value(Token::Like, ignore_case("like")),
+            value(Token::Escape, ignore_case("escape")),
+            value(Token::Regexp, ignore_case("regexp")),
+            value(Token::Regexp, ignore_case("rlike")),
+        )),
+        alt((
+            value(Token::Alter, ignore_case("alter")),
+            value(Token::Add, ignore_case("add")),
+            value(Token::Column, ignore_case("column")),
+            value(Token::Rename, ignore_case("rename")),
+            value(Token::Modify, ignore_case("modify")),
+            value(Token::To, ignore_case("to")),
+            value(Token::Over, ignore_case("over")),
+            // must come before "row"
+            value(Token::Rows, ignore_case("rows")),
+            value(Token::Row, ignore_case("row")),
+            value(Token::Range, ignore_case("range")),
+            value(Token::Unbounded, ignore_case("unbounded")),
+            value(Token::Preceding, ignore_case("preceding")),
+            value(Token::Following, ignore_case("following")),
+            value(Token::Current, ignore_case("current")),
+        )),
+        alt((
+            value(Token::Case, ignore_case("case")),
+            value(Token::When, ignore_case("when")),
+            value(Token::Then, ignore_case("then")),
+            value(Token::Else, ignore_case("else")),
+            value(Token::End, ignore_case("end")),
+            value(Token::Cast, ignore_case("cast")),
+            value(
+                Token::BooleanType,
+                alt((ignore_case("boolean"), ignore_case("bool"))),
+            ),
+            // must come after every other keyword starting with "in"
+            value(Token::In, ignore_case("in")),
+            value(Token::Between, ignore_case("between")),
+            value(
+                Token::DecimalType,
+                alt((ignore_case("decimal"), ignore_case("numeric"))),
+            ),
         )),
     ))
     .parse(input)?;
@@ -169,13 +245,17 @@ fn differentiate_number(input: &str) -> IResult<&str, Token> {
             let i: i64 = i64::from_str_radix(binary_str, 2).expect("should be infallible");
             Token::Int(i)
         }),
-        map(all_consuming(digit1), |str| {
-            let i: i64 = i64::from_str(str).expect("should be infallible");
-            Token::Int(i)
+        map(all_consuming(digit1), |str: &str| match i64::from_str(str) {
+            Ok(i) => Token::Int(i),
+            Err(_) => Token::Decimal(Cow::Borrowed(str)),
         }),
-        map(rest, |str| {
-            let f: f64 = f64::from_str(str).expect("recognize float should not fail");
-            Token::Float(f)
+        map(rest, |str: &str| {
+            if str.contains(['e', 'E']) {
+                let f: f64 = f64::from_str(str).expect("recognize float should not fail");
+                Token::Float(f)
+            } else {
+                Token::Decimal(Cow::Borrowed(str))
+            }
         }),
     ))(input)
 }
@@ -186,17 +266,21 @@ fn op(input: &str) -> IResult<&str, Token> {
         value(Token::Dot, char('.')),
         value(Token::Star, char('*')),
         value(Token::Plus, char('+')),
+        // must come before "-"
+        value(Token::LongArrow, tag("->>")),
+        value(Token::Arrow, tag("->")),
         value(Token::Minus, char('-')),
         value(Token::Divide, char('/')),
         value(Token::Eq, char('=')),
         value(Token::Neq, tag("<>")),
         value(Token::Neq, tag("!=")),
-        value(Token::Less, char('<')),
         value(Token::LessEq, tag("<=")),
-        value(Token::Greater, char('>')),
+        value(Token::Less, char('<')),
         value(Token::GreaterEq, tag(">=")),
+        value(Token::Greater, char('>')),
         value(Token::LParen, char('(')),
         value(Token::RParen, char(')')),
+        value(Token::DoubleColon, tag("::")),
         value(Token::Colon, char(':')),
         value(Token::SemiColon, char(';')),
         value(Token::QMark, char('?')),
@@ -268,9 +352,20 @@ mod tests {
 
     #[test]
     fn tokenize_float() {
-        assert_token!("101.", Token::Float, 101.);
-        assert_token!("101.23", Token::Float, 101.23);
         assert_token!("5e10", Token::Float, 5e10);
+        assert_token!("1.5E-3", Token::Float, 1.5e-3);
+    }
+
+    #[test]
+    fn tokenize_decimal() {
+        assert_token!("101.", Token::Decimal, "101.");
+        assert_token!("101.23", Token::Decimal, "101.23");
+        assert_token!(
+            "1234567890123456789.01",
+            Token::Decimal,
+            "1234567890123456789.01"
+        );
+        assert_token!("99999999999999999999", Token::Decimal, "99999999999999999999");
     }
 
     #[test]
@@ -284,6 +379,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tokenize_keyword_prefix_at_end() {
+        let query = "id int";
+        let mut tokenizer = Tokenizer::new(query);
+        tokenizer.next_token().expect("should have next token");
+        let (_, token, _) = tokenizer.next_token().expect("should have next token");
+        assert_eq!(token, Token::IntType, "should be the int type");
+        let (_, token, _) = tokenizer.next_token().expect("should have next token");
+        assert_eq!(token, Token::Eof, "should be eof");
+    }
+
     #[test]
     fn recognize_ident() {
         let query = "user";
@@ -3,7 +3,7 @@ use nom::character::complete::multispace0;
 use nom::combinator::map;
 use nom::error::{ErrorKind, ParseError};
 use nom::sequence::tuple;
-use nom::{Compare, CompareResult, IResult, InputLength, InputTake, Needed, Parser};
+use nom::{Compare, CompareResult, IResult, InputLength, InputTake, Parser};
 
 pub fn ignore_whitespace<'a, O, E: ParseError<&'a str>, F: Parser<&'a str, O, E>>(
     parser: F,
@@ -21,10 +21,8 @@ pub fn ignore_case<'a, Error: ParseError<&'a str>>(
         let res: IResult<_, _, Error> =
             match (i.to_lowercase().as_str()).compare(t.to_lowercase().as_str()) {
                 CompareResult::Ok => Ok(i.take_split(tag_len)),
-                CompareResult::Incomplete => {
-                    Err(nom::Err::Incomplete(Needed::new(tag_len - i.input_len())))
-                }
-                CompareResult::Error => {
+                // the whole query is always available, so running out of input is a mismatch
+                CompareResult::Incomplete | CompareResult::Error => {
                     let e: ErrorKind = ErrorKind::Tag;
                     Err(nom::Err::Error(Error::from_error_kind(i, e)))
                 }
@@ -11,15 +11,28 @@ pub enum Token<'a> {
     Explain,
     Create,
     Drop,
+    Alter,
+    Add,
+    Column,
+    Rename,
+    Modify,
+    To,
     Delete,
     Insert,
+    Update,
+    Set,
     Load,
     Data,
     Infile,
     Fields,
     Values,
     Table,
+    Schema,
     Index,
+    If,
+    Exists,
+    Cascade,
+    Use,
     AutoIncrement,
     Default,
     Key,
@@ -27,15 +40,34 @@ pub enum Token<'a> {
     Unique,
     Foreign,
     Constraint,
+    References,
+    Restrict,
+    Check,
     Into,
     Order,
     Group,
+    Having,
     Collate,
     Partition,
     By,
     Terminated,
     Limit,
     Offset,
+    Union,
+    All,
+    Distinct,
+    Intersect,
+    Except,
+    With,
+    Recursive,
+    Over,
+    Rows,
+    Row,
+    Range,
+    Unbounded,
+    Preceding,
+    Following,
+    Current,
 
     From,
     As,
@@ -52,12 +84,29 @@ pub enum Token<'a> {
     Where,
     And,
     Or,
+    Like,
+    Escape,
+    Regexp,
+    In,
+    Between,
+
+    Case,
+    Cast,
+    Extract,
+    Zone,
+    When,
+    Then,
+    Else,
+    End,
 
     Comma,
     Dot,
     LParen,
     RParen,
     Colon,
+    DoubleColon,
+    Arrow,
+    LongArrow,
     SemiColon,
     QMark,
 
@@ -78,14 +127,27 @@ pub enum Token<'a> {
 
     String(Cow<'a, str>),
     VarCharType,
+    TextType,
     Binary(Cow<'a, [u8]>),
     VarBinaryType,
+    BlobType,
     Int(i64),
     IntType,
     Float(f64),
+    /// A fractional number without an exponent, or an integer too large for an `i64`, kept as
+    /// written so it can be read exactly
+    Decimal(Cow<'a, str>),
     FloatType,
+    DecimalType,
+    JsonType,
+    UuidType,
     Boolean(bool),
     BooleanType,
+    DateType,
+    TimeType,
+    TimestampType,
+    TimestampTzType,
+    IntervalType,
 
     Null,
 
@@ -111,6 +173,12 @@ pub enum TokenError {
     UnexpectedEof,
     #[error(transparent)]
     NomError(#[from] nom::error::Error<String>),
+    #[error("escape must be a single character, found {0:?}")]
+    InvalidEscape(String),
+    #[error("invalid decimal({precision}, {scale}), the precision must be between 1 and 38 and the scale at most the precision")]
+    InvalidDecimal { precision: i64, scale: i64 },
+    #[error("invalid width {0}, must be between 1 and 65535")]
+    InvalidWidth(i64),
 }
 
 #[cfg(test)]
@@ -70,6 +70,11 @@ pub trait ToSql {
 #[cfg(test)]
 mod tests {
     mod select {
+        use crate::ast::{
+            BinaryOp, CompoundOperator, DataType, DecimalType, Expr, FrameBound, FrameUnits,
+            FunctionArgs, OrderDirection, Query, ResultColumn, TimestampType, VarBinaryType,
+            VarCharType, Window, WindowFrame,
+        };
         use crate::QueryParser;
 
         #[test]
@@ -125,5 +130,999 @@ mod tests {
             println!("{}", serde_json::to_string_pretty(&q).unwrap());
             println!("{q:?}");
         }
+
+        #[test]
+        fn parse_having() {
+            static QUERY: &str = r"
+            SELECT level, count(id)
+            FROM weaver.users
+            GROUP BY level
+            HAVING count(id) > 1 AND max(age) < 30
+            ORDER BY level";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            let Query::Select(select) = q else {
+                panic!("expected select")
+            };
+            assert!(select.group_by.is_some());
+            assert!(matches!(
+                select.having,
+                Some(Expr::Binary {
+                    op: BinaryOp::And,
+                    ..
+                })
+            ));
+            assert!(select.order_by.is_some());
+        }
+
+        #[test]
+        fn parse_case() {
+            static QUERY: &str = r"
+            SELECT
+                CASE WHEN age < 18 THEN 'minor' WHEN age < 65 THEN 'adult' ELSE 'senior' END,
+                CASE level WHEN 1 THEN 'low' END AS level_name,
+                IF(age IS NULL, 0, age)
+            FROM weaver.users";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            let Query::Select(select) = q else {
+                panic!("expected select")
+            };
+            let exprs = select
+                .columns
+                .iter()
+                .map(|column| match column {
+                    ResultColumn::Expr { expr, .. } => expr,
+                    _ => panic!("expected expression"),
+                })
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                exprs[0],
+                Expr::Case { operand: None, branches, otherwise: Some(_) } if branches.len() == 2
+            ));
+            assert!(matches!(
+                exprs[1],
+                Expr::Case { operand: Some(_), branches, otherwise: None } if branches.len() == 1
+            ));
+            assert!(matches!(
+                exprs[2],
+                Expr::Case { operand: None, branches, otherwise: Some(_) } if branches.len() == 1
+            ));
+        }
+
+        #[test]
+        fn parse_pattern_matching() {
+            static QUERY: &str = r"
+            SELECT * FROM weaver.users
+            WHERE name LIKE 'j%' AND name NOT LIKE '%!_%' ESCAPE '!' AND email RLIKE '^[a-z]+@'";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            let Query::Select(select) = q else {
+                panic!("expected select")
+            };
+            let mut ops = vec![];
+            let mut condition = select.condition.as_ref().expect("has condition");
+            while let Expr::Binary {
+                left,
+                op: BinaryOp::And,
+                right,
+            } = condition
+            {
+                let Expr::Binary { op, .. } = &**right else {
+                    panic!("expected binary expression")
+                };
+                ops.push(op.clone());
+                condition = left;
+            }
+            let Expr::Binary { op, .. } = condition else {
+                panic!("expected binary expression")
+            };
+            ops.push(op.clone());
+            assert_eq!(
+                ops,
+                [
+                    BinaryOp::Regexp,
+                    BinaryOp::NotLike { escape: Some('!') },
+                    BinaryOp::Like { escape: None }
+                ]
+            );
+
+            assert!(
+                query_parser
+                    .parse("SELECT * FROM weaver.users WHERE name LIKE 'a' ESCAPE 'ab'")
+                    .is_err(),
+                "escape must be a single character"
+            );
+        }
+
+        #[test]
+        fn parse_in_between_exists() {
+            static QUERY: &str = r"
+            SELECT * FROM weaver.users
+            WHERE age NOT BETWEEN 1 AND 17 AND id IN (1, 2, 3) AND name NOT IN (SELECT name FROM weaver.banned)
+                AND NOT EXISTS (SELECT * FROM weaver.orders WHERE orders.user_id = users.id)";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            let Query::Select(select) = q else {
+                panic!("expected select")
+            };
+            let mut conjuncts = vec![];
+            let mut condition = select.condition.as_ref().expect("has condition");
+            while let Expr::Binary {
+                left,
+                op: BinaryOp::And,
+                right,
+            } = condition
+            {
+                conjuncts.push(&**right);
+                condition = left;
+            }
+            conjuncts.push(condition);
+
+            assert!(matches!(conjuncts[0], Expr::Exists { negated: true, .. }));
+            assert!(matches!(
+                conjuncts[1],
+                Expr::InSubQuery { negated: true, query, .. } if query.columns.len() == 1
+            ));
+            assert!(matches!(
+                conjuncts[2],
+                Expr::InList { negated: false, list, .. } if list.len() == 3
+            ));
+            assert!(matches!(
+                conjuncts[3],
+                Expr::Between { negated: true, low, high, .. }
+                    if **low == Expr::from(1) && **high == Expr::from(17)
+            ));
+        }
+
+        #[test]
+        fn parse_scalar_sub_query() {
+            static QUERY: &str = r"
+            SELECT name, (SELECT max(total) FROM weaver.orders WHERE orders.user_id = users.id)
+            FROM weaver.users
+            WHERE age > (SELECT avg(age) FROM weaver.users)";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            let Query::Select(select) = q else {
+                panic!("expected select")
+            };
+            assert!(matches!(
+                &select.columns[1],
+                ResultColumn::Expr { expr: Expr::SubQuery { query }, .. } if query.condition.is_some()
+            ));
+            assert!(matches!(
+                select.condition,
+                Some(Expr::Binary { right, .. }) if matches!(*right, Expr::SubQuery { .. })
+            ));
+        }
+
+        #[test]
+        fn parse_compound_select() {
+            static QUERY: &str = r"
+            SELECT name FROM weaver.users WHERE age > 30
+            UNION ALL SELECT name FROM weaver.admins
+            EXCEPT SELECT name FROM weaver.banned
+            ORDER BY name LIMIT 5";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            let Query::Select(select) = q else {
+                panic!("expected select")
+            };
+            assert!(select.condition.is_some());
+            assert_eq!(
+                select
+                    .compound
+                    .iter()
+                    .map(|(op, _)| *op)
+                    .collect::<Vec<_>>(),
+                [CompoundOperator::UnionAll, CompoundOperator::Except]
+            );
+            assert!(select
+                .compound
+                .iter()
+                .all(|(_, select)| select.order_by.is_none() && select.limit.is_none()));
+            assert!(select.order_by.is_some());
+            assert_eq!(select.limit, Some(5));
+        }
+
+        #[test]
+        fn parse_with() {
+            static QUERY: &str = r"
+            WITH RECURSIVE reports(id, depth) AS (
+                SELECT id, 0 FROM weaver.org WHERE manager_id IS NULL
+                UNION ALL
+                SELECT org.id, depth + 1 FROM weaver.org JOIN reports ON org.manager_id = reports.id
+            ), adults AS (SELECT * FROM weaver.users WHERE age >= 18)
+            SELECT id FROM reports ORDER BY depth";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            let Query::Select(select) = q else {
+                panic!("expected select")
+            };
+            let with = select.with.expect("should have common table expressions");
+            assert!(with.recursive);
+            assert_eq!(
+                with.tables
+                    .iter()
+                    .map(|table| table.name.as_ref())
+                    .collect::<Vec<_>>(),
+                ["reports", "adults"]
+            );
+            assert_eq!(with.tables[0].columns.as_ref().map(Vec::len), Some(2));
+            assert_eq!(with.tables[0].select.compound.len(), 1);
+            assert!(with.tables[1].columns.is_none());
+            assert!(select.order_by.is_some());
+        }
+
+        #[test]
+        fn parse_window_functions() {
+            static QUERY: &str = r"
+            SELECT name, row_number() OVER (PARTITION BY dept ORDER BY salary DESC),
+                sum(salary) OVER (ORDER BY id ROWS BETWEEN 2 PRECEDING AND CURRENT ROW),
+                lag(salary, 1) OVER ()
+            FROM weaver.employees";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            let Query::Select(select) = q else {
+                panic!("expected select")
+            };
+            let windows = select
+                .columns
+                .iter()
+                .filter_map(|column| match column {
+                    ResultColumn::Expr {
+                        expr: Expr::WindowFunction { window, .. },
+                        ..
+                    } => Some(window.as_ref()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(windows.len(), 3);
+            assert_eq!(windows[0].partition_by.len(), 1);
+            assert_eq!(
+                windows[0].order_by[0].1,
+                Some(OrderDirection::Desc),
+                "window should be ordered by descending salary"
+            );
+            assert_eq!(
+                windows[1].frame,
+                Some(WindowFrame {
+                    units: FrameUnits::Rows,
+                    start: FrameBound::Preceding(2),
+                    end: FrameBound::CurrentRow,
+                })
+            );
+            assert_eq!(windows[2], &Window::default());
+        }
+
+        #[test]
+        fn parse_cast() {
+            static QUERY: &str = r"
+            SELECT CAST(age AS float), '12'::int, -age::float * 2, 1 + 1.5e0, 2.5e0 * 2, 1 / 0,
+                cast(age + 1 as int), cast(age * 2 > 10 and age < 100 as boolean)
+            FROM weaver.users";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            let Query::Select(select) = q else {
+                panic!("expected select")
+            };
+            let exprs = select
+                .columns
+                .iter()
+                .map(|column| match column {
+                    ResultColumn::Expr { expr, .. } => expr,
+                    _ => panic!("expected expression"),
+                })
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                exprs[0],
+                Expr::Cast {
+                    data_type: DataType::Float(_),
+                    ..
+                }
+            ));
+            assert!(matches!(
+                exprs[1],
+                Expr::Cast {
+                    data_type: DataType::Int(_),
+                    ..
+                }
+            ));
+            assert!(
+                matches!(
+                    exprs[2],
+                    Expr::Binary { left, op: BinaryOp::Multiply, .. }
+                        if matches!(left.as_ref(), Expr::Unary { expr, .. }
+                            if matches!(expr.as_ref(), Expr::Cast { .. }))
+                ),
+                "casts should bind tighter than any operator"
+            );
+            assert_eq!(
+                exprs[3],
+                &Expr::from(2.5),
+                "integers should be promoted to floats"
+            );
+            assert_eq!(exprs[4], &Expr::from(5.0));
+            assert!(
+                matches!(exprs[5], Expr::Binary { .. }),
+                "dividing by zero should be left to the evaluator"
+            );
+            assert!(
+                matches!(
+                    exprs[6],
+                    Expr::Cast { expr, data_type: DataType::Int(_) }
+                        if matches!(expr.as_ref(), Expr::Binary { op: BinaryOp::Plus, .. })
+                ),
+                "the argument of a cast should be a full expression"
+            );
+            assert!(matches!(
+                exprs[7],
+                Expr::Cast { expr, data_type: DataType::BooleanType(_) }
+                    if matches!(expr.as_ref(), Expr::Binary { op: BinaryOp::And, .. })
+            ));
+        }
+
+        #[test]
+        fn parse_temporal() {
+            static QUERY: &str = r"
+            SELECT date '2024-01-02', timestamp with time zone '2024-01-02 03:04:05+01',
+                created::timestamptz, extract(year from created), interval '1 day' * 2, date, time,
+                extract(day from created + interval '1 day')
+            FROM weaver.events";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            let Query::Select(select) = q else {
+                panic!("expected select")
+            };
+            let exprs = select
+                .columns
+                .iter()
+                .map(|column| match column {
+                    ResultColumn::Expr { expr, .. } => expr,
+                    _ => panic!("expected expression"),
+                })
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                exprs[0],
+                Expr::Cast {
+                    data_type: DataType::DateType(_),
+                    ..
+                }
+            ));
+            for expr in &exprs[1..3] {
+                assert!(matches!(
+                    expr,
+                    Expr::Cast {
+                        data_type: DataType::TimestampType(TimestampType {
+                            with_time_zone: true
+                        }),
+                        ..
+                    }
+                ));
+            }
+            assert!(
+                matches!(exprs[3], Expr::FunctionCall { function, .. } if function.as_ref() == "date_part"),
+                "extract should be a call to date_part"
+            );
+            assert!(matches!(
+                exprs[4],
+                Expr::Binary {
+                    op: BinaryOp::Multiply,
+                    ..
+                }
+            ));
+            assert!(
+                matches!(exprs[5], Expr::Column { .. }) && matches!(exprs[6], Expr::Column { .. }),
+                "date and time should still be usable as column names"
+            );
+            assert!(
+                matches!(
+                    exprs[7],
+                    Expr::FunctionCall { function, args: FunctionArgs::Params { exprs, .. } }
+                        if function.as_ref() == "date_part"
+                            && matches!(exprs[1], Expr::Binary { op: BinaryOp::Plus, .. })
+                ),
+                "the argument of extract should be a full expression"
+            );
+        }
+
+        #[test]
+        fn parse_decimal() {
+            static QUERY: &str = r"
+            SELECT CAST(price AS decimal(10, 2)), price::numeric, CAST(price AS DECIMAL(5))
+            FROM weaver.orders";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            let Query::Select(select) = q else {
+                panic!("expected select")
+            };
+            let types = select
+                .columns
+                .iter()
+                .map(|column| match column {
+                    ResultColumn::Expr {
+                        expr: Expr::Cast { data_type, .. },
+                        ..
+                    } => *data_type,
+                    _ => panic!("expected cast"),
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                types,
+                [
+                    DataType::DecimalType(DecimalType(10, 2)),
+                    DataType::DecimalType(DecimalType(DecimalType::DEFAULT_PRECISION, 0)),
+                    DataType::DecimalType(DecimalType(5, 0)),
+                ]
+            );
+
+            for invalid in ["decimal(39, 2)", "decimal(0)", "decimal(5, 6)"] {
+                assert!(
+                    query_parser
+                        .parse(&format!(
+                            "SELECT CAST(price AS {invalid}) FROM weaver.orders"
+                        ))
+                        .is_err(),
+                    "{invalid} should not parse"
+                );
+            }
+        }
+
+        #[test]
+        fn parse_string_widths() {
+            static QUERY: &str = r"
+            SELECT CAST(name AS varchar(1000)), name::text, CAST(contents AS blob), contents::varbinary(65535)
+            FROM weaver.files";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            let Query::Select(select) = q else {
+                panic!("expected select")
+            };
+            let types = select
+                .columns
+                .iter()
+                .map(|column| match column {
+                    ResultColumn::Expr {
+                        expr: Expr::Cast { data_type, .. },
+                        ..
+                    } => *data_type,
+                    _ => panic!("expected cast"),
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                types,
+                [
+                    DataType::VarCharType(VarCharType(1000)),
+                    DataType::VarCharType(VarCharType(VarCharType::MAX_WIDTH)),
+                    DataType::VarBinaryType(VarBinaryType(VarBinaryType::MAX_WIDTH)),
+                    DataType::VarBinaryType(VarBinaryType(65535)),
+                ]
+            );
+
+            for invalid in ["varchar(0)", "varchar(65536)", "varbinary(-1)"] {
+                assert!(
+                    query_parser
+                        .parse(&format!("SELECT CAST(name AS {invalid}) FROM weaver.files"))
+                        .is_err(),
+                    "{invalid} should not parse"
+                );
+            }
+        }
+
+        #[test]
+        fn parse_distinct() {
+            static QUERY: &str = "SELECT DISTINCT dept, count(DISTINCT salary), count(*) \
+                FROM weaver.employees GROUP BY dept";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            let Query::Select(select) = q else {
+                panic!("expected select")
+            };
+            assert!(select.distinct);
+            let args = select
+                .columns
+                .iter()
+                .filter_map(|column| match column {
+                    ResultColumn::Expr {
+                        expr: Expr::FunctionCall { args, .. },
+                        ..
+                    } => Some(args),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                args[0],
+                FunctionArgs::Params { distinct: true, .. }
+            ));
+            assert_eq!(args[1], &FunctionArgs::Wildcard { distinct: false });
+        }
+
+        #[test]
+        fn parse_json() {
+            static QUERY: &str = r"
+            SELECT payload -> 'tags' -> 0, payload->>'kind', CAST(body AS json)
+            FROM weaver.events";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            let Query::Select(select) = q else {
+                panic!("expected select")
+            };
+            let exprs = select
+                .columns
+                .iter()
+                .map(|column| match column {
+                    ResultColumn::Expr { expr, .. } => expr,
+                    _ => panic!("expected expr"),
+                })
+                .collect::<Vec<_>>();
+            let Expr::Binary {
+                left,
+                op: BinaryOp::JsonGet,
+                ..
+            } = exprs[0]
+            else {
+                panic!("expected json get, got {:?}", exprs[0])
+            };
+            assert!(
+                matches!(
+                    &**left,
+                    Expr::Binary {
+                        op: BinaryOp::JsonGet,
+                        ..
+                    }
+                ),
+                "json operators should be left associative"
+            );
+            assert!(matches!(
+                exprs[1],
+                Expr::Binary {
+                    op: BinaryOp::JsonGetText,
+                    ..
+                }
+            ));
+            assert!(matches!(
+                exprs[2],
+                Expr::Cast {
+                    data_type: DataType::JsonType(_),
+                    ..
+                }
+            ));
+        }
+
+        #[test]
+        fn parse_uuid() {
+            static QUERY: &str = r"
+            SELECT uuid(), id::uuid, UUID '0f8fad5b-d9cb-469f-a165-70867728950e', uuid
+            FROM weaver.sessions";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            let Query::Select(select) = q else {
+                panic!("expected select")
+            };
+            let exprs = select
+                .columns
+                .iter()
+                .map(|column| match column {
+                    ResultColumn::Expr { expr, .. } => expr,
+                    _ => panic!("expected expr"),
+                })
+                .collect::<Vec<_>>();
+            assert!(
+                matches!(exprs[0], Expr::FunctionCall { function, .. } if function.as_ref() == "uuid")
+            );
+            for expr in &exprs[1..3] {
+                assert!(matches!(
+                    expr,
+                    Expr::Cast {
+                        data_type: DataType::UuidType(_),
+                        ..
+                    }
+                ));
+            }
+            assert!(
+                matches!(exprs[3], Expr::Column { .. }),
+                "uuid should still be usable as a column name"
+            );
+        }
+    }
+
+    mod insert {
+        use crate::ast::{InsertSource, Query};
+        use crate::QueryParser;
+
+        #[test]
+        fn parse_insert_values() {
+            static QUERY: &str =
+                "INSERT INTO weaver.users (name, age) VALUES ('josh', 25), ('chris', 1+2);";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            println!("{}", serde_json::to_string_pretty(&q).unwrap());
+            let Query::Insert(insert) = q else {
+                panic!("expected insert query")
+            };
+            assert_eq!(insert.columns.len(), 2);
+            assert!(matches!(insert.source, InsertSource::Values(ref rows) if rows.len() == 2));
+        }
+
+        #[test]
+        fn parse_insert_select() {
+            static QUERY: &str = "INSERT INTO users SELECT name, age FROM weaver.people";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            println!("{}", serde_json::to_string_pretty(&q).unwrap());
+            let Query::Insert(insert) = q else {
+                panic!("expected insert query")
+            };
+            assert!(insert.columns.is_empty());
+            assert!(matches!(insert.source, InsertSource::Select(_)));
+        }
+    }
+
+    mod update {
+        use crate::ast::Query;
+        use crate::QueryParser;
+
+        #[test]
+        fn parse_update() {
+            static QUERY: &str =
+                "UPDATE weaver.users SET age = age + 1, name = 'josh' WHERE id = 1;";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            println!("{}", serde_json::to_string_pretty(&q).unwrap());
+            let Query::Update(update) = q else {
+                panic!("expected update query")
+            };
+            assert_eq!(update.set.len(), 2);
+            assert!(update.condition.is_some());
+        }
+    }
+
+    mod delete {
+        use crate::ast::Query;
+        use crate::QueryParser;
+
+        #[test]
+        fn parse_delete() {
+            static QUERY: &str = "DELETE FROM weaver.users WHERE age > 30;";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            println!("{}", serde_json::to_string_pretty(&q).unwrap());
+            let Query::Delete(delete) = q else {
+                panic!("expected delete query")
+            };
+            assert_eq!(delete.table.to_string(), "users");
+            assert!(delete.condition.is_some());
+        }
+
+        #[test]
+        fn parse_delete_all() {
+            static QUERY: &str = "delete from users;";
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(QUERY).expect("could not parse");
+            let Query::Delete(delete) = q else {
+                panic!("expected delete query")
+            };
+            assert!(delete.schema.is_none());
+            assert!(delete.condition.is_none());
+        }
+    }
+
+    mod drop {
+        use crate::ast::Query;
+        use crate::QueryParser;
+
+        #[test]
+        fn parse_drop_table() {
+            let mut query_parser = QueryParser::new();
+            let q = query_parser
+                .parse("DROP TABLE IF EXISTS weaver.users;")
+                .expect("could not parse");
+            let Query::DropTable(drop_table) = q else {
+                panic!("expected drop table query")
+            };
+            assert!(drop_table.if_exists);
+            assert_eq!(drop_table.name.to_string(), "users");
+
+            let q = query_parser
+                .parse("drop table users;")
+                .expect("could not parse");
+            let Query::DropTable(drop_table) = q else {
+                panic!("expected drop table query")
+            };
+            assert!(!drop_table.if_exists);
+            assert!(drop_table.schema.is_none());
+        }
+
+        #[test]
+        fn parse_drop_schema() {
+            let mut query_parser = QueryParser::new();
+            let q = query_parser
+                .parse("DROP SCHEMA test CASCADE;")
+                .expect("could not parse");
+            let Query::DropSchema(drop_schema) = q else {
+                panic!("expected drop schema query")
+            };
+            assert!(drop_schema.cascade);
+            assert_eq!(drop_schema.name.to_string(), "test");
+        }
+    }
+
+    mod schema {
+        use crate::ast::{Create, Query};
+        use crate::QueryParser;
+
+        #[test]
+        fn parse_create_schema() {
+            let mut query_parser = QueryParser::new();
+            let q = query_parser
+                .parse("CREATE SCHEMA test;")
+                .expect("could not parse");
+            let Query::Create(Create::Schema(create_schema)) = q else {
+                panic!("expected create schema query")
+            };
+            assert_eq!(create_schema.name.to_string(), "test");
+        }
+
+        #[test]
+        fn parse_use() {
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse("use test;").expect("could not parse");
+            let Query::Use(schema) = q else {
+                panic!("expected use query")
+            };
+            assert_eq!(schema.to_string(), "test");
+        }
+    }
+
+    mod index {
+        use crate::ast::{BinaryOp, Create, Expr, IndexColumn, Query};
+        use crate::QueryParser;
+
+        #[test]
+        fn parse_create_index() {
+            let mut query_parser = QueryParser::new();
+            let q = query_parser
+                .parse("CREATE INDEX idx_name ON test.people (name, age);")
+                .expect("could not parse");
+            let Query::Create(Create::Index(create_index)) = q else {
+                panic!("expected create index query")
+            };
+            assert!(!create_index.unique);
+            assert_eq!(create_index.name.to_string(), "idx_name");
+            assert_eq!(create_index.schema.unwrap().to_string(), "test");
+            assert_eq!(create_index.table.to_string(), "people");
+            assert_eq!(create_index.columns.len(), 2);
+        }
+
+        #[test]
+        fn parse_create_unique_index() {
+            let mut query_parser = QueryParser::new();
+            let q = query_parser
+                .parse("create unique index idx_name on people (name);")
+                .expect("could not parse");
+            let Query::Create(Create::Index(create_index)) = q else {
+                panic!("expected create index query")
+            };
+            assert!(create_index.unique);
+            assert!(create_index.schema.is_none());
+        }
+
+        #[test]
+        fn parse_create_expression_index() {
+            let mut query_parser = QueryParser::new();
+            let q = query_parser
+                .parse("create index idx_kind on events (id, (payload ->> 'kind'));")
+                .expect("could not parse");
+            let Query::Create(Create::Index(create_index)) = q else {
+                panic!("expected create index query")
+            };
+            assert!(matches!(
+                &create_index.columns[..],
+                [
+                    IndexColumn::Column(_),
+                    IndexColumn::Expr(Expr::Binary {
+                        op: BinaryOp::JsonGetText,
+                        ..
+                    })
+                ]
+            ));
+        }
+    }
+
+    mod alter {
+        use crate::ast::{AlterTableAction, Query};
+        use crate::QueryParser;
+
+        #[test]
+        fn parse_alter_table() {
+            let mut query_parser = QueryParser::new();
+            let q = query_parser
+                .parse(
+                    "ALTER TABLE test.people ADD COLUMN age int, DROP COLUMN height, \
+                    RENAME COLUMN name TO full_name, MODIFY COLUMN id bigint not null;",
+                )
+                .expect("could not parse");
+            let Query::AlterTable(alter_table) = q else {
+                panic!("expected alter table query")
+            };
+            assert_eq!(alter_table.schema.unwrap().to_string(), "test");
+            assert_eq!(alter_table.name.to_string(), "people");
+            assert!(matches!(
+                &alter_table.actions[..],
+                [
+                    AlterTableAction::AddColumn(_),
+                    AlterTableAction::DropColumn(_),
+                    AlterTableAction::RenameColumn { .. },
+                    AlterTableAction::ModifyColumn(_),
+                ]
+            ));
+        }
+
+        #[test]
+        fn parse_alter_table_without_column_keyword() {
+            let mut query_parser = QueryParser::new();
+            let q = query_parser
+                .parse("alter table people add age int;")
+                .expect("could not parse");
+            let Query::AlterTable(alter_table) = q else {
+                panic!("expected alter table query")
+            };
+            assert!(alter_table.schema.is_none());
+            let [AlterTableAction::AddColumn(column)] = &alter_table.actions[..] else {
+                panic!("expected a single add column")
+            };
+            assert_eq!(column.id.to_string(), "age");
+        }
+
+        #[test]
+        fn parse_query_ending_in_keyword_prefix() {
+            let mut query_parser = QueryParser::new();
+            query_parser
+                .parse("alter table people add age int")
+                .expect("`int` is a prefix of `into`, but should still parse");
+        }
+    }
+
+    mod constraints {
+        use crate::ast::{
+            BinaryOp, Constraint, Create, CreateDefinition, Expr, Query, ReferentialAction,
+        };
+        use crate::QueryParser;
+
+        fn constraints(query: &str) -> Vec<(Option<String>, Constraint)> {
+            let mut query_parser = QueryParser::new();
+            let q = query_parser.parse(query).expect("could not parse");
+            let Query::Create(Create::Table(create_table)) = q else {
+                panic!("expected create table query")
+            };
+            create_table
+                .create_definitions
+                .into_iter()
+                .filter_map(|def| match def {
+                    CreateDefinition::Column(_) => None,
+                    CreateDefinition::Constraint(constraint) => Some((
+                        constraint.key_name().map(|name| name.to_string()),
+                        constraint.constraint,
+                    )),
+                })
+                .collect()
+        }
+
+        #[test]
+        fn parse_table_constraints() {
+            let constraints = constraints(
+                "create table test.people (first varchar(32), last varchar(32), age int, \
+                primary key (first, last), unique key uk_age (age), key (last), index idx_age (age));",
+            );
+            assert!(matches!(
+                &constraints[..],
+                [
+                    (None, Constraint::PrimaryKey { columns }),
+                    (Some(uk_age), Constraint::Unique { .. }),
+                    (None, Constraint::Key { .. }),
+                    (Some(idx_age), Constraint::Key { .. }),
+                ] if columns.len() == 2 && uk_age == "uk_age" && idx_age == "idx_age"
+            ));
+        }
+
+        #[test]
+        fn parse_named_constraints() {
+            let constraints = constraints(
+                "create table people (first varchar(32), last varchar(32), \
+                constraint pk primary key (first), constraint uk_name unique (first, last));",
+            );
+            assert!(matches!(
+                &constraints[..],
+                [
+                    (None, Constraint::PrimaryKey { .. }),
+                    (Some(uk_name), Constraint::Unique { columns, .. }),
+                ] if uk_name == "uk_name" && columns.len() == 2
+            ));
+        }
+
+        #[test]
+        fn parse_foreign_keys() {
+            let constraints = constraints(
+                "create table pets (id int, owner int, vet int, \
+                constraint fk_owner foreign key (owner) references test.people (id) on delete cascade, \
+                foreign key (vet) references vets (id) on update set null on delete restrict);",
+            );
+            let [(
+                Some(fk_owner),
+                Constraint::ForeignKey {
+                    references: owner, ..
+                },
+            ), (
+                None,
+                Constraint::ForeignKey {
+                    references: vet, ..
+                },
+            )] = &constraints[..]
+            else {
+                panic!("expected two foreign keys, got {constraints:#?}")
+            };
+            assert_eq!(fk_owner, "fk_owner");
+            assert_eq!(owner.schema.as_ref().map(|s| s.as_ref()), Some("test"));
+            assert_eq!(owner.on_delete, ReferentialAction::Cascade);
+            assert_eq!(owner.on_update, ReferentialAction::Restrict);
+            assert!(vet.schema.is_none());
+            assert_eq!(vet.on_delete, ReferentialAction::Restrict);
+            assert_eq!(vet.on_update, ReferentialAction::SetNull);
+        }
+
+        #[test]
+        fn parse_checks() {
+            let constraints = constraints(
+                "create table people (age int, height float, \
+                constraint ck_age check (age >= 0), check (height > 0.5 and height < 3.0));",
+            );
+            let [(Some(ck_age), Constraint::Check { expr: age }), (None, Constraint::Check { expr: height })] =
+                &constraints[..]
+            else {
+                panic!("expected two checks, got {constraints:#?}")
+            };
+            assert_eq!(ck_age, "ck_age");
+            assert!(matches!(
+                age,
+                Expr::Binary {
+                    op: BinaryOp::GreaterEq,
+                    ..
+                }
+            ));
+            assert!(matches!(
+                height,
+                Expr::Binary {
+                    op: BinaryOp::And,
+                    ..
+                }
+            ));
+        }
+
+        #[test]
+        fn parse_column_defaults() {
+            let mut query_parser = QueryParser::new();
+            let q = query_parser
+                .parse("create table people (id int, name varchar(8) not null default 'none', created int default now());")
+                .expect("could not parse");
+            let Query::Create(Create::Table(create_table)) = q else {
+                panic!("expected create table query")
+            };
+            let defaults = create_table
+                .create_definitions
+                .iter()
+                .filter_map(|def| match def {
+                    CreateDefinition::Column(column) => Some(column.default.as_ref()),
+                    CreateDefinition::Constraint(_) => None,
+                })
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                &defaults[..],
+                [
+                    None,
+                    Some(Expr::Literal { .. }),
+                    Some(Expr::FunctionCall { .. })
+                ]
+            ));
+        }
     }
 }
@@ -1,6 +1,9 @@
 //! The data that is actually stored
 pub mod charset;
+pub mod decimal;
+pub mod json;
 pub mod row;
+pub mod temporal;
 pub mod types;
 pub mod values;
 
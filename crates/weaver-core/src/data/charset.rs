@@ -1,9 +1,15 @@
 //! A charset must provide a mechanism for creating a total order over strings of a given max length
 //! The default charset shall be ASCII
 
+use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
+use std::num::NonZeroUsize;
+
+use lru::LruCache;
+use once_cell::sync::Lazy;
+use regex::{Regex, RegexBuilder};
 
 /// A collation defines some of characters and gives it a lexicographical order
 #[derive(Debug)]
@@ -23,6 +29,11 @@ impl Collation {
         }
     }
 
+    /// Gets the name of the collation
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     /// Gets the lexicographical value of a char, if present within the collation
     pub fn to_lexicographical_value(&self, c: char) -> Option<u32> {
         self.chars.get(&c).copied()
@@ -80,11 +91,175 @@ impl Collation {
     }
 }
 
+/// Pattern matching
+impl Collation {
+    /// Checks if two chars are equal in this collation. Chars that aren't in the collation are only
+    /// equal to themselves.
+    pub fn chars_eq(&self, l: char, r: char) -> bool {
+        l == r
+            || matches!(
+                (self.to_lexicographical_value(l), self.to_lexicographical_value(r)),
+                (Some(l), Some(r)) if l == r
+            )
+    }
+
+    /// Checks if this collation treats upper and lower case ascii letters as equal
+    pub fn is_case_insensitive(&self) -> bool {
+        ('a'..='z').all(|c| self.chars_eq(c, c.to_ascii_uppercase()))
+    }
+
+    /// Gets the lowest and highest strings, ordered by their chars, that are equal to a string in
+    /// this collation. Every string equal to it sorts between the two.
+    pub fn char_order_bounds(&self, s: &str) -> (String, String) {
+        s.chars()
+            .map(|c| {
+                let equal = || {
+                    self.to_lexicographical_value(c)
+                        .into_iter()
+                        .flat_map(|value| {
+                            self.chars
+                                .iter()
+                                .filter(move |(_, &other)| other == value)
+                                .map(|(&c, _)| c)
+                        })
+                        .chain([c])
+                };
+                (equal().min().unwrap_or(c), equal().max().unwrap_or(c))
+            })
+            .unzip()
+    }
+
+    /// Checks if a string matches a `like` pattern, where `%` matches any number of chars and `_`
+    /// matches exactly one char. A char following the `escape` char is matched literally.
+    pub fn like(&self, s: &str, pattern: &str, escape: char) -> bool {
+        let pattern = LikePart::parse(pattern, escape);
+        let s = s.chars().collect::<Vec<_>>();
+
+        let (mut s_idx, mut p_idx) = (0, 0);
+        // where to resume if matching fails after the last `%`
+        let mut retry: Option<(usize, usize)> = None;
+        while s_idx < s.len() {
+            match pattern.get(p_idx) {
+                Some(LikePart::Many) => {
+                    p_idx += 1;
+                    retry = Some((p_idx, s_idx));
+                }
+                Some(LikePart::One) => {
+                    p_idx += 1;
+                    s_idx += 1;
+                }
+                Some(&LikePart::Char(c)) if self.chars_eq(c, s[s_idx]) => {
+                    p_idx += 1;
+                    s_idx += 1;
+                }
+                _ => match retry {
+                    // let the last `%` match one more char
+                    Some((retry_p_idx, retry_s_idx)) => {
+                        p_idx = retry_p_idx;
+                        s_idx = retry_s_idx + 1;
+                        retry = Some((retry_p_idx, s_idx));
+                    }
+                    None => return false,
+                },
+            }
+        }
+        pattern[p_idx..]
+            .iter()
+            .all(|part| matches!(part, LikePart::Many))
+    }
+
+    /// Gets the chars every string matching a `like` pattern must start with
+    pub fn like_prefix(pattern: &str, escape: char) -> String {
+        LikePart::parse(pattern, escape)
+            .into_iter()
+            .map_while(|part| match part {
+                LikePart::Char(c) => Some(c),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Checks if a string contains a match of a regular expression. The regular expression is case
+    /// insensitive if this collation is.
+    pub fn regexp(&self, s: &str, pattern: &str) -> Result<bool, regex::Error> {
+        let case_insensitive = self.is_case_insensitive();
+        REGEX_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let key = (pattern.to_string(), case_insensitive);
+            let regex = match cache.get(&key) {
+                Some(regex) => regex,
+                None => {
+                    let regex = RegexBuilder::new(pattern)
+                        .case_insensitive(case_insensitive)
+                        .build()?;
+                    cache.get_or_insert(key, || regex)
+                }
+            };
+            Ok(regex.is_match(s))
+        })
+    }
+}
+
+thread_local! {
+    /// Recently compiled regular expressions, as the same pattern is usually matched against many rows
+    static REGEX_CACHE: RefCell<LruCache<(String, bool), Regex>> =
+        RefCell::new(LruCache::new(NonZeroUsize::new(16).unwrap()));
+}
+
+/// A part of a `like` pattern
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum LikePart {
+    /// `%`
+    Many,
+    /// `_`
+    One,
+    Char(char),
+}
+
+impl LikePart {
+    fn parse(pattern: &str, escape: char) -> Vec<LikePart> {
+        let mut chars = pattern.chars();
+        let mut parts = vec![];
+        while let Some(c) = chars.next() {
+            parts.push(match c {
+                c if c == escape => LikePart::Char(chars.next().unwrap_or(escape)),
+                '%' => LikePart::Many,
+                '_' => LikePart::One,
+                c => LikePart::Char(c),
+            });
+        }
+        parts
+    }
+}
+
+/// The collations columns can be declared with
+static COLLATIONS: Lazy<[Collation; 2]> = Lazy::new(|| [Collation::utf8(), Collation::utf8_ci()]);
+
 /// Default charsets
 impl Collation {
     pub fn utf8() -> Self {
         Collation::new("utf8", [])
     }
+
+    /// Like [utf8](Self::utf8), but upper and lower case ascii letters are equal
+    pub fn utf8_ci() -> Self {
+        Collation::new(
+            "utf8_ci",
+            ('a'..='z').flat_map(|c| [(c, c as u32), (c.to_ascii_uppercase(), c as u32)]),
+        )
+    }
+
+    /// Gets the collation strings are compared in when their column doesn't declare one
+    pub fn default_collation() -> &'static Collation {
+        &COLLATIONS[0]
+    }
+
+    /// Gets a collation columns can be declared with by its name
+    pub fn named(name: &str) -> Option<&'static Collation> {
+        COLLATIONS
+            .iter()
+            .find(|collation| collation.name.eq_ignore_ascii_case(name))
+    }
 }
 
 impl Display for Collation {
@@ -92,3 +267,63 @@ impl Display for Collation {
         write!(f, "{}", self.name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::data::charset::Collation;
+
+    #[test]
+    fn like() {
+        let utf8 = Collation::utf8();
+        assert!(utf8.like("hello", "hello", '\\'));
+        assert!(utf8.like("hello", "h%", '\\'));
+        assert!(utf8.like("hello", "%l%o", '\\'));
+        assert!(utf8.like("hello", "h_l_o", '\\'));
+        assert!(utf8.like("", "%", '\\'));
+        assert!(!utf8.like("hello", "h_l", '\\'));
+        assert!(!utf8.like("hello", "%x%", '\\'));
+        assert!(!utf8.like("hello", "HELLO", '\\'));
+
+        assert!(utf8.like("100%", "100\\%", '\\'));
+        assert!(!utf8.like("1000", "100\\%", '\\'));
+        assert!(utf8.like("a_b", "a!_b", '!'));
+        assert!(!utf8.like("axb", "a!_b", '!'));
+    }
+
+    #[test]
+    fn like_with_collation() {
+        let collation = Collation::utf8_ci();
+        assert!(collation.like("hello", "HE%", '\\'));
+        assert!(collation.like("HeLLo", "%ll_", '\\'));
+        assert!(!collation.like("hello", "HE", '\\'));
+    }
+
+    #[test]
+    fn like_prefix() {
+        assert_eq!(Collation::like_prefix("abc%", '\\'), "abc");
+        assert_eq!(Collation::like_prefix("a\\%c_d", '\\'), "a%c");
+        assert_eq!(Collation::like_prefix("%abc", '\\'), "");
+    }
+
+    #[test]
+    fn char_order_bounds() {
+        assert_eq!(
+            Collation::utf8().char_order_bounds("aB1"),
+            ("aB1".to_string(), "aB1".to_string())
+        );
+        assert_eq!(
+            Collation::utf8_ci().char_order_bounds("aB1"),
+            ("AB1".to_string(), "ab1".to_string())
+        );
+    }
+
+    #[test]
+    fn regexp() {
+        let utf8 = Collation::utf8();
+        assert!(utf8.regexp("hello world", "o w").unwrap());
+        assert!(utf8.regexp("hello", "^h[a-z]+o$").unwrap());
+        assert!(!utf8.regexp("hello", "^H").unwrap());
+        assert!(Collation::utf8_ci().regexp("hello", "^H").unwrap());
+        assert!(utf8.regexp("hello", "(").is_err());
+    }
+}
@@ -0,0 +1,438 @@
+//! Exact fixed point numbers
+
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use weaver_ast::ast::DecimalType;
+
+use crate::error::WeaverError;
+
+/// The most digits a decimal can have
+pub const MAX_PRECISION: u8 = DecimalType::MAX_PRECISION;
+
+/// The fewest digits after the decimal point a quotient is given, so that dividing integers
+/// doesn't truncate
+const MIN_DIVISION_SCALE: u8 = 6;
+
+/// A decimal number, stored as an integer that is scaled down by `10^scale`.
+///
+/// Decimals are compared by their values, so `1.5` and `1.50` are equal even though their scales
+/// differ.
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
+pub struct Decimal {
+    #[serde(with = "unscaled_string")]
+    unscaled: i128,
+    scale: u8,
+}
+
+/// Serializes the unscaled value as a string, as 128-bit integers can't be deserialized from an
+/// untagged [`DbVal`](crate::data::values::DbVal)
+mod unscaled_string {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(unscaled: &i128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(unscaled)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i128, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+impl Decimal {
+    /// Creates a decimal of `unscaled * 10^-scale`
+    pub fn new(unscaled: i128, scale: u8) -> Self {
+        Self { unscaled, scale }
+    }
+
+    /// The value of this decimal without the decimal point
+    pub fn unscaled(&self) -> i128 {
+        self.unscaled
+    }
+
+    /// The number of digits after the decimal point
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    /// The number of digits in this decimal, including the ones after the decimal point
+    pub fn digits(&self) -> u8 {
+        let mut digits = 0;
+        let mut remaining = self.unscaled.unsigned_abs();
+        while remaining > 0 {
+            digits += 1;
+            remaining /= 10;
+        }
+        digits
+    }
+
+    /// Checks whether this decimal can be stored as a `decimal(precision, scale)` without losing
+    /// any digits
+    pub fn fits(&self, precision: u8, scale: u8) -> bool {
+        self.scale == scale && self.digits() <= precision
+    }
+
+    /// Changes the number of digits after the decimal point, rounding half away from zero if the
+    /// scale is reduced. Gives `None` if the value no longer fits in a decimal.
+    pub fn rescale(&self, scale: u8) -> Option<Self> {
+        let unscaled = match scale.cmp(&self.scale) {
+            Ordering::Equal => self.unscaled,
+            Ordering::Greater => self.unscaled.checked_mul(pow10(scale - self.scale)?)?,
+            Ordering::Less => {
+                let divisor = pow10(self.scale - scale)?;
+                let quotient = self.unscaled / divisor;
+                let remainder = self.unscaled % divisor;
+                if remainder.unsigned_abs() * 2 >= divisor.unsigned_abs() {
+                    quotient + self.unscaled.signum()
+                } else {
+                    quotient
+                }
+            }
+        };
+        Some(Self::new(unscaled, scale)).filter(|decimal| decimal.digits() <= MAX_PRECISION)
+    }
+
+    /// Adds two decimals, giving a result with the given scale
+    pub fn checked_add(&self, other: &Self, scale: u8) -> Option<Self> {
+        let common = self.scale.max(other.scale);
+        let sum = self
+            .rescale(common)?
+            .unscaled
+            .checked_add(other.rescale(common)?.unscaled)?;
+        Self::new(sum, common).rescale(scale)
+    }
+
+    /// Subtracts a decimal from this one, giving a result with the given scale
+    pub fn checked_sub(&self, other: &Self, scale: u8) -> Option<Self> {
+        self.checked_add(&other.checked_neg()?, scale)
+    }
+
+    /// Multiplies two decimals, giving a result with the given scale
+    pub fn checked_mul(&self, other: &Self, scale: u8) -> Option<Self> {
+        let product = self.unscaled.checked_mul(other.unscaled)?;
+        Self::new(product, self.scale + other.scale).rescale(scale)
+    }
+
+    /// Divides this decimal by another by long division, giving a result with the given scale that
+    /// is rounded half away from zero. Gives `None` if the divisor is zero.
+    pub fn checked_div(&self, other: &Self, scale: u8) -> Option<Self> {
+        if other.unscaled == 0 {
+            return None;
+        }
+        // scales the dividend so the quotient of the unscaled values has the right scale, plus
+        // one more digit that is used for rounding
+        let shift = scale as i16 + other.scale as i16 - self.scale as i16 + 1;
+        let (dividend, shift) = if shift < 0 {
+            (
+                self.rescale(self.scale - shift.unsigned_abs() as u8)?
+                    .unscaled,
+                0,
+            )
+        } else {
+            (self.unscaled, shift as u8)
+        };
+        let divisor = other.unscaled;
+        let mut quotient = dividend / divisor;
+        let mut remainder = dividend % divisor;
+        for _ in 0..shift {
+            let carried = remainder.checked_mul(10)?;
+            quotient = quotient.checked_mul(10)?.checked_add(carried / divisor)?;
+            remainder = carried % divisor;
+        }
+        let rounded = quotient / 10 + (quotient % 10) / 5;
+        Some(Self::new(rounded, scale)).filter(|decimal| decimal.digits() <= MAX_PRECISION)
+    }
+
+    pub fn checked_neg(&self) -> Option<Self> {
+        Some(Self::new(self.unscaled.checked_neg()?, self.scale))
+    }
+
+    /// The integer part of this decimal
+    pub fn trunc(&self) -> i128 {
+        self.unscaled / 10_i128.pow(self.scale as u32)
+    }
+
+    /// Gets the nearest float to this decimal
+    pub fn to_f64(&self) -> f64 {
+        self.to_string()
+            .parse()
+            .expect("decimals are always valid floats")
+    }
+
+    /// Converts a float to a decimal using the fewest digits that still read back as the same
+    /// float, so that `0.1` becomes `0.1` and not `0.1000000000000000055511151231257827`
+    pub fn from_f64(float: f64) -> Option<Self> {
+        if !float.is_finite() {
+            return None;
+        }
+        float.to_string().parse().ok()
+    }
+
+    /// The integer and fractional parts of this decimal, with the fractional part scaled to
+    /// `scale` digits, so that decimals of any two scales can be compared without overflowing
+    fn parts(&self, scale: u8) -> (i128, i128) {
+        let divisor = 10_i128.pow(self.scale as u32);
+        (
+            self.unscaled.div_euclid(divisor),
+            self.unscaled.rem_euclid(divisor) * 10_i128.pow((scale - self.scale) as u32),
+        )
+    }
+}
+
+/// Gets `10^exp`, if it fits in an `i128`
+fn pow10(exp: u8) -> Option<i128> {
+    10_i128.checked_pow(exp as u32)
+}
+
+/// Gets the `(precision, scale)` of the result of adding, subtracting, multiplying or dividing
+/// decimals, following the usual rules for exact numerics. When the precision would exceed
+/// [`MAX_PRECISION`], the scale is reduced to make room for the integer digits but is kept at
+/// least 6 digits (or the natural scale, if smaller).
+pub fn result_precision(op: ResultOp, left: (u8, u8), right: (u8, u8)) -> (u8, u8) {
+    let ((p1, s1), (p2, s2)) = (
+        (left.0 as i16, left.1 as i16),
+        (right.0 as i16, right.1 as i16),
+    );
+    let (precision, scale) = match op {
+        ResultOp::Add => {
+            let scale = s1.max(s2);
+            ((p1 - s1).max(p2 - s2) + scale + 1, scale)
+        }
+        ResultOp::Multiply => (p1 + p2 + 1, s1 + s2),
+        ResultOp::Divide => {
+            let scale = (MIN_DIVISION_SCALE as i16).max(s1 + p2 + 1);
+            (p1 - s1 + s2 + scale, scale)
+        }
+    };
+    let max = MAX_PRECISION as i16;
+    if precision <= max {
+        return (precision as u8, scale as u8);
+    }
+    let integer_digits = precision - scale;
+    let scale = (max - integer_digits)
+        .max(scale.min(MIN_DIVISION_SCALE as i16))
+        .min(scale)
+        .max(0);
+    (MAX_PRECISION, scale as u8)
+}
+
+/// The kinds of arithmetic that give decimals different precisions. Subtraction is the same as
+/// addition.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ResultOp {
+    Add,
+    Multiply,
+    Divide,
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        self.parts(scale).cmp(&other.parts(scale))
+    }
+}
+
+impl Hash for Decimal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // equal decimals must hash the same, so trailing zeros are ignored
+        let (mut unscaled, mut scale) = (self.unscaled, self.scale);
+        while scale > 0 && unscaled % 10 == 0 {
+            unscaled /= 10;
+            scale -= 1;
+        }
+        unscaled.hash(state);
+        scale.hash(state);
+    }
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let digits = self.unscaled.unsigned_abs().to_string();
+        let sign = if self.unscaled < 0 { "-" } else { "" };
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{sign}{digits}");
+        }
+        let digits = format!("{digits:0>width$}", width = scale + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+        write!(f, "{sign}{integer}.{fraction}")
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = WeaverError;
+
+    /// Parses decimals like `-12.50`, `.5` or `1e-3`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || WeaverError::ParseError(s.to_string());
+        let trimmed = s.trim();
+        let (mantissa, exponent) = match trimmed.find(['e', 'E']) {
+            Some(idx) => (
+                &trimmed[..idx],
+                trimmed[idx + 1..].parse::<i16>().map_err(|_| invalid())?,
+            ),
+            None => (trimmed, 0),
+        };
+        let (negative, mantissa) = match mantissa.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, mantissa.strip_prefix('+').unwrap_or(mantissa)),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if integer.is_empty() && fraction.is_empty()
+            || !integer
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+        let digits = format!("{integer}{fraction}");
+        let digits = digits.trim_start_matches('0');
+        if digits.len() > MAX_PRECISION as usize {
+            return Err(invalid());
+        }
+        let unscaled = if digits.is_empty() {
+            0
+        } else {
+            digits.parse::<i128>().map_err(|_| invalid())?
+        };
+        let unscaled = if negative { -unscaled } else { unscaled };
+        let scale = fraction.len() as i16 - exponent;
+        if scale >= 0 {
+            let scale = u8::try_from(scale)
+                .ok()
+                .filter(|scale| *scale <= MAX_PRECISION)
+                .ok_or_else(invalid)?;
+            Ok(Self::new(unscaled, scale))
+        } else {
+            Self::new(unscaled, 0)
+                .checked_mul(
+                    &Self::new(pow10(scale.unsigned_abs() as u8).ok_or_else(invalid)?, 0),
+                    0,
+                )
+                .ok_or_else(invalid)
+        }
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Self {
+        Self::new(value as i128, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{result_precision, Decimal, ResultOp};
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().expect("valid decimal")
+    }
+
+    #[test]
+    fn parse_and_display() {
+        for (input, expected) in [
+            ("12.50", "12.50"),
+            ("-0.05", "-0.05"),
+            (".5", "0.5"),
+            ("+7", "7"),
+            ("1.5e2", "150"),
+            ("25e-3", "0.025"),
+            ("00012", "12"),
+        ] {
+            assert_eq!(dec(input).to_string(), expected, "{input}");
+        }
+        for invalid in [
+            "",
+            ".",
+            "1.2.3",
+            "abc",
+            "1e",
+            "123456789012345678901234567890123456789",
+        ] {
+            assert!(invalid.parse::<Decimal>().is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn compare_across_scales() {
+        assert_eq!(dec("1.5"), dec("1.500"));
+        assert!(dec("-1.5") < dec("-1.25"));
+        assert!(dec("0.1") > dec("0.09999"));
+        assert!(dec("10") > dec("9.999999999999999999999999999999999999"));
+    }
+
+    #[test]
+    fn rescale_rounds_half_away_from_zero() {
+        assert_eq!(dec("1.235").rescale(2), Some(dec("1.24")));
+        assert_eq!(dec("-1.235").rescale(2), Some(dec("-1.24")));
+        assert_eq!(dec("1.234").rescale(2), Some(dec("1.23")));
+        assert_eq!(
+            dec("1.2").rescale(3).map(|d| d.to_string()),
+            Some("1.200".to_string())
+        );
+    }
+
+    #[test]
+    fn exact_arithmetic() {
+        assert_eq!(
+            dec("0.1").checked_add(&dec("0.2"), 1).unwrap().to_string(),
+            "0.3"
+        );
+        assert_eq!(
+            dec("19.99").checked_mul(&dec("3"), 2).unwrap().to_string(),
+            "59.97"
+        );
+        assert_eq!(
+            dec("10.00").checked_div(&dec("3"), 6).unwrap().to_string(),
+            "3.333333"
+        );
+        assert_eq!(
+            dec("2").checked_div(&dec("3"), 2).unwrap().to_string(),
+            "0.67"
+        );
+        assert_eq!(
+            dec("-2").checked_div(&dec("3"), 2).unwrap().to_string(),
+            "-0.67"
+        );
+        assert_eq!(dec("1").checked_div(&dec("0"), 2), None);
+    }
+
+    #[test]
+    fn result_precisions() {
+        assert_eq!(result_precision(ResultOp::Add, (10, 2), (5, 3)), (12, 3));
+        assert_eq!(
+            result_precision(ResultOp::Multiply, (10, 2), (5, 3)),
+            (16, 5)
+        );
+        assert_eq!(
+            result_precision(ResultOp::Divide, (10, 2), (10, 2)),
+            (23, 13)
+        );
+        assert_eq!(
+            result_precision(ResultOp::Divide, (38, 10), (38, 10)),
+            (38, 6),
+            "the scale should be reduced to make room for integer digits"
+        );
+    }
+}
@@ -0,0 +1,262 @@
+//! Json documents
+
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::data::values::DbVal;
+use crate::error::WeaverError;
+
+/// A json document, kept in a normalized form without any insignificant whitespace and with the
+/// members of every object sorted by key.
+///
+/// Documents are compared by their normalized text, so documents that only differ in formatting or
+/// in the order of their members are equal.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct Json {
+    json: String,
+}
+
+/// A step of a json path, like `.key` or `[0]`
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum PathStep {
+    Key(String),
+    Index(i64),
+}
+
+impl Json {
+    /// Wraps text that's already normalized, such as text read back from storage
+    pub(crate) fn from_normalized(json: String) -> Self {
+        Self { json }
+    }
+
+    /// The normalized text of this document
+    pub fn as_str(&self) -> &str {
+        &self.json
+    }
+
+    /// Parses this document
+    pub fn value(&self) -> Value {
+        serde_json::from_str(&self.json).expect("json is validated when it's created")
+    }
+
+    /// Gets the member of an object with the given key
+    pub fn get(&self, key: &str) -> Option<Json> {
+        match self.value() {
+            Value::Object(mut object) => object.remove(key).map(Json::from),
+            _ => None,
+        }
+    }
+
+    /// Gets the element of an array at the given index, where negative indices count from the end
+    pub fn index(&self, index: i64) -> Option<Json> {
+        match self.value() {
+            Value::Array(mut array) => {
+                let index = if index < 0 {
+                    array.len().checked_sub(index.unsigned_abs() as usize)?
+                } else {
+                    index as usize
+                };
+                (index < array.len()).then(|| Json::from(array.swap_remove(index)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Gets the document at a path like `$.items[0].name`, where `$` is this document. Keys that
+    /// aren't plain identifiers can be quoted, as in `$."first name"`.
+    ///
+    /// Gives `None` if nothing is found at the path.
+    pub fn extract(&self, path: &str) -> Result<Option<Json>, WeaverError> {
+        let steps = parse_path(path)?;
+        let mut value = self.value();
+        for step in steps {
+            let next = match (value, step) {
+                (Value::Object(mut object), PathStep::Key(key)) => object.remove(&key),
+                (Value::Array(mut array), PathStep::Index(index)) => {
+                    let index = index as usize;
+                    (index < array.len()).then(|| array.swap_remove(index))
+                }
+                _ => None,
+            };
+            match next {
+                Some(next) => value = next,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(Json::from(value)))
+    }
+
+    /// Gets this document as text, which is the string itself for json strings and the normalized
+    /// text of anything else. Gives `None` for json nulls.
+    pub fn text(&self) -> Option<String> {
+        match self.value() {
+            Value::Null => None,
+            Value::String(s) => Some(s),
+            _ => Some(self.json.clone()),
+        }
+    }
+
+    /// Gets the number of elements in this document, if it's an array
+    pub fn array_length(&self) -> Option<i64> {
+        match self.value() {
+            Value::Array(array) => Some(array.len() as i64),
+            _ => None,
+        }
+    }
+
+    /// Gets an array of the keys of this document, if it's an object
+    pub fn object_keys(&self) -> Option<Json> {
+        match self.value() {
+            Value::Object(object) => Some(Json::from(Value::Array(
+                object
+                    .into_iter()
+                    .map(|(key, _)| Value::String(key))
+                    .collect(),
+            ))),
+            _ => None,
+        }
+    }
+}
+
+/// Parses the steps of a json path, which must start at the root `$`
+fn parse_path(path: &str) -> Result<Vec<PathStep>, WeaverError> {
+    let invalid = || WeaverError::InvalidJsonPath(path.to_string());
+    let mut rest = path.trim().strip_prefix('$').ok_or_else(invalid)?;
+    let mut steps = vec![];
+    while !rest.is_empty() {
+        if let Some(quoted) = rest.strip_prefix(".\"") {
+            let end = quoted.find('"').ok_or_else(invalid)?;
+            steps.push(PathStep::Key(quoted[..end].to_string()));
+            rest = &quoted[end + 1..];
+        } else if let Some(key) = rest.strip_prefix('.') {
+            let end = key.find(['.', '[']).unwrap_or(key.len());
+            if end == 0 {
+                return Err(invalid());
+            }
+            steps.push(PathStep::Key(key[..end].to_string()));
+            rest = &key[end..];
+        } else if let Some(index) = rest.strip_prefix('[') {
+            let end = index.find(']').ok_or_else(invalid)?;
+            let index_value = index[..end].trim().parse::<u32>().map_err(|_| invalid())?;
+            steps.push(PathStep::Index(index_value as i64));
+            rest = &index[end + 1..];
+        } else {
+            return Err(invalid());
+        }
+    }
+    Ok(steps)
+}
+
+/// Gets a value as a json document. Strings are parsed, so text holding json can be used wherever
+/// a document is expected.
+///
+/// Gives `None` for nulls.
+pub fn as_json(value: &DbVal) -> Result<Option<Cow<'_, Json>>, WeaverError> {
+    match value {
+        DbVal::Null => Ok(None),
+        DbVal::Json(json) => Ok(Some(Cow::Borrowed(json))),
+        DbVal::String(s, _) => s.parse().map(|json| Some(Cow::Owned(json))),
+        other => Err(WeaverError::InvalidOperand {
+            op: "json".to_string(),
+            operand: other.clone(),
+        }),
+    }
+}
+
+impl From<Value> for Json {
+    fn from(value: Value) -> Self {
+        Self {
+            json: value.to_string(),
+        }
+    }
+}
+
+impl FromStr for Json {
+    type Err = WeaverError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str::<Value>(s)
+            .map(Json::from)
+            .map_err(|e| WeaverError::InvalidJson(e.to_string()))
+    }
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Json;
+
+    fn json(s: &str) -> Json {
+        s.parse().expect("valid json")
+    }
+
+    #[test]
+    fn normalizes() {
+        assert_eq!(
+            json(r#" { "b" : [1, 2], "a": {"d": null, "c": "x"} } "#).to_string(),
+            r#"{"a":{"c":"x","d":null},"b":[1,2]}"#
+        );
+        assert_eq!(json(r#"{"a":1,"b":2}"#), json(r#"{"b": 2, "a": 1}"#));
+        for invalid in ["", "{", "{'a': 1}", "[1,]", "nope"] {
+            assert!(invalid.parse::<Json>().is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn get_members() {
+        let doc = json(r#"{"name": "weaver", "tags": ["db", "rust"], "meta": null}"#);
+        assert_eq!(
+            doc.get("name").and_then(|name| name.text()),
+            Some("weaver".to_string())
+        );
+        assert_eq!(doc.get("tags"), Some(json(r#"["db","rust"]"#)));
+        assert_eq!(doc.get("meta"), Some(json("null")));
+        assert_eq!(doc.get("meta").and_then(|meta| meta.text()), None);
+        assert_eq!(doc.get("missing"), None);
+        let tags = doc.get("tags").unwrap();
+        assert_eq!(tags.index(1), Some(json(r#""rust""#)));
+        assert_eq!(tags.index(-2), Some(json(r#""db""#)));
+        assert_eq!(tags.index(2), None);
+        assert_eq!(tags.index(-3), None);
+        assert_eq!(tags.get("db"), None);
+    }
+
+    #[test]
+    fn extract_paths() {
+        let doc = json(r#"{"a": {"b": [10, {"c": true}]}, "first name": "x"}"#);
+        for (path, expected) in [
+            ("$", Some(doc.clone())),
+            ("$.a.b[0]", Some(json("10"))),
+            ("$.a.b[1].c", Some(json("true"))),
+            ("$.\"first name\"", Some(json(r#""x""#))),
+            ("$.a.b[2]", None),
+            ("$.a.missing", None),
+            ("$.a[0]", None),
+        ] {
+            assert_eq!(doc.extract(path).unwrap(), expected, "{path}");
+        }
+        for invalid in ["", "a.b", "$.", "$..a", "$[x]", "$[0", "$.\"a"] {
+            assert!(doc.extract(invalid).is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn arrays_and_objects() {
+        assert_eq!(json("[1, [2, 3], {}]").array_length(), Some(3));
+        assert_eq!(json("{}").array_length(), None);
+        assert_eq!(
+            json(r#"{"b": 1, "a": 2}"#).object_keys(),
+            Some(json(r#"["a","b"]"#))
+        );
+        assert_eq!(json("[1]").object_keys(), None);
+    }
+}
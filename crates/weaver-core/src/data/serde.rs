@@ -1,10 +1,15 @@
+use chrono::{Datelike, NaiveDate, TimeZone, Utc};
 use nom::bytes::complete::take;
 use nom::combinator::map;
 use nom::error::ParseError;
 use nom::{Finish, IResult};
 use tracing::trace;
+use uuid::Uuid;
 
+use crate::data::decimal::Decimal;
+use crate::data::json::Json;
 use crate::data::row::Row;
+use crate::data::temporal::{micros_to_time, time_to_micros, Interval};
 use crate::data::types::Type;
 use crate::data::values::DbVal;
 use crate::storage::ReadDataError;
@@ -61,6 +66,38 @@ impl DataSerializer {
             DbVal::Float(float) => {
                 self.bytes.extend(float.to_be_bytes());
             }
+            DbVal::Decimal(decimal, _) => {
+                // the scale comes from the type, which every value of a column shares
+                self.bytes.extend(sortable_i128(decimal.unscaled()));
+            }
+            DbVal::Date(date) => {
+                self.bytes.extend(sortable_i32(date.num_days_from_ce()));
+            }
+            DbVal::Time(time) => {
+                self.bytes.extend(time_to_micros(time).to_be_bytes());
+            }
+            DbVal::Timestamp(timestamp) => {
+                self.bytes
+                    .extend(sortable_i64(timestamp.and_utc().timestamp_micros()));
+            }
+            DbVal::TimestampTz(timestamp) => {
+                self.bytes
+                    .extend(sortable_i64(timestamp.timestamp_micros()));
+            }
+            DbVal::Interval(interval) => {
+                // the length comes first so intervals are sorted by it
+                self.bytes.extend(sortable_i128(interval.total_micros()));
+                self.bytes.extend(sortable_i32(interval.months));
+                self.bytes.extend(sortable_i32(interval.days));
+                self.bytes.extend(sortable_i64(interval.micros));
+            }
+            DbVal::Json(json) => {
+                self.bytes
+                    .extend((json.as_str().len() as u32).to_be_bytes());
+                self.bytes.extend(json.as_str().bytes());
+            }
+            // big endian bytes already sort like the uuids they hold
+            DbVal::Uuid(uuid) => self.bytes.extend(uuid.as_bytes()),
             DbVal::Null => {}
         }
     }
@@ -79,6 +116,40 @@ const FLOAT_DISC: u8 = 2;
 const BOOLEAN_DISC: u8 = 3;
 const STRING_DISC: u8 = 4;
 const BINARY_DISC: u8 = 5;
+const DATE_DISC: u8 = 6;
+const TIME_DISC: u8 = 7;
+const TIMESTAMP_DISC: u8 = 8;
+const TIMESTAMP_TZ_DISC: u8 = 9;
+const INTERVAL_DISC: u8 = 10;
+const DECIMAL_DISC: u8 = 11;
+const JSON_DISC: u8 = 12;
+const UUID_DISC: u8 = 13;
+
+/// Encodes a signed integer with its sign bit flipped, so that the encoded bytes sort in the same
+/// order as the integers. Temporal values are encoded this way so they work as b+ tree keys.
+fn sortable_i32(i: i32) -> [u8; 4] {
+    ((i as u32) ^ (1 << 31)).to_be_bytes()
+}
+
+fn sortable_i64(i: i64) -> [u8; 8] {
+    ((i as u64) ^ (1 << 63)).to_be_bytes()
+}
+
+fn sortable_i128(i: i128) -> [u8; 16] {
+    ((i as u128) ^ (1 << 127)).to_be_bytes()
+}
+
+fn from_sortable_i32(bytes: &[u8]) -> i32 {
+    (u32::from_be_bytes(bytes.try_into().expect("4 bytes")) ^ (1 << 31)) as i32
+}
+
+fn from_sortable_i64(bytes: &[u8]) -> i64 {
+    (u64::from_be_bytes(bytes.try_into().expect("8 bytes")) ^ (1 << 63)) as i64
+}
+
+fn from_sortable_i128(bytes: &[u8]) -> i128 {
+    (u128::from_be_bytes(bytes.try_into().expect("16 bytes")) ^ (1 << 127)) as i128
+}
 
 fn serialize_type(ty: Type) -> Box<[u8]> {
     match ty {
@@ -97,6 +168,14 @@ fn serialize_type(ty: Type) -> Box<[u8]> {
         Type::Integer => Box::new([INTEGER_DISC]),
         Type::Boolean => Box::new([BOOLEAN_DISC]),
         Type::Float => Box::new([FLOAT_DISC]),
+        Type::Decimal(precision, scale) => Box::new([DECIMAL_DISC, precision, scale]),
+        Type::Date => Box::new([DATE_DISC]),
+        Type::Time => Box::new([TIME_DISC]),
+        Type::Timestamp => Box::new([TIMESTAMP_DISC]),
+        Type::TimestampTz => Box::new([TIMESTAMP_TZ_DISC]),
+        Type::Interval => Box::new([INTERVAL_DISC]),
+        Type::Json => Box::new([JSON_DISC]),
+        Type::Uuid => Box::new([UUID_DISC]),
     }
 }
 
@@ -168,6 +247,12 @@ impl DataDeserializer {
                     buffer = rest;
                     output.push(DbVal::Binary(Vec::from(bytes), max));
                 }
+                Some(Type::Json) => {
+                    let (rest, bytes) = parse_byte_string(buffer).finish()?;
+                    buffer = rest;
+                    let s = String::from_utf8(Vec::from(bytes))?;
+                    output.push(DbVal::Json(Json::from_normalized(s)));
+                }
                 Some(Type::Integer) => {
                     let (rest, bytes) =
                         take::<_, _, nom::error::Error<_>>(8_usize)(buffer).finish()?;
@@ -188,6 +273,62 @@ impl DataDeserializer {
                     let float_be: [u8; 8] = bytes.try_into().unwrap();
                     output.push(DbVal::Float(f64::from_be_bytes(float_be)))
                 }
+                Some(Type::Decimal(precision, scale)) => {
+                    let (rest, bytes) =
+                        take::<_, _, nom::error::Error<_>>(16_usize)(buffer).finish()?;
+                    buffer = rest;
+                    output.push(DbVal::Decimal(
+                        Decimal::new(from_sortable_i128(bytes), scale),
+                        precision,
+                    ))
+                }
+                Some(Type::Date) => {
+                    let (rest, bytes) =
+                        take::<_, _, nom::error::Error<_>>(4_usize)(buffer).finish()?;
+                    buffer = rest;
+                    let date = NaiveDate::from_num_days_from_ce_opt(from_sortable_i32(bytes))
+                        .ok_or(ReadDataError::TemporalOutOfRange)?;
+                    output.push(DbVal::Date(date))
+                }
+                Some(Type::Time) => {
+                    let (rest, bytes) =
+                        take::<_, _, nom::error::Error<_>>(8_usize)(buffer).finish()?;
+                    buffer = rest;
+                    let micros = i64::from_be_bytes(bytes.try_into().unwrap());
+                    let time = micros_to_time(micros).ok_or(ReadDataError::TemporalOutOfRange)?;
+                    output.push(DbVal::Time(time))
+                }
+                Some(ty @ (Type::Timestamp | Type::TimestampTz)) => {
+                    let (rest, bytes) =
+                        take::<_, _, nom::error::Error<_>>(8_usize)(buffer).finish()?;
+                    buffer = rest;
+                    let timestamp = Utc
+                        .timestamp_micros(from_sortable_i64(bytes))
+                        .single()
+                        .ok_or(ReadDataError::TemporalOutOfRange)?;
+                    output.push(if ty == Type::Timestamp {
+                        DbVal::Timestamp(timestamp.naive_utc())
+                    } else {
+                        DbVal::TimestampTz(timestamp)
+                    })
+                }
+                Some(Type::Interval) => {
+                    let (rest, bytes) =
+                        take::<_, _, nom::error::Error<_>>(32_usize)(buffer).finish()?;
+                    buffer = rest;
+                    // skips the length, which is only there for ordering
+                    output.push(DbVal::Interval(Interval::new(
+                        from_sortable_i32(&bytes[16..20]),
+                        from_sortable_i32(&bytes[20..24]),
+                        from_sortable_i64(&bytes[24..32]),
+                    )))
+                }
+                Some(Type::Uuid) => {
+                    let (rest, bytes) =
+                        take::<_, _, nom::error::Error<_>>(16_usize)(buffer).finish()?;
+                    buffer = rest;
+                    output.push(DbVal::Uuid(Uuid::from_slice(bytes).expect("took 16 bytes")));
+                }
                 None => {
                     output.push(DbVal::Null);
                 }
@@ -213,6 +354,13 @@ fn parse_type(bytes: &[u8]) -> IResult<&[u8], Option<Type>> {
         INTEGER_DISC => Ok((bytes, Some(Type::Integer))),
         FLOAT_DISC => Ok((bytes, Some(Type::Float))),
         BOOLEAN_DISC => Ok((bytes, Some(Type::Boolean))),
+        DATE_DISC => Ok((bytes, Some(Type::Date))),
+        TIME_DISC => Ok((bytes, Some(Type::Time))),
+        TIMESTAMP_DISC => Ok((bytes, Some(Type::Timestamp))),
+        TIMESTAMP_TZ_DISC => Ok((bytes, Some(Type::TimestampTz))),
+        INTERVAL_DISC => Ok((bytes, Some(Type::Interval))),
+        JSON_DISC => Ok((bytes, Some(Type::Json))),
+        UUID_DISC => Ok((bytes, Some(Type::Uuid))),
         STRING_DISC => {
             let (rest, max_len) = u16_parser()(bytes)?;
             Ok((rest, Some(Type::String(max_len))))
@@ -221,6 +369,10 @@ fn parse_type(bytes: &[u8]) -> IResult<&[u8], Option<Type>> {
             let (rest, max_len) = u16_parser()(bytes)?;
             Ok((rest, Some(Type::Binary(max_len))))
         }
+        DECIMAL_DISC => {
+            let (rest, digits) = take(2_usize)(bytes)?;
+            Ok((rest, Some(Type::Decimal(digits[0], digits[1]))))
+        }
         _disc => panic!("unknown type discriminant: {_disc}"),
     }
 }
@@ -307,6 +459,7 @@ mod tests {
     use crate::data::serde::{
         parse_byte_string, serialize_data_typed, serialize_data_untyped, DataSerializer, SerdeMode,
     };
+    use crate::data::temporal::{parse_date, parse_time, parse_timestamp, parse_timestamp_tz};
     use crate::data::types::Type;
     use crate::data::values::DbVal;
     use crate::key::KeyData;
@@ -353,4 +506,105 @@ mod tests {
         let row_de = Row::from(read);
         assert_eq!(row, row_de);
     }
+
+    #[test]
+    fn temporal_encodings_preserve_order() {
+        let sorted = [
+            vec![
+                DbVal::Date(parse_date("0044-03-15").unwrap()),
+                DbVal::Date(parse_date("1969-12-31").unwrap()),
+                DbVal::Date(parse_date("2024-02-29").unwrap()),
+            ],
+            vec![
+                DbVal::Time(parse_time("00:00:00").unwrap()),
+                DbVal::Time(parse_time("09:30:00.5").unwrap()),
+                DbVal::Time(parse_time("23:59:59.999999").unwrap()),
+            ],
+            vec![
+                DbVal::Timestamp(parse_timestamp("1969-12-31 23:59:59").unwrap()),
+                DbVal::Timestamp(parse_timestamp("1970-01-01 00:00:00").unwrap()),
+                DbVal::Timestamp(parse_timestamp("2024-01-01 12:00:00.000001").unwrap()),
+            ],
+            vec![
+                DbVal::TimestampTz(parse_timestamp_tz("1900-01-01 00:00:00+05").unwrap()),
+                DbVal::TimestampTz(parse_timestamp_tz("2024-01-01 12:00:00+01").unwrap()),
+                DbVal::TimestampTz(parse_timestamp_tz("2024-01-01 12:00:00Z").unwrap()),
+            ],
+            vec![
+                DbVal::Interval("-1 day".parse().unwrap()),
+                DbVal::Interval("23:00:00".parse().unwrap()),
+                DbVal::Interval("1 mon".parse().unwrap()),
+                DbVal::Interval("1 mon 1 day".parse().unwrap()),
+            ],
+        ];
+        for values in sorted {
+            let ty = values[0].value_type().unwrap();
+            let encoded = values
+                .iter()
+                .map(|val| serialize_data_untyped(Row::from([val.clone()])))
+                .collect::<Vec<_>>();
+            assert!(
+                encoded.windows(2).all(|pair| pair[0] < pair[1]),
+                "{ty} values should encode in order"
+            );
+            for (val, bytes) in values.iter().zip(encoded) {
+                let read =
+                    super::deserialize_data_untyped(&bytes, [ty]).expect("could not deserialize");
+                assert_eq!(&read[0], val);
+            }
+        }
+    }
+
+    #[test]
+    fn decimal_encoding_preserves_order() {
+        let ty = Type::Decimal(12, 2);
+        let values = [
+            "-1000.50",
+            "-0.01",
+            "0.00",
+            "0.10",
+            "12.34",
+            "9999999999.99",
+        ]
+        .map(|s| ty.parse_value(s).unwrap());
+        let encoded = values
+            .iter()
+            .map(|val| serialize_data_untyped(Row::from([val.clone()])))
+            .collect::<Vec<_>>();
+        assert!(encoded.windows(2).all(|pair| pair[0] < pair[1]));
+        for (val, bytes) in values.iter().zip(encoded) {
+            let read =
+                super::deserialize_data_untyped(&bytes, [ty]).expect("could not deserialize");
+            assert_eq!(read[0].to_string(), val.to_string());
+        }
+
+        let typed = serialize_data_typed(Row::from([values[4].clone()]));
+        let read = super::deserialize_data_typed(&typed).expect("could not deserialize");
+        assert_eq!(read[0].value_type(), Some(ty));
+    }
+
+    #[test]
+    fn uuid_encoding_preserves_order() {
+        let values = [
+            "00000000-0000-0000-0000-000000000000",
+            "0f8fad5b-d9cb-469f-a165-70867728950e",
+            "7c9e6679-7425-40de-944b-e07fc1f90ae7",
+            "ffffffff-ffff-ffff-ffff-ffffffffffff",
+        ]
+        .map(|s| Type::Uuid.parse_value(s).unwrap());
+        let encoded = values
+            .iter()
+            .map(|val| serialize_data_untyped(Row::from([val.clone()])))
+            .collect::<Vec<_>>();
+        assert!(encoded.windows(2).all(|pair| pair[0] < pair[1]));
+        for (val, bytes) in values.iter().zip(encoded) {
+            let read = super::deserialize_data_untyped(&bytes, [Type::Uuid])
+                .expect("could not deserialize");
+            assert_eq!(&read[0], val);
+        }
+
+        let typed = serialize_data_typed(Row::from([values[1].clone(), DbVal::Null]));
+        let read = super::deserialize_data_typed(&typed).expect("could not deserialize");
+        assert_eq!(read, [values[1].clone(), DbVal::Null]);
+    }
 }
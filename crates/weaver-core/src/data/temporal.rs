@@ -0,0 +1,503 @@
+//! Dates, times, timestamps and intervals
+
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use chrono::{
+    DateTime, Datelike, Days, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, SubsecRound,
+    TimeZone, Timelike, Utc,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::WeaverError;
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+const MICROS_PER_MINUTE: i64 = 60 * MICROS_PER_SECOND;
+const MICROS_PER_HOUR: i64 = 60 * MICROS_PER_MINUTE;
+const MICROS_PER_DAY: i64 = 24 * MICROS_PER_HOUR;
+/// Months are compared and divided as if they were always 30 days long
+const DAYS_PER_MONTH: i64 = 30;
+
+/// A span of time, kept as separate months, days and microseconds because neither months nor days
+/// have a fixed length.
+///
+/// Intervals are ordered by their length when months are 30 days long and days are 24 hours long.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub micros: i64,
+}
+
+impl Interval {
+    pub fn new(months: i32, days: i32, micros: i64) -> Self {
+        Self {
+            months,
+            days,
+            micros,
+        }
+    }
+
+    /// Gets the interval between two timestamps, as whole days and the microseconds left over
+    pub fn between(start: NaiveDateTime, end: NaiveDateTime) -> Result<Self, WeaverError> {
+        let micros = (end - start)
+            .num_microseconds()
+            .ok_or(WeaverError::TemporalOutOfRange)?;
+        let days =
+            i32::try_from(micros / MICROS_PER_DAY).map_err(|_| WeaverError::TemporalOutOfRange)?;
+        Ok(Self::new(0, days, micros % MICROS_PER_DAY))
+    }
+
+    /// The length of this interval in microseconds, using 30 day months
+    pub fn total_micros(&self) -> i128 {
+        (self.months as i128 * DAYS_PER_MONTH as i128 + self.days as i128) * MICROS_PER_DAY as i128
+            + self.micros as i128
+    }
+
+    pub fn checked_neg(&self) -> Option<Self> {
+        Some(Self::new(
+            self.months.checked_neg()?,
+            self.days.checked_neg()?,
+            self.micros.checked_neg()?,
+        ))
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(Self::new(
+            self.months.checked_add(other.months)?,
+            self.days.checked_add(other.days)?,
+            self.micros.checked_add(other.micros)?,
+        ))
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.checked_add(&other.checked_neg()?)
+    }
+
+    /// Scales this interval, spilling fractional months into days and fractional days into
+    /// microseconds
+    pub fn checked_mul(&self, factor: f64) -> Option<Self> {
+        let months = self.months as f64 * factor;
+        let days = self.days as f64 * factor + months.fract() * DAYS_PER_MONTH as f64;
+        let micros = self.micros as f64 * factor + days.fract() * MICROS_PER_DAY as f64;
+        let in_range =
+            |value: f64, min: f64, max: f64| value.is_finite() && (min..=max).contains(&value);
+        if !in_range(months, i32::MIN as f64, i32::MAX as f64)
+            || !in_range(days, i32::MIN as f64, i32::MAX as f64)
+            || !in_range(micros, i64::MIN as f64, i64::MAX as f64)
+        {
+            return None;
+        }
+        Some(Self::new(
+            months.trunc() as i32,
+            days.trunc() as i32,
+            micros.round() as i64,
+        ))
+    }
+
+    /// Adds this interval to a timestamp, adding the months first, then the days and then the
+    /// microseconds
+    pub fn add_to(&self, timestamp: NaiveDateTime) -> Result<NaiveDateTime, WeaverError> {
+        let with_months = if self.months >= 0 {
+            timestamp.checked_add_months(Months::new(self.months.unsigned_abs()))
+        } else {
+            timestamp.checked_sub_months(Months::new(self.months.unsigned_abs()))
+        };
+        let with_days = with_months.and_then(|timestamp| {
+            if self.days >= 0 {
+                timestamp.checked_add_days(Days::new(self.days.unsigned_abs() as u64))
+            } else {
+                timestamp.checked_sub_days(Days::new(self.days.unsigned_abs() as u64))
+            }
+        });
+        with_days
+            .and_then(|timestamp| timestamp.checked_add_signed(Duration::microseconds(self.micros)))
+            .ok_or(WeaverError::TemporalOutOfRange)
+    }
+}
+
+impl PartialOrd for Interval {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Interval {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // equally long intervals are still ordered by their parts to stay consistent with eq
+        self.total_micros()
+            .cmp(&other.total_micros())
+            .then_with(|| {
+                (self.months, self.days, self.micros).cmp(&(other.months, other.days, other.micros))
+            })
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let plural = |count: i64| if count.abs() == 1 { "" } else { "s" };
+        let mut parts = vec![];
+        let (years, months) = (self.months / 12, self.months % 12);
+        if years != 0 {
+            parts.push(format!("{years} year{}", plural(years as i64)));
+        }
+        if months != 0 {
+            parts.push(format!("{months} mon{}", plural(months as i64)));
+        }
+        if self.days != 0 {
+            parts.push(format!("{} day{}", self.days, plural(self.days as i64)));
+        }
+        if self.micros != 0 || parts.is_empty() {
+            let sign = if self.micros < 0 { "-" } else { "" };
+            let micros = self.micros.unsigned_abs();
+            let (hours, micros) = (
+                micros / MICROS_PER_HOUR as u64,
+                micros % MICROS_PER_HOUR as u64,
+            );
+            let (minutes, micros) = (
+                micros / MICROS_PER_MINUTE as u64,
+                micros % MICROS_PER_MINUTE as u64,
+            );
+            let (seconds, micros) = (
+                micros / MICROS_PER_SECOND as u64,
+                micros % MICROS_PER_SECOND as u64,
+            );
+            let mut time = format!("{sign}{hours:02}:{minutes:02}:{seconds:02}");
+            if micros != 0 {
+                time.push_str(format!(".{micros:06}").trim_end_matches('0'));
+            }
+            parts.push(time);
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl FromStr for Interval {
+    type Err = WeaverError;
+
+    /// Parses intervals like `1 year 2 months`, `3 days 04:05:06` or `-1.5 hours`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || WeaverError::ParseError(s.to_string());
+        let mut interval = Interval::default();
+        let mut months = 0.0;
+        let mut days = 0.0;
+        let mut micros = 0.0;
+        let mut words = s.split_whitespace().peekable();
+        if words.peek().is_none() {
+            return Err(invalid());
+        }
+        while let Some(word) = words.next() {
+            if word.contains(':') {
+                let (sign, time) = match word.strip_prefix('-') {
+                    Some(time) => (-1.0, time),
+                    None => (1.0, word.trim_start_matches('+')),
+                };
+                let mut fields = time.split(':');
+                let hours = fields.next().and_then(|f| f.parse::<u32>().ok());
+                let minutes = fields.next().and_then(|f| f.parse::<u32>().ok());
+                let seconds = fields.next().map_or(Some(0.0), |f| f.parse::<f64>().ok());
+                let (Some(hours), Some(minutes), Some(seconds), None) =
+                    (hours, minutes, seconds, fields.next())
+                else {
+                    return Err(invalid());
+                };
+                micros += sign
+                    * (hours as f64 * MICROS_PER_HOUR as f64
+                        + minutes as f64 * MICROS_PER_MINUTE as f64
+                        + seconds * MICROS_PER_SECOND as f64);
+                continue;
+            }
+
+            let amount = word.parse::<f64>().map_err(|_| invalid())?;
+            let unit = words.next().ok_or_else(invalid)?.to_lowercase();
+            let singular = match unit.as_str() {
+                "ms" | "us" => &unit,
+                unit => unit.trim_end_matches('s'),
+            };
+            match singular {
+                "year" | "y" => months += amount * 12.0,
+                "mon" | "month" => months += amount,
+                "week" | "w" => days += amount * 7.0,
+                "day" | "d" => days += amount,
+                "hour" | "h" => micros += amount * MICROS_PER_HOUR as f64,
+                "min" | "minute" | "m" => micros += amount * MICROS_PER_MINUTE as f64,
+                "sec" | "second" | "" => micros += amount * MICROS_PER_SECOND as f64,
+                "ms" | "millisecond" => micros += amount * 1000.0,
+                "us" | "microsecond" => micros += amount,
+                _ => return Err(invalid()),
+            }
+        }
+
+        // fractional months and days are spilled into the smaller units
+        interval.months = months.trunc() as i32;
+        days += months.fract() * DAYS_PER_MONTH as f64;
+        interval.days = days.trunc() as i32;
+        micros += days.fract() * MICROS_PER_DAY as f64;
+        if !micros.is_finite() || micros.abs() >= i64::MAX as f64 {
+            return Err(invalid());
+        }
+        interval.micros = micros.round() as i64;
+        Ok(interval)
+    }
+}
+
+/// Parses a date like `2024-02-29`
+pub fn parse_date(s: &str) -> Result<NaiveDate, WeaverError> {
+    NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+        .map_err(|_| WeaverError::ParseError(s.to_string()))
+}
+
+/// Parses a time of day like `13:45`, `13:45:30` or `13:45:30.25`, keeping microseconds
+pub fn parse_time(s: &str) -> Result<NaiveTime, WeaverError> {
+    ["%H:%M:%S%.f", "%H:%M"]
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(s.trim(), format).ok())
+        .map(|time| time.trunc_subsecs(6))
+        .ok_or_else(|| WeaverError::ParseError(s.to_string()))
+}
+
+/// Parses a timestamp without a time zone, like `2024-02-29 13:45:30`. The time is optional and
+/// the date and time may also be separated by a `T`.
+pub fn parse_timestamp(s: &str) -> Result<NaiveDateTime, WeaverError> {
+    let s = s.trim();
+    [
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+    .or_else(|| parse_date(s).ok().map(|date| date.and_time(NaiveTime::MIN)))
+    .map(|timestamp| timestamp.trunc_subsecs(6))
+    .ok_or_else(|| WeaverError::ParseError(s.to_string()))
+}
+
+/// Parses a timestamp with an optional utc offset, like `2024-02-29 13:45:30+02:00` or
+/// `2024-02-29T11:45:30Z`. Timestamps without an offset are in utc.
+pub fn parse_timestamp_tz(s: &str) -> Result<DateTime<Utc>, WeaverError> {
+    let s = s.trim();
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .or_else(|| {
+            ["%Y-%m-%d %H:%M:%S%.f%#z", "%Y-%m-%dT%H:%M:%S%.f%#z"]
+                .iter()
+                .find_map(|format| DateTime::parse_from_str(s, format).ok())
+        })
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .or_else(|| parse_timestamp(s).ok().map(|timestamp| timestamp.and_utc()))
+        .map(|timestamp| timestamp.trunc_subsecs(6))
+        .ok_or_else(|| WeaverError::ParseError(s.to_string()))
+}
+
+/// The current time, to the microsecond as that's the precision timestamps are stored with
+pub fn now() -> DateTime<Utc> {
+    Utc::now().trunc_subsecs(6)
+}
+
+/// Converts seconds since the unix epoch into a timestamp
+pub fn from_epoch_seconds(seconds: f64) -> Result<DateTime<Utc>, WeaverError> {
+    let micros = seconds * MICROS_PER_SECOND as f64;
+    if !micros.is_finite() || micros.abs() >= i64::MAX as f64 {
+        return Err(WeaverError::TemporalOutOfRange);
+    }
+    Utc.timestamp_micros(micros.round() as i64)
+        .single()
+        .ok_or(WeaverError::TemporalOutOfRange)
+}
+
+/// Truncates a timestamp to the start of the given unit, from `microseconds` up to `millennium`
+pub fn truncate(timestamp: NaiveDateTime, unit: &str) -> Result<NaiveDateTime, WeaverError> {
+    let date = timestamp.date();
+    let time = timestamp.time();
+    let start_of_day = |date: NaiveDate| Some(date.and_time(NaiveTime::MIN));
+    let truncated = match unit.to_lowercase().as_str() {
+        "microsecond" | "microseconds" => Some(timestamp.trunc_subsecs(6)),
+        "millisecond" | "milliseconds" => Some(timestamp.trunc_subsecs(3)),
+        "second" => Some(timestamp.trunc_subsecs(0)),
+        "minute" => time
+            .with_second(0)
+            .map(|time| date.and_time(time.trunc_subsecs(0))),
+        "hour" => time
+            .with_minute(0)
+            .and_then(|time| time.with_second(0))
+            .map(|time| date.and_time(time.trunc_subsecs(0))),
+        "day" => start_of_day(date),
+        "week" => start_of_day(date - Days::new(date.weekday().num_days_from_monday() as u64)),
+        "month" => date.with_day(1).and_then(start_of_day),
+        "quarter" => date
+            .with_day(1)
+            .and_then(|date| date.with_month0(date.month0() / 3 * 3))
+            .and_then(start_of_day),
+        "year" => date.with_ordinal(1).and_then(start_of_day),
+        "decade" => {
+            NaiveDate::from_ymd_opt(date.year().div_euclid(10) * 10, 1, 1).and_then(start_of_day)
+        }
+        "century" => NaiveDate::from_ymd_opt((date.year() - 1).div_euclid(100) * 100 + 1, 1, 1)
+            .and_then(start_of_day),
+        "millennium" => {
+            NaiveDate::from_ymd_opt((date.year() - 1).div_euclid(1000) * 1000 + 1, 1, 1)
+                .and_then(start_of_day)
+        }
+        _ => return Err(WeaverError::UnknownTemporalUnit(unit.to_string())),
+    };
+    truncated.ok_or(WeaverError::TemporalOutOfRange)
+}
+
+/// Extracts a field, like the `year` or the `epoch` in seconds, from a timestamp
+pub fn extract(timestamp: NaiveDateTime, field: &str) -> Result<f64, WeaverError> {
+    let seconds = timestamp.second() as f64 + timestamp.nanosecond() as f64 / 1e9;
+    Ok(match field.to_lowercase().as_str() {
+        "microsecond" | "microseconds" => seconds * 1e6,
+        "millisecond" | "milliseconds" => seconds * 1e3,
+        "second" => seconds,
+        "minute" => timestamp.minute() as f64,
+        "hour" => timestamp.hour() as f64,
+        "day" => timestamp.day() as f64,
+        "dow" => timestamp.weekday().num_days_from_sunday() as f64,
+        "isodow" => timestamp.weekday().number_from_monday() as f64,
+        "doy" => timestamp.ordinal() as f64,
+        "week" => timestamp.iso_week().week() as f64,
+        "month" => timestamp.month() as f64,
+        "quarter" => (timestamp.month0() / 3 + 1) as f64,
+        "year" => timestamp.year() as f64,
+        "epoch" => timestamp.and_utc().timestamp_micros() as f64 / 1e6,
+        _ => return Err(WeaverError::UnknownTemporalUnit(field.to_string())),
+    })
+}
+
+/// Extracts a field from an interval, where the `epoch` is its total length in seconds
+pub fn extract_from_interval(interval: &Interval, field: &str) -> Result<f64, WeaverError> {
+    let seconds_of_minute = (interval.micros % MICROS_PER_MINUTE) as f64 / 1e6;
+    Ok(match field.to_lowercase().as_str() {
+        "microsecond" | "microseconds" => seconds_of_minute * 1e6,
+        "millisecond" | "milliseconds" => seconds_of_minute * 1e3,
+        "second" => seconds_of_minute,
+        "minute" => (interval.micros % MICROS_PER_HOUR / MICROS_PER_MINUTE) as f64,
+        "hour" => (interval.micros / MICROS_PER_HOUR) as f64,
+        "day" => interval.days as f64,
+        "month" => (interval.months % 12) as f64,
+        "year" => (interval.months / 12) as f64,
+        "epoch" => interval.total_micros() as f64 / 1e6,
+        _ => return Err(WeaverError::UnknownTemporalUnit(field.to_string())),
+    })
+}
+
+/// Microseconds since midnight
+pub fn time_to_micros(time: &NaiveTime) -> i64 {
+    time.num_seconds_from_midnight() as i64 * MICROS_PER_SECOND + time.nanosecond() as i64 / 1000
+}
+
+/// Adds microseconds to a time of day, wrapping around midnight
+pub fn add_micros_to_time(time: &NaiveTime, micros: i64) -> NaiveTime {
+    let micros = (time_to_micros(time) + micros % MICROS_PER_DAY).rem_euclid(MICROS_PER_DAY);
+    micros_to_time(micros).expect("always within a day")
+}
+
+/// The time of day the given microseconds after midnight
+pub fn micros_to_time(micros: i64) -> Option<NaiveTime> {
+    NaiveTime::from_num_seconds_from_midnight_opt(
+        u32::try_from(micros / MICROS_PER_SECOND).ok()?,
+        u32::try_from(micros % MICROS_PER_SECOND).ok()? * 1000,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::data::temporal::{parse_timestamp, parse_timestamp_tz, truncate, Interval};
+
+    #[test]
+    fn parse_intervals() {
+        assert_eq!(
+            "1 year 2 mons 3 days 04:05:06.5"
+                .parse::<Interval>()
+                .unwrap(),
+            Interval::new(14, 3, 14_706_500_000)
+        );
+        assert_eq!(
+            "1.5 days".parse::<Interval>().unwrap(),
+            Interval::new(0, 1, 12 * 3_600_000_000)
+        );
+        assert_eq!(
+            "-2 hours 30 minutes".parse::<Interval>().unwrap(),
+            Interval::new(0, 0, -90 * 60_000_000)
+        );
+        assert!("3 fortnights".parse::<Interval>().is_err());
+        assert!("".parse::<Interval>().is_err());
+    }
+
+    #[test]
+    fn display_intervals() {
+        for interval in [
+            "1 year 2 mons 3 days 04:05:06.5",
+            "-2 days -01:00:00",
+            "00:00:00",
+        ] {
+            assert_eq!(interval.parse::<Interval>().unwrap().to_string(), interval);
+        }
+    }
+
+    #[test]
+    fn order_intervals() {
+        let month = "1 month".parse::<Interval>().unwrap();
+        let days = "31 days".parse::<Interval>().unwrap();
+        let hours = "25 hours".parse::<Interval>().unwrap();
+        assert!(month < days);
+        assert!("1 day".parse::<Interval>().unwrap() < hours);
+        assert_ne!(month, "30 days".parse::<Interval>().unwrap());
+    }
+
+    #[test]
+    fn add_intervals() {
+        let timestamp = parse_timestamp("2024-01-31 12:00").unwrap();
+        let interval = "1 month 1 day 01:00".parse::<Interval>().unwrap();
+        assert_eq!(
+            interval.add_to(timestamp).unwrap(),
+            parse_timestamp("2024-03-01 13:00").unwrap(),
+            "months are added before days"
+        );
+    }
+
+    #[test]
+    fn parse_timestamps() {
+        let expected = NaiveDate::from_ymd_opt(2024, 2, 29)
+            .unwrap()
+            .and_hms_opt(11, 45, 30)
+            .unwrap();
+        assert_eq!(parse_timestamp("2024-02-29T11:45:30").unwrap(), expected);
+        for timestamp in [
+            "2024-02-29 13:45:30+02:00",
+            "2024-02-29T11:45:30Z",
+            "2024-02-29 11:45:30",
+        ] {
+            assert_eq!(
+                parse_timestamp_tz(timestamp).unwrap().naive_utc(),
+                expected,
+                "{timestamp}"
+            );
+        }
+    }
+
+    #[test]
+    fn truncate_timestamps() {
+        let timestamp = parse_timestamp("2024-05-17 13:45:30.25").unwrap();
+        for (unit, expected) in [
+            ("second", "2024-05-17 13:45:30"),
+            ("hour", "2024-05-17 13:00"),
+            ("week", "2024-05-13"),
+            ("quarter", "2024-04-01"),
+            ("year", "2024-01-01"),
+            ("century", "2001-01-01"),
+        ] {
+            assert_eq!(
+                truncate(timestamp, unit).unwrap(),
+                parse_timestamp(expected).unwrap(),
+                "{unit}"
+            );
+        }
+        assert!(truncate(timestamp, "fortnight").is_err());
+    }
+}
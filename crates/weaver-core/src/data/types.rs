@@ -3,17 +3,46 @@ use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
+use chrono::NaiveTime;
 use weaver_ast::ast;
 use weaver_ast::ast::{
-    BinaryOp, ColumnRef, DataType, Expr, FunctionArgs, VarBinaryType, VarCharType,
+    BinaryOp, ColumnRef, DataType, DecimalType, Expr, Literal, TimestampType, VarBinaryType,
+    VarCharType,
 };
 
+use crate::data::decimal::{result_precision, Decimal, ResultOp, MAX_PRECISION};
+
+use crate::data::temporal::{
+    from_epoch_seconds, parse_date, parse_time, parse_timestamp, parse_timestamp_tz, Interval,
+};
 use crate::data::values::DbVal;
 use crate::error::WeaverError;
-use crate::queries::execution::evaluation::functions::{ArgType, FunctionRegistry};
-use crate::queries::execution::evaluation::{find_function, FunctionKind};
+use crate::queries::execution::evaluation::functions::FunctionRegistry;
+use crate::queries::execution::evaluation::window::window_function_type;
+use crate::queries::execution::evaluation::{
+    arg_types, find_function, unplanned_sub_query, FunctionKind,
+};
 use crate::storage::tables::table_schema::TableSchema;
 
+/// The type of a value stored in the database.
+///
+/// When values of different types meet, such as the operands of an arithmetic expression or the
+/// results of a `case`, they are promoted to their [common type](Self::common_type) along the
+/// following lattice:
+///
+/// ```text
+/// boolean -> int -> decimal(p, s) -> float
+/// string(n) -> binary(n)
+/// date -> timestamp -> timestamp with time zone
+/// ```
+///
+/// Strings and binaries also widen to any longer length, decimals widen to any precision and scale
+/// with at least as many digits on either side of the decimal point, and timestamps without a time zone are
+/// taken to be in utc. Promotions never lose information, so they are applied implicitly. The
+/// reverse edges, from binaries to strings and from ints to booleans, are only taken when the value
+/// is representable in the narrower type, and [casts](Self::cast) can convert between any types
+/// whose values can be parsed from each other. Strings are also parsed when they're assigned to or
+/// compared with a decimal, json, uuid or [temporal](Self::is_temporal) value.
 #[derive(Debug, Deserialize, Serialize, Hash, Eq, PartialEq, Copy, Clone)]
 pub enum Type {
     String(u16),
@@ -21,6 +50,17 @@ pub enum Type {
     Integer,
     Boolean,
     Float,
+    /// A fixed point number with a precision and a scale
+    Decimal(u8, u8),
+    Date,
+    Time,
+    Timestamp,
+    TimestampTz,
+    Interval,
+    /// A [json document](crate::data::json::Json)
+    Json,
+    /// A uuid, stored as its 16 bytes so keys sort in byte order
+    Uuid,
 }
 
 impl Display for Type {
@@ -31,6 +71,14 @@ impl Display for Type {
             Type::Integer => write!(f, "int"),
             Type::Boolean => write!(f, "boolean"),
             Type::Float => write!(f, "float"),
+            Type::Decimal(precision, scale) => write!(f, "decimal({precision}, {scale})"),
+            Type::Date => write!(f, "date"),
+            Type::Time => write!(f, "time"),
+            Type::Timestamp => write!(f, "timestamp"),
+            Type::TimestampTz => write!(f, "timestamp with time zone"),
+            Type::Interval => write!(f, "interval"),
+            Type::Json => write!(f, "json"),
+            Type::Uuid => write!(f, "uuid"),
         }
     }
 }
@@ -45,11 +93,289 @@ impl Type {
             (Integer, DbVal::Integer(..)) => true,
             (Boolean, DbVal::Boolean(..)) => true,
             (Float, DbVal::Float(..)) => true,
+            (Decimal(precision, scale), DbVal::Decimal(decimal, _)) => {
+                decimal.fits(*precision, *scale)
+            }
+            (Date, DbVal::Date(..)) => true,
+            (Time, DbVal::Time(..)) => true,
+            (Timestamp, DbVal::Timestamp(..)) => true,
+            (TimestampTz, DbVal::TimestampTz(..)) => true,
+            (Interval, DbVal::Interval(..)) => true,
+            (Json, DbVal::Json(..)) => true,
+            (Uuid, DbVal::Uuid(..)) => true,
             (_, DbVal::Null) => true,
             _ => false,
         }
     }
 
+    /// Gets the narrowest type both this and the `other` type are promoted to, if there is one
+    pub fn common_type(&self, other: &Type) -> Option<Type> {
+        use Type::*;
+        match (*self, *other) {
+            (String(len), String(other_len)) => Some(String(len.max(other_len))),
+            (String(len) | Binary(len), String(other_len) | Binary(other_len)) => {
+                Some(Binary(len.max(other_len)))
+            }
+            (Boolean, Integer) | (Integer, Boolean) => Some(Integer),
+            (Decimal(..), Decimal(..) | Boolean | Integer) | (Boolean | Integer, Decimal(..)) => {
+                let (p1, s1) = self.decimal_digits()?;
+                let (p2, s2) = other.decimal_digits()?;
+                let scale = s1.max(s2);
+                Some(Decimal(
+                    ((p1 - s1).max(p2 - s2) + scale).min(MAX_PRECISION),
+                    scale,
+                ))
+            }
+            (Decimal(..), Float) | (Float, Decimal(..)) => Some(Float),
+            (Boolean | Integer, Float) | (Float, Boolean | Integer) => Some(Float),
+            (Date, Timestamp) | (Timestamp, Date) => Some(Timestamp),
+            (Date | Timestamp, TimestampTz) | (TimestampTz, Date | Timestamp) => Some(TimestampTz),
+            (this, other) if this == other => Some(this),
+            _ => None,
+        }
+    }
+
+    /// Gets the type of the result of applying an arithmetic operator to values of this and the
+    /// `other` type, if the operator can be applied to them.
+    ///
+    /// Besides numbers and concatenation, dates can be moved by days, intervals can be added to and
+    /// subtracted from points in time, and subtracting two points in time gives the interval
+    /// between them.
+    pub fn arithmetic(&self, op: &BinaryOp, other: &Type) -> Option<Type> {
+        use BinaryOp::{Divide, Minus, Multiply, Plus};
+        use Type::*;
+        match (*self, op, *other) {
+            (Date, Plus | Minus, Integer) | (Integer, Plus, Date) => Some(Date),
+            (Date, Minus, Date) => Some(Integer),
+            (Time, Plus | Minus, Interval) | (Interval, Plus, Time) => Some(Time),
+            (Time, Minus, Time) => Some(Interval),
+            (Date | Timestamp, Plus | Minus, Interval) | (Interval, Plus, Date | Timestamp) => {
+                Some(Timestamp)
+            }
+            (TimestampTz, Plus | Minus, Interval) | (Interval, Plus, TimestampTz) => {
+                Some(TimestampTz)
+            }
+            (Date | Timestamp | TimestampTz, Minus, Date | Timestamp | TimestampTz) => {
+                Some(Interval)
+            }
+            (Decimal(..), _, Decimal(..) | Boolean | Integer)
+            | (Boolean | Integer, _, Decimal(..)) => {
+                let op = match op {
+                    Plus | Minus => ResultOp::Add,
+                    Multiply => ResultOp::Multiply,
+                    _ => ResultOp::Divide,
+                };
+                let (precision, scale) =
+                    result_precision(op, self.decimal_digits()?, other.decimal_digits()?);
+                Some(Decimal(precision, scale))
+            }
+            (Interval, Plus | Minus, Interval)
+            | (Interval, Multiply | Divide, Integer | Float | Decimal(..))
+            | (Integer | Float | Decimal(..), Multiply, Interval) => Some(Interval),
+            (this, _, other) if this.is_temporal() || other.is_temporal() => None,
+            (Json | Uuid, _, _) | (_, _, Json | Uuid) => None,
+            (this, _, other) => this.common_type(&other),
+        }
+    }
+
+    /// The precision and scale of the narrowest decimal that can hold every value of this type,
+    /// if there is one
+    fn decimal_digits(&self) -> Option<(u8, u8)> {
+        match self {
+            Type::Boolean => Some((1, 0)),
+            // i64::MAX has 19 digits
+            Type::Integer => Some((19, 0)),
+            Type::Decimal(precision, scale) => Some((*precision, *scale)),
+            _ => None,
+        }
+    }
+
+    /// Checks whether this is a date, time, timestamp or interval
+    pub fn is_temporal(&self) -> bool {
+        matches!(
+            self,
+            Type::Date | Type::Time | Type::Timestamp | Type::TimestampTz | Type::Interval
+        )
+    }
+
+    /// Checks whether this is a date or a timestamp, with or without a time zone
+    pub fn is_point_in_time(&self) -> bool {
+        matches!(self, Type::Date | Type::Timestamp | Type::TimestampTz)
+    }
+
+    /// Checks whether every value of this type can be stored as the `other` type
+    pub fn widens_to(&self, other: &Type) -> bool {
+        self.common_type(other).as_ref() == Some(other)
+    }
+
+    /// Checks whether values of this type can be [coerced](Self::coerce) into the `other` type. The
+    /// length, precision or format of each value is only checked once it's coerced.
+    pub fn coerces_to(&self, other: &Type) -> bool {
+        use Type::*;
+        match (*self, *other) {
+            (this, other) if this.widens_to(&other) => true,
+            (String(_) | Binary(_), String(_) | Binary(_)) => true,
+            (Boolean | Integer | Float | Decimal(..), Decimal(..)) => true,
+            (Integer, Boolean) => true,
+            (String(_), other) => other.is_temporal() || matches!(other, Json | Uuid),
+            _ => false,
+        }
+    }
+
+    /// Converts a value of a type that [widens](Self::widens_to) to this type into a value of this
+    /// type
+    pub fn widen(&self, val: DbVal) -> DbVal {
+        match (self, val) {
+            (Type::String(len), DbVal::String(s, _)) => DbVal::String(s, *len),
+            (Type::Binary(len), DbVal::Binary(b, _)) => DbVal::Binary(b, *len),
+            (Type::Binary(len), DbVal::String(s, _)) => DbVal::Binary(s.into_bytes(), *len),
+            (Type::Integer, DbVal::Boolean(b)) => DbVal::Integer(b as i64),
+            (Type::Float, DbVal::Integer(i)) => DbVal::Float(i as f64),
+            (Type::Float, DbVal::Boolean(b)) => DbVal::Float(b as u8 as f64),
+            (Type::Float, DbVal::Decimal(decimal, _)) => DbVal::Float(decimal.to_f64()),
+            (
+                Type::Decimal(precision, scale),
+                val @ (DbVal::Integer(_) | DbVal::Boolean(_) | DbVal::Decimal(..)),
+            ) => {
+                let decimal = match val {
+                    DbVal::Integer(i) => Decimal::from(i),
+                    DbVal::Boolean(b) => Decimal::from(b as i64),
+                    DbVal::Decimal(decimal, _) => decimal,
+                    _ => unreachable!(),
+                };
+                match decimal.rescale(*scale) {
+                    Some(decimal) => DbVal::Decimal(decimal, *precision),
+                    None => DbVal::Decimal(decimal, *precision),
+                }
+            }
+            (Type::Timestamp, DbVal::Date(date)) => DbVal::Timestamp(date.and_time(NaiveTime::MIN)),
+            (Type::TimestampTz, DbVal::Date(date)) => {
+                DbVal::TimestampTz(date.and_time(NaiveTime::MIN).and_utc())
+            }
+            (Type::TimestampTz, DbVal::Timestamp(timestamp)) => {
+                DbVal::TimestampTz(timestamp.and_utc())
+            }
+            (_, val) => val,
+        }
+    }
+
+    /// Coerces a value being assigned to a column of this type. Values are promoted along the
+    /// lattice, and binaries and ints are narrowed back to strings and booleans when they are
+    /// valid utf-8 or either `0` or `1`.
+    pub fn coerce(&self, val: DbVal) -> Result<DbVal, WeaverError> {
+        let coerced = match (self, val) {
+            (_, val) if self.validate(&val) => return Ok(val),
+            (Type::String(len), DbVal::Binary(b, b_len)) => match String::from_utf8(b) {
+                Ok(s) => DbVal::String(s, *len),
+                Err(e) => DbVal::Binary(e.into_bytes(), b_len),
+            },
+            (Type::Boolean, DbVal::Integer(i @ (0 | 1))) => DbVal::Boolean(i == 1),
+            (Type::Json, DbVal::String(s, _)) => DbVal::Json(s.parse()?),
+            (Type::Uuid, DbVal::String(s, _)) => self.parse_value(s)?,
+            (Type::Decimal(precision, scale), DbVal::Float(f)) => {
+                match Decimal::from_f64(f).and_then(|decimal| decimal.rescale(*scale)) {
+                    Some(decimal) => DbVal::Decimal(decimal, *precision),
+                    None => DbVal::Float(f),
+                }
+            }
+            (ty, DbVal::String(s, len)) if ty.is_temporal() || matches!(ty, Type::Decimal(..)) => {
+                match ty.parse_value(&s) {
+                    Ok(parsed) => parsed,
+                    Err(_) => DbVal::String(s, len),
+                }
+            }
+            (_, val) => self.widen(val),
+        };
+        if !self.validate(&coerced) {
+            return Err(WeaverError::TypeError {
+                expected: *self,
+                actual: coerced,
+            });
+        }
+        Ok(coerced)
+    }
+
+    /// Explicitly converts a value to this type, as done by `cast(value as type)`. Unlike
+    /// [coercion](Self::coerce), floats are truncated to ints, strings are parsed and every value
+    /// can be displayed as a string. Strings longer than this type allows are not truncated.
+    pub fn cast(&self, val: DbVal) -> Result<DbVal, WeaverError> {
+        let invalid = |val: &DbVal| WeaverError::InvalidCast(val.clone(), *self);
+        let cast = match (self, &val) {
+            (_, DbVal::Null) => DbVal::Null,
+            (Type::String(len), DbVal::Binary(b, _)) => DbVal::String(
+                std::str::from_utf8(b)
+                    .map_err(|_| invalid(&val))?
+                    .to_string(),
+                *len,
+            ),
+            (Type::String(len), other) => DbVal::String(other.to_string(), *len),
+            (Type::Binary(len), DbVal::String(s, _)) => DbVal::Binary(s.as_bytes().to_vec(), *len),
+            (Type::Integer, DbVal::Float(f)) => {
+                let truncated = f.trunc();
+                if !(i64::MIN as f64..i64::MAX as f64).contains(&truncated) {
+                    return Err(invalid(&val));
+                }
+                DbVal::Integer(truncated as i64)
+            }
+            (Type::Integer, DbVal::Decimal(decimal, _)) => {
+                DbVal::Integer(i64::try_from(decimal.trunc()).map_err(|_| invalid(&val))?)
+            }
+            (Type::Decimal(precision, scale), DbVal::Float(f)) => DbVal::Decimal(
+                Decimal::from_f64(*f)
+                    .and_then(|decimal| decimal.rescale(*scale))
+                    .ok_or_else(|| invalid(&val))?,
+                *precision,
+            ),
+            (Type::Decimal(..), DbVal::String(s, _)) => {
+                self.parse_value(s).map_err(|_| invalid(&val))?
+            }
+            (Type::Decimal(..), DbVal::Integer(_) | DbVal::Boolean(_) | DbVal::Decimal(..)) => {
+                self.widen(val.clone())
+            }
+            (Type::Integer, DbVal::String(s, _)) => {
+                DbVal::Integer(s.trim().parse().map_err(|_| invalid(&val))?)
+            }
+            (Type::Float, DbVal::String(s, _)) => {
+                DbVal::Float(s.trim().parse().map_err(|_| invalid(&val))?)
+            }
+            (Type::Boolean, DbVal::Integer(i)) => DbVal::Boolean(*i != 0),
+            (Type::Boolean, DbVal::String(s, _)) => {
+                DbVal::Boolean(s.trim().to_lowercase().parse().map_err(|_| invalid(&val))?)
+            }
+            (ty, DbVal::String(s, _))
+                if ty.is_temporal() || matches!(ty, Type::Json | Type::Uuid) =>
+            {
+                ty.parse_value(s).map_err(|_| invalid(&val))?
+            }
+            (Type::Date, DbVal::Timestamp(timestamp)) => DbVal::Date(timestamp.date()),
+            (Type::Date, DbVal::TimestampTz(timestamp)) => DbVal::Date(timestamp.date_naive()),
+            (Type::Time, DbVal::Timestamp(timestamp)) => DbVal::Time(timestamp.time()),
+            (Type::Time, DbVal::TimestampTz(timestamp)) => DbVal::Time(timestamp.time()),
+            (Type::Timestamp, DbVal::TimestampTz(timestamp)) => {
+                DbVal::Timestamp(timestamp.naive_utc())
+            }
+            (Type::TimestampTz, DbVal::Integer(seconds)) => {
+                DbVal::TimestampTz(from_epoch_seconds(*seconds as f64).map_err(|_| invalid(&val))?)
+            }
+            (Type::TimestampTz, DbVal::Float(seconds)) => {
+                DbVal::TimestampTz(from_epoch_seconds(*seconds).map_err(|_| invalid(&val))?)
+            }
+            (_, other) if other.value_type().is_some_and(|ty| ty.widens_to(self)) => {
+                self.widen(val.clone())
+            }
+            (Type::Binary(len), DbVal::Binary(b, _)) => DbVal::Binary(b.clone(), *len),
+            (Type::Binary(len), DbVal::Uuid(uuid)) => DbVal::Binary(uuid.as_bytes().to_vec(), *len),
+            (Type::Uuid, DbVal::Binary(b, _)) => {
+                DbVal::Uuid(uuid::Uuid::from_slice(b).map_err(|_| invalid(&val))?)
+            }
+            _ => return Err(invalid(&val)),
+        };
+        if !self.validate(&cast) {
+            return Err(invalid(&val));
+        }
+        Ok(cast)
+    }
+
     /// Attempts to parse a string based on the type
     pub fn parse_value<S: AsRef<str>>(&self, s: S) -> Result<DbVal, WeaverError> {
         let db_val: DbVal = match self {
@@ -58,6 +384,19 @@ impl Type {
             Type::Integer => i64::from_str(s.as_ref())?.into(),
             Type::Boolean => bool::from_str(s.as_ref())?.into(),
             Type::Float => f64::from_str(s.as_ref())?.into(),
+            Type::Decimal(precision, scale) => DbVal::Decimal(
+                Decimal::from_str(s.as_ref())?
+                    .rescale(*scale)
+                    .ok_or(WeaverError::NumericOutOfRange(*self))?,
+                *precision,
+            ),
+            Type::Date => parse_date(s.as_ref())?.into(),
+            Type::Time => parse_time(s.as_ref())?.into(),
+            Type::Timestamp => parse_timestamp(s.as_ref())?.into(),
+            Type::TimestampTz => parse_timestamp_tz(s.as_ref())?.into(),
+            Type::Interval => Interval::from_str(s.as_ref())?.into(),
+            Type::Json => DbVal::Json(s.as_ref().parse()?),
+            Type::Uuid => uuid::Uuid::parse_str(s.as_ref().trim())?.into(),
         };
         if !self.validate(&db_val) {
             return Err(WeaverError::TypeError {
@@ -74,9 +413,22 @@ impl From<ast::DataType> for Type {
         match value {
             DataType::Int(_) => Type::Integer,
             DataType::Float(_) => Type::Float,
-            DataType::VarCharType(VarCharType(len)) => Type::String(len as u16),
-            DataType::VarBinaryType(VarBinaryType(len)) => Type::Binary(len as u16),
+            DataType::DecimalType(DecimalType(precision, scale)) => Type::Decimal(precision, scale),
+            DataType::VarCharType(VarCharType(len)) => Type::String(len),
+            DataType::VarBinaryType(VarBinaryType(len)) => Type::Binary(len),
             DataType::BooleanType(_) => Type::Boolean,
+            DataType::DateType(_) => Type::Date,
+            DataType::TimeType(_) => Type::Time,
+            DataType::TimestampType(TimestampType { with_time_zone }) => {
+                if with_time_zone {
+                    Type::TimestampTz
+                } else {
+                    Type::Timestamp
+                }
+            }
+            DataType::IntervalType(_) => Type::Interval,
+            DataType::JsonType(_) => Type::Json,
+            DataType::UuidType(_) => Type::Uuid,
         }
     }
 }
@@ -97,7 +449,7 @@ impl DbTypeOf for DbVal {
         _functions: &FunctionRegistry,
         _context_schema: Option<&TableSchema>,
     ) -> Result<Type, WeaverError> {
-        self.value_type().ok_or_else(|| unreachable!())
+        self.value_type().ok_or(WeaverError::UntypedNull)
     }
 }
 
@@ -131,33 +483,81 @@ impl DbTypeOf for Expr {
                 | BinaryOp::GreaterEq
                 | BinaryOp::LessEq
                 | BinaryOp::And
-                | BinaryOp::Or => Ok(Type::Boolean),
-                BinaryOp::Plus | BinaryOp::Minus | BinaryOp::Multiply | BinaryOp::Divide => left
-                    .type_of(functions, context_schema)
-                    .or(right.type_of(functions, context_schema)),
+                | BinaryOp::Or
+                | BinaryOp::Like { .. }
+                | BinaryOp::NotLike { .. }
+                | BinaryOp::Regexp => Ok(Type::Boolean),
+                BinaryOp::JsonGet => Ok(Type::Json),
+                BinaryOp::JsonGetText => Ok(Type::String(u16::MAX)),
+                // a null takes the type of the other operand
+                BinaryOp::Plus | BinaryOp::Minus | BinaryOp::Multiply | BinaryOp::Divide => {
+                    match (
+                        left.type_of(functions, context_schema),
+                        right.type_of(functions, context_schema),
+                    ) {
+                        (Err(WeaverError::UntypedNull), other)
+                        | (other, Err(WeaverError::UntypedNull)) => other,
+                        (Ok(left), Ok(right)) => left
+                            .arithmetic(op, &right)
+                            .ok_or(WeaverError::IncompatibleTypes(left, right)),
+                        (Err(e), _) | (_, Err(e)) => Err(e),
+                    }
+                }
             },
+            Expr::Cast { data_type, .. } => Ok(Type::from(*data_type)),
             Expr::FunctionCall { function, args } => {
                 let FunctionKind { normal, aggregate } =
                     find_function(functions, function, args, context_schema)?;
 
-                normal
-                    .or(aggregate)
-                    .map(|func| *func.return_type())
-                    .ok_or_else(|| {
-                        let arg_types = match args {
-                            FunctionArgs::Params { exprs, .. } => exprs
-                                .iter()
-                                .map(|i| i.type_of(functions, context_schema))
-                                .flat_map(|i| i.ok())
-                                .map(ArgType::One)
-                                .collect(),
-                            FunctionArgs::Wildcard { .. } => {
-                                vec![ArgType::Rows]
-                            }
-                        };
-                        WeaverError::UnknownFunction(function.to_string(), arg_types)
-                    })
+                match (normal, aggregate) {
+                    (Some(func), _) => {
+                        func.return_type(&arg_types(functions, args, false, context_schema))
+                    }
+                    (None, Some(func)) => {
+                        func.return_type(&arg_types(functions, args, true, context_schema))
+                    }
+                    (None, None) => None,
+                }
+                .ok_or_else(|| {
+                    WeaverError::UnknownFunction(
+                        function.to_string(),
+                        arg_types(functions, args, false, context_schema),
+                    )
+                })
             }
+            // the widest type of the results, ignoring nulls
+            Expr::Case {
+                branches,
+                otherwise,
+                ..
+            } => branches
+                .iter()
+                .map(|(_, result)| result)
+                .chain(otherwise.as_deref())
+                .filter(|result| {
+                    !matches!(
+                        result,
+                        Expr::Literal {
+                            literal: Literal::Null
+                        }
+                    )
+                })
+                .try_fold(None, |common: Option<Type>, result| {
+                    let ty = result.type_of(functions, context_schema)?;
+                    Ok::<_, WeaverError>(Some(match common {
+                        None => ty,
+                        Some(common) => common
+                            .common_type(&ty)
+                            .ok_or(WeaverError::IncompatibleTypes(common, ty))?,
+                    }))
+                })?
+                .ok_or(WeaverError::UntypedNull),
+            Expr::InList { .. }
+            | Expr::Between { .. }
+            | Expr::InSubQuery { .. }
+            | Expr::Exists { .. } => Ok(Type::Boolean),
+            Expr::SubQuery { .. } => Err(unplanned_sub_query(self)),
+            Expr::WindowFunction { .. } => window_function_type(self, functions, context_schema),
         }
     }
 }
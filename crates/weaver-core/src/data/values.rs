@@ -5,12 +5,16 @@ use std::fmt::{Debug, Display, Formatter};
 use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use derive_more::From;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use weaver_ast::ast;
 
+use crate::data::decimal::Decimal;
+use crate::data::json::Json;
+use crate::data::temporal::Interval;
 use crate::data::types::Type;
 use crate::error::WeaverError;
 
@@ -23,6 +27,15 @@ pub enum DbVal {
     Integer(i64),
     Boolean(bool),
     Float(f64),
+    /// A decimal and the precision it's stored with
+    Decimal(Decimal, u8),
+    Date(NaiveDate),
+    Time(NaiveTime),
+    Timestamp(NaiveDateTime),
+    TimestampTz(DateTime<Utc>),
+    Interval(Interval),
+    Json(Json),
+    Uuid(Uuid),
     Null,
 }
 
@@ -36,11 +49,12 @@ impl DbVal {
         }
     }
 
+    /// Gets the value as a float, widening decimals
     pub fn float_value(&self) -> Option<f64> {
-        if let Self::Float(i) = self {
-            Some(*i)
-        } else {
-            None
+        match self {
+            Self::Float(i) => Some(*i),
+            Self::Decimal(decimal, _) => Some(decimal.to_f64()),
+            _ => None,
         }
     }
 
@@ -66,7 +80,15 @@ impl DbVal {
             &DbVal::Binary(_, max_len) => Type::Binary(max_len),
             DbVal::Integer(_) => Type::Integer,
             DbVal::Boolean(_) => Type::Boolean,
+            DbVal::Decimal(decimal, precision) => Type::Decimal(*precision, decimal.scale()),
             DbVal::Float(_) => Type::Float,
+            DbVal::Date(_) => Type::Date,
+            DbVal::Time(_) => Type::Time,
+            DbVal::Timestamp(_) => Type::Timestamp,
+            DbVal::TimestampTz(_) => Type::TimestampTz,
+            DbVal::Interval(_) => Type::Interval,
+            DbVal::Json(_) => Type::Json,
+            DbVal::Uuid(_) => Type::Uuid,
             DbVal::Null => {
                 return None;
             }
@@ -109,6 +131,14 @@ impl From<ast::Literal> for DbVal {
             ast::Literal::String(s) => DbVal::String(s, u16::MAX),
             ast::Literal::Integer(i) => DbVal::Integer(i),
             ast::Literal::Float(f) => DbVal::Float(f),
+            // only literals with more digits than any decimal can hold are rounded to floats
+            ast::Literal::Decimal(decimal) => match decimal.as_str().parse::<Decimal>() {
+                Ok(parsed) => {
+                    let precision = parsed.digits().max(parsed.scale()).max(1);
+                    DbVal::Decimal(parsed, precision)
+                }
+                Err(_) => DbVal::Float(decimal.as_str().parse().unwrap_or(f64::NAN)),
+            },
             ast::Literal::Boolean(b) => DbVal::Boolean(b),
             ast::Literal::Binary(binary) => DbVal::Binary(binary.into(), u16::MAX),
             ast::Literal::Null => DbVal::Null,
@@ -116,6 +146,30 @@ impl From<ast::Literal> for DbVal {
     }
 }
 
+impl From<DbVal> for ast::Literal {
+    fn from(value: DbVal) -> Self {
+        match value {
+            DbVal::String(s, _) => ast::Literal::String(s),
+            DbVal::Binary(binary, _) => ast::Literal::Binary(binary.into()),
+            DbVal::Integer(i) => ast::Literal::Integer(i),
+            DbVal::Boolean(b) => ast::Literal::Boolean(b),
+            DbVal::Float(f) => ast::Literal::Float(f),
+            DbVal::Decimal(decimal, _) => {
+                ast::Literal::Decimal(ast::DecimalLiteral::new(decimal.to_string()))
+            }
+            // parsed back into the temporal, json or uuid type whenever it's compared to one
+            DbVal::Date(_)
+            | DbVal::Time(_)
+            | DbVal::Timestamp(_)
+            | DbVal::TimestampTz(_)
+            | DbVal::Interval(_)
+            | DbVal::Json(_)
+            | DbVal::Uuid(_) => ast::Literal::String(value.to_string()),
+            DbVal::Null => ast::Literal::Null,
+        }
+    }
+}
+
 impl From<&[u8]> for DbVal {
     fn from(value: &[u8]) -> Self {
         DbVal::binary(value, None)
@@ -146,14 +200,6 @@ impl From<String> for DbVal {
     }
 }
 
-impl From<Uuid> for DbVal {
-    fn from(value: Uuid) -> Self {
-        let as_binary = value.as_bytes();
-        let binary = Vec::from(as_binary.as_slice());
-        DbVal::Binary(binary, 16)
-    }
-}
-
 impl<T> From<Option<T>> for DbVal
 where
     DbVal: From<T>,
@@ -197,6 +243,30 @@ impl Display for DbVal {
             DbVal::Float(fl) => {
                 write!(f, "{fl}")
             }
+            DbVal::Decimal(decimal, _) => {
+                write!(f, "{decimal}")
+            }
+            DbVal::Date(date) => {
+                write!(f, "{date}")
+            }
+            DbVal::Time(time) => {
+                write!(f, "{time}")
+            }
+            DbVal::Timestamp(timestamp) => {
+                write!(f, "{timestamp}")
+            }
+            DbVal::TimestampTz(timestamp) => {
+                write!(f, "{}", timestamp.format("%Y-%m-%d %H:%M:%S%.f%:z"))
+            }
+            DbVal::Interval(interval) => {
+                write!(f, "{interval}")
+            }
+            DbVal::Json(json) => {
+                write!(f, "{json}")
+            }
+            DbVal::Uuid(uuid) => {
+                write!(f, "{uuid}")
+            }
             DbVal::Null => {
                 write!(f, "")
             }
@@ -229,6 +299,30 @@ impl Debug for DbVal {
             DbVal::Float(fl) => {
                 write!(f, "{fl}_f64")
             }
+            DbVal::Decimal(decimal, _) => {
+                write!(f, "{decimal}_dec")
+            }
+            DbVal::Date(_) => {
+                write!(f, "date '{self}'")
+            }
+            DbVal::Time(_) => {
+                write!(f, "time '{self}'")
+            }
+            DbVal::Timestamp(_) => {
+                write!(f, "timestamp '{self}'")
+            }
+            DbVal::TimestampTz(_) => {
+                write!(f, "timestamptz '{self}'")
+            }
+            DbVal::Interval(_) => {
+                write!(f, "interval '{self}'")
+            }
+            DbVal::Json(_) => {
+                write!(f, "json '{self}'")
+            }
+            DbVal::Uuid(_) => {
+                write!(f, "uuid '{self}'")
+            }
             DbVal::Null => {
                 write!(f, "null")
             }
@@ -247,6 +341,14 @@ impl PartialEq for DbVal {
             (Integer(l), Float(r)) => *l as f64 == *r,
             (Float(l), Integer(r)) => *l as i64 == *r,
             (Float(l), Float(r)) => l.total_cmp(r).is_eq(),
+            (Decimal(l, _), Decimal(r, _)) => l == r,
+            (Date(l), Date(r)) => l == r,
+            (Time(l), Time(r)) => l == r,
+            (Timestamp(l), Timestamp(r)) => l == r,
+            (TimestampTz(l), TimestampTz(r)) => l == r,
+            (Interval(l), Interval(r)) => l == r,
+            (Json(l), Json(r)) => l == r,
+            (Uuid(l), Uuid(r)) => l == r,
             (Null, Null) => true,
             _ => false,
         }
@@ -271,8 +373,16 @@ impl Ord for DbVal {
             (Integer(l), Float(r)) => (*l as f64).total_cmp(r),
             (Float(l), Integer(r)) => (*l as i64).cmp(r),
             (Float(l), Float(r)) => l.total_cmp(r),
+            (Decimal(l, _), Decimal(r, _)) => l.cmp(r),
 
             (Boolean(l), Boolean(r)) => l.cmp(r),
+            (Date(l), Date(r)) => l.cmp(r),
+            (Time(l), Time(r)) => l.cmp(r),
+            (Timestamp(l), Timestamp(r)) => l.cmp(r),
+            (TimestampTz(l), TimestampTz(r)) => l.cmp(r),
+            (Interval(l), Interval(r)) => l.cmp(r),
+            (Json(l), Json(r)) => l.cmp(r),
+            (Uuid(l), Uuid(r)) => l.cmp(r),
             (Null, Null) => Ordering::Equal,
             (_, Null) => Ordering::Greater,
             (Null, _) => Ordering::Less,
@@ -289,6 +399,14 @@ impl Hash for DbVal {
             DbVal::Integer(s) => s.hash(state),
             DbVal::Boolean(s) => s.hash(state),
             DbVal::Float(f) => u64::from_be_bytes(f.to_be_bytes()).hash(state),
+            DbVal::Decimal(decimal, _) => decimal.hash(state),
+            DbVal::Date(date) => date.hash(state),
+            DbVal::Time(time) => time.hash(state),
+            DbVal::Timestamp(timestamp) => timestamp.hash(state),
+            DbVal::TimestampTz(timestamp) => timestamp.hash(state),
+            DbVal::Interval(interval) => interval.hash(state),
+            DbVal::Json(json) => json.hash(state),
+            DbVal::Uuid(uuid) => uuid.hash(state),
             DbVal::Null => {}
         }
     }
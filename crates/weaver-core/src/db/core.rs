@@ -1,29 +1,32 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::path::{Path, PathBuf};
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
 
 use fs2::FileExt;
-use parking_lot::RwLock;
 use tracing::{debug, debug_span, field, info, trace};
 
-use crate::data::row::Row;
+use crate::data::row::{OwnedRow, Row};
 use crate::data::values::DbVal;
 pub use bootstrap::{bootstrap, weaver_schemata_schema, weaver_tables_schema};
+use weaver_ast::ast::{AlterTableAction, IndexColumn};
 use weaver_ast::ToSql;
 
 use crate::db::start_db::start_db;
 use crate::dynamic_table::{DynamicTable, EngineKey, HasSchema, Table};
-use crate::dynamic_table_factory::DynamicTableFactory;
+use crate::dynamic_table_factory::{DynamicTableFactory, DynamicTableFactoryDelegate};
 use crate::error::WeaverError;
 use crate::monitoring::{monitor_fn, Monitor, MonitorCollector, Monitorable, Stats};
+use crate::rows::{KeyIndex, KeyIndexKind};
 use crate::storage::engine::{StorageEngine, StorageEngineDelegate};
-use crate::storage::tables::shared_table::SharedTable;
-use crate::storage::tables::table_schema::TableSchema;
+use crate::storage::tables::shared_table::{OpenTables, SharedTable};
+use crate::storage::tables::table_schema::{Key, TableSchema, TableSchemaBuilder};
 use crate::tx::coordinator::TxCoordinator;
 use crate::tx::Tx;
 
 mod bootstrap;
+mod foreign_keys;
 
 /// A db core. Represents some part of a distributed db
 #[derive(Debug)]
@@ -32,7 +35,7 @@ pub struct WeaverDbCore {
     lock_file: Option<File>,
     engines: HashMap<EngineKey, StorageEngineDelegate>,
     default_engine: Option<EngineKey>,
-    open_tables: RwLock<HashMap<(String, String), SharedTable>>,
+    open_tables: Arc<OpenTables>,
     pub(crate) tx_coordinator: Option<TxCoordinator>,
     monitor: OnceLock<CoreMonitor>,
 }
@@ -140,7 +143,7 @@ impl WeaverDbCore {
             if let Some(monitor) = self.monitor.get() {
                 monitor.collector.clone().push_monitorable(&*table);
             }
-            open_tables.insert((schema, name), SharedTable::new(table));
+            open_tables.insert((schema, name), SharedTable::with_open_tables(table, &self.open_tables));
             Ok(())
         }
     }
@@ -203,7 +206,7 @@ impl WeaverDbCore {
                         DbVal::from(schema.name()),
                         DbVal::from(schema.to_sql()),
                         DbVal::from(serde_json::to_string(schema)?),
-                        DbVal::Null,
+                        DbVal::from(false),
                     ]),
                 )?;
                 tables_table.commit(&tx);
@@ -221,7 +224,7 @@ impl WeaverDbCore {
 
         open_tables.insert(
             (schema.schema().to_string(), schema.name().to_string()),
-            SharedTable::new(table),
+            SharedTable::with_open_tables(table, &self.open_tables),
         );
 
         Ok(())
@@ -263,12 +266,517 @@ impl WeaverDbCore {
             })
     }
 
+    /// Drops a table, closing it, removing it from `weaver.tables`, and deleting any storage backing
+    /// it.
+    ///
+    /// Only tables tracked by `weaver.tables` that are not protected, and that aren't referenced by
+    /// the foreign keys of other tables, can be dropped.
+    pub fn drop_table(&self, schema: &str, name: &str) -> Result<(), WeaverError> {
+        if let Some(foreign_key) = self.referenced_by(schema, name, |child| {
+            child.schema() != schema || child.name() != name
+        }) {
+            return Err(WeaverError::Referenced {
+                name: format!("{schema}.{name}"),
+                foreign_key: foreign_key.name().to_string(),
+            });
+        }
+        self.remove_table(schema, name)
+    }
+
+    /// Drops a table without checking whether it's referenced by other tables.
+    ///
+    /// The table is closed and its storage destroyed before it's removed from `weaver.tables`, so
+    /// a table whose storage can't be destroyed is kept open and tracked.
+    fn remove_table(&self, schema: &str, name: &str) -> Result<(), WeaverError> {
+        let table = self.get_open_table(schema, name)?;
+        let table_schema = table.schema().clone();
+        let tx = Tx::default();
+        let entry = self.unprotected_table_entry(&tx, schema, name)?;
+        let engine = self
+            .engines
+            .get(table_schema.engine())
+            .ok_or_else(|| WeaverError::UnknownStorageEngine(table_schema.engine().clone()))?;
+
+        self.close_table(schema, name)?;
+        if let Err(err) = engine.factory().destroy(&table_schema) {
+            self.open_tables
+                .write()
+                .insert((schema.to_string(), name.to_string()), table);
+            return Err(err);
+        }
+        drop(table);
+
+        let tables_table = self.get_open_table("weaver", "tables")?;
+        let tables_schema = tables_table.schema();
+        let primary_key = tables_schema.primary_key()?;
+        tables_table.delete(
+            &tx,
+            &KeyIndex::new(
+                primary_key.name(),
+                KeyIndexKind::One(tables_schema.key_data(primary_key, &entry)),
+                None,
+                None,
+            ),
+        )?;
+        tables_table.commit(&tx);
+        tx.commit();
+        debug!("dropped table {schema}.{name}");
+        Ok(())
+    }
+
+    /// Creates a new schema, adding it to `weaver.schemata`.
+    pub fn create_schema(&self, schema: &str) -> Result<(), WeaverError> {
+        let tx = Tx::default();
+        if self.schema_entry(&tx, schema)?.is_some() {
+            return Err(WeaverError::SchemaAlreadyExists(schema.to_string()));
+        }
+        let schemata_table = self.get_open_table("weaver", "schemata")?;
+        let schemata_schema = schemata_table.schema();
+        // ids are assigned explicitly, as the bootstrapped `weaver` schema doesn't use the auto
+        // increment
+        let next_id = schemata_table
+            .all(&tx)?
+            .into_iter()
+            .filter_map(|row| row[(schemata_schema, "id")].int_value())
+            .max()
+            .unwrap_or(0)
+            + 1;
+        schemata_table.insert(&tx, Row::from([DbVal::from(next_id), DbVal::from(schema)]))?;
+        schemata_table.commit(&tx);
+        tx.commit();
+        debug!("created schema {schema}");
+        Ok(())
+    }
+
+    /// Checks if a schema exists within `weaver.schemata`
+    pub fn schema_exists(&self, schema: &str) -> Result<bool, WeaverError> {
+        let tx = Tx::default();
+        let exists = self.schema_entry(&tx, schema)?.is_some();
+        tx.commit();
+        Ok(exists)
+    }
+
+    /// Drops a schema, removing it from `weaver.schemata`.
+    ///
+    /// Schemas that still contain tables can only be dropped if `cascade` is set, in which case every
+    /// table within the schema is dropped as well.
+    pub fn drop_schema(&self, schema: &str, cascade: bool) -> Result<(), WeaverError> {
+        let tx = Tx::default();
+        let entry = self
+            .schema_entry(&tx, schema)?
+            .ok_or_else(|| WeaverError::SchemaNotFound(schema.to_string()))?;
+
+        let tables = self
+            .get_open_tables()
+            .filter(|table| table.schema().schema() == schema)
+            .map(|table| table.schema().name().to_string())
+            .collect::<Vec<_>>();
+        if !tables.is_empty() && !cascade {
+            return Err(WeaverError::SchemaNotEmpty(schema.to_string()));
+        }
+        // make sure every table can be dropped before dropping any of them
+        for table in &tables {
+            self.unprotected_table_entry(&tx, schema, table)?;
+            if let Some(foreign_key) =
+                self.referenced_by(schema, table, |child| child.schema() != schema)
+            {
+                return Err(WeaverError::Referenced {
+                    name: format!("{schema}.{table}"),
+                    foreign_key: foreign_key.name().to_string(),
+                });
+            }
+        }
+        for table in &tables {
+            self.remove_table(schema, table)?;
+        }
+
+        let schemata_table = self.get_open_table("weaver", "schemata")?;
+        let schemata_schema = schemata_table.schema();
+        let primary_key = schemata_schema.primary_key()?;
+        schemata_table.delete(
+            &tx,
+            &KeyIndex::new(
+                primary_key.name(),
+                KeyIndexKind::One(schemata_schema.key_data(primary_key, &entry)),
+                None,
+                None,
+            ),
+        )?;
+        schemata_table.commit(&tx);
+        tx.commit();
+        debug!("dropped schema {schema}");
+        Ok(())
+    }
+
+    /// Creates a secondary index on an open table.
+    ///
+    /// The table's definition within `weaver.tables` is replaced and the table is reopened, which
+    /// backfills the new index from the rows already present. Indexing an expression adds a
+    /// generated column, so the storage of the table is rebuilt to fill it in for every row.
+    pub fn create_index(
+        &self,
+        schema: &str,
+        name: &str,
+        index: &str,
+        columns: &[IndexColumn],
+        unique: bool,
+    ) -> Result<(), WeaverError> {
+        let table = self.get_open_table(schema, name)?;
+        let old_schema = table.schema().clone();
+        let new_schema = TableSchemaBuilder::from(&old_schema)
+            .index_on(index, columns, unique)?
+            .build()?;
+        let generates = columns
+            .iter()
+            .any(|column| matches!(column, IndexColumn::Expr(_)));
+        let rows = if unique || generates {
+            let tx = Tx::default();
+            let rows = table
+                .all(&tx)?
+                .into_iter()
+                .map(|row| old_schema.public_only(row).to_owned())
+                .collect::<Vec<_>>();
+            tx.commit();
+            rows
+        } else {
+            vec![]
+        };
+        if unique {
+            // the rows already present must satisfy the new key
+            let full_rows = rows
+                .iter()
+                .map(|row| {
+                    let mut full_row = Row::new(new_schema.all_columns().len());
+                    for (idx, val) in row.iter().enumerate() {
+                        full_row[idx] = val.clone();
+                    }
+                    new_schema.fill_generated(&mut full_row)?;
+                    Ok(full_row.to_owned())
+                })
+                .collect::<Result<Vec<_>, WeaverError>>()?;
+            check_unique(&new_schema, new_schema.get_key(index)?, &full_rows)?;
+        }
+        drop(table);
+        self.replace_table_schema(&new_schema, generates.then_some(rows))?;
+        debug!("created index {index} on {schema}.{name}");
+        Ok(())
+    }
+
+    /// Alters the definition of an open table.
+    ///
+    /// Every row of the table is migrated to the new definition and the storage of the table is
+    /// rebuilt. Nothing is changed if any row doesn't fit the new definition.
+    pub fn alter_table(
+        &self,
+        schema: &str,
+        name: &str,
+        actions: &[AlterTableAction],
+    ) -> Result<(), WeaverError> {
+        let table = self.get_open_table(schema, name)?;
+        let old_schema = table.schema().clone();
+        let mut builder = TableSchemaBuilder::from(&old_schema);
+        // every column of the new definition, along with the column it's migrated from
+        let mut sources = old_schema
+            .columns()
+            .iter()
+            .map(|col| (col.name().to_string(), Some(col.name().to_string())))
+            .collect::<Vec<_>>();
+        for action in actions {
+            match action {
+                AlterTableAction::AddColumn(column_def) => {
+                    let column = column_def.id.to_string();
+                    if sources.iter().any(|(name, _)| *name == column) {
+                        return Err(WeaverError::ColumnAlreadyExists(column));
+                    }
+                    builder = builder
+                        .column_definition(column_def.try_into()?)
+                        .column_keys(column_def)?;
+                    sources.push((column, None));
+                }
+                AlterTableAction::DropColumn(column) => {
+                    builder = builder.drop_column(column.as_ref())?;
+                    sources.retain(|(name, _)| name != column.as_ref());
+                }
+                AlterTableAction::RenameColumn { from, to } => {
+                    builder = builder.rename_column(from.as_ref(), to.as_ref())?;
+                    for (name, _) in &mut sources {
+                        if name == from.as_ref() {
+                            *name = to.to_string();
+                        }
+                    }
+                }
+                AlterTableAction::ModifyColumn(column_def) => {
+                    builder = builder
+                        .modify_column(column_def.try_into()?)?
+                        .column_keys(column_def)?;
+                }
+            }
+        }
+        let new_schema = builder.build()?;
+        self.check_foreign_key_parents(&new_schema)?;
+        self.check_referenced_by(&new_schema)?;
+
+        let tx = Tx::default();
+        let old_rows = table
+            .all(&tx)?
+            .into_iter()
+            .map(|row| row.to_owned())
+            .collect::<Vec<_>>();
+        tx.commit();
+        drop(table);
+        let carried_over = sources
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, (_, source))| source.is_some().then_some(idx))
+            .collect::<Vec<_>>();
+        let rows = old_rows
+            .iter()
+            .map(|old_row| {
+                new_schema
+                    .columns()
+                    .iter()
+                    .zip(&sources)
+                    .map(|(col, (_, source))| {
+                        let val = source
+                            .as_ref()
+                            .and_then(|source| old_schema.column_index(source))
+                            .map(|idx| col.data_type().widen(old_row[idx].clone().into_owned()))
+                            .unwrap_or(DbVal::Null);
+                        if val == DbVal::Null
+                            && col.non_null()
+                            && (source.is_some() || !col.has_default())
+                            && col.auto_increment().is_none()
+                        {
+                            return Err(WeaverError::NullNotAllowed(col.name().to_string()));
+                        }
+                        Ok(val)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .and_then(|vals| {
+                        // added columns start with their defaults
+                        let mut row = Row::from(vals);
+                        new_schema.fill_defaults(&mut row, &carried_over)?;
+                        Ok(row.to_owned())
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for key in new_schema.keys().iter().filter(|key| key.unique()) {
+            // keys using system columns are filled in once the rows are inserted
+            if key
+                .columns()
+                .iter()
+                .all(|col| new_schema.columns().iter().any(|c| c.name() == col))
+            {
+                check_unique(&new_schema, key, &rows)?;
+            }
+        }
+
+        self.replace_table_schema(&new_schema, Some(rows))?;
+        debug!("altered table {schema}.{name}");
+        Ok(())
+    }
+
+    /// Reopens an open table using a new definition, then replaces its definition within
+    /// `weaver.tables`.
+    ///
+    /// If `rows` are given, the storage of the table is rebuilt to only contain them. The rows are
+    /// first inserted into staging storage, which then replaces the storage of the table once every
+    /// row has been stored and validated. Otherwise the rows already stored by the table are kept.
+    /// `weaver.tables` is only updated once the table has been reopened, so a table that can't be
+    /// reopened is left as it was.
+    ///
+    /// Only tables tracked by `weaver.tables` that are not protected can be altered.
+    fn replace_table_schema(
+        &self,
+        table_schema: &TableSchema,
+        rows: Option<Vec<OwnedRow>>,
+    ) -> Result<(), WeaverError> {
+        let (schema, name) = (table_schema.schema(), table_schema.name());
+        let tx = Tx::default();
+        let mut entry = self.unprotected_table_entry(&tx, schema, name)?;
+        let factory = self
+            .engines
+            .get(table_schema.engine())
+            .ok_or_else(|| WeaverError::UnknownStorageEngine(table_schema.engine().clone()))?
+            .factory();
+        let staged = match rows {
+            Some(rows) => Some(self.stage_rows(&factory, table_schema, rows)?),
+            None => None,
+        };
+
+        // anything still buffered by the table has to be written before it's reopened
+        let old_table = self.get_open_table(schema, name)?;
+        old_table.commit(&tx);
+        self.close_table(schema, name)?;
+        let reopened = match staged {
+            Some(staged) => factory
+                .promote_staged(staged, table_schema, self)
+                .map(|table| {
+                    if let Some(monitor) = self.monitor.get() {
+                        monitor.collector.clone().push_monitorable(&*table);
+                    }
+                    self.open_tables.write().insert(
+                        (schema.to_string(), name.to_string()),
+                        SharedTable::with_open_tables(table, &self.open_tables),
+                    );
+                }),
+            // in memory tables are volatile, so their rows have to be carried over
+            None if *table_schema.engine() == EngineKey::in_memory() => {
+                self.carry_over_rows(&old_table, table_schema)
+            }
+            None => self.open_table(table_schema),
+        };
+        if let Err(err) = reopened {
+            // a failed promotion leaves the storage of the old table in place, so it's put back as
+            // it was
+            self.open_tables
+                .write()
+                .insert((schema.to_string(), name.to_string()), old_table);
+            factory.destroy_staged(table_schema)?;
+            return Err(err);
+        }
+        drop(old_table);
+
+        let tables_table = self.get_open_table("weaver", "tables")?;
+        let tables_schema = tables_table.schema();
+        let ddl = tables_schema
+            .column_index("table_ddl")
+            .expect("weaver.tables has a ddl column");
+        let ddl_json = tables_schema
+            .column_index("table_ddl_json")
+            .expect("weaver.tables has a ddl json column");
+        entry[ddl] = Cow::Owned(DbVal::from(table_schema.to_sql()));
+        entry[ddl_json] = Cow::Owned(DbVal::from(serde_json::to_string(table_schema)?));
+        tables_table.update(&tx, Row::from(entry))?;
+        tables_table.commit(&tx);
+        tx.commit();
+        Ok(())
+    }
+
+    /// Opens an in memory table using a new definition, then inserts the rows of the table it
+    /// replaces. The new table is closed again if any row can't be inserted.
+    fn carry_over_rows(
+        &self,
+        old_table: &SharedTable,
+        table_schema: &TableSchema,
+    ) -> Result<(), WeaverError> {
+        let tx = Tx::default();
+        let rows = old_table
+            .all(&tx)?
+            .into_iter()
+            .map(|row| old_table.schema().public_only(row).to_owned())
+            .collect::<Vec<_>>();
+        tx.commit();
+        self.open_table(table_schema)?;
+
+        let (schema, name) = (table_schema.schema(), table_schema.name());
+        let new_table = self.get_open_table(schema, name)?;
+        let tx = Tx::default();
+        let result = rows
+            .into_iter()
+            .try_for_each(|row| new_table.insert(&tx, Row::from(row)));
+        new_table.commit(&tx);
+        tx.commit();
+        if result.is_err() {
+            self.close_table(schema, name)?;
+        }
+        result
+    }
+
+    /// Inserts rows into a table opened in staging storage. The staging storage is removed if any
+    /// row can't be stored.
+    fn stage_rows(
+        &self,
+        factory: &DynamicTableFactoryDelegate,
+        table_schema: &TableSchema,
+        rows: Vec<OwnedRow>,
+    ) -> Result<Table, WeaverError> {
+        let staged = factory.open_staged(table_schema, self)?;
+        let tx = Tx::default();
+        let result = rows
+            .into_iter()
+            .try_for_each(|row| staged.insert(&tx, Row::from(row)));
+        staged.commit(&tx);
+        tx.commit();
+        if let Err(err) = result {
+            drop(staged);
+            factory.destroy_staged(table_schema)?;
+            return Err(err);
+        }
+        Ok(staged)
+    }
+
+    /// Gets the `weaver.tables` entry of a table, failing if the table is protected.
+    fn unprotected_table_entry(
+        &self,
+        tx: &Tx,
+        schema: &str,
+        name: &str,
+    ) -> Result<OwnedRow, WeaverError> {
+        let protected = || WeaverError::ProtectedTable {
+            schema: schema.to_string(),
+            table: name.to_string(),
+        };
+        let schemata_schema = weaver_schemata_schema()?;
+        let schema_id = self
+            .schema_entry(tx, schema)?
+            .map(|row| row[(&schemata_schema, "id")].clone().into_owned())
+            .ok_or_else(|| WeaverError::SchemaNotFound(schema.to_string()))?;
+
+        let tables_table = self.get_open_table("weaver", "tables")?;
+        let tables_schema = tables_table.schema();
+        let entry = tables_table
+            .all(tx)?
+            .into_iter()
+            .find(|row| {
+                *row[(tables_schema, "schema_id")] == schema_id
+                    && row[(tables_schema, "name")].string_value() == Some(name)
+            })
+            // tables that aren't tracked are managed by the system
+            .ok_or_else(protected)?;
+        if entry[(tables_schema, "protected")].bool_value() == Some(true) {
+            return Err(protected());
+        }
+        Ok(entry.to_owned())
+    }
+
+    /// Gets the `weaver.schemata` entry of a schema, if it exists
+    fn schema_entry(&self, tx: &Tx, schema: &str) -> Result<Option<OwnedRow>, WeaverError> {
+        let schemata_table = self.get_open_table("weaver", "schemata")?;
+        let schemata_schema = schemata_table.schema();
+        let entry = schemata_table
+            .all(tx)?
+            .into_iter()
+            .find(|row| row[(schemata_schema, "name")].string_value() == Some(schema))
+            .map(|row| row.to_owned());
+        Ok(entry)
+    }
+
     /// Gets the path this weaver db is open in
     pub fn path(&self) -> &Path {
         &self.path
     }
 }
 
+/// Checks that no two rows share the same non-null values for a unique key
+fn check_unique(
+    table_schema: &TableSchema,
+    key: &Key,
+    rows: &[OwnedRow],
+) -> Result<(), WeaverError> {
+    let mut seen = HashSet::new();
+    for row in rows {
+        let key_data = table_schema.key_data(key, row);
+        if key_data.iter().all(|val| **val != DbVal::Null) && !seen.insert(key_data.clone()) {
+            return Err(WeaverError::UniqueKeyViolation {
+                key: key.name().to_string(),
+                value: key_data,
+            });
+        }
+    }
+    Ok(())
+}
+
 impl Drop for WeaverDbCore {
     fn drop(&mut self) {
         if let Some(lock_file) = self.lock_file.take() {
@@ -90,8 +90,8 @@ pub fn weaver_tables_schema() -> Result<TableSchema, WeaverError> {
         .column("id", Type::Integer, true, None, 1)?
         .column("schema_id", Type::Integer, true, None, None)?
         .column("name", Type::String(255), true, None, None)?
-        .column("table_ddl", Type::String(1 << 11), true, None, None)?
-        .column("table_ddl_json", Type::String(1 << 11), true, None, None)?
+        .column("table_ddl", Type::String(u16::MAX), true, None, None)?
+        .column("table_ddl_json", Type::String(u16::MAX), true, None, None)?
         .column("protected", Type::Boolean, true, DbVal::from(false), None)?
         .primary(&["id"])?
         .index("FK_schema_id", &["schema_id"], false)?
@@ -0,0 +1,103 @@
+//! Checks that the foreign keys of table definitions can be enforced
+
+use std::mem::discriminant;
+
+use weaver_ast::ast::ReferentialAction;
+
+use crate::db::core::WeaverDbCore;
+use crate::dynamic_table::HasSchema;
+use crate::error::WeaverError;
+use crate::storage::tables::shared_table::foreign_keys::{parent_key, referencing};
+use crate::storage::tables::shared_table::SharedTable;
+use crate::storage::tables::table_schema::{ForeignKey, TableSchema};
+
+impl WeaverDbCore {
+    /// Checks that the foreign keys of a table can be enforced.
+    ///
+    /// The referenced columns must be a unique key of the parent table, and have the same types as
+    /// the referencing columns.
+    pub fn check_foreign_key_parents(&self, table_schema: &TableSchema) -> Result<(), WeaverError> {
+        for foreign_key in table_schema.foreign_keys() {
+            let (schema, name) = foreign_key.parent();
+            if foreign_key.references(table_schema.schema(), table_schema.name()) {
+                check_parent(foreign_key, table_schema, table_schema)?;
+            } else {
+                let parent = self.get_open_table(schema, name)?;
+                check_parent(foreign_key, table_schema, parent.schema())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that a new definition of a table can still be referenced by the foreign keys of
+    /// other tables
+    pub fn check_referenced_by(&self, table_schema: &TableSchema) -> Result<(), WeaverError> {
+        for (child, foreign_key) in self.referencing(table_schema.schema(), table_schema.name()) {
+            let child_schema = child.schema();
+            if child_schema.schema() != table_schema.schema()
+                || child_schema.name() != table_schema.name()
+            {
+                check_parent(&foreign_key, child_schema, table_schema)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets whether a table is referenced by a foreign key of any table matching the given filter,
+    /// returning the first such foreign key
+    pub fn referenced_by<F: Fn(&TableSchema) -> bool>(
+        &self,
+        schema: &str,
+        name: &str,
+        filter: F,
+    ) -> Option<ForeignKey> {
+        self.referencing(schema, name)
+            .into_iter()
+            .find(|(table, _)| filter(table.schema()))
+            .map(|(_, foreign_key)| foreign_key)
+    }
+
+    /// Gets every foreign key referencing a table, along with the table it belongs to
+    fn referencing(&self, schema: &str, name: &str) -> Vec<(SharedTable, ForeignKey)> {
+        referencing(self.get_open_tables(), schema, name)
+    }
+}
+
+/// Checks that a foreign key can reference the parent table
+fn check_parent(
+    foreign_key: &ForeignKey,
+    table_schema: &TableSchema,
+    parent_schema: &TableSchema,
+) -> Result<(), WeaverError> {
+    let illegal = |reason: String| WeaverError::IllegalForeignKey {
+        name: foreign_key.name().to_string(),
+        reason,
+    };
+    parent_key(parent_schema, foreign_key)?;
+
+    let set_null = foreign_key.on_delete() == ReferentialAction::SetNull
+        || foreign_key.on_update() == ReferentialAction::SetNull;
+    for (col, parent_col) in foreign_key
+        .columns()
+        .iter()
+        .zip(foreign_key.parent_columns())
+    {
+        let column = table_schema
+            .get_column(col)
+            .ok_or_else(|| WeaverError::ColumnNotFound(col.clone()))?;
+        let parent_column = parent_schema
+            .get_column(parent_col)
+            .ok_or_else(|| WeaverError::ColumnNotFound(parent_col.clone()))?;
+        if discriminant(&column.data_type()) != discriminant(&parent_column.data_type()) {
+            return Err(illegal(format!(
+                "`{col}` is {} but references `{parent_col}`, which is {}",
+                column.data_type(),
+                parent_column.data_type()
+            )));
+        }
+        if set_null && column.non_null() {
+            return Err(illegal(format!("`{col}` can not be set to null")));
+        }
+    }
+    Ok(())
+}
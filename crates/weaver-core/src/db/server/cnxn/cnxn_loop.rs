@@ -12,7 +12,7 @@ use weaver_ast::ast::Query;
 
 use crate::cancellable_task::Cancel;
 use crate::cnxn::{Message, MessageStream, RemoteDbReq, RemoteDbResp};
-use crate::db::server::layers::packets::{DbReqBody, DbResp};
+use crate::db::server::layers::packets::{DbReq, DbReqBody, DbResp};
 use crate::db::server::processes::{ProcessState, RemoteWeaverProcess};
 use crate::db::server::socket::DbSocket;
 use crate::error::WeaverError;
@@ -78,10 +78,13 @@ fn handle_message<S: MessageStream + Send>(
         Message::Req(req) => {
             trace!("Received req {:?}", req);
             child.set_state(ProcessState::Active);
+            let ctx = child.info();
 
             let mut send_request =
                 |body: DbReqBody, tx: &mut Option<Tx>| -> Result<RemoteDbResp, WeaverError> {
-                    let mut resp = socket.send((body, span.clone()));
+                    let mut req = DbReq::from((body, span.clone()));
+                    req.set_ctx(ctx.clone());
+                    let mut resp = socket.send(req);
                     resp.on_cancel(cancel.clone());
                     let resp = resp.join()?;
                     trace!("using response: {:?}", resp);
@@ -123,21 +126,31 @@ fn handle_message<S: MessageStream + Send>(
                 RemoteDbReq::Query(query) => {
                     trace!("received query = {query:#?}");
                     child.set_info(&query);
-                    match tx.take() {
+                    let using = using_schema(&query);
+                    let resp = match tx.take() {
                         None => send_request(DbReqBody::TxQuery(Tx::default(), query), tx),
                         Some(existing_tx) => {
                             send_request(DbReqBody::TxQuery(existing_tx, query), tx)
                         }
+                    };
+                    if let (Ok(RemoteDbResp::Ok), Some(schema)) = (&resp, using) {
+                        child.set_using(schema);
                     }
+                    resp
                 }
                 RemoteDbReq::DelegatedQuery(ref query) => {
                     let query: Query = Query::parse(query)?;
-                    match tx.take() {
+                    let using = using_schema(&query);
+                    let resp = match tx.take() {
                         None => send_request(DbReqBody::TxQuery(Tx::default(), query), tx),
                         Some(existing_tx) => {
                             send_request(DbReqBody::TxQuery(existing_tx, query), tx)
                         }
+                    };
+                    if let (Ok(RemoteDbResp::Ok), Some(schema)) = (&resp, using) {
+                        child.set_using(schema);
                     }
+                    resp
                 }
                 RemoteDbReq::Ping => send_request(DbReqBody::Ping, tx),
                 RemoteDbReq::StartTransaction => send_request(DbReqBody::StartTransaction, tx),
@@ -186,3 +199,11 @@ fn handle_message<S: MessageStream + Send>(
     }
     Ok(true)
 }
+
+/// Gets the schema a query switches the connection to, if it's a `use` query
+fn using_schema(query: &Query) -> Option<String> {
+    match query {
+        Query::Use(schema) => Some(schema.to_string()),
+        _ => None,
+    }
+}
@@ -25,7 +25,8 @@ struct WeaverProcessShared {
     started: Instant,
     user: String,
     host: String,
-    using: Option<String>,
+    /// The default schema of this process
+    using: RwLock<Option<String>>,
 }
 
 /// A weaver process
@@ -63,7 +64,7 @@ impl WeaverProcess {
             started,
             user: user.name().to_string(),
             host: user.host().to_string(),
-            using: None,
+            using: RwLock::new(None),
         });
         (
             WeaverProcess {
@@ -96,7 +97,7 @@ impl WeaverProcess {
             info: self.info.read().clone(),
             user: self.shared.user.clone(),
             host: self.shared.host.clone(),
-            using: self.shared.using.clone(),
+            using: self.shared.using.read().clone(),
         }
     }
 
@@ -171,6 +172,11 @@ impl RemoteWeaverProcess {
         *self.info.write() = info.to_string();
     }
 
+    /// Sets the default schema used by this process
+    pub fn set_using<S: ToString>(&mut self, schema: S) {
+        *self.shared.using.write() = Some(schema.to_string());
+    }
+
     pub fn make_idle(&mut self) {
         self.set_state(ProcessState::Idle);
         self.set_info("");
@@ -185,7 +191,7 @@ impl RemoteWeaverProcess {
             info: self.info.read().clone(),
             user: self.shared.user.clone(),
             host: self.shared.host.clone(),
-            using: self.shared.using.clone(),
+            using: self.shared.using.read().clone(),
         }
     }
 }
@@ -281,6 +287,16 @@ impl ProcessManager {
         Ok(pid)
     }
 
+    /// Clears the default schema of every process that is using the given schema
+    pub fn clear_using(&self, schema: &str) {
+        for process in self.processes.read().values() {
+            let mut using = process.shared.using.write();
+            if using.as_deref() == Some(schema) {
+                *using = None;
+            }
+        }
+    }
+
     /// Tries to kill a running task, returning whether the operation was successful.
     pub fn kill(&self, pid: &WeaverPid) -> Result<(), WeaverError> {
         let Some(process) = self.processes.write().remove(pid) else {
@@ -72,6 +72,8 @@ pub trait DynamicTable: Monitorable + HasSchema + Send + Sync {
     fn size_estimate(&self, key_index: &KeyIndex) -> Result<u64, WeaverError>;
 
     /// Update an existing row. Fails if no row with primary key is already present
+    ///
+    /// The row must contain every column, including system columns, as returned by [`read`](Self::read).
     fn update(&self, tx: &Tx, row: Row) -> Result<(), WeaverError>;
 
     /// Delete by key
@@ -7,6 +7,36 @@ use std::fmt::{Debug, Formatter};
 
 pub trait DynamicTableFactory: Send + Sync + Monitorable {
     fn open(&self, schema: &TableSchema, core: &WeaverDbCore) -> Result<Table, WeaverError>;
+
+    /// Removes any storage backing the table with the given schema. The table should already be
+    /// closed.
+    fn destroy(&self, _schema: &TableSchema) -> Result<(), WeaverError> {
+        Ok(())
+    }
+
+    /// Opens a table with the given schema in empty staging storage, separate from the storage of
+    /// the table itself.
+    fn open_staged(&self, schema: &TableSchema, core: &WeaverDbCore) -> Result<Table, WeaverError> {
+        self.open(schema, core)
+    }
+
+    /// Makes the storage of a staged table the storage of the table itself, replacing the storage
+    /// the table had, and returns the table. The table should already be closed.
+    ///
+    /// If the staged storage can't be promoted, the storage of the table must be left untouched.
+    fn promote_staged(
+        &self,
+        staged: Table,
+        _schema: &TableSchema,
+        _core: &WeaverDbCore,
+    ) -> Result<Table, WeaverError> {
+        Ok(staged)
+    }
+
+    /// Removes the staging storage of a table. The staged table should already be closed.
+    fn destroy_staged(&self, _schema: &TableSchema) -> Result<(), WeaverError> {
+        Ok(())
+    }
 }
 
 /// A delegated dynamic table that allows for object safe access over arbitrary types
@@ -24,6 +54,27 @@ impl DynamicTableFactory for DynamicTableFactoryDelegate {
     fn open(&self, schema: &TableSchema, core: &WeaverDbCore) -> Result<Table, WeaverError> {
         self.table_factory.open(schema, core)
     }
+
+    fn destroy(&self, schema: &TableSchema) -> Result<(), WeaverError> {
+        self.table_factory.destroy(schema)
+    }
+
+    fn open_staged(&self, schema: &TableSchema, core: &WeaverDbCore) -> Result<Table, WeaverError> {
+        self.table_factory.open_staged(schema, core)
+    }
+
+    fn promote_staged(
+        &self,
+        staged: Table,
+        schema: &TableSchema,
+        core: &WeaverDbCore,
+    ) -> Result<Table, WeaverError> {
+        self.table_factory.promote_staged(staged, schema, core)
+    }
+
+    fn destroy_staged(&self, schema: &TableSchema) -> Result<(), WeaverError> {
+        self.table_factory.destroy_staged(schema)
+    }
 }
 
 impl DynamicTableFactoryDelegate {
@@ -31,8 +31,42 @@ use crate::storage::{ReadDataError, WriteDataError};
 pub enum WeaverError {
     #[error("Illegal auto increment: {reason}")]
     IllegalAutoIncrement { reason: String },
+    #[error("Illegal default: {reason}")]
+    IllegalDefault { reason: String },
+    #[error("Illegal collation: {reason}")]
+    IllegalCollation { reason: String },
     #[error("Unexpected value of type found. (expected {expected:?}, received: {actual:?})")]
     TypeError { expected: Type, actual: DbVal },
+    #[error("Types {0} and {1} are incompatible")]
+    IncompatibleTypes(Type, Type),
+    #[error("The type of null can not be inferred")]
+    UntypedNull,
+    #[error("Can not cast {0} to {1}")]
+    InvalidCast(DbVal, Type),
+    #[error("Can not apply `{op}` to {left} and {right}")]
+    InvalidOperands {
+        op: String,
+        left: DbVal,
+        right: DbVal,
+    },
+    #[error("Can not apply `{op}` to {operand}")]
+    InvalidOperand { op: String, operand: DbVal },
+    #[error("Division by zero")]
+    DivideByZero,
+    #[error("Integer overflow")]
+    IntegerOverflow,
+    #[error("Value out of range for {0}")]
+    NumericOutOfRange(Type),
+    #[error("Date or time out of range")]
+    TemporalOutOfRange,
+    #[error("Unknown date or time unit {0:?}")]
+    UnknownTemporalUnit(String),
+    #[error("Invalid json: {0}")]
+    InvalidJson(String),
+    #[error("Invalid json path {0:?}")]
+    InvalidJsonPath(String),
+    #[error(transparent)]
+    RegexError(#[from] regex::Error),
     #[error("Illegal definition for column {col:?}: {reason}")]
     IllegalColumnDefinition {
         col: OwnedCol,
@@ -104,6 +138,8 @@ pub enum WeaverError {
     ParseBoolError(#[from] ParseBoolError),
     #[error(transparent)]
     ParseFloatError(#[from] ParseFloatError),
+    #[error(transparent)]
+    ParseUuidError(#[from] uuid::Error),
     #[error("Could not parse {0:?}")]
     ParseError(String),
     #[error("Could not use unqualified table reference without in-use schema")]
@@ -151,6 +187,16 @@ pub enum WeaverError {
     VirtualPagerError(#[from] VirtualPagerError),
     #[error("No strategy for {0}")]
     NoStrategyForJoin(JoinClause),
+    #[error("Unsupported subquery in {0}: {1}")]
+    UnsupportedSubQuery(Expr, String),
+    #[error("Unsupported window function {0}: {1}")]
+    UnsupportedWindowFunction(Expr, String),
+    #[error("Subquery {0} returned more than one row")]
+    SubQueryReturnedManyRows(Expr),
+    #[error("Unsupported common table expression {0}: {1}")]
+    UnsupportedCommonTableExpr(String, String),
+    #[error("Recursive common table expression {0} did not finish within {1} iterations")]
+    RecursionLimitReached(String, usize),
     #[error("Unknown function: {0}({})", _1.iter().map(ToString::to_string).collect::<Vec<_>>().join(","))]
     UnknownFunction(String, Vec<ArgType>),
     #[error("Column not resolved")]
@@ -179,6 +225,44 @@ pub enum WeaverError {
     CancelTaskFailed,
     #[error("schema `{0}` does not exist")]
     SchemaNotFound(String),
+    #[error("schema `{0}` already exists")]
+    SchemaAlreadyExists(String),
+    #[error("table `{schema}.{table}` is protected and can not be dropped or altered")]
+    ProtectedTable { schema: String, table: String },
+    #[error("schema `{0}` still contains tables. Use `drop schema {0} cascade` to drop them too")]
+    SchemaNotEmpty(String),
+    #[error("duplicate entry {value:?} for unique key `{key}`")]
+    UniqueKeyViolation { key: String, value: KeyData },
+    #[error("key `{0}` already exists")]
+    KeyAlreadyExists(String),
+    #[error("column `{0}` already exists")]
+    ColumnAlreadyExists(String),
+    #[error("column `{0}` is specified more than once")]
+    DuplicateColumn(String),
+    #[error("column `{0}` is part of the primary key and can not be dropped")]
+    PrimaryKeyColumn(String),
+    #[error("can not change column `{column}` from {from} to {to}")]
+    IllegalTypeChange {
+        column: String,
+        from: Type,
+        to: Type,
+    },
+    #[error("column `{0}` can not be null")]
+    NullNotAllowed(String),
+    #[error("Illegal foreign key `{name}`: {reason}")]
+    IllegalForeignKey { name: String, reason: String },
+    #[error("no row referenced by foreign key `{foreign_key}` matches {value:?}")]
+    ForeignKeyViolation { foreign_key: String, value: KeyData },
+    #[error("row with {value:?} is still referenced through foreign key `{foreign_key}`")]
+    RowReferenced { foreign_key: String, value: KeyData },
+    #[error("`{name}` is still referenced by foreign key `{foreign_key}`")]
+    Referenced { name: String, foreign_key: String },
+    #[error("Illegal check `{name}`: {reason}")]
+    IllegalCheck { name: String, reason: String },
+    #[error("Illegal index `{name}`: {reason}")]
+    IllegalIndex { name: String, reason: String },
+    #[error("row violates check constraint `{name}`: {check}")]
+    CheckViolation { name: String, check: String },
 
     #[error(transparent)]
     SerdeJsonError(#[from] serde_json::Error),
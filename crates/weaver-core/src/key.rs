@@ -11,7 +11,12 @@ use tracing::trace;
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct KeyData(OwnedRow);
 
-impl KeyData {}
+impl KeyData {
+    /// Gets the first `len` values of this key, or the whole key if it is shorter
+    pub fn prefix(&self, len: usize) -> KeyData {
+        KeyData::from(self.0.slice(..len.min(self.0.len())))
+    }
+}
 
 impl Debug for KeyData {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -127,11 +132,11 @@ impl KeyDataRange {
 
         let min = [&self.0, &other.0]
             .into_iter()
-            .min_by(|&a, &b| compare_bounds(a, b))
+            .min_by(|&a, &b| compare_lower_bounds(a, b))
             .unwrap();
         let max = [&self.1, &other.1]
             .into_iter()
-            .max_by(|&a, &b| compare_bounds(a, b))
+            .max_by(|&a, &b| compare_upper_bounds(a, b))
             .unwrap();
 
         let range = Self(min.clone(), max.clone());
@@ -152,11 +157,11 @@ impl KeyDataRange {
 
         let lower = [&self.0, &other.0]
             .into_iter()
-            .max_by(|&a, &b| compare_bounds(a, b))
+            .max_by(|&a, &b| compare_lower_bounds(a, b))
             .unwrap();
         let upper = [&self.1, &other.1]
             .into_iter()
-            .min_by(|&a, &b| compare_bounds(a, b))
+            .min_by(|&a, &b| compare_upper_bounds(a, b))
             .unwrap();
 
         let range = Self(lower.clone(), upper.clone());
@@ -169,6 +174,23 @@ impl KeyDataRange {
         Some(range)
     }
 
+    /// Sorts ranges by their lower bound, combining any ranges that share keys
+    pub fn merge(mut ranges: Vec<Self>) -> Vec<Self> {
+        ranges.sort_by(|a, b| compare_lower_bounds(&a.0, &b.0));
+        let mut merged: Vec<Self> = vec![];
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if starts_before_end(&range.0, &last.1) => {
+                    if compare_upper_bounds(&range.1, &last.1).is_gt() {
+                        last.1 = range.1;
+                    }
+                }
+                _ => merged.push(range),
+            }
+        }
+        merged
+    }
+
     pub fn start_bound(&self) -> Bound<&KeyData> {
         self.0.as_ref()
     }
@@ -213,18 +235,44 @@ fn partial_compare_bounds<T: PartialOrd>(b1: &Bound<T>, b2: &Bound<T>) -> Option
     }
 }
 
-fn compare_bounds<T: Ord>(b1: &Bound<T>, b2: &Bound<T>) -> Ordering {
+/// Compares two lower bounds, where an unbounded lower bound is the least
+fn compare_lower_bounds<T: Ord>(b1: &Bound<T>, b2: &Bound<T>) -> Ordering {
     match (b1, b2) {
         (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
         (Bound::Excluded(x), Bound::Excluded(y)) => x.cmp(y),
         (Bound::Included(x), Bound::Included(y)) => x.cmp(y),
-        (Bound::Included(_), Bound::Excluded(_)) => todo!(),
-        (Bound::Excluded(_), Bound::Included(_)) => todo!(),
+        (Bound::Included(x), Bound::Excluded(y)) => x.cmp(y).then(Ordering::Less),
+        (Bound::Excluded(x), Bound::Included(y)) => x.cmp(y).then(Ordering::Greater),
         (Bound::Unbounded, _) => Ordering::Less,
         (_, Bound::Unbounded) => Ordering::Greater,
     }
 }
 
+/// Compares two upper bounds, where an unbounded upper bound is the greatest
+fn compare_upper_bounds<T: Ord>(b1: &Bound<T>, b2: &Bound<T>) -> Ordering {
+    match (b1, b2) {
+        (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
+        (Bound::Excluded(x), Bound::Excluded(y)) => x.cmp(y),
+        (Bound::Included(x), Bound::Included(y)) => x.cmp(y),
+        (Bound::Included(x), Bound::Excluded(y)) => x.cmp(y).then(Ordering::Greater),
+        (Bound::Excluded(x), Bound::Included(y)) => x.cmp(y).then(Ordering::Less),
+        (Bound::Unbounded, _) => Ordering::Greater,
+        (_, Bound::Unbounded) => Ordering::Less,
+    }
+}
+
+/// Checks if a range starting at the lower bound shares any key with a range ending at the upper
+/// bound
+fn starts_before_end<T: Ord>(lower: &Bound<T>, upper: &Bound<T>) -> bool {
+    match (lower, upper) {
+        (Bound::Unbounded, _) | (_, Bound::Unbounded) => true,
+        (Bound::Included(x), Bound::Included(y)) => x <= y,
+        (Bound::Included(x), Bound::Excluded(y))
+        | (Bound::Excluded(x), Bound::Included(y))
+        | (Bound::Excluded(x), Bound::Excluded(y)) => x < y,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::data::values::DbVal;
@@ -254,6 +302,46 @@ mod tests {
         assert!(!range.contains(&KeyData::from([DbVal::Float(f64::MAX)])));
     }
 
+    #[test]
+    fn intersect_ranges() {
+        let page = KeyDataRange::from(KeyData::from([1])..=KeyData::from([5]));
+        let below = KeyDataRange::from(..KeyData::from([3]));
+        let intersection = page.intersection(&below).expect("should overlap");
+        assert!(intersection.contains(&KeyData::from([1])));
+        assert!(intersection.contains(&KeyData::from([2])));
+        assert!(!intersection.contains(&KeyData::from([3])));
+
+        let above = KeyDataRange::from(KeyData::from([3])..);
+        let intersection = page.intersection(&above).expect("should overlap");
+        assert!(!intersection.contains(&KeyData::from([2])));
+        assert!(intersection.contains(&KeyData::from([5])));
+        assert!(!intersection.contains(&KeyData::from([6])));
+    }
+
+    #[test]
+    fn merge_ranges() {
+        let merged = KeyDataRange::merge(vec![
+            KeyDataRange::from(KeyData::from([5])..),
+            KeyDataRange::from(..KeyData::from([3])),
+            KeyDataRange::from(KeyData::from([1])..=KeyData::from([4])),
+            KeyDataRange::from(KeyData::from([7])..KeyData::from([9])),
+        ]);
+        assert_eq!(merged.len(), 2);
+        assert!(merged[0].contains(&KeyData::from([4])));
+        assert!(!merged[0].contains(&KeyData::from([5])));
+        assert!(!merged[1].contains(&KeyData::from([4])));
+        assert!(merged[1].contains(&KeyData::from([100])));
+
+        let split = KeyDataRange::merge(vec![
+            KeyDataRange::from(..KeyData::from([3])),
+            KeyDataRange(
+                std::ops::Bound::Excluded(KeyData::from([3])),
+                std::ops::Bound::Unbounded,
+            ),
+        ]);
+        assert_eq!(split.len(), 2, "ranges excluding the same key are disjoint");
+    }
+
     #[test]
     fn hash_keys() {
         let mut hash_set = HashSet::<KeyData>::new();
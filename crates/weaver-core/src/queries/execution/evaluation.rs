@@ -2,6 +2,7 @@ use std::borrow::Cow;
 
 use std::collections::BTreeMap;
 
+use chrono::{Days, NaiveDateTime, NaiveTime};
 use itertools::Itertools;
 
 use tracing::trace;
@@ -10,8 +11,12 @@ use uuid::Uuid;
 use builtins::BUILTIN_FUNCTIONS_REGISTRY;
 use weaver_ast::ast::{BinaryOp, ColumnRef, Expr, FunctionArgs, Identifier, UnaryOp};
 
+use crate::data::charset::Collation;
+use crate::data::decimal::{Decimal, MAX_PRECISION};
+use crate::data::json::as_json;
 use crate::data::row::Row;
-use crate::data::types::DbTypeOf;
+use crate::data::temporal::{add_micros_to_time, time_to_micros, Interval};
+use crate::data::types::{DbTypeOf, Type};
 use crate::data::values::DbVal;
 use crate::error::WeaverError;
 use crate::queries::execution::evaluation::functions::{
@@ -22,6 +27,7 @@ use crate::storage::tables::table_schema::TableSchema;
 
 pub mod builtins;
 pub mod functions;
+pub mod window;
 
 #[derive(Debug)]
 pub struct ExpressionEvaluator {
@@ -131,26 +137,50 @@ fn runtime_eval_many_rows<'a>(
         }
         Expr::Unary { op, expr } => {
             let child = runtime_eval_many_rows(expr, rows, scope, function_registry)?;
-            Ok(Cow::Owned(evaluate_unary(op, child)))
+            Ok(Cow::Owned(evaluate_unary(op, child)?))
+        }
+        Expr::Cast { expr, data_type } => {
+            let value = runtime_eval_many_rows(expr, rows, scope, function_registry)?;
+            Ok(Cow::Owned(Type::from(*data_type).cast(value.into_owned())?))
         }
         Expr::Binary { left, op, right } => {
             let left = runtime_eval_many_rows(left, rows, scope, function_registry)?;
             let right = runtime_eval_many_rows(right, rows, scope, function_registry)?;
-            Ok(Cow::Owned(evaluate_binary(op, left, right)))
+            Ok(Cow::Owned(evaluate_binary(
+                op,
+                left,
+                right,
+                collation_of(expr, scope),
+            )?))
         }
         Expr::FunctionCall {
             function: function_name,
             args,
         } => {
-            let FunctionKind {
-                aggregate: Some(function),
-                ..
-            } = find_function(function_registry, function_name, args, scope)?
-            else {
-                return Err(WeaverError::UnknownFunction(
-                    function_name.to_string(),
-                    arg_types(function_registry, args, true, scope),
-                ));
+            let FunctionKind { normal, aggregate } =
+                find_function(function_registry, function_name, args, scope)?;
+            let Some(function) = aggregate else {
+                // a normal function applied to aggregates, like `coalesce(max(age), 0)`
+                let (Some(function), FunctionArgs::Params { exprs, .. }) = (normal, args) else {
+                    return Err(WeaverError::UnknownFunction(
+                        function_name.to_string(),
+                        arg_types(function_registry, args, true, scope),
+                    ));
+                };
+                if evaluates_operands(expr) {
+                    return evaluate_coalesce(expr, scope, function_registry, |expr| {
+                        runtime_eval_many_rows(expr, rows, scope, function_registry)
+                    });
+                }
+                let args = exprs
+                    .iter()
+                    .map(|expr| {
+                        runtime_eval_many_rows(expr, rows, scope, function_registry)
+                            .map(ArgValue::One)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let args = widen_to_common_type(expr, function, args, scope, function_registry)?;
+                return Ok(Cow::Owned(function.execute(args)?));
             };
 
             let args = match args {
@@ -159,25 +189,45 @@ fn runtime_eval_many_rows<'a>(
                     exprs,
                     ordered_by,
                 } => {
-                    let mut args = exprs
-                        .iter()
-                        .map(|expr| -> Result<_, WeaverError> {
-                            let mut evals = vec![];
-                            for &row in rows {
-                                let evaluated =
-                                    runtime_eval_single_row(expr, row, scope, function_registry)?;
-                                evals.push(evaluated);
-                            }
-                            Ok(evals)
-                        })
-                        .collect::<Result<Vec<_>, _>>()?;
-                    if *distinct {
-                        args = args.into_iter().unique().collect();
+                    let eval = |exprs: &[Expr], row| {
+                        exprs
+                            .iter()
+                            .map(|expr| {
+                                runtime_eval_single_row(expr, row, scope, function_registry)
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                    };
+                    let mut evaluated = vec![];
+                    for &row in rows {
+                        let values = eval(exprs, row)?;
+                        // null is never one of the distinct values being aggregated
+                        if *distinct && values.iter().any(|value| **value == DbVal::Null) {
+                            continue;
+                        }
+                        let order = match ordered_by {
+                            Some(ordered_by) => eval(ordered_by, row)?,
+                            None => vec![],
+                        };
+                        evaluated.push((order, values));
+                    }
+                    // the values are passed in ascending order, keeping the order of equal rows
+                    if ordered_by.is_some() {
+                        evaluated.sort_by(|(l, _), (r, _)| l.cmp(r));
                     }
-                    if let Some(ordered_by) = ordered_by {
-                        todo!("ordered by {:?}", ordered_by)
+                    let mut evaluated = evaluated
+                        .into_iter()
+                        .map(|(_, values)| values)
+                        .collect::<Vec<_>>();
+                    if *distinct {
+                        evaluated = evaluated.into_iter().unique().collect();
                     }
 
+                    let mut args = vec![vec![]; exprs.len()];
+                    for values in evaluated {
+                        for (arg, value) in args.iter_mut().zip(values) {
+                            arg.push(value);
+                        }
+                    }
                     args.into_iter().map(ArgValue::Many).collect()
                 }
                 FunctionArgs::Wildcard { distinct } => {
@@ -192,6 +242,19 @@ fn runtime_eval_many_rows<'a>(
             let result = function.execute(args)?;
             Ok(Cow::Owned(result))
         }
+        Expr::Case { .. } => evaluate_case(expr, scope, function_registry, |expr| {
+            runtime_eval_many_rows(expr, rows, scope, function_registry)
+        }),
+        Expr::InList { .. } => evaluate_in_list(expr, |expr| {
+            runtime_eval_many_rows(expr, rows, scope, function_registry)
+        }),
+        Expr::Between { .. } => evaluate_between(expr, |expr| {
+            runtime_eval_many_rows(expr, rows, scope, function_registry)
+        }),
+        Expr::InSubQuery { .. } | Expr::Exists { .. } | Expr::SubQuery { .. } => {
+            Err(unplanned_sub_query(expr))
+        }
+        Expr::WindowFunction { .. } => Err(unplanned_window_function(expr)),
     }
 }
 
@@ -203,7 +266,7 @@ fn runtime_eval_single_row<'a>(
     functions: &FunctionRegistry,
 ) -> Result<Cow<'a, DbVal>, WeaverError> {
     let mut stack: Vec<Cow<'a, DbVal>> = vec![];
-    let ops = expr.postfix();
+    let ops = expr.postfix_until(&evaluates_operands);
     trace!("evaluating using schema {schema:?}");
     for op in ops {
         match op {
@@ -222,9 +285,19 @@ fn runtime_eval_single_row<'a>(
                         "missing value on stack for uniop".to_string(),
                     )
                 })?;
-                let next = evaluate_unary(unary, expr);
+                let next = evaluate_unary(unary, expr)?;
                 stack.push(Cow::Owned(next));
             }
+            Expr::Cast { data_type, .. } => {
+                let value = stack.pop().ok_or_else(|| {
+                    WeaverError::EvaluationFailed(
+                        op.clone(),
+                        "missing value on stack for cast".to_string(),
+                    )
+                })?;
+                let cast = Type::from(*data_type).cast(value.into_owned())?;
+                stack.push(Cow::Owned(cast));
+            }
             Expr::Binary {
                 left: _l,
                 op: bin_op,
@@ -243,7 +316,7 @@ fn runtime_eval_single_row<'a>(
                     )
                 })?;
 
-                let evaluated: DbVal = evaluate_binary(bin_op, l, r);
+                let evaluated: DbVal = evaluate_binary(bin_op, l, r, collation_of(op, schema))?;
                 stack.push(Cow::Owned(evaluated));
             }
             Expr::FunctionCall {
@@ -260,6 +333,13 @@ fn runtime_eval_single_row<'a>(
                         arg_types(functions, args, false, schema),
                     ));
                 };
+                if evaluates_operands(op) {
+                    let result = evaluate_coalesce(op, schema, functions, |expr| {
+                        runtime_eval_single_row(expr, row, schema, functions)
+                    })?;
+                    stack.push(result);
+                    continue;
+                }
 
                 let args = match args {
                     FunctionArgs::Params { exprs, .. } => {
@@ -277,13 +357,34 @@ fn runtime_eval_single_row<'a>(
                         ))
                     }
                 };
+                let args = widen_to_common_type(op, function, args, schema, functions)?;
 
                 let result = function.execute(args)?;
                 stack.push(Cow::Owned(result));
             }
-            #[allow(unreachable_patterns)]
-            _expr => {
-                todo!("evaluate {_expr}")
+            Expr::Case { .. } => {
+                let result = evaluate_case(op, schema, functions, |expr| {
+                    runtime_eval_single_row(expr, row, schema, functions)
+                })?;
+                stack.push(result);
+            }
+            Expr::InList { .. } => {
+                let result = evaluate_in_list(op, |expr| {
+                    runtime_eval_single_row(expr, row, schema, functions)
+                })?;
+                stack.push(result);
+            }
+            Expr::Between { .. } => {
+                let result = evaluate_between(op, |expr| {
+                    runtime_eval_single_row(expr, row, schema, functions)
+                })?;
+                stack.push(result);
+            }
+            Expr::InSubQuery { .. } | Expr::Exists { .. } | Expr::SubQuery { .. } => {
+                return Err(unplanned_sub_query(op));
+            }
+            Expr::WindowFunction { .. } => {
+                return Err(unplanned_window_function(op));
             }
         }
     }
@@ -340,7 +441,7 @@ pub fn find_function<'a, 't>(
     Ok(FunctionKind { normal, aggregate })
 }
 
-fn arg_types<'t>(
+pub(crate) fn arg_types<'t>(
     functions: &FunctionRegistry,
     args: &FunctionArgs,
     is_agg: bool,
@@ -361,71 +462,577 @@ fn arg_types<'t>(
     arg_types
 }
 
-fn evaluate_binary(bin_op: &BinaryOp, l: Cow<DbVal>, r: Cow<DbVal>) -> DbVal {
-    match bin_op {
-        BinaryOp::Eq => (l == r).into(),
-        BinaryOp::Neq => (l != r).into(),
-        BinaryOp::Greater => (l > r).into(),
-        BinaryOp::Less => (l < r).into(),
-        BinaryOp::GreaterEq => (l >= r).into(),
-        BinaryOp::LessEq => (l <= r).into(),
-        BinaryOp::Plus => match (l.as_ref(), r.as_ref()) {
-            (DbVal::Integer(l), DbVal::Integer(r)) => (l + r).into(),
-            (DbVal::Float(l), DbVal::Float(r)) => (l + r).into(),
-            (DbVal::String(l, _), DbVal::String(r, _)) => format!("{l}{r}").into(),
-            (DbVal::Binary(l, l_len), DbVal::Binary(r, r_len)) => DbVal::Binary(
-                l.iter().chain(r.iter()).copied().collect::<Vec<u8>>(),
-                l_len.saturating_add(*r_len),
-            ),
-            _ => panic!("can not apply `+` to {l} and {r}"),
-        },
-        BinaryOp::Minus => match (l.as_ref(), r.as_ref()) {
-            (DbVal::Integer(l), DbVal::Integer(r)) => (l - r).into(),
-            (DbVal::Float(l), DbVal::Float(r)) => (l - r).into(),
-            _ => panic!("can not apply `-` to {l} and {r}"),
-        },
-        BinaryOp::Multiply => match (l.as_ref(), r.as_ref()) {
-            (DbVal::Integer(l), DbVal::Integer(r)) => (l * r).into(),
-            (DbVal::Float(l), DbVal::Float(r)) => (l + r).into(),
-            _ => panic!("can not apply `*` to {l} and {r}"),
-        },
-        BinaryOp::Divide => match (l.as_ref(), r.as_ref()) {
-            (DbVal::Integer(l), DbVal::Integer(r)) => (l / r).into(),
-            (DbVal::Float(l), DbVal::Float(r)) => (l / r).into(),
-            _ => panic!("can not apply `/` to {l} and {r}"),
-        },
-        BinaryOp::And => {
-            if let (DbVal::Boolean(left), DbVal::Boolean(right)) = (l.as_ref(), r.as_ref()) {
-                (*left && *right).into()
-            } else {
-                panic!("can not apply `and` to {l} and {r}");
+/// Widens the arguments of a function taking values of their common type to the type of the call,
+/// like the results of a `case`
+fn widen_to_common_type<'a>(
+    call: &Expr,
+    function: &DbFunction,
+    args: Vec<ArgValue<'a>>,
+    schema: &TableSchema,
+    functions: &FunctionRegistry,
+) -> Result<Vec<ArgValue<'a>>, WeaverError> {
+    if !function.takes_common_type() {
+        return Ok(args);
+    }
+    let ty = call.type_of(functions, Some(schema))?;
+    Ok(args
+        .into_iter()
+        .map(|arg| match arg {
+            ArgValue::One(value)
+                if value
+                    .value_type()
+                    .is_some_and(|value_type| value_type != ty) =>
+            {
+                ArgValue::One(Cow::Owned(ty.widen(value.into_owned())))
             }
+            arg => arg,
+        })
+        .collect())
+}
+
+/// Evaluates a `case` expression. Only the conditions up to the first matching branch and the
+/// result of that branch are evaluated.
+fn evaluate_case<'a, F>(
+    case: &Expr,
+    schema: &TableSchema,
+    functions: &FunctionRegistry,
+    mut eval: F,
+) -> Result<Cow<'a, DbVal>, WeaverError>
+where
+    F: FnMut(&Expr) -> Result<Cow<'a, DbVal>, WeaverError>,
+{
+    let Expr::Case {
+        operand,
+        branches,
+        otherwise,
+    } = case
+    else {
+        unreachable!("only case expressions can be evaluated as a case")
+    };
+
+    let operand = operand.as_deref().map(&mut eval).transpose()?;
+    let mut matched = otherwise.as_deref();
+    for (condition, result) in branches {
+        let condition = eval(condition)?;
+        let is_match = match &operand {
+            // null is never equal to anything, not even null
+            Some(operand) => **operand != DbVal::Null && operand == &condition,
+            None => condition.bool_value() == Some(true),
+        };
+        if is_match {
+            matched = Some(result);
+            break;
+        }
+    }
+
+    let Some(result) = matched else {
+        return Ok(Cow::Owned(DbVal::Null));
+    };
+    widen_to_type_of(case, eval(result)?, schema, functions)
+}
+
+/// Functions evaluating their arguments themselves, which are only evaluated up to the first
+/// non-null one
+static LAZY_FUNCTIONS: &[&str] = &["coalesce", "ifnull"];
+
+/// Gets whether an expression evaluates its own operands, instead of being given their values
+fn evaluates_operands(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::FunctionCall {
+            function,
+            args: FunctionArgs::Params { .. },
+        } if LAZY_FUNCTIONS.contains(&function.as_ref().to_lowercase().as_str())
+    )
+}
+
+/// Evaluates a call to `coalesce` or `ifnull`. Only the arguments up to the first non-null one
+/// are evaluated.
+fn evaluate_coalesce<'a, F>(
+    call: &Expr,
+    schema: &TableSchema,
+    functions: &FunctionRegistry,
+    mut eval: F,
+) -> Result<Cow<'a, DbVal>, WeaverError>
+where
+    F: FnMut(&Expr) -> Result<Cow<'a, DbVal>, WeaverError>,
+{
+    let Expr::FunctionCall {
+        args: FunctionArgs::Params { exprs, .. },
+        ..
+    } = call
+    else {
+        unreachable!("only function calls can be evaluated as a coalesce")
+    };
+
+    for expr in exprs {
+        let value = eval(expr)?;
+        if *value != DbVal::Null {
+            return widen_to_type_of(call, value, schema, functions);
         }
-        BinaryOp::Or => {
-            if let (DbVal::Boolean(left), DbVal::Boolean(right)) = (l.as_ref(), r.as_ref()) {
-                (*left || *right).into()
+    }
+    Ok(Cow::Owned(DbVal::Null))
+}
+
+/// Widens the value of one of the results of an expression to the type of the expression
+fn widen_to_type_of<'a>(
+    expr: &Expr,
+    value: Cow<'a, DbVal>,
+    schema: &TableSchema,
+    functions: &FunctionRegistry,
+) -> Result<Cow<'a, DbVal>, WeaverError> {
+    match value.value_type() {
+        Some(value_type) => {
+            let ty = expr.type_of(functions, Some(schema))?;
+            if value_type == ty {
+                Ok(value)
             } else {
-                panic!("can not apply `or` to {l} and {r}");
+                Ok(Cow::Owned(ty.widen(value.into_owned())))
             }
         }
+        None => Ok(value),
+    }
+}
+
+/// Evaluates an `in` list. The list is only evaluated up to the first value equal to the expression.
+fn evaluate_in_list<'a, F>(in_list: &Expr, mut eval: F) -> Result<Cow<'a, DbVal>, WeaverError>
+where
+    F: FnMut(&Expr) -> Result<Cow<'a, DbVal>, WeaverError>,
+{
+    let Expr::InList {
+        expr,
+        list,
+        negated,
+    } = in_list
+    else {
+        unreachable!("only in lists can be evaluated as an in list")
+    };
+
+    let value = eval(expr)?;
+    is_in(&value, list.iter().map(eval), *negated)
+}
+
+/// Checks if a value is one of the items, which are only evaluated up to the first one equal to
+/// the value. The result is null if no item is equal to the value but some item is null.
+pub(crate) fn is_in<'a, 'b, I>(
+    value: &DbVal,
+    items: I,
+    negated: bool,
+) -> Result<Cow<'a, DbVal>, WeaverError>
+where
+    I: IntoIterator<Item = Result<Cow<'b, DbVal>, WeaverError>>,
+{
+    if *value == DbVal::Null {
+        return Ok(Cow::Owned(DbVal::Null));
     }
+    // a null item might be equal to the value, so the result is unknown without a match
+    let mut any_null = false;
+    for item in items {
+        let item = item?;
+        if *item == DbVal::Null {
+            any_null = true;
+        } else if *item == *value {
+            return Ok(Cow::Owned((!negated).into()));
+        }
+    }
+    if any_null {
+        return Ok(Cow::Owned(DbVal::Null));
+    }
+    Ok(Cow::Owned(negated.into()))
+}
+
+/// Evaluates a `between` expression, where both bounds are inclusive
+fn evaluate_between<'a, F>(between: &Expr, mut eval: F) -> Result<Cow<'a, DbVal>, WeaverError>
+where
+    F: FnMut(&Expr) -> Result<Cow<'a, DbVal>, WeaverError>,
+{
+    let Expr::Between {
+        expr,
+        low,
+        high,
+        negated,
+    } = between
+    else {
+        unreachable!("only between expressions can be evaluated as a between")
+    };
+
+    let value = eval(expr)?;
+    let low = eval(low)?;
+    let high = eval(high)?;
+    if [&value, &low, &high]
+        .iter()
+        .any(|val| ***val == DbVal::Null)
+    {
+        return Ok(Cow::Owned(DbVal::Null));
+    }
+    let within = low <= value && value <= high;
+    Ok(Cow::Owned((within != *negated).into()))
+}
+
+/// Subqueries are planned as joins or run by apply nodes, so they are never evaluated directly
+pub(crate) fn unplanned_sub_query(expr: &Expr) -> WeaverError {
+    WeaverError::UnsupportedSubQuery(
+        expr.clone(),
+        "can not be used in this part of a query".to_string(),
+    )
+}
+
+pub(crate) fn unplanned_window_function(expr: &Expr) -> WeaverError {
+    WeaverError::UnsupportedWindowFunction(
+        expr.clone(),
+        "can only be used in the selected columns".to_string(),
+    )
+}
+
+/// Gets the collation the strings of a binary expression are compared in, which is the collation
+/// of the column among its operands
+fn collation_of(binary: &Expr, schema: &TableSchema) -> &'static Collation {
+    let Expr::Binary { left, right, .. } = binary else {
+        return Collation::default_collation();
+    };
+    [left, right]
+        .into_iter()
+        .find_map(|operand| match &**operand {
+            Expr::Column {
+                column: ColumnRef::Resolved(column),
+            } => schema.column_by_source(column),
+            _ => None,
+        })
+        .map(|column| column.collation())
+        .unwrap_or_else(Collation::default_collation)
 }
 
-fn evaluate_unary(unary: &UnaryOp, expr: Cow<DbVal>) -> DbVal {
-    match unary {
-        UnaryOp::Not => match expr.as_ref() {
-            DbVal::Binary(binary, i) => {
-                DbVal::Binary(binary.iter().map(|b| !*b).collect::<Vec<_>>(), *i)
+/// Evaluates a binary operation, where strings are pattern matched in the given collation
+fn evaluate_binary(
+    bin_op: &BinaryOp,
+    l: Cow<DbVal>,
+    r: Cow<DbVal>,
+    collation: &Collation,
+) -> Result<DbVal, WeaverError> {
+    let invalid = |l: &DbVal, r: &DbVal| WeaverError::InvalidOperands {
+        op: bin_op.to_string(),
+        left: l.clone(),
+        right: r.clone(),
+    };
+    Ok(match bin_op {
+        BinaryOp::Eq
+        | BinaryOp::Neq
+        | BinaryOp::Greater
+        | BinaryOp::Less
+        | BinaryOp::GreaterEq
+        | BinaryOp::LessEq => {
+            // ordering against an unknown value has an unknown result. Equality is kept, as
+            // `is null` and `is not null` compare against null.
+            if !matches!(bin_op, BinaryOp::Eq | BinaryOp::Neq)
+                && (*l == DbVal::Null || *r == DbVal::Null)
+            {
+                return Ok(DbVal::Null);
+            }
+            let (l, r) = promote(l, r).map_err(|(l, r)| {
+                WeaverError::IncompatibleTypes(
+                    l.value_type().expect("nulls are never promoted"),
+                    r.value_type().expect("nulls are never promoted"),
+                )
+            })?;
+            match bin_op {
+                BinaryOp::Eq => (l == r).into(),
+                BinaryOp::Neq => (l != r).into(),
+                BinaryOp::Greater => (l > r).into(),
+                BinaryOp::Less => (l < r).into(),
+                BinaryOp::GreaterEq => (l >= r).into(),
+                _ => (l <= r).into(),
+            }
+        }
+        BinaryOp::Plus | BinaryOp::Minus | BinaryOp::Multiply | BinaryOp::Divide => {
+            if *l == DbVal::Null || *r == DbVal::Null {
+                return Ok(DbVal::Null);
             }
-            DbVal::Integer(i) => DbVal::Integer(!i),
-            _other => panic!("can not bitwise negate {_other}"),
+            if l.value_type().is_some_and(|t| t.is_temporal())
+                || r.value_type().is_some_and(|t| t.is_temporal())
+            {
+                return evaluate_temporal_arithmetic(bin_op, &l, &r)?
+                    .ok_or_else(|| invalid(&l, &r));
+            }
+            // decimal results get their precision from the operands before they're promoted
+            let result_type = l
+                .value_type()
+                .zip(r.value_type())
+                .and_then(|(l_type, r_type)| l_type.arithmetic(bin_op, &r_type));
+            let (l, r) = promote(l, r).map_err(|(l, r)| invalid(&l, &r))?;
+            match (bin_op, l.as_ref(), r.as_ref()) {
+                (BinaryOp::Divide, DbVal::Integer(_), DbVal::Integer(0)) => {
+                    return Err(WeaverError::DivideByZero)
+                }
+                (_, &DbVal::Integer(left), &DbVal::Integer(right)) => match bin_op {
+                    BinaryOp::Plus => left.checked_add(right),
+                    BinaryOp::Minus => left.checked_sub(right),
+                    BinaryOp::Multiply => left.checked_mul(right),
+                    _ => left.checked_div(right),
+                }
+                .ok_or(WeaverError::IntegerOverflow)?
+                .into(),
+                (_, DbVal::Decimal(left, _), DbVal::Decimal(right, _)) => {
+                    let Some(ty @ Type::Decimal(precision, scale)) = result_type else {
+                        return Err(invalid(&l, &r));
+                    };
+                    let result = match bin_op {
+                        BinaryOp::Plus => left.checked_add(right, scale),
+                        BinaryOp::Minus => left.checked_sub(right, scale),
+                        BinaryOp::Multiply => left.checked_mul(right, scale),
+                        _ if right.unscaled() == 0 => return Err(WeaverError::DivideByZero),
+                        _ => left.checked_div(right, scale),
+                    };
+                    DbVal::Decimal(
+                        result
+                            .filter(|decimal| decimal.digits() <= precision)
+                            .ok_or(WeaverError::NumericOutOfRange(ty))?,
+                        precision,
+                    )
+                }
+                (_, &DbVal::Float(left), &DbVal::Float(right)) => match bin_op {
+                    BinaryOp::Plus => left + right,
+                    BinaryOp::Minus => left - right,
+                    BinaryOp::Multiply => left * right,
+                    _ => left / right,
+                }
+                .into(),
+                (BinaryOp::Plus, DbVal::String(left, _), DbVal::String(right, _)) => {
+                    format!("{left}{right}").into()
+                }
+                (BinaryOp::Plus, DbVal::Binary(left, l_len), DbVal::Binary(right, r_len)) => {
+                    DbVal::Binary(
+                        left.iter()
+                            .chain(right.iter())
+                            .copied()
+                            .collect::<Vec<u8>>(),
+                        l_len.saturating_add(*r_len),
+                    )
+                }
+                _ => return Err(invalid(&l, &r)),
+            }
+        }
+        // three-valued logic, where null is unknown
+        BinaryOp::And => match (l.as_ref(), r.as_ref()) {
+            (DbVal::Boolean(false), DbVal::Boolean(_) | DbVal::Null)
+            | (DbVal::Boolean(_) | DbVal::Null, DbVal::Boolean(false)) => false.into(),
+            (DbVal::Boolean(true), DbVal::Boolean(true)) => true.into(),
+            (DbVal::Boolean(_) | DbVal::Null, DbVal::Boolean(_) | DbVal::Null) => DbVal::Null,
+            _ => return Err(invalid(&l, &r)),
         },
-        UnaryOp::Negate => match expr.as_ref() {
-            DbVal::Integer(i) => DbVal::Integer(-i),
-            DbVal::Float(f) => DbVal::Float(-f),
-            _other => panic!("can not negate {_other}"),
+        BinaryOp::Or => match (l.as_ref(), r.as_ref()) {
+            (DbVal::Boolean(true), DbVal::Boolean(_) | DbVal::Null)
+            | (DbVal::Boolean(_) | DbVal::Null, DbVal::Boolean(true)) => true.into(),
+            (DbVal::Boolean(false), DbVal::Boolean(false)) => false.into(),
+            (DbVal::Boolean(_) | DbVal::Null, DbVal::Boolean(_) | DbVal::Null) => DbVal::Null,
+            _ => return Err(invalid(&l, &r)),
         },
+        BinaryOp::Like { escape } | BinaryOp::NotLike { escape } => {
+            match (l.as_ref(), r.as_ref()) {
+                (DbVal::Null, _) | (_, DbVal::Null) => DbVal::Null,
+                (DbVal::String(s, _), DbVal::String(pattern, _)) => {
+                    let is_match = collation.like(s, pattern, escape.unwrap_or('\\'));
+                    (is_match == matches!(bin_op, BinaryOp::Like { .. })).into()
+                }
+                _ => return Err(invalid(&l, &r)),
+            }
+        }
+        BinaryOp::Regexp => match (l.as_ref(), r.as_ref()) {
+            (DbVal::Null, _) | (_, DbVal::Null) => DbVal::Null,
+            (DbVal::String(s, _), DbVal::String(pattern, _)) => {
+                collation.regexp(s, pattern)?.into()
+            }
+            _ => return Err(invalid(&l, &r)),
+        },
+        BinaryOp::JsonGet | BinaryOp::JsonGetText => {
+            let Some(json) = as_json(&l)? else {
+                return Ok(DbVal::Null);
+            };
+            let member = match r.as_ref() {
+                DbVal::Null => return Ok(DbVal::Null),
+                DbVal::String(key, _) => json.get(key),
+                &DbVal::Integer(index) => json.index(index),
+                _ => return Err(invalid(&l, &r)),
+            };
+            match (member, bin_op) {
+                (None, _) => DbVal::Null,
+                (Some(member), BinaryOp::JsonGet) => DbVal::Json(member),
+                (Some(member), _) => member.text().map(DbVal::from).unwrap_or(DbVal::Null),
+            }
+        }
+    })
+}
+
+/// Date and time arithmetic, for the operand types given by [Type::arithmetic]. Gives `None` if
+/// the operator can't be used with the operands.
+fn evaluate_temporal_arithmetic(
+    bin_op: &BinaryOp,
+    l: &DbVal,
+    r: &DbVal,
+) -> Result<Option<DbVal>, WeaverError> {
+    use BinaryOp::{Divide, Minus, Multiply, Plus};
+    let as_timestamp = |val: &DbVal| match val {
+        DbVal::Date(date) => Some(date.and_time(NaiveTime::MIN)),
+        DbVal::Timestamp(timestamp) => Some(*timestamp),
+        DbVal::TimestampTz(timestamp) => Some(timestamp.naive_utc()),
+        _ => None,
+    };
+    let negated = |interval: &Interval| {
+        if *bin_op == Minus {
+            interval
+                .checked_neg()
+                .ok_or(WeaverError::TemporalOutOfRange)
+        } else {
+            Ok(*interval)
+        }
+    };
+    Ok(Some(match (l, bin_op, r) {
+        (DbVal::Date(date), Plus | Minus, &DbVal::Integer(days))
+        | (&DbVal::Integer(days), Plus, DbVal::Date(date)) => {
+            let shifted = if (days < 0) == (*bin_op == Minus) {
+                date.checked_add_days(Days::new(days.unsigned_abs()))
+            } else {
+                date.checked_sub_days(Days::new(days.unsigned_abs()))
+            };
+            DbVal::Date(shifted.ok_or(WeaverError::TemporalOutOfRange)?)
+        }
+        (DbVal::Date(left), Minus, DbVal::Date(right)) => {
+            DbVal::Integer(left.signed_duration_since(*right).num_days())
+        }
+        (DbVal::Time(time), Plus | Minus, DbVal::Interval(interval))
+        | (DbVal::Interval(interval), Plus, DbVal::Time(time)) => {
+            // only the time of day part of the interval can move a time
+            DbVal::Time(add_micros_to_time(time, negated(interval)?.micros))
+        }
+        (DbVal::Time(left), Minus, DbVal::Time(right)) => DbVal::Interval(Interval::new(
+            0,
+            0,
+            time_to_micros(left) - time_to_micros(right),
+        )),
+        (point, Plus | Minus, DbVal::Interval(interval))
+        | (DbVal::Interval(interval), Plus, point)
+            if as_timestamp(point).is_some() =>
+        {
+            let timestamp: NaiveDateTime =
+                negated(interval)?.add_to(as_timestamp(point).expect("checked above"))?;
+            match point {
+                DbVal::TimestampTz(_) => DbVal::TimestampTz(timestamp.and_utc()),
+                _ => DbVal::Timestamp(timestamp),
+            }
+        }
+        (left, Minus, right) if as_timestamp(left).is_some() && as_timestamp(right).is_some() => {
+            DbVal::Interval(Interval::between(
+                as_timestamp(right).expect("checked above"),
+                as_timestamp(left).expect("checked above"),
+            )?)
+        }
+        (DbVal::Interval(left), Plus | Minus, DbVal::Interval(right)) => DbVal::Interval(
+            left.checked_add(&negated(right)?)
+                .ok_or(WeaverError::TemporalOutOfRange)?,
+        ),
+        (DbVal::Interval(interval), Multiply | Divide, factor)
+        | (factor, Multiply, DbVal::Interval(interval)) => {
+            let factor = match *factor {
+                DbVal::Integer(i) => i as f64,
+                DbVal::Float(f) => f,
+                DbVal::Decimal(ref decimal, _) => decimal.to_f64(),
+                _ => return Ok(None),
+            };
+            let factor = if *bin_op == Divide {
+                if factor == 0.0 {
+                    return Err(WeaverError::DivideByZero);
+                }
+                1.0 / factor
+            } else {
+                factor
+            };
+            DbVal::Interval(
+                interval
+                    .checked_mul(factor)
+                    .ok_or(WeaverError::TemporalOutOfRange)?,
+            )
+        }
+        _ => return Ok(None),
+    }))
+}
+
+/// Promotes two non-null values of different types to their [common type](Type::common_type),
+/// giving the values back if they have none.
+#[allow(clippy::type_complexity)]
+fn promote<'a>(
+    l: Cow<'a, DbVal>,
+    r: Cow<'a, DbVal>,
+) -> Result<(Cow<'a, DbVal>, Cow<'a, DbVal>), (Cow<'a, DbVal>, Cow<'a, DbVal>)> {
+    let (Some(l_type), Some(r_type)) = (l.value_type(), r.value_type()) else {
+        return Ok((l, r));
+    };
+    if std::mem::discriminant(&l_type) == std::mem::discriminant(&r_type) {
+        return Ok((l, r));
     }
+    let Some(common) = l_type.common_type(&r_type) else {
+        // strings are read as decimals, json, uuids, dates and times when compared to them
+        let parse = |s: &DbVal, ty: Type| match (s, ty) {
+            // keeps every digit of the string, rather than rounding it to the decimal's scale
+            (DbVal::String(s, _), Type::Decimal(..)) => s
+                .parse::<Decimal>()
+                .ok()
+                .map(|decimal| Cow::Owned(DbVal::Decimal(decimal, MAX_PRECISION))),
+            (DbVal::String(..), ty)
+                if ty.is_temporal() || matches!(ty, Type::Json | Type::Uuid) =>
+            {
+                ty.cast(s.clone()).ok().map(Cow::Owned)
+            }
+            _ => None,
+        };
+        return match (l_type, r_type) {
+            (Type::String(_), ty) => match parse(&l, ty) {
+                Some(l) => Ok((l, r)),
+                None => Err((l, r)),
+            },
+            (ty, Type::String(_)) => match parse(&r, ty) {
+                Some(r) => Ok((l, r)),
+                None => Err((l, r)),
+            },
+            _ => Err((l, r)),
+        };
+    };
+    let widen = |val: Cow<'a, DbVal>, ty: Type| {
+        if ty == common {
+            val
+        } else {
+            Cow::Owned(common.widen(val.into_owned()))
+        }
+    };
+    Ok((widen(l, l_type), widen(r, r_type)))
+}
+
+fn evaluate_unary(unary: &UnaryOp, expr: Cow<DbVal>) -> Result<DbVal, WeaverError> {
+    Ok(match (unary, expr.as_ref()) {
+        (_, DbVal::Null) => DbVal::Null,
+        (UnaryOp::Not, DbVal::Binary(binary, i)) => {
+            DbVal::Binary(binary.iter().map(|b| !*b).collect::<Vec<_>>(), *i)
+        }
+        (UnaryOp::Not, DbVal::Integer(i)) => DbVal::Integer(!i),
+        (UnaryOp::Not, DbVal::Boolean(b)) => DbVal::Boolean(!b),
+        (UnaryOp::Negate, DbVal::Integer(i)) => {
+            DbVal::Integer(i.checked_neg().ok_or(WeaverError::IntegerOverflow)?)
+        }
+        (UnaryOp::Negate, DbVal::Float(f)) => DbVal::Float(-f),
+        (UnaryOp::Negate, DbVal::Decimal(decimal, precision)) => DbVal::Decimal(
+            decimal.checked_neg().ok_or(WeaverError::IntegerOverflow)?,
+            *precision,
+        ),
+        (UnaryOp::Negate, DbVal::Interval(interval)) => DbVal::Interval(
+            interval
+                .checked_neg()
+                .ok_or(WeaverError::TemporalOutOfRange)?,
+        ),
+        (_, operand) => {
+            return Err(WeaverError::InvalidOperand {
+                op: unary.to_string(),
+                operand: operand.clone(),
+            })
+        }
+    })
 }
 
 fn get_from_column<'a>(
@@ -453,13 +1060,22 @@ fn get_from_column<'a>(
 
 #[cfg(test)]
 mod tests {
+    use std::borrow::Cow;
+
     use weaver_ast::ast::{BinaryOp, Expr, FunctionArgs, Identifier, Literal, ResolvedColumnRef};
 
+    use crate::data::charset::Collation;
     use crate::data::row::Row;
     use crate::data::types::Type;
+    use crate::data::values::DbVal;
     use crate::error::WeaverError;
     use crate::queries::execution::evaluation::builtins::BUILTIN_FUNCTIONS_REGISTRY;
-    use crate::queries::execution::evaluation::{runtime_eval_many_rows, runtime_eval_single_row};
+    use crate::queries::execution::evaluation::functions::{
+        ArgType, ArgValue, DbFunction, FunctionRegistry,
+    };
+    use crate::queries::execution::evaluation::{
+        evaluate_binary, runtime_eval_many_rows, runtime_eval_single_row,
+    };
     use crate::storage::tables::table_schema::{TableSchema, TableSchemaBuilder};
 
     #[test]
@@ -483,6 +1099,89 @@ mod tests {
         assert_eq!(result.int_value(), Some(63));
     }
 
+    #[test]
+    fn promoted_arithmetic() {
+        let stored = &Row::new(0);
+        let evaluate = |left: Expr, op: BinaryOp, right: Expr| {
+            runtime_eval_single_row(
+                &Expr::Binary {
+                    left: Box::new(left),
+                    op,
+                    right: Box::new(right),
+                },
+                stored,
+                &TableSchema::empty(),
+                &BUILTIN_FUNCTIONS_REGISTRY,
+            )
+            .map(|result| result.into_owned())
+        };
+        assert_eq!(
+            evaluate(Expr::from(2.5), BinaryOp::Multiply, Expr::from(2)).unwrap(),
+            DbVal::Float(5.0)
+        );
+        assert_eq!(
+            evaluate(Expr::from(1), BinaryOp::Minus, Expr::from(Literal::Null)).unwrap(),
+            DbVal::Null
+        );
+        assert!(matches!(
+            evaluate(Expr::from(1), BinaryOp::Divide, Expr::from(0)),
+            Err(WeaverError::DivideByZero)
+        ));
+        assert!(matches!(
+            evaluate(Expr::from(i64::MAX), BinaryOp::Plus, Expr::from(1)),
+            Err(WeaverError::IntegerOverflow)
+        ));
+        assert!(matches!(
+            evaluate(Expr::from("a".to_string()), BinaryOp::Minus, Expr::from(1)),
+            Err(WeaverError::InvalidOperands { .. })
+        ));
+    }
+
+    #[test]
+    fn three_valued_logic() {
+        let evaluate = |l: DbVal, op: BinaryOp, r: DbVal| {
+            evaluate_binary(
+                &op,
+                Cow::Owned(l),
+                Cow::Owned(r),
+                Collation::default_collation(),
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            evaluate(true.into(), BinaryOp::And, false.into()),
+            false.into()
+        );
+        assert_eq!(
+            evaluate(DbVal::Null, BinaryOp::And, false.into()),
+            false.into()
+        );
+        assert_eq!(
+            evaluate(DbVal::Null, BinaryOp::And, true.into()),
+            DbVal::Null
+        );
+        assert_eq!(
+            evaluate(false.into(), BinaryOp::Or, true.into()),
+            true.into()
+        );
+        assert_eq!(
+            evaluate(DbVal::Null, BinaryOp::Or, true.into()),
+            true.into()
+        );
+        assert_eq!(
+            evaluate(false.into(), BinaryOp::Or, DbVal::Null),
+            DbVal::Null
+        );
+        assert_eq!(
+            evaluate(DbVal::Null, BinaryOp::GreaterEq, 0.into()),
+            DbVal::Null
+        );
+        assert_eq!(
+            evaluate(DbVal::Null, BinaryOp::Eq, DbVal::Null),
+            true.into()
+        );
+    }
+
     #[test]
     fn single_arg_function() {
         let stored = &Row::new(0);
@@ -618,4 +1317,188 @@ mod tests {
         .expect("couldn't get minimum value");
         assert_eq!(result.int_value(), Some(3), "distinct count should be 3");
     }
+
+    #[test]
+    fn distinct_sum() {
+        let rows = &[
+            &Row::from([1_i64]),
+            &Row::from([2_i64]),
+            &Row::from([3_i64]),
+        ];
+        let sum = |distinct| {
+            runtime_eval_many_rows(
+                &Expr::FunctionCall {
+                    function: Identifier::new("sum"),
+                    args: FunctionArgs::Params {
+                        distinct,
+                        exprs: vec![Expr::from(2)],
+                        ordered_by: None,
+                    },
+                },
+                rows,
+                &TableSchema::empty(),
+                &BUILTIN_FUNCTIONS_REGISTRY,
+            )
+            .expect("couldn't get sum")
+            .int_value()
+        };
+        assert_eq!(sum(false), Some(6));
+        assert_eq!(
+            sum(true),
+            Some(2),
+            "only one distinct value should be summed"
+        );
+    }
+
+    #[test]
+    fn ordered_aggregate_arguments() {
+        let rows = &[
+            &Row::from([1_i64, 3_i64]),
+            &Row::from([2_i64, 1_i64]),
+            &Row::from([3_i64, 2_i64]),
+        ];
+        let schema = TableSchemaBuilder::new("s", "t")
+            .column("value", Type::Integer, true, None, None)
+            .unwrap()
+            .column("position", Type::Integer, true, None, None)
+            .unwrap()
+            .build()
+            .unwrap();
+        let column = |name| Expr::Column {
+            column: ResolvedColumnRef::new("s", "t", name).into(),
+        };
+        // an aggregate whose result depends on the order of the values
+        let functions = FunctionRegistry::from_iter([(
+            "first",
+            DbFunction::builtin(vec![ArgType::Many(Type::Integer)], Type::Integer, |args| {
+                let ArgValue::Many(values) = &args[0] else {
+                    panic!()
+                };
+                Ok(values
+                    .first()
+                    .map(|value| value.as_ref().clone())
+                    .unwrap_or(DbVal::Null))
+            }),
+        )]);
+        let first = |ordered_by| {
+            runtime_eval_many_rows(
+                &Expr::FunctionCall {
+                    function: Identifier::new("first"),
+                    args: FunctionArgs::Params {
+                        distinct: false,
+                        exprs: vec![column("value")],
+                        ordered_by,
+                    },
+                },
+                rows,
+                &schema,
+                &functions,
+            )
+            .expect("couldn't get first value")
+            .int_value()
+        };
+        assert_eq!(first(None), Some(1));
+        assert_eq!(first(Some(vec![column("position")])), Some(2));
+    }
+
+    #[test]
+    fn lazy_case() {
+        let stored = &Row::new(0);
+        // the result of the first branch would panic if it was evaluated
+        let case = |operand: i64| Expr::Case {
+            operand: Some(Box::new(Expr::from(operand))),
+            branches: vec![
+                (
+                    Expr::from(1),
+                    Expr::Binary {
+                        left: Box::new(Expr::from(1)),
+                        op: BinaryOp::Divide,
+                        right: Box::new(Expr::from(0)),
+                    },
+                ),
+                (Expr::from(2), Expr::from(2.5)),
+            ],
+            otherwise: Some(Box::new(Expr::from(3))),
+        };
+        let result = runtime_eval_single_row(
+            &case(2),
+            stored,
+            &TableSchema::empty(),
+            &BUILTIN_FUNCTIONS_REGISTRY,
+        )
+        .expect("could not evaluate case");
+        assert_eq!(result.float_value(), Some(2.5));
+
+        let result = runtime_eval_single_row(
+            &case(3),
+            stored,
+            &TableSchema::empty(),
+            &BUILTIN_FUNCTIONS_REGISTRY,
+        )
+        .expect("could not evaluate case");
+        assert_eq!(
+            result.float_value(),
+            Some(3.0),
+            "else should be widened to the type of the case"
+        );
+    }
+
+    #[test]
+    fn null_handling_functions() {
+        let stored = &Row::from([DbVal::Null, DbVal::from("hello")]);
+        let schema = TableSchemaBuilder::new("s", "t")
+            .column("a", Type::String(16), false, None, None)
+            .unwrap()
+            .column("b", Type::String(16), false, None, None)
+            .unwrap()
+            .build()
+            .unwrap();
+        let a = Expr::Column {
+            column: ResolvedColumnRef::new("s", "t", "a").into(),
+        };
+        let b = Expr::Column {
+            column: ResolvedColumnRef::new("s", "t", "b").into(),
+        };
+        let call = |function: &str, exprs: Vec<Expr>| {
+            runtime_eval_single_row(
+                &Expr::FunctionCall {
+                    function: Identifier::new(function),
+                    args: FunctionArgs::Params {
+                        distinct: false,
+                        exprs,
+                        ordered_by: None,
+                    },
+                },
+                stored,
+                &schema,
+                &BUILTIN_FUNCTIONS_REGISTRY,
+            )
+            .map(|result| result.into_owned())
+        };
+
+        assert_eq!(
+            call(
+                "coalesce",
+                vec![a.clone(), b.clone(), Expr::from("other".to_string())]
+            )
+            .unwrap(),
+            DbVal::from("hello")
+        );
+        assert_eq!(
+            call("ifnull", vec![a.clone(), Expr::from("default".to_string())]).unwrap(),
+            DbVal::from("default")
+        );
+        assert_eq!(
+            call("nullif", vec![b.clone(), Expr::from("hello".to_string())]).unwrap(),
+            DbVal::Null
+        );
+        assert_eq!(
+            call("nullif", vec![b.clone(), a.clone()]).unwrap(),
+            DbVal::from("hello")
+        );
+        assert!(matches!(
+            call("coalesce", vec![a, Expr::from(1)]),
+            Err(WeaverError::UnknownFunction(_, _))
+        ));
+    }
 }
@@ -1,15 +1,36 @@
 use std::cmp::Ordering;
 
 use once_cell::sync::Lazy;
+use uuid::Uuid;
 
+use crate::data::decimal::{Decimal, MAX_PRECISION};
+use crate::data::json::as_json;
+use crate::data::temporal;
 use crate::data::types::Type;
 use crate::data::values::DbVal;
+use crate::error::WeaverError;
 use crate::queries::execution::evaluation::functions::{
     ArgType, ArgValue, DbFunction, FunctionRegistry,
 };
 
+/// The temporal types that `min` and `max` are defined for
+const TEMPORAL_TYPES: [Type; 5] = [
+    Type::Date,
+    Type::Time,
+    Type::Timestamp,
+    Type::TimestampTz,
+    Type::Interval,
+];
+
+/// The types json functions accept documents as, where strings are parsed
+const JSON_TYPES: [Type; 2] = [Type::Json, Type::String(u16::MAX)];
+
+/// The fewest digits after the decimal point `avg` gives a decimal, so the average of whole numbers
+/// isn't truncated
+const MIN_AVG_SCALE: u8 = 6;
+
 pub static BUILTIN_FUNCTIONS_REGISTRY: Lazy<FunctionRegistry> = Lazy::new(|| {
-    FunctionRegistry::from_iter([
+    let mut registry = FunctionRegistry::from_iter([
         (
             "count",
             DbFunction::builtin(vec![ArgType::Rows], Type::Integer, |args| match &args[0] {
@@ -106,7 +127,9 @@ pub static BUILTIN_FUNCTIONS_REGISTRY: Lazy<FunctionRegistry> = Lazy::new(|| {
                 let (sum, count) = vals
                     .iter()
                     .flat_map(|i| i.int_value())
-                    .fold((0, 0), |(sum, count), next| (sum + next, count + 1));
+                    .fold((0_i128, 0), |(sum, count), next| {
+                        (sum + next as i128, count + 1)
+                    });
                 if count == 0 {
                     return Ok(DbVal::Float(f64::NAN));
                 }
@@ -132,6 +155,38 @@ pub static BUILTIN_FUNCTIONS_REGISTRY: Lazy<FunctionRegistry> = Lazy::new(|| {
                 Ok(DbVal::Float(sum / count as f64))
             }),
         ),
+        (
+            "sum",
+            DbFunction::builtin(vec![ArgType::Many(Type::Integer)], Type::Integer, |args| {
+                let ArgValue::Many(vals) = &args[0] else {
+                    panic!()
+                };
+
+                let mut ints = vals.iter().flat_map(|i| i.int_value());
+                let Some(first) = ints.next() else {
+                    return Ok(DbVal::Null);
+                };
+                ints.try_fold(first, |sum, next| {
+                    sum.checked_add(next).ok_or(WeaverError::IntegerOverflow)
+                })
+                .map(DbVal::Integer)
+            }),
+        ),
+        (
+            "sum",
+            DbFunction::builtin(vec![ArgType::Many(Type::Float)], Type::Float, |args| {
+                let ArgValue::Many(vals) = &args[0] else {
+                    panic!()
+                };
+
+                Ok(vals
+                    .iter()
+                    .flat_map(|i| i.float_value())
+                    .reduce(|sum, next| sum + next)
+                    .map(DbVal::Float)
+                    .unwrap_or(DbVal::Null))
+            }),
+        ),
         (
             "pow",
             DbFunction::builtin(
@@ -194,5 +249,338 @@ pub static BUILTIN_FUNCTIONS_REGISTRY: Lazy<FunctionRegistry> = Lazy::new(|| {
                 Ok(i.abs().into())
             }),
         ),
-    ])
+        (
+            "now",
+            DbFunction::builtin(vec![], Type::TimestampTz, |_| {
+                Ok(DbVal::TimestampTz(temporal::now()))
+            }),
+        ),
+        (
+            "uuid",
+            // a random, version 4 uuid
+            DbFunction::builtin(vec![], Type::Uuid, |_| Ok(Uuid::new_v4().into())),
+        ),
+        (
+            "gen_random_uuid",
+            DbFunction::builtin(vec![], Type::Uuid, |_| Ok(Uuid::new_v4().into())),
+        ),
+        (
+            "to_timestamp",
+            // seconds since the unix epoch
+            DbFunction::builtin(
+                vec![ArgType::One(Type::Integer)],
+                Type::TimestampTz,
+                |args| {
+                    let ArgValue::One(seconds) = &args[0] else {
+                        panic!()
+                    };
+
+                    match seconds.int_value() {
+                        Some(seconds) => Ok(temporal::from_epoch_seconds(seconds as f64)?.into()),
+                        None => Ok(DbVal::Null),
+                    }
+                },
+            ),
+        ),
+        (
+            "to_timestamp",
+            DbFunction::builtin(vec![ArgType::One(Type::Float)], Type::TimestampTz, |args| {
+                let ArgValue::One(seconds) = &args[0] else {
+                    panic!()
+                };
+
+                match seconds.float_value() {
+                    Some(seconds) => Ok(temporal::from_epoch_seconds(seconds)?.into()),
+                    None => Ok(DbVal::Null),
+                }
+            }),
+        ),
+    ]);
+    registry.extend(null_handling_functions());
+    registry.extend(temporal_functions());
+    registry.extend(decimal_aggregates());
+    registry.extend(json_functions());
+    registry
 });
+
+/// Creates `sum`, `avg`, `min` and `max` for decimals of every scale. The results have the most
+/// precision possible, so sums only overflow if they don't fit in any decimal.
+fn decimal_aggregates() -> impl Iterator<Item = (&'static str, DbFunction)> {
+    (0..=MAX_PRECISION).flat_map(|scale| {
+        let ty = Type::Decimal(MAX_PRECISION, scale);
+        let avg_ty = Type::Decimal(MAX_PRECISION, scale.max(MIN_AVG_SCALE));
+        let decimals = |args: &[ArgValue<'_>]| -> Vec<Decimal> {
+            let ArgValue::Many(vals) = &args[0] else {
+                panic!()
+            };
+
+            vals.iter()
+                .filter_map(|val| match val.as_ref() {
+                    DbVal::Decimal(decimal, _) => Some(*decimal),
+                    _ => None,
+                })
+                .collect()
+        };
+        let sum = move |decimals: &[Decimal]| {
+            decimals
+                .iter()
+                .try_fold(Decimal::new(0, scale), |sum, next| {
+                    sum.checked_add(next, scale)
+                        .ok_or(WeaverError::NumericOutOfRange(ty))
+                })
+        };
+        [
+            (
+                "sum",
+                DbFunction::builtin(vec![ArgType::Many(ty)], ty, move |args| {
+                    let decimals = decimals(&args);
+                    if decimals.is_empty() {
+                        return Ok(DbVal::Null);
+                    }
+                    Ok(DbVal::Decimal(sum(&decimals)?, MAX_PRECISION))
+                }),
+            ),
+            (
+                "avg",
+                DbFunction::builtin(vec![ArgType::Many(ty)], avg_ty, move |args| {
+                    let decimals = decimals(&args);
+                    if decimals.is_empty() {
+                        return Ok(DbVal::Null);
+                    }
+                    let Type::Decimal(_, avg_scale) = avg_ty else {
+                        unreachable!()
+                    };
+                    let count = Decimal::from(decimals.len() as i64);
+                    let avg = sum(&decimals)?
+                        .checked_div(&count, avg_scale)
+                        .ok_or(WeaverError::NumericOutOfRange(avg_ty))?;
+                    Ok(DbVal::Decimal(avg, MAX_PRECISION))
+                }),
+            ),
+            (
+                "min",
+                DbFunction::builtin(vec![ArgType::Many(ty)], ty, move |args| {
+                    Ok(decimals(&args)
+                        .into_iter()
+                        .min()
+                        .map(|min| DbVal::Decimal(min, MAX_PRECISION))
+                        .unwrap_or(DbVal::Null))
+                }),
+            ),
+            (
+                "max",
+                DbFunction::builtin(vec![ArgType::Many(ty)], ty, move |args| {
+                    Ok(decimals(&args)
+                        .into_iter()
+                        .max()
+                        .map(|max| DbVal::Decimal(max, MAX_PRECISION))
+                        .unwrap_or(DbVal::Null))
+                }),
+            ),
+        ]
+    })
+}
+
+/// Creates `date_trunc`, `date_part` (which `extract` is parsed into), `min` and `max` for the
+/// temporal types
+fn temporal_functions() -> impl Iterator<Item = (&'static str, DbFunction)> {
+    TEMPORAL_TYPES.into_iter().flat_map(|ty| {
+        let truncated = match ty {
+            Type::Timestamp | Type::TimestampTz => Some((
+                "date_trunc",
+                DbFunction::builtin(
+                    vec![ArgType::One(Type::String(u16::MAX)), ArgType::One(ty)],
+                    ty,
+                    |args| {
+                        let [ArgValue::One(unit), ArgValue::One(value)] = &args[..] else {
+                            panic!()
+                        };
+
+                        Ok(match (unit.as_ref(), value.as_ref()) {
+                            (DbVal::String(unit, _), DbVal::Timestamp(timestamp)) => {
+                                DbVal::Timestamp(temporal::truncate(*timestamp, unit)?)
+                            }
+                            (DbVal::String(unit, _), DbVal::TimestampTz(timestamp)) => {
+                                temporal::truncate(timestamp.naive_utc(), unit)?
+                                    .and_utc()
+                                    .into()
+                            }
+                            _ => DbVal::Null,
+                        })
+                    },
+                ),
+            )),
+            _ => None,
+        };
+        let part = match ty {
+            Type::Time => None,
+            _ => Some((
+                "date_part",
+                DbFunction::builtin(
+                    vec![ArgType::One(Type::String(u16::MAX)), ArgType::One(ty)],
+                    Type::Float,
+                    |args| {
+                        let [ArgValue::One(field), ArgValue::One(value)] = &args[..] else {
+                            panic!()
+                        };
+
+                        let field = match field.as_ref() {
+                            DbVal::String(field, _) => field,
+                            _ => return Ok(DbVal::Null),
+                        };
+                        let part = match value.as_ref() {
+                            DbVal::Date(date) => {
+                                temporal::extract(date.and_time(Default::default()), field)?
+                            }
+                            DbVal::Timestamp(timestamp) => temporal::extract(*timestamp, field)?,
+                            DbVal::TimestampTz(timestamp) => {
+                                temporal::extract(timestamp.naive_utc(), field)?
+                            }
+                            DbVal::Interval(interval) => {
+                                temporal::extract_from_interval(interval, field)?
+                            }
+                            _ => return Ok(DbVal::Null),
+                        };
+                        Ok(DbVal::Float(part))
+                    },
+                ),
+            )),
+        };
+        let extremes = [
+            (
+                "min",
+                DbFunction::builtin(vec![ArgType::Many(ty)], ty, |args| {
+                    let ArgValue::Many(vals) = &args[0] else {
+                        panic!()
+                    };
+
+                    Ok(vals
+                        .iter()
+                        .filter(|val| ***val != DbVal::Null)
+                        .min()
+                        .map(|val| val.clone().into_owned())
+                        .unwrap_or(DbVal::Null))
+                }),
+            ),
+            (
+                "max",
+                DbFunction::builtin(vec![ArgType::Many(ty)], ty, |args| {
+                    let ArgValue::Many(vals) = &args[0] else {
+                        panic!()
+                    };
+
+                    Ok(vals
+                        .iter()
+                        .filter(|val| ***val != DbVal::Null)
+                        .max()
+                        .map(|val| val.clone().into_owned())
+                        .unwrap_or(DbVal::Null))
+                }),
+            ),
+        ];
+        truncated.into_iter().chain(part).chain(extremes)
+    })
+}
+
+/// Creates `json_extract`, `json_array_length`, `json_object_keys` and `json_valid` for json
+/// documents and for strings holding them
+fn json_functions() -> impl Iterator<Item = (&'static str, DbFunction)> {
+    JSON_TYPES.into_iter().flat_map(|ty| {
+        [
+            (
+                "json_extract",
+                DbFunction::builtin(
+                    vec![ArgType::One(ty), ArgType::One(Type::String(u16::MAX))],
+                    Type::Json,
+                    |args| {
+                        let [ArgValue::One(json), ArgValue::One(path)] = &args[..] else {
+                            panic!()
+                        };
+
+                        Ok(match (as_json(json)?, path.as_ref()) {
+                            (Some(json), DbVal::String(path, _)) => {
+                                json.extract(path)?.map(DbVal::Json).unwrap_or(DbVal::Null)
+                            }
+                            _ => DbVal::Null,
+                        })
+                    },
+                ),
+            ),
+            (
+                "json_array_length",
+                DbFunction::builtin(vec![ArgType::One(ty)], Type::Integer, |args| {
+                    let [ArgValue::One(json)] = &args[..] else {
+                        panic!()
+                    };
+
+                    Ok(as_json(json)?
+                        .and_then(|json| json.array_length())
+                        .map(DbVal::Integer)
+                        .unwrap_or(DbVal::Null))
+                }),
+            ),
+            (
+                "json_object_keys",
+                DbFunction::builtin(vec![ArgType::One(ty)], Type::Json, |args| {
+                    let [ArgValue::One(json)] = &args[..] else {
+                        panic!()
+                    };
+
+                    Ok(as_json(json)?
+                        .and_then(|json| json.object_keys())
+                        .map(DbVal::Json)
+                        .unwrap_or(DbVal::Null))
+                }),
+            ),
+            (
+                "json_valid",
+                DbFunction::builtin(vec![ArgType::One(ty)], Type::Boolean, |args| {
+                    let [ArgValue::One(value)] = &args[..] else {
+                        panic!()
+                    };
+
+                    Ok(match value.as_ref() {
+                        DbVal::Null => DbVal::Null,
+                        value => as_json(value).is_ok().into(),
+                    })
+                }),
+            ),
+        ]
+    })
+}
+
+/// Creates `coalesce`, `ifnull` and `nullif`, which take values of any types with a common type.
+/// The evaluator evaluates the arguments of `coalesce` and `ifnull` itself, stopping at the first
+/// non-null one.
+fn null_handling_functions() -> impl Iterator<Item = (&'static str, DbFunction)> {
+    [
+        ("coalesce", DbFunction::builtin_common(1, true, coalesce)),
+        ("ifnull", DbFunction::builtin_common(2, false, coalesce)),
+        (
+            "nullif",
+            DbFunction::builtin_common(2, false, |args| {
+                let [ArgValue::One(value), ArgValue::One(other)] = &args[..] else {
+                    panic!()
+                };
+
+                if value == other {
+                    Ok(DbVal::Null)
+                } else {
+                    Ok(value.clone().into_owned())
+                }
+            }),
+        ),
+    ]
+    .into_iter()
+}
+
+/// Gets the first non-null argument
+fn coalesce(args: Vec<ArgValue<'_>>) -> Result<DbVal, WeaverError> {
+    Ok(args
+        .into_iter()
+        .find_map(|arg| match arg {
+            ArgValue::One(value) if *value != DbVal::Null => Some(value.into_owned()),
+            _ => None,
+        })
+        .unwrap_or(DbVal::Null))
+}
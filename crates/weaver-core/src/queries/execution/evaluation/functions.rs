@@ -25,7 +25,8 @@ type BuiltinFn = dyn Fn(Vec<ArgValue<'_>>) -> Result<DbVal, WeaverError> + Send
 #[derive(Debug, Clone)]
 pub struct DbFunction {
     parameters: Vec<ArgType>,
-    return_type: Type,
+    return_type: ReturnType,
+    variadic: bool,
     body: FunctionBody,
 }
 
@@ -37,7 +38,27 @@ impl DbFunction {
     {
         Self {
             parameters,
-            return_type: return_ty,
+            return_type: ReturnType::Fixed(return_ty),
+            variadic: false,
+            body: FunctionBody::Builtin(Arc::from(Box::new(func) as Box<BuiltinFn>)),
+        }
+    }
+
+    /// Create a new builtin db function taking `arity` values of any types that have a
+    /// [common type](Type::common_type), which it returns a value of. A variadic function can be
+    /// given any number of values past the first `arity`.
+    pub fn builtin_common<F>(arity: usize, variadic: bool, func: F) -> Self
+    where
+        F: Fn(Vec<ArgValue<'_>>) -> Result<DbVal, WeaverError> + Send + Sync + 'static,
+    {
+        assert!(
+            arity > 0,
+            "a function taking a common type needs a parameter"
+        );
+        Self {
+            parameters: vec![ArgType::Common; arity],
+            return_type: ReturnType::Common,
+            variadic,
             body: FunctionBody::Builtin(Arc::from(Box::new(func) as Box<BuiltinFn>)),
         }
     }
@@ -58,9 +79,15 @@ impl DbFunction {
         &self.parameters
     }
 
-    /// Gets the return type of the function
-    pub fn return_type(&self) -> &Type {
-        &self.return_type
+    /// Checks whether the arguments are promoted to their common type before the function is
+    /// executed
+    pub fn takes_common_type(&self) -> bool {
+        self.return_type == ReturnType::Common
+    }
+
+    /// Gets the return type of the function when it's given arguments of the given types
+    pub fn return_type(&self, args: &[ArgType]) -> Option<Type> {
+        self.signature().return_type(args)
     }
 
     /// Gets the signature of the function
@@ -68,6 +95,7 @@ impl DbFunction {
         FunctionSignature {
             args: self.parameters.clone(),
             ret_type: self.return_type,
+            variadic: self.variadic,
         }
     }
 
@@ -93,6 +121,9 @@ pub enum ArgType {
     Row,
     /// allows for passing many rows (aggregated)
     Rows,
+    /// One value of any type, as long as it has a common type with the other values passed as
+    /// this
+    Common,
 }
 
 impl Debug for ArgType {
@@ -110,6 +141,9 @@ impl Debug for ArgType {
             ArgType::Rows => {
                 write!(f, "(row)")
             }
+            ArgType::Common => {
+                write!(f, "T")
+            }
         }
     }
 }
@@ -124,7 +158,7 @@ impl ArgType {
     /// helper to determine if a type is aggregate or not
     fn is_aggregate(&self) -> bool {
         match self {
-            ArgType::One(_) | ArgType::Row => false,
+            ArgType::One(_) | ArgType::Row | ArgType::Common => false,
             ArgType::Many(_) | ArgType::Rows => true,
         }
     }
@@ -155,6 +189,24 @@ impl<'a> ArgValue<'a> {
     }
 }
 
+/// The type of the value a function returns
+#[derive(Debug, Hash, Clone, Copy, Eq, PartialEq)]
+enum ReturnType {
+    /// Always the same type
+    Fixed(Type),
+    /// The common type of the arguments
+    Common,
+}
+
+impl Display for ReturnType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReturnType::Fixed(ty) => write!(f, "{ty}"),
+            ReturnType::Common => write!(f, "T"),
+        }
+    }
+}
+
 #[derive(Clone, DebugCustom)]
 enum FunctionBody {
     #[debug(fmt = "<builtin>")]
@@ -193,19 +245,19 @@ impl FunctionRegistry {
         }
     }
 
-    /// tries to get a db
+    /// tries to get a db. An overload taking the argument types as they are is preferred over
+    /// one that takes floats in place of decimals.
     pub fn get(&self, name: impl AsRef<str>, args: &[ArgType]) -> Option<&DbFunction> {
-        self.functions
-            .get(&name.as_ref().to_lowercase())
-            .and_then(|overloads| {
-                overloads.iter().find_map(|(sig, func)| {
-                    if sig.valid_args(args) {
-                        Some(func)
-                    } else {
-                        None
-                    }
-                })
+        let overloads = self.functions.get(&name.as_ref().to_lowercase())?;
+        [false, true].into_iter().find_map(|widen_decimals| {
+            overloads.iter().find_map(|(sig, func)| {
+                if sig.valid_args(args, widen_decimals) {
+                    Some(func)
+                } else {
+                    None
+                }
             })
+        })
     }
 
     /// Gets the total number of functions registered
@@ -255,37 +307,48 @@ impl<S: AsRef<str>> Extend<(S, DbFunction)> for FunctionRegistry {
 #[derive(Hash, Eq, PartialEq, Clone)]
 pub struct FunctionSignature {
     args: Vec<ArgType>,
-    ret_type: Type,
+    ret_type: ReturnType,
+    variadic: bool,
 }
 
 impl Debug for FunctionSignature {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "({}) -> {}",
+            "({}{}) -> {}",
             self.args.iter().map(|i| format!("{i:?}")).join(","),
+            if self.variadic { "..." } else { "" },
             self.ret_type
         )
     }
 }
 
 impl FunctionSignature {
-    /// checks if valid args
-    fn valid_args<'a, I: IntoIterator<Item = &'a ArgType>>(&self, args: I) -> bool {
+    /// checks if valid args, where decimals may be passed as floats if `widen_decimals` is set
+    fn valid_args<'a, I: IntoIterator<Item = &'a ArgType>>(
+        &self,
+        args: I,
+        widen_decimals: bool,
+    ) -> bool {
         let input_args = args.into_iter().collect::<Vec<_>>();
-        if input_args.len() != self.args.len() {
+        if input_args.len() != self.args.len()
+            && !(self.variadic && input_args.len() > self.args.len())
+        {
             return false;
         }
 
-        for (input, expected) in input_args.into_iter().zip(self.args.iter()) {
+        for (i, &input) in input_args.iter().enumerate() {
+            let Some(expected) = self.parameter(i) else {
+                return false;
+            };
             match (input, expected) {
                 (ArgType::Many(ty), ArgType::Many(e_ty)) => {
-                    if ty != e_ty {
+                    if !Self::valid_type(ty, e_ty, widen_decimals) {
                         return false;
                     }
                 }
                 (ArgType::One(ty), ArgType::One(e_ty)) => {
-                    if ty != e_ty {
+                    if !Self::valid_type(ty, e_ty, widen_decimals) {
                         return false;
                     }
                 }
@@ -298,11 +361,60 @@ impl FunctionSignature {
                 (ArgType::Rows, ArgType::Rows) | (ArgType::Row, ArgType::Row) => {
                     // good
                 }
+                (ArgType::One(_), ArgType::Common) => {
+                    // checked against the other values below
+                }
                 _ => return false,
             }
         }
 
-        true
+        !self.args.contains(&ArgType::Common) || self.common_type(input_args).is_some()
+    }
+
+    /// Gets the parameter the argument at an index is passed as, where the arguments past the
+    /// parameters of a variadic function are all passed as the last parameter
+    fn parameter(&self, index: usize) -> Option<&ArgType> {
+        match self.args.get(index) {
+            None if self.variadic => self.args.last(),
+            parameter => parameter,
+        }
+    }
+
+    /// Gets the common type of the arguments passed as [common](ArgType::Common) parameters
+    fn common_type<'a, I: IntoIterator<Item = &'a ArgType>>(&self, args: I) -> Option<Type> {
+        args.into_iter()
+            .enumerate()
+            .filter(|(i, _)| self.parameter(*i) == Some(&ArgType::Common))
+            .try_fold(None, |common: Option<Type>, (_, arg)| {
+                let (ArgType::One(ty) | ArgType::Many(ty)) = arg else {
+                    return None;
+                };
+                Some(Some(match common {
+                    None => *ty,
+                    Some(common) => common.common_type(ty)?,
+                }))
+            })
+            .flatten()
+    }
+
+    /// Gets the return type when given arguments of the given types
+    fn return_type(&self, args: &[ArgType]) -> Option<Type> {
+        match self.ret_type {
+            ReturnType::Fixed(ty) => Some(ty),
+            ReturnType::Common => self.common_type(args),
+        }
+    }
+
+    /// checks if a type is valid for a parameter. The maximum length of a string or binary doesn't
+    /// matter to functions, so they accept strings and binaries of any length. Likewise, decimals
+    /// of any precision are accepted, but the scale must match.
+    fn valid_type(ty: &Type, expected: &Type, widen_decimals: bool) -> bool {
+        match (ty, expected) {
+            (Type::String(_), Type::String(_)) | (Type::Binary(_), Type::Binary(_)) => true,
+            (Type::Decimal(_, scale), Type::Decimal(_, expected_scale)) => scale == expected_scale,
+            (Type::Decimal(..), Type::Float) => widen_decimals,
+            (ty, expected) => ty == expected,
+        }
     }
 }
 
@@ -367,4 +479,37 @@ mod tests {
             .expect("should get function 2");
         assert_ne!(function1.signature(), function2.signature());
     }
+
+    #[test]
+    fn variadic_common_type() {
+        let mut function_r = FunctionRegistry::empty();
+        function_r
+            .add(
+                "first",
+                DbFunction::builtin_common(1, true, |mut args| {
+                    let ArgValue::One(value) = args.remove(0) else {
+                        panic!("unexpected value")
+                    };
+                    Ok(value.into_owned())
+                }),
+            )
+            .expect("couldn't add function");
+
+        let args = [
+            ArgType::One(Type::Integer),
+            ArgType::One(Type::Decimal(5, 2)),
+            ArgType::One(Type::Boolean),
+        ];
+        let function = function_r
+            .get("first", &args)
+            .expect("should take any number of arguments");
+        assert_eq!(function.return_type(&args), Some(Type::Decimal(21, 2)));
+        assert!(function_r.get("first", &[]).is_none());
+        assert!(function_r
+            .get(
+                "first",
+                &[ArgType::One(Type::Integer), ArgType::One(Type::Uuid)]
+            )
+            .is_none());
+    }
 }
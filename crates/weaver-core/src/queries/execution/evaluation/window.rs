@@ -0,0 +1,397 @@
+//! Window functions are evaluated over the rows of a window around each row instead of only the
+//! row itself. Rows are split into partitions, and each partition is sorted. The frame of a row is
+//! the part of its partition that aggregates are evaluated over.
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::ops::Range;
+
+use weaver_ast::ast::{
+    Expr, FrameBound, FrameUnits, FunctionArgs, Identifier, OrderBy, OrderDirection, Window,
+    WindowFrame,
+};
+
+use crate::data::row::Row;
+use crate::data::types::{DbTypeOf, Type};
+use crate::data::values::DbVal;
+use crate::error::WeaverError;
+use crate::queries::execution::evaluation::functions::FunctionRegistry;
+use crate::queries::execution::evaluation::{
+    arg_types, find_function, runtime_eval_many_rows, runtime_eval_single_row, ExpressionEvaluator,
+    FunctionKind,
+};
+use crate::storage::tables::table_schema::TableSchema;
+
+/// The functions that can only be used with a window. Any other window function must be an
+/// aggregate.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum WindowFunctionKind {
+    /// The position of the row within its partition
+    RowNumber,
+    /// The position of the first row ordered the same as the row
+    Rank,
+    /// The number of distinct orderings up to and including the row
+    DenseRank,
+    /// A value of the row a number of rows before the row
+    Lag,
+    /// A value of the row a number of rows after the row
+    Lead,
+    /// A value of the first row of the frame
+    FirstValue,
+    /// An aggregate of every row of the frame
+    Aggregate,
+}
+
+impl WindowFunctionKind {
+    fn new(function: &Identifier) -> Self {
+        match function.as_ref().to_lowercase().as_str() {
+            "row_number" => Self::RowNumber,
+            "rank" => Self::Rank,
+            "dense_rank" => Self::DenseRank,
+            "lag" => Self::Lag,
+            "lead" => Self::Lead,
+            "first_value" => Self::FirstValue,
+            _ => Self::Aggregate,
+        }
+    }
+}
+
+/// Gets the type of the values of a window function, checking that it is called correctly
+pub fn window_function_type(
+    expr: &Expr,
+    functions: &FunctionRegistry,
+    context_schema: Option<&TableSchema>,
+) -> Result<Type, WeaverError> {
+    let Expr::WindowFunction {
+        function,
+        args,
+        window,
+    } = expr
+    else {
+        panic!("{expr} is not a window function")
+    };
+    let unsupported =
+        |reason: &str| WeaverError::UnsupportedWindowFunction(expr.clone(), reason.to_string());
+
+    if let Some(WindowFrame {
+        units: FrameUnits::Range,
+        start,
+        end,
+    }) = &window.frame
+    {
+        let has_offset = [start, end]
+            .iter()
+            .any(|bound| matches!(bound, FrameBound::Preceding(_) | FrameBound::Following(_)));
+        if has_offset {
+            let [OrderBy(order, _)] = window.order_by.as_slice() else {
+                return Err(unsupported(
+                    "a range with an offset must be ordered by exactly one value",
+                ));
+            };
+            if !matches!(
+                order.type_of(functions, context_schema)?,
+                Type::Integer | Type::Float
+            ) {
+                return Err(unsupported(
+                    "a range with an offset must be ordered by a number",
+                ));
+            }
+        }
+    }
+
+    let exprs = match args {
+        FunctionArgs::Params { exprs, .. } => exprs.as_slice(),
+        FunctionArgs::Wildcard { .. } => &[],
+    };
+    match WindowFunctionKind::new(function) {
+        WindowFunctionKind::RowNumber
+        | WindowFunctionKind::Rank
+        | WindowFunctionKind::DenseRank => {
+            if !exprs.is_empty() {
+                return Err(unsupported("takes no arguments"));
+            }
+            Ok(Type::Integer)
+        }
+        WindowFunctionKind::Lag | WindowFunctionKind::Lead => {
+            let [value, rest @ ..] = exprs else {
+                return Err(unsupported("must be given a value"));
+            };
+            if rest.len() > 2 {
+                return Err(unsupported("takes at most an offset and a default"));
+            }
+            if let Some(offset) = rest.first() {
+                if !matches!(offset, Expr::Literal { .. })
+                    || offset.type_of(functions, context_schema)? != Type::Integer
+                {
+                    return Err(unsupported("the offset must be an integer"));
+                }
+            }
+            value.type_of(functions, context_schema)
+        }
+        WindowFunctionKind::FirstValue => {
+            let [value] = exprs else {
+                return Err(unsupported("must be given exactly one value"));
+            };
+            value.type_of(functions, context_schema)
+        }
+        WindowFunctionKind::Aggregate => {
+            let FunctionKind { aggregate, .. } =
+                find_function(functions, function, args, context_schema)?;
+            aggregate
+                .and_then(|aggregate| {
+                    aggregate.return_type(&arg_types(functions, args, true, context_schema))
+                })
+                .ok_or_else(|| unsupported("must be an aggregate"))
+        }
+    }
+}
+
+impl ExpressionEvaluator {
+    /// Evaluates a window function for every row, returning the values in the same order as the
+    /// rows
+    pub fn evaluate_window_function(
+        &self,
+        expr: &Expr,
+        rows: &[Row],
+        schema: &TableSchema,
+    ) -> Result<Vec<DbVal>, WeaverError> {
+        let Expr::WindowFunction {
+            function,
+            args,
+            window,
+        } = expr
+        else {
+            panic!("{expr} is not a window function")
+        };
+        let exprs = match args {
+            FunctionArgs::Params { exprs, .. } => exprs.as_slice(),
+            FunctionArgs::Wildcard { .. } => &[],
+        };
+        let kind = WindowFunctionKind::new(function);
+        let eval = |expr: &Expr, row: &Row| -> Result<DbVal, WeaverError> {
+            runtime_eval_single_row(expr, row, schema, &self.functions).map(Cow::into_owned)
+        };
+
+        let mut keys = Vec::with_capacity(rows.len());
+        for row in rows {
+            let partition = window
+                .partition_by
+                .iter()
+                .map(|expr| eval(expr, row))
+                .collect::<Result<Vec<_>, _>>()?;
+            let order = window
+                .order_by
+                .iter()
+                .map(|OrderBy(expr, _)| eval(expr, row))
+                .collect::<Result<Vec<_>, _>>()?;
+            keys.push((partition, order));
+        }
+        let mut sorted = (0..rows.len()).collect::<Vec<_>>();
+        sorted.sort_by(|&l, &r| {
+            keys[l]
+                .0
+                .cmp(&keys[r].0)
+                .then_with(|| compare_order(&window.order_by, &keys[l].1, &keys[r].1))
+        });
+
+        let aggregate = Expr::FunctionCall {
+            function: function.clone(),
+            args: args.clone(),
+        };
+        let mut values = vec![DbVal::Null; rows.len()];
+        let mut partition_start = 0;
+        while partition_start < sorted.len() {
+            let partition_end = sorted[partition_start..]
+                .iter()
+                .position(|&idx| keys[idx].0 != keys[sorted[partition_start]].0)
+                .map_or(sorted.len(), |len| partition_start + len);
+            let partition = &sorted[partition_start..partition_end];
+            partition_start = partition_end;
+
+            // rows ordered the same are peers, which are always in the same frame of a range
+            let mut peers: Vec<(usize, i64)> = Vec::with_capacity(partition.len());
+            let mut dense_rank = 0;
+            for (pos, &idx) in partition.iter().enumerate() {
+                match peers.last() {
+                    Some(&(start, _)) if keys[partition[start]].1 == keys[idx].1 => {
+                        peers.push((start, dense_rank))
+                    }
+                    _ => {
+                        dense_rank += 1;
+                        peers.push((pos, dense_rank))
+                    }
+                }
+            }
+
+            for (pos, &idx) in partition.iter().enumerate() {
+                let row = &rows[idx];
+                values[idx] = match kind {
+                    WindowFunctionKind::RowNumber => DbVal::Integer(pos as i64 + 1),
+                    WindowFunctionKind::Rank => DbVal::Integer(peers[pos].0 as i64 + 1),
+                    WindowFunctionKind::DenseRank => DbVal::Integer(peers[pos].1),
+                    WindowFunctionKind::Lag | WindowFunctionKind::Lead => {
+                        let offset = match exprs.get(1) {
+                            Some(offset) => eval(offset, row)?.int_value().unwrap_or(0),
+                            None => 1,
+                        };
+                        let offset = if kind == WindowFunctionKind::Lag {
+                            -offset
+                        } else {
+                            offset
+                        };
+                        match usize::try_from(pos as i64 + offset)
+                            .ok()
+                            .and_then(|target| partition.get(target))
+                        {
+                            Some(&target) => eval(&exprs[0], &rows[target])?,
+                            None => match exprs.get(2) {
+                                Some(default) => eval(default, row)?,
+                                None => DbVal::Null,
+                            },
+                        }
+                    }
+                    WindowFunctionKind::FirstValue => {
+                        let frame = frame(window, partition, &keys, &peers, pos);
+                        match partition[frame].first() {
+                            Some(&first) => eval(&exprs[0], &rows[first])?,
+                            None => DbVal::Null,
+                        }
+                    }
+                    WindowFunctionKind::Aggregate => {
+                        let frame = frame(window, partition, &keys, &peers, pos);
+                        let frame_rows = partition[frame]
+                            .iter()
+                            .map(|&idx| &rows[idx])
+                            .collect::<Vec<_>>();
+                        runtime_eval_many_rows(&aggregate, &frame_rows, schema, &self.functions)?
+                            .into_owned()
+                    }
+                };
+            }
+        }
+
+        Ok(values)
+    }
+}
+
+/// Compares the values ordering two rows
+fn compare_order(order_by: &[OrderBy], l: &[DbVal], r: &[DbVal]) -> Ordering {
+    order_by
+        .iter()
+        .zip(l.iter().zip(r))
+        .map(
+            |(OrderBy(_, direction), (l, r))| match direction.unwrap_or_default() {
+                OrderDirection::Asc => l.cmp(r),
+                OrderDirection::Desc => r.cmp(l),
+            },
+        )
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Gets the positions within a sorted partition of the rows in the frame of the row at `pos`.
+///
+/// Without a frame, the frame of an ordered window is every row up to the last peer of the row,
+/// and the frame of an unordered window is the whole partition.
+fn frame(
+    window: &Window,
+    partition: &[usize],
+    keys: &[(Vec<DbVal>, Vec<DbVal>)],
+    peers: &[(usize, i64)],
+    pos: usize,
+) -> Range<usize> {
+    let len = partition.len();
+    let frame = match &window.frame {
+        Some(frame) => frame.clone(),
+        None if window.order_by.is_empty() => WindowFrame {
+            units: FrameUnits::Rows,
+            start: FrameBound::UnboundedPreceding,
+            end: FrameBound::UnboundedFollowing,
+        },
+        None => WindowFrame {
+            units: FrameUnits::Range,
+            start: FrameBound::UnboundedPreceding,
+            end: FrameBound::CurrentRow,
+        },
+    };
+
+    let (start, end) = match frame.units {
+        FrameUnits::Rows => {
+            let start = match frame.start {
+                FrameBound::UnboundedPreceding => 0,
+                FrameBound::Preceding(n) => pos.saturating_sub(n as usize),
+                FrameBound::CurrentRow => pos,
+                FrameBound::Following(n) => pos.saturating_add(n as usize),
+                FrameBound::UnboundedFollowing => len,
+            };
+            let end = match frame.end {
+                FrameBound::UnboundedPreceding => 0,
+                FrameBound::Preceding(n) => (pos + 1).saturating_sub(n as usize),
+                FrameBound::CurrentRow => pos + 1,
+                FrameBound::Following(n) => pos.saturating_add(n as usize).saturating_add(1),
+                FrameBound::UnboundedFollowing => len,
+            };
+            (start, end)
+        }
+        FrameUnits::Range => {
+            let peers_start = peers[pos].0;
+            let peers_end = peers[peers_start..]
+                .iter()
+                .position(|&(start, _)| start != peers_start)
+                .map_or(len, |count| peers_start + count);
+            // how far ahead each row is ordered from the row, so that it never decreases
+            let distance = |idx: usize| -> Option<f64> {
+                let direction = match window.order_by.first()?.1.unwrap_or_default() {
+                    OrderDirection::Asc => 1.0,
+                    OrderDirection::Desc => -1.0,
+                };
+                let value = |idx: usize| match keys[partition[idx]].1.first()? {
+                    DbVal::Integer(i) => Some(*i as f64),
+                    DbVal::Float(f) => Some(*f),
+                    _ => None,
+                };
+                let current = value(pos)?;
+                let distance = match value(idx) {
+                    Some(value) => value - current,
+                    // nulls are ordered before every other value
+                    None => f64::NEG_INFINITY,
+                };
+                Some(distance * direction)
+            };
+            // the number of rows ordered at most `offset` ahead of the row, where a row that isn't
+            // ordered by a number only has its peers in range
+            let within = |offset: f64, inclusive: bool| -> usize {
+                if distance(pos).is_none() {
+                    return if offset < 0.0 || (offset == 0.0 && !inclusive) {
+                        peers_start
+                    } else {
+                        peers_end
+                    };
+                }
+                (0..len)
+                    .take_while(|&idx| {
+                        let distance = distance(idx).unwrap_or(f64::NEG_INFINITY);
+                        distance < offset || (inclusive && distance == offset)
+                    })
+                    .count()
+            };
+            let start = match frame.start {
+                FrameBound::UnboundedPreceding => 0,
+                FrameBound::Preceding(n) => within(-(n as f64), false),
+                FrameBound::CurrentRow => peers_start,
+                FrameBound::Following(n) => within(n as f64, false),
+                FrameBound::UnboundedFollowing => len,
+            };
+            let end = match frame.end {
+                FrameBound::UnboundedPreceding => 0,
+                FrameBound::Preceding(n) => within(-(n as f64), true),
+                FrameBound::CurrentRow => peers_end,
+                FrameBound::Following(n) => within(n as f64, true),
+                FrameBound::UnboundedFollowing => len,
+            };
+            (start, end)
+        }
+    };
+    let end = end.min(len);
+    start.min(end)..end
+}
@@ -1,35 +1,41 @@
 use std::borrow::Cow;
 use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 
 use std::sync::{Arc, Weak};
 
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 
 use parking_lot::RwLock;
 use rayon::prelude::*;
 use tracing::trace;
 use tracing::{debug, debug_span};
 
-use weaver_ast::ast;
-use weaver_ast::ast::{CreateDefinition, CreateTable, LoadData, OrderDirection};
+use weaver_ast::ast::{
+    CompoundOperator, CreateDefinition, CreateTable, Expr, LoadData, OrderDirection,
+};
 
-use crate::data::row::Row;
+use crate::data::row::{OwnedRow, Row};
 use crate::data::values::DbVal;
 use crate::db::core::WeaverDbCore;
 use crate::db::server::WeakWeaverDb;
-use crate::dynamic_table::{DynamicTable, HasSchema};
+use crate::dynamic_table::{DynamicTable, EngineKey, HasSchema};
 use crate::error::WeaverError;
-use crate::queries::execution::evaluation::ExpressionEvaluator;
+use crate::queries::execution::evaluation::{is_in, ExpressionEvaluator};
 use crate::queries::execution::strategies::join::{
     HashJoinTableStrategy, JoinParameters, JoinStrategy,
 };
-use crate::queries::query_plan::{QueryPlan, QueryPlanKind, QueryPlanNode};
+use crate::queries::query_plan::{QueryPlan, QueryPlanKind, QueryPlanNode, SubQueryPlan};
 use crate::rows::OwnedRows;
 use crate::rows::{RefRows, Rows};
 
-use crate::storage::tables::table_schema::TableSchemaBuilder;
+use crate::storage::tables::table_schema::{TableSchema, TableSchemaBuilder};
 use crate::tx::Tx;
 
+/// The most iterations a recursive common table expression can run for
+pub const MAX_RECURSION_ITERATIONS: usize = 1000;
+
 /// The query executor is responsible for executing queries against the database
 /// in performant ways.
 ///
@@ -73,7 +79,7 @@ impl QueryExecutor {
         expression_evaluator: &ExpressionEvaluator,
         core: &Arc<RwLock<WeaverDbCore>>,
     ) -> Result<OwnedRows, WeaverError> {
-        let mut stack = root.prefix_order();
+        let mut stack = root.execution_order();
         let mut row_stack: Vec<Box<dyn Rows>> = vec![];
 
         while let Some(node) = stack.pop() {
@@ -183,68 +189,338 @@ impl QueryExecutor {
                         Ok(())
                     })?;
                 }
-                QueryPlanKind::CreateTable { table_def } => {
-                    let CreateTable {
-                        schema,
-                        name,
-                        create_definitions,
-                    } = table_def;
+                QueryPlanKind::Compound {
+                    left: _,
+                    right: _,
+                    op,
+                } => {
+                    debug_span!("compound").in_scope(|| -> Result<(), WeaverError> {
+                        let mut left = row_stack.pop().expect("no left side of compound");
+                        let mut right = row_stack.pop().expect("no right side of compound");
+                        // rows of both sides must have the same types to be compared
+                        let columns = node.schema.columns();
+                        let widen = |row: Row| -> Row<'static> {
+                            Row::from_iter(row.iter().zip(columns).map(|(value, column)| {
+                                Cow::Owned(column.data_type().widen(value.as_ref().clone()))
+                            }))
+                        };
 
-                    let mut schema_builder = TableSchemaBuilder::new(
-                        schema.as_ref().ok_or(WeaverError::NoDefaultSchema)?,
-                        name,
-                    );
-
-                    for create_def in create_definitions {
-                        match *create_def {
-                            CreateDefinition::Column(ast::ColumnDefinition {
-                                ref id,
-                                data_type,
-                                non_null,
-                                auto_increment,
-                                unique,
-                                key,
-                                primary,
-                            }) => {
-                                schema_builder = schema_builder.column(
-                                    id,
-                                    data_type.into(),
-                                    non_null,
-                                    None,
-                                    auto_increment.then_some(0),
-                                )?;
-                                if unique || key && !primary {
-                                    schema_builder = schema_builder.index(
-                                        &format!("SK_{}", id),
-                                        &[id.as_ref()],
-                                        unique,
-                                    )?
-                                } else if primary {
-                                    schema_builder = schema_builder.primary(&[id.as_ref()])?
+                        let owned: Vec<_> = match op {
+                            CompoundOperator::UnionAll => {
+                                let mut owned = vec![];
+                                while let Some(row) = left.next().or_else(|| right.next()) {
+                                    owned.push(widen(row));
                                 }
+                                owned
+                            }
+                            CompoundOperator::Union => {
+                                let mut distinct = IndexSet::new();
+                                while let Some(row) = left.next().or_else(|| right.next()) {
+                                    distinct.insert(widen(row));
+                                }
+                                distinct.into_iter().collect()
+                            }
+                            CompoundOperator::Intersect | CompoundOperator::Except => {
+                                let mut right_rows = HashSet::new();
+                                while let Some(row) = right.next() {
+                                    right_rows.insert(widen(row));
+                                }
+                                let keep_matches = *op == CompoundOperator::Intersect;
+                                let mut distinct = IndexSet::new();
+                                while let Some(row) = left.next() {
+                                    let row = widen(row);
+                                    if right_rows.contains(&row) == keep_matches {
+                                        distinct.insert(row);
+                                    }
+                                }
+                                distinct.into_iter().collect()
+                            }
+                        };
+
+                        row_stack.push(Box::new(RefRows::new(node.schema.clone(), owned)));
+                        Ok(())
+                    })?;
+                }
+                QueryPlanKind::Recursive {
+                    name,
+                    base: _,
+                    recursive,
+                    distinct,
+                } => {
+                    debug_span!("recursive").in_scope(|| -> Result<(), WeaverError> {
+                        let mut base = row_stack.pop().expect("no base of recursion");
+                        // rows of every iteration must have the same types to be compared
+                        let columns = node.schema.columns();
+                        let widen = |row: Row| -> Vec<DbVal> {
+                            row.iter()
+                                .zip(columns)
+                                .map(|(value, column)| {
+                                    column.data_type().widen(value.as_ref().clone())
+                                })
+                                .collect()
+                        };
+
+                        let mut seen = HashSet::new();
+                        let mut working = vec![];
+                        while let Some(row) = base.next() {
+                            let row = widen(row);
+                            if !*distinct || seen.insert(row.clone()) {
+                                working.push(row);
                             }
-                            CreateDefinition::Constraint(_) => {
-                                todo!()
+                        }
+
+                        let mut found = vec![];
+                        let mut iterations = 0;
+                        while !working.is_empty() {
+                            if iterations == MAX_RECURSION_ITERATIONS {
+                                return Err(WeaverError::RecursionLimitReached(
+                                    name.clone(),
+                                    MAX_RECURSION_ITERATIONS,
+                                ));
                             }
+                            iterations += 1;
+
+                            let mut plan = (**recursive).clone();
+                            plan.bind_working_table(name, &working);
+                            let mut rows = self.execute_node_non_recursive(
+                                tx,
+                                &plan,
+                                expression_evaluator,
+                                core,
+                            )?;
+                            let mut next = vec![];
+                            while let Some(row) = rows.next() {
+                                let row = widen(row);
+                                if !*distinct || seen.insert(row.clone()) {
+                                    next.push(row);
+                                }
+                            }
+                            found.append(&mut working);
+                            working = next;
                         }
-                    }
+                        debug!("recursion of {name} finished after {iterations} iterations");
 
-                    schema_builder = schema_builder.engine(
-                        core.read()
-                            .default_engine()
-                            .expect("no default engine")
-                            .clone(),
-                    );
+                        let owned = found
+                            .into_iter()
+                            .map(|row| Row::from_iter(row.into_iter().map(Cow::Owned)))
+                            .collect::<Vec<_>>();
+                        row_stack.push(Box::new(RefRows::new(node.schema.clone(), owned)));
+                        Ok(())
+                    })?;
+                }
+                QueryPlanKind::Apply {
+                    applied: _,
+                    sub_queries,
+                } => {
+                    debug_span!("apply").in_scope(|| -> Result<(), WeaverError> {
+                        let mut applied = row_stack.pop().expect("nothing to apply");
+                        let outer_indices = sub_queries
+                            .iter()
+                            .map(|sub_query| {
+                                sub_query
+                                    .outer_columns
+                                    .iter()
+                                    .map(|column| {
+                                        applied.schema().column_index_by_source(column).ok_or_else(
+                                            || WeaverError::ColumnNotFound(column.to_string()),
+                                        )
+                                    })
+                                    .collect::<Result<Vec<_>, _>>()
+                            })
+                            .collect::<Result<Vec<_>, _>>()?;
+                        let mut cached =
+                            vec![HashMap::<Vec<DbVal>, Vec<DbVal>>::new(); sub_queries.len()];
 
-                    let schema = schema_builder.build()?;
-                    trace!("created schema {schema:#?} from ddl");
+                        let mut owned = vec![];
+                        while let Some(row) = applied.next() {
+                            let mut values = Vec::with_capacity(sub_queries.len());
+                            for ((sub_query, indices), cached) in
+                                sub_queries.iter().zip(&outer_indices).zip(&mut cached)
+                            {
+                                let outer = indices
+                                    .iter()
+                                    .map(|&idx| row[idx].as_ref().clone())
+                                    .collect::<Vec<_>>();
+                                let found = match cached.entry(outer) {
+                                    Entry::Occupied(occupied) => occupied.into_mut(),
+                                    Entry::Vacant(vacant) => {
+                                        let found = self.execute_sub_query(
+                                            tx,
+                                            sub_query,
+                                            vacant.key(),
+                                            expression_evaluator,
+                                            core,
+                                        )?;
+                                        vacant.insert(found)
+                                    }
+                                };
+                                let value = match &sub_query.expr {
+                                    Expr::Exists { negated, .. } => {
+                                        DbVal::from(found.is_empty() == *negated)
+                                    }
+                                    Expr::InSubQuery { expr, negated, .. } => {
+                                        let value = expression_evaluator.evaluate_one_row(
+                                            expr,
+                                            &row,
+                                            applied.schema(),
+                                            None,
+                                        )?;
+                                        is_in(
+                                            &value,
+                                            found.iter().map(|item| Ok(Cow::Borrowed(item))),
+                                            *negated,
+                                        )?
+                                        .into_owned()
+                                    }
+                                    _ => match found.as_slice() {
+                                        [] => DbVal::Null,
+                                        [value] => value.clone(),
+                                        _ => {
+                                            return Err(WeaverError::SubQueryReturnedManyRows(
+                                                sub_query.expr.clone(),
+                                            ))
+                                        }
+                                    },
+                                };
+                                values.push(Cow::Owned(value));
+                            }
+                            owned.push(Row::from_iter(row.iter().cloned().chain(values)));
+                        }
 
-                    let result = core.read().open_table(&schema);
+                        row_stack.push(Box::new(RefRows::new(node.schema.clone(), owned)));
+                        Ok(())
+                    })?;
+                }
+                QueryPlanKind::Distinct {
+                    deduplicated: _,
+                    sorted,
+                } => {
+                    debug_span!("distinct").in_scope(|| -> Result<(), WeaverError> {
+                        let mut deduplicated = row_stack.pop().expect("nothing to deduplicate");
+                        let owned: Vec<_> = if *sorted {
+                            let mut owned: Vec<Row> = vec![];
+                            while let Some(row) = deduplicated.next() {
+                                if owned.last() != Some(&row) {
+                                    owned.push(row);
+                                }
+                            }
+                            owned
+                        } else {
+                            let mut distinct = IndexSet::new();
+                            while let Some(row) = deduplicated.next() {
+                                distinct.insert(row);
+                            }
+                            distinct.into_iter().collect()
+                        };
+
+                        row_stack.push(Box::new(RefRows::new(node.schema.clone(), owned)));
+                        Ok(())
+                    })?;
+                }
+                QueryPlanKind::Window {
+                    windowed: _,
+                    functions,
+                } => {
+                    debug_span!("window").in_scope(|| -> Result<(), WeaverError> {
+                        let mut windowed = row_stack.pop().expect("nothing to window");
+                        let schema = windowed.schema().clone();
+                        let mut rows = vec![];
+                        while let Some(row) = windowed.next() {
+                            rows.push(row);
+                        }
+                        let values = functions
+                            .iter()
+                            .map(|function| {
+                                expression_evaluator
+                                    .evaluate_window_function(function, &rows, &schema)
+                            })
+                            .collect::<Result<Vec<_>, _>>()?;
+
+                        let owned: Vec<_> = rows
+                            .into_iter()
+                            .enumerate()
+                            .map(|(idx, row)| {
+                                Row::from_iter(row.iter().cloned().chain(
+                                    values.iter().map(|values| Cow::Owned(values[idx].clone())),
+                                ))
+                            })
+                            .collect();
+                        row_stack.push(Box::new(RefRows::new(node.schema.clone(), owned)));
+                        Ok(())
+                    })?;
+                }
+                QueryPlanKind::CreateTable { table_def } => {
+                    let engine = core
+                        .read()
+                        .default_engine()
+                        .expect("no default engine")
+                        .clone();
+
+                    let result = Self::create_table_schema(table_def, engine).and_then(|schema| {
+                        core.read().check_foreign_key_parents(&schema)?;
+                        core.read().open_table(&schema)
+                    });
                     trace!("open table resulted in {:?}", result);
                     let as_row = Box::new(QueryPlan::ddl_result(result.map(|()| "ok")));
                     row_stack.push(as_row);
                     trace!("core after open: {:#?}", core.read());
                 }
+                QueryPlanKind::CreateSchema { schema } => {
+                    let result = core.read().create_schema(schema);
+                    trace!("create schema resulted in {:?}", result);
+                    row_stack.push(Box::new(QueryPlan::ddl_result(result.map(|()| "ok"))));
+                }
+                QueryPlanKind::CreateIndex {
+                    schema,
+                    table,
+                    name,
+                    columns,
+                    unique,
+                } => {
+                    let result = core
+                        .read()
+                        .create_index(schema, table, name, columns, *unique);
+                    trace!("create index resulted in {:?}", result);
+                    row_stack.push(Box::new(QueryPlan::ddl_result(result.map(|()| "ok"))));
+                }
+                QueryPlanKind::Use { schema } => {
+                    // the connection only switches schemas if this succeeds
+                    if !core.read().schema_exists(schema)? {
+                        return Err(WeaverError::SchemaNotFound(schema.clone()));
+                    }
+                    row_stack.push(Box::new(QueryPlan::ddl_result(Ok::<_, WeaverError>("ok"))));
+                }
+                QueryPlanKind::DropTable {
+                    schema,
+                    table,
+                    if_exists,
+                } => {
+                    let result = match core.read().drop_table(schema, table) {
+                        Err(WeaverError::NoTableFound { .. }) if *if_exists => Ok(()),
+                        result => result,
+                    };
+                    trace!("drop table resulted in {:?}", result);
+                    row_stack.push(Box::new(QueryPlan::ddl_result(result.map(|()| "ok"))));
+                }
+                QueryPlanKind::DropSchema { schema, cascade } => {
+                    let result = core.read().drop_schema(schema, *cascade);
+                    trace!("drop schema resulted in {:?}", result);
+                    if let (Ok(()), Some(server)) = (&result, self.server.upgrade()) {
+                        server.with_process_manager(|process_manager| {
+                            process_manager.clear_using(schema)
+                        });
+                    }
+                    row_stack.push(Box::new(QueryPlan::ddl_result(result.map(|()| "ok"))));
+                }
+                QueryPlanKind::AlterTable {
+                    schema,
+                    table,
+                    actions,
+                } => {
+                    let result = core.read().alter_table(schema, table, actions);
+                    trace!("alter table resulted in {:?}", result);
+                    row_stack.push(Box::new(QueryPlan::ddl_result(result.map(|()| "ok"))));
+                }
                 QueryPlanKind::LoadData { load_data } => {
                     let LoadData {
                         infile,
@@ -285,6 +561,11 @@ impl QueryExecutor {
                         },
                     )?;
 
+                    let supplied = column_indexes_and_types
+                        .iter()
+                        .map(|&(col_idx, _)| col_idx)
+                        .collect::<Vec<_>>();
+
                     let iter = csv_reader.records();
                     let rows = iter
                         .into_iter()
@@ -306,7 +587,9 @@ impl QueryExecutor {
                                         Ok(())
                                     },
                                 )?;
-                            Ok(Row::from(row))
+                            let mut row = Row::from(row);
+                            table.schema().fill_defaults(&mut row, &supplied)?;
+                            Ok(row)
                         })
                         .collect::<Result<Vec<_>, WeaverError>>()?;
                     trace!("rows created: {}", rows.len());
@@ -339,6 +622,10 @@ impl QueryExecutor {
 
                         grouped_rows.entry(grouping).or_default().push(row);
                     }
+                    // without a group by, all rows are a single group even if there are none
+                    if grouped_by.is_empty() && grouped_rows.is_empty() {
+                        grouped_rows.insert(vec![], vec![]);
+                    }
 
                     trace!("grouped rows: {:#?}", grouped_rows);
                     let mut owned = vec![];
@@ -422,6 +709,193 @@ impl QueryExecutor {
                         row_stack.push(Box::new(RefRows::new(node.schema.clone(), vec![])));
                     }
                 }
+                QueryPlanKind::Values { values } => {
+                    debug_span!("values").in_scope(|| -> Result<(), WeaverError> {
+                        let empty = Row::new(0);
+                        let mut owned = vec![];
+                        for value_exprs in values {
+                            let mut new_row = Row::new(value_exprs.len());
+                            for (idx, expr) in value_exprs.iter().enumerate() {
+                                let eval = expression_evaluator.evaluate_one_row(
+                                    expr,
+                                    &empty,
+                                    &node.schema,
+                                    node.id(),
+                                )?;
+                                new_row[idx] = Cow::Owned(eval.into_owned());
+                            }
+                            owned.push(new_row);
+                        }
+
+                        row_stack.push(Box::new(RefRows::new(node.schema.clone(), owned)));
+                        Ok(())
+                    })?;
+                }
+                QueryPlanKind::Insert {
+                    schema,
+                    table,
+                    columns,
+                    ..
+                } => {
+                    debug_span!("insert").in_scope(|| -> Result<(), WeaverError> {
+                        let mut source = row_stack.pop().expect("nothing to insert");
+                        let table = core.read().get_open_table(schema, table)?;
+
+                        let column_indexes = columns
+                            .iter()
+                            .map(|column| {
+                                table
+                                    .schema()
+                                    .column_index(column)
+                                    .ok_or_else(|| WeaverError::ColumnNotFound(column.clone()))
+                            })
+                            .collect::<Result<Vec<_>, _>>()?;
+
+                        let result = (|| -> Result<usize, WeaverError> {
+                            let mut new_rows = vec![];
+                            while let Some(row) = source.next() {
+                                let mut new_row = vec![DbVal::Null; table.schema().columns().len()];
+                                for (&col_idx, value) in column_indexes.iter().zip(row.iter()) {
+                                    new_row[col_idx] = value.as_ref().clone();
+                                }
+                                let mut new_row = Row::from(new_row);
+                                // an explicit null is kept, only omitted columns get defaults
+                                table
+                                    .schema()
+                                    .fill_defaults(&mut new_row, &column_indexes)?;
+                                new_rows.push(OwnedRow::from(new_row));
+                            }
+                            // either every row is inserted or none are
+                            table.insert_rows(tx, new_rows)
+                        })();
+                        trace!("insert resulted in {result:?}");
+
+                        row_stack.push(Box::new(QueryPlan::ddl_result(result)));
+                        Ok(())
+                    })?;
+                }
+                QueryPlanKind::Update {
+                    schema,
+                    table,
+                    key_index,
+                    condition,
+                    set,
+                } => {
+                    debug_span!("update").in_scope(|| -> Result<(), WeaverError> {
+                        let table = core.read().get_open_table(schema, table)?;
+                        let table_schema = table.schema().clone();
+
+                        let set = set
+                            .iter()
+                            .map(|(column, expr)| {
+                                table_schema
+                                    .column_index(column)
+                                    .map(|idx| (idx, expr))
+                                    .ok_or_else(|| WeaverError::ColumnNotFound(column.clone()))
+                            })
+                            .collect::<Result<Vec<_>, _>>()?;
+
+                        let result = (|| -> Result<usize, WeaverError> {
+                            let rows = {
+                                let mut read = table.read(tx, key_index)?;
+                                let mut rows = vec![];
+                                while let Some(row) = read.next() {
+                                    rows.push(row.to_owned());
+                                }
+                                rows
+                            };
+
+                            let mut updates = vec![];
+                            for row in rows {
+                                let row = Row::from(row);
+                                if let Some(condition) = condition {
+                                    let matches = expression_evaluator
+                                        .evaluate_one_row(
+                                            condition,
+                                            &row,
+                                            &table_schema,
+                                            node.id(),
+                                        )?
+                                        .bool_value();
+                                    if matches != Some(true) {
+                                        continue;
+                                    }
+                                }
+
+                                let mut new_row = row.slice(..);
+                                for &(idx, expr) in &set {
+                                    let value = expression_evaluator.evaluate_one_row(
+                                        expr,
+                                        &row,
+                                        &table_schema,
+                                        node.id(),
+                                    )?;
+                                    new_row[idx] = Cow::Owned(value.into_owned());
+                                }
+                                updates.push((OwnedRow::from(&row), OwnedRow::from(new_row)));
+                            }
+                            // either every row is updated or none are
+                            table.update_rows(tx, updates)
+                        })();
+                        trace!("update resulted in {result:?}");
+
+                        row_stack.push(Box::new(QueryPlan::ddl_result(result)));
+                        Ok(())
+                    })?;
+                }
+                QueryPlanKind::Delete {
+                    schema,
+                    table,
+                    key_index,
+                    condition,
+                } => {
+                    debug_span!("delete").in_scope(|| -> Result<(), WeaverError> {
+                        let table = core.read().get_open_table(schema, table)?;
+                        let table_schema = table.schema().clone();
+
+                        let result = (|| -> Result<Vec<Row<'static>>, WeaverError> {
+                            let rows = {
+                                let mut read = table.read(tx, key_index)?;
+                                let mut rows = vec![];
+                                while let Some(row) = read.next() {
+                                    rows.push(row.to_owned());
+                                }
+                                rows
+                            };
+
+                            let mut deleted = vec![];
+                            for row in rows {
+                                let row = Row::from(row);
+                                if let Some(condition) = condition {
+                                    let matches = expression_evaluator
+                                        .evaluate_one_row(
+                                            condition,
+                                            &row,
+                                            &table_schema,
+                                            node.id(),
+                                        )?
+                                        .bool_value();
+                                    if matches != Some(true) {
+                                        continue;
+                                    }
+                                }
+
+                                if table.delete_row(tx, &row)?.is_some() {
+                                    deleted
+                                        .push(Row::from(table_schema.public_only(row).to_owned()));
+                                }
+                            }
+                            Ok(deleted)
+                        })();
+                        trace!("delete resulted in {result:?}");
+
+                        row_stack.push(Box::new(QueryPlan::delete_result(
+                            node.schema.clone(),
+                            result,
+                        )));
+                        Ok(())
+                    })?;
+                }
                 QueryPlanKind::KillProcess { pid } => {
                     let server = self.server.upgrade().expect("no server running");
 
@@ -439,4 +913,65 @@ impl QueryExecutor {
         let result = row_stack.pop().expect("no rows object at top of stack");
         Ok(OwnedRows::from(result))
     }
+
+    /// Runs a subquery with the values of the outer columns it uses, getting the first value of
+    /// every row it returns
+    fn execute_sub_query(
+        &self,
+        tx: &Tx,
+        sub_query: &SubQueryPlan,
+        outer: &[DbVal],
+        expression_evaluator: &ExpressionEvaluator,
+        core: &Arc<RwLock<WeaverDbCore>>,
+    ) -> Result<Vec<DbVal>, WeaverError> {
+        let mut plan = sub_query.plan.clone();
+        plan.bind_columns(
+            &sub_query
+                .outer_columns
+                .iter()
+                .cloned()
+                .zip(outer.iter().cloned())
+                .collect(),
+        );
+
+        let mut rows = self.execute_node_non_recursive(tx, &plan, expression_evaluator, core)?;
+        let mut found = vec![];
+        while let Some(row) = rows.next() {
+            found.push(row[0].as_ref().clone());
+        }
+        Ok(found)
+    }
+
+    /// Creates the schema of a table from its definition
+    fn create_table_schema(
+        table_def: &CreateTable,
+        engine: EngineKey,
+    ) -> Result<TableSchema, WeaverError> {
+        let CreateTable {
+            schema,
+            name,
+            create_definitions,
+        } = table_def;
+
+        let mut schema_builder =
+            TableSchemaBuilder::new(schema.as_ref().ok_or(WeaverError::NoDefaultSchema)?, name);
+
+        // constraints can refer to columns defined after them
+        for create_def in create_definitions {
+            if let CreateDefinition::Column(column_def) = create_def {
+                schema_builder = schema_builder
+                    .column_definition(column_def.try_into()?)
+                    .column_keys(column_def)?;
+            }
+        }
+        for create_def in create_definitions {
+            if let CreateDefinition::Constraint(constraint_def) = create_def {
+                schema_builder = schema_builder.constraint(constraint_def)?;
+            }
+        }
+
+        let schema = schema_builder.engine(engine).build()?;
+        trace!("created schema {schema:#?} from ddl");
+        Ok(schema)
+    }
 }
@@ -2,14 +2,14 @@
 
 use std::borrow::Cow;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Display, Formatter};
 use std::sync::Arc;
 
 use static_assertions::assert_obj_safe;
 use tracing::{debug, instrument, trace, Level};
 
-use weaver_ast::ast::{BinaryOp, Expr, JoinClause, JoinConstraint, JoinOperator};
+use weaver_ast::ast::{BinaryOp, ColumnRef, Expr, JoinClause, JoinConstraint, JoinOperator};
 
 use crate::data::row::Row;
 use crate::data::values::DbVal;
@@ -143,34 +143,26 @@ impl Strategy for HashJoinTableStrategy {
 
 impl JoinStrategy for HashJoinTableStrategy {
     fn join_cost(&self, join_parameters: &JoinClause) -> Option<Cost> {
-        if join_parameters.op != JoinOperator::Inner {
+        if !matches!(
+            join_parameters.op,
+            JoinOperator::Inner
+                | JoinOperator::Semi
+                | JoinOperator::Anti
+                | JoinOperator::NullAwareAnti
+        ) {
             return None;
         }
 
-        let Expr::Binary {
-            left,
-            op: BinaryOp::Eq,
-            right,
-        } = &join_parameters.constraint.on
-        else {
-            return None;
-        };
-
-        let Expr::Column {
-            column: left_column,
-        } = &**left
-        else {
-            return None;
-        };
-
-        let Expr::Column {
-            column: right_column,
-        } = &**right
-        else {
-            return None;
-        };
+        let columns = equal_columns(&join_parameters.constraint.on)?;
 
-        debug!("can run a hash-join on {left_column} and {right_column}");
+        debug!(
+            "can run a hash-join on {}",
+            columns
+                .iter()
+                .map(|(left, right)| format!("{left} and {right}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
         Some(Cost::new(1.1, 1, None))
     }
 
@@ -182,7 +174,13 @@ impl JoinStrategy for HashJoinTableStrategy {
         join_clause: &JoinClause,
     ) -> Result<QueryPlanNode, WeaverError> {
         let JoinClause { op, constraint, .. } = join_clause;
-        let target_schema = left.schema().join(right.schema());
+        let target_schema = match op {
+            // only rows from the left side are kept
+            JoinOperator::Semi | JoinOperator::Anti | JoinOperator::NullAwareAnti => {
+                left.schema().clone()
+            }
+            _ => left.schema().join(right.schema()),
+        };
         QueryPlanNode::builder()
             .cost(self.join_cost(join_clause).unwrap())
             .rows(rows)
@@ -200,69 +198,95 @@ impl JoinStrategy for HashJoinTableStrategy {
         &self,
         join_parameters: JoinParameters<'r>,
     ) -> Result<Box<dyn Rows<'r> + 'r>, WeaverError> {
-        let Expr::Binary {
-            left,
-            op: BinaryOp::Eq,
-            right,
-        } = &join_parameters.constraint.on
-        else {
-            unreachable!();
-        };
-        let Expr::Column {
-            column: left_column,
-        } = &**left
-        else {
-            unreachable!();
-        };
-
-        let Expr::Column {
-            column: right_column,
-        } = &**right
-        else {
-            unreachable!();
-        };
+        let columns = equal_columns(&join_parameters.constraint.on).expect("must be equalities");
 
         let mut left_table = join_parameters.left;
         let mut right_table = join_parameters.right;
 
-        let mut hash_map = HashMap::<Cow<DbVal>, (bool, Vec<Row>)>::new();
-        let left_column = left_column.resolved().expect("must be resolved");
-        let left_idx = left_table
-            .schema()
-            .column_index_by_source(left_column)
-            .unwrap_or_else(|| {
-                panic!(
-                    "could not get index of column {left_column} for left side {:?}",
-                    left_table.schema().columns()
-                )
-            });
-        let right_column = right_column.resolved().expect("must be resolved");
-        let right_idx = right_table
-            .schema()
-            .column_index_by_source(right_column)
-            .unwrap_or_else(|| {
-                panic!(
-                    "could not get index of column {right_column} for right side {:?}",
-                    right_table.schema().columns()
-                )
-            });
+        let mut hash_map = HashMap::<Vec<Cow<DbVal>>, (bool, Vec<Row>)>::new();
+        let mut left_indices = vec![];
+        let mut right_indices = vec![];
+        for (left_column, right_column) in columns {
+            let left_column = left_column.resolved().expect("must be resolved");
+            left_indices.push(
+                left_table
+                    .schema()
+                    .column_index_by_source(left_column)
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "could not get index of column {left_column} for left side {:?}",
+                            left_table.schema().columns()
+                        )
+                    }),
+            );
+            let right_column = right_column.resolved().expect("must be resolved");
+            right_indices.push(
+                right_table
+                    .schema()
+                    .column_index_by_source(right_column)
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "could not get index of column {right_column} for right side {:?}",
+                            right_table.schema().columns()
+                        )
+                    }),
+            );
+        }
+        let key = |row: &Row<'r>, indices: &[usize]| -> Vec<Cow<'r, DbVal>> {
+            indices.iter().map(|&idx| row[idx].clone()).collect()
+        };
+        let has_null = |key: &[Cow<DbVal>]| key.iter().any(|value| **value == DbVal::Null);
 
         let mut i = 0;
 
+        if let JoinOperator::Semi | JoinOperator::Anti | JoinOperator::NullAwareAnti =
+            join_parameters.op
+        {
+            // null is never equal to a key, so null keys never match
+            let mut right_keys = HashSet::<Vec<Cow<DbVal>>>::new();
+            let mut right_has_null = false;
+            while let Some(right_row) = right_table.next() {
+                let key = key(&right_row, &right_indices);
+                if has_null(&key) {
+                    right_has_null = true;
+                } else {
+                    right_keys.insert(key);
+                }
+                i += 1;
+            }
+            let right_is_empty = right_keys.is_empty() && !right_has_null;
+            let mut rows = vec![];
+            while let Some(left_row) = left_table.next() {
+                let key = key(&left_row, &left_indices);
+                let keep = match join_parameters.op {
+                    JoinOperator::Semi => !has_null(&key) && right_keys.contains(&key),
+                    JoinOperator::Anti => has_null(&key) || !right_keys.contains(&key),
+                    // a comparison with null is unknown, which `not in` doesn't keep
+                    _ => {
+                        right_is_empty
+                            || (!right_has_null && !has_null(&key) && !right_keys.contains(&key))
+                    }
+                };
+                if keep {
+                    rows.push(left_row);
+                }
+                i += 1;
+            }
+            debug!("{} completed in {i} iterations", join_parameters.op);
+
+            return Ok(Box::new(RefRows::new(join_parameters.schema, rows)));
+        }
+
         while let Some(row) = left_table.next() {
-            let db_val = row[left_idx].clone();
             hash_map
-                .entry(db_val)
+                .entry(key(&row, &left_indices))
                 .or_insert_with(|| (false, vec![]))
                 .1
                 .push(row);
             i += 1;
         }
         while let Some(right_row) = right_table.next() {
-            let db_val = right_row
-                .get(right_idx)
-                .unwrap_or_else(|| panic!("failed to get index {right_idx} of row {right_row:?}"));
-            if let Some((used, rows)) = hash_map.get_mut(db_val) {
+            if let Some((used, rows)) = hash_map.get_mut(&key(&right_row, &right_indices)) {
                 *used = true;
                 for left_row in rows {
                     let joined = Row::from_iter(left_row.iter().chain(right_row.iter()).cloned());
@@ -282,3 +306,30 @@ impl JoinStrategy for HashJoinTableStrategy {
         Ok(Box::new(RefRows::new(join_parameters.schema, rows)))
     }
 }
+
+/// Gets the pairs of left and right columns that a join condition requires to be equal, if the
+/// condition is nothing but a conjunction of such equalities
+fn equal_columns(on: &Expr) -> Option<Vec<(&ColumnRef, &ColumnRef)>> {
+    match on {
+        Expr::Binary {
+            left,
+            op: BinaryOp::And,
+            right,
+        } => {
+            let mut columns = equal_columns(left)?;
+            columns.extend(equal_columns(right)?);
+            Some(columns)
+        }
+        Expr::Binary {
+            left,
+            op: BinaryOp::Eq,
+            right,
+        } => match (&**left, &**right) {
+            (Expr::Column { column: left }, Expr::Column { column: right }) => {
+                Some(vec![(left, right)])
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
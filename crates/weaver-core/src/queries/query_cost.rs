@@ -90,6 +90,14 @@ static QUERY_COSTS: &[(&str, Cost)] = &[
     ("FILTER", Cost::new(1.0, 1, None)),
     ("ORDER", Cost::new(1.0, 2, None)),
     ("LIMIT-OFFSET", Cost::new(1.0, 1, None)),
+    ("APPLY", Cost::new(1.0, 1, None)),
+    ("WINDOW", Cost::new(1.0, 2, None)),
+    ("DISTINCT", Cost::new(1.0, 1, None)),
+    ("COMPOUND", Cost::new(1.0, 1, None)),
+    ("VALUES", Cost::new(1.0, 1, None)),
+    ("INSERT", Cost::new(1.5, 1, None)),
+    ("UPDATE", Cost::new(1.5, 1, None)),
+    ("DELETE", Cost::new(1.5, 1, None)),
 ];
 
 impl Default for CostTable {
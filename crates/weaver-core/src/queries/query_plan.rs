@@ -1,10 +1,14 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
 use std::fmt::{Debug, Formatter};
 
 use itertools::Itertools;
 use uuid::Uuid;
 
+use weaver_ast::ast::visitor::{visit_expr_mut, VisitorMut};
 use weaver_ast::ast::{
-    CreateTable, Expr, JoinConstraint, JoinOperator, LoadData, OrderDirection, ReferencesCols,
+    AlterTableAction, ColumnRef, CompoundOperator, CreateTable, Expr, IndexColumn, JoinConstraint,
+    JoinOperator, LoadData, OrderDirection, ReferencesCols, ResolvedColumnRef,
 };
 
 use crate::data::row::Row;
@@ -75,6 +79,59 @@ impl QueryPlan {
         RefRows::new(Self::ddl_result_schema(), [row])
     }
 
+    /// The schema of a delete result, which is like the [ddl result](Self::ddl_result_schema)
+    /// followed by the public columns of the table rows were deleted from.
+    ///
+    /// The summary columns are named `@@ok` and `@@err` instead, as those names can't collide with
+    /// the columns of the table.
+    pub fn delete_result_schema(table_schema: &TableSchema) -> Result<TableSchema, WeaverError> {
+        table_schema
+            .columns()
+            .iter()
+            .try_fold(
+                TableSchema::builder("<query>", "<result>")
+                    .column("@@ok", Type::String(255), false, None, None)?
+                    .column("@@err", Type::String(255), false, None, None)?,
+                |builder, column| {
+                    builder.column(column.name(), column.data_type(), false, None, None)
+                },
+            )?
+            .build()
+    }
+
+    /// Creates the result of a delete. The first row is a ddl result holding either the amount of
+    /// deleted rows or the error, and is followed by every deleted row.
+    pub fn delete_result<E>(
+        schema: TableSchema,
+        result: Result<Vec<Row<'static>>, E>,
+    ) -> impl Rows<'static>
+    where
+        E: ToString,
+    {
+        let padding = schema.columns().len() - 2;
+        let (summary, deleted) = match result {
+            Ok(deleted) => (
+                [DbVal::string(deleted.len().to_string(), None), DbVal::Null],
+                deleted,
+            ),
+            Err(err) => ([DbVal::Null, DbVal::string(err.to_string(), None)], vec![]),
+        };
+        let summary = summary
+            .into_iter()
+            .chain(std::iter::repeat(DbVal::Null).take(padding))
+            .collect::<Vec<_>>();
+        let deleted = deleted.into_iter().map(|row| {
+            [DbVal::Null, DbVal::Null]
+                .into_iter()
+                .chain(row)
+                .collect::<Vec<_>>()
+        });
+        RefRows::new(
+            schema,
+            std::iter::once(summary).chain(deleted).map(Row::from),
+        )
+    }
+
     /// Converts this query plan into rows in postfix order
     pub fn as_rows(&self) -> OwnedRows {
         let mut rows = vec![];
@@ -171,15 +228,37 @@ impl QueryPlanNode {
     /// Gets the actual cost of the query plan node
     pub fn cost(&self) -> f64 {
         match &self.kind {
-            QueryPlanKind::HashJoin { left, right, .. } => {
+            QueryPlanKind::HashJoin { left, right, .. }
+            | QueryPlanKind::Compound { left, right, .. } => {
                 self.cost.get_cost(self.rows as usize) + left.cost() + right.cost()
             }
+            QueryPlanKind::Recursive {
+                base, recursive, ..
+            } => self.cost.get_cost(self.rows as usize) + base.cost() + recursive.cost(),
             QueryPlanKind::Filter { filtered, .. } => {
                 self.cost.get_cost(self.rows as usize) + filtered.cost()
             }
             QueryPlanKind::Project {
                 projected: node, ..
+            }
+            | QueryPlanKind::Window { windowed: node, .. }
+            | QueryPlanKind::Distinct {
+                deduplicated: node, ..
             } => self.cost.get_cost(self.rows as usize) + node.cost(),
+            QueryPlanKind::Insert { source, .. } => {
+                self.cost.get_cost(self.rows as usize) + source.cost()
+            }
+            QueryPlanKind::Apply {
+                applied,
+                sub_queries,
+            } => {
+                self.cost.get_cost(self.rows as usize)
+                    + applied.cost()
+                    + sub_queries
+                        .iter()
+                        .map(|sub_query| sub_query.plan.cost())
+                        .sum::<f64>()
+            }
             _ => self.cost.get_cost(self.rows as usize),
         }
     }
@@ -206,7 +285,9 @@ impl QueryPlanNode {
                         .and_then(|k| k.first())
                         .map(|k| match k.kind() {
                             KeyIndexKind::All => "ALL",
-                            KeyIndexKind::Range { .. } => "range",
+                            KeyIndexKind::Range { .. }
+                            | KeyIndexKind::Many(_)
+                            | KeyIndexKind::Ranges(_) => "range",
                             KeyIndexKind::One(_) => "const",
                         })
                         .unwrap_or("ALL")
@@ -265,11 +346,19 @@ impl QueryPlanNode {
                 ); // columns
             }
             QueryPlanKind::HashJoin {
+                join_kind,
                 on: JoinConstraint { on },
                 ..
             } => {
                 values.push("".into()); // table
-                values.push("hash-join".into());
+                values.push(
+                    match join_kind {
+                        JoinOperator::Semi => "hash-semi-join",
+                        JoinOperator::Anti | JoinOperator::NullAwareAnti => "hash-anti-join",
+                        _ => "hash-join",
+                    }
+                    .into(),
+                );
                 values.push("".into()); // possible keys
                 values.push(
                     on.columns()
@@ -281,6 +370,67 @@ impl QueryPlanNode {
                         .into(),
                 ); // columns
             }
+            QueryPlanKind::Compound { op, .. } => {
+                values.push("".into()); // table
+                values.push(op.to_string().replace(' ', "-").into()); // join kind
+                values.push("".into()); // possible keys
+                values.push("".into()); // columns
+            }
+            QueryPlanKind::Recursive { name, .. } => {
+                values.push(name.into()); // table
+                values.push("recursive".into()); // join kind
+                values.push("".into()); // possible keys
+                values.push("".into()); // columns
+            }
+            QueryPlanKind::WorkingTable { name } => {
+                values.push(name.into()); // table
+                values.push("working-table".into()); // join kind
+                values.push("".into()); // possible keys
+                values.push("".into()); // columns
+            }
+            QueryPlanKind::Distinct { sorted, .. } => {
+                values.push("".into()); // table
+                values.push(
+                    if *sorted {
+                        "sorted-distinct"
+                    } else {
+                        "hash-distinct"
+                    }
+                    .into(),
+                ); // join kind
+                values.push("".into()); // possible keys
+                values.push("".into()); // columns
+            }
+            QueryPlanKind::Window { functions, .. } => {
+                values.push("".into()); // table
+                values.push("window".into()); // join kind
+                values.push("".into()); // possible keys
+                values.push(
+                    functions
+                        .iter()
+                        .flat_map(|function| function.columns())
+                        .map(|i| i.to_string())
+                        .unique()
+                        .collect::<Vec<_>>()
+                        .join(",")
+                        .into(),
+                ); // columns
+            }
+            QueryPlanKind::Apply { sub_queries, .. } => {
+                values.push("".into()); // table
+                values.push("apply".into()); // join kind
+                values.push("".into()); // possible keys
+                values.push(
+                    sub_queries
+                        .iter()
+                        .flat_map(|sub_query| &sub_query.outer_columns)
+                        .map(|i| i.to_string())
+                        .unique()
+                        .collect::<Vec<_>>()
+                        .join(",")
+                        .into(),
+                ); // columns
+            }
             QueryPlanKind::CreateTable { table_def } => {
                 values.push(
                     format!("{}.{}", table_def.schema.as_ref().unwrap(), table_def.name).into(),
@@ -289,6 +439,37 @@ impl QueryPlanNode {
                 values.push("".into()); // possible keys
                 values.push("".into()); // columns
             }
+            QueryPlanKind::CreateSchema { schema } => {
+                values.push(schema.as_str().into()); // table
+                values.push("create".into());
+                values.push("".into()); // possible keys
+                values.push("".into()); // columns
+            }
+            QueryPlanKind::CreateIndex {
+                schema,
+                table,
+                name,
+                columns,
+                ..
+            } => {
+                values.push(format!("{}.{}", schema, table).into()); // table
+                values.push("create".into());
+                values.push(name.as_str().into()); // possible keys
+                values.push(
+                    columns
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(",")
+                        .into(),
+                ); // columns
+            }
+            QueryPlanKind::Use { schema } => {
+                values.push(schema.as_str().into()); // table
+                values.push("use".into());
+                values.push("".into()); // possible keys
+                values.push("".into()); // columns
+            }
             QueryPlanKind::Explain { .. } => {}
             QueryPlanKind::LoadData {
                 load_data: LoadData { schema, name, .. },
@@ -340,6 +521,66 @@ impl QueryPlanNode {
                 values.push("".into()); // columns
             }
 
+            QueryPlanKind::Insert { schema, table, .. } => {
+                values.push(format!("{}.{}", schema, table).into()); // table
+                values.push("insert".into());
+                values.push("".into()); // possible keys
+                values.push("".into()); // columns
+            }
+            QueryPlanKind::Values { .. } => {
+                values.push("".into()); // table
+                values.push("values".into());
+                values.push("".into()); // possible keys
+                values.push("".into()); // columns
+            }
+            QueryPlanKind::Update {
+                schema,
+                table,
+                key_index,
+                set,
+                ..
+            } => {
+                values.push(format!("{}.{}", schema, table).into()); // table
+                values.push("update".into());
+                values.push(key_index.key_name().into()); // possible keys
+                values.push(
+                    set.iter()
+                        .map(|(column, _)| column.as_str())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                        .into(),
+                ); // columns
+            }
+            QueryPlanKind::DropTable { schema, table, .. } => {
+                values.push(format!("{}.{}", schema, table).into()); // table
+                values.push("drop".into());
+                values.push("".into()); // possible keys
+                values.push("".into()); // columns
+            }
+            QueryPlanKind::DropSchema { schema, .. } => {
+                values.push(schema.as_str().into()); // table
+                values.push("drop".into());
+                values.push("".into()); // possible keys
+                values.push("".into()); // columns
+            }
+            QueryPlanKind::AlterTable { schema, table, .. } => {
+                values.push(format!("{}.{}", schema, table).into()); // table
+                values.push("alter".into());
+                values.push("".into()); // possible keys
+                values.push("".into()); // columns
+            }
+            QueryPlanKind::Delete {
+                schema,
+                table,
+                key_index,
+                ..
+            } => {
+                values.push(format!("{}.{}", schema, table).into()); // table
+                values.push("delete".into());
+                values.push(key_index.key_name().into()); // possible keys
+                values.push("".into()); // columns
+            }
+
             QueryPlanKind::KillProcess { .. } => {
                 values.push("weaver.processes".into()); // table
                 values.push("kill-process".into()); // join kind
@@ -385,13 +626,27 @@ impl QueryPlanNode {
             QueryPlanKind::Project {
                 projected: node, ..
             } => vec![node],
-            QueryPlanKind::HashJoin { left, right, .. } => {
+            QueryPlanKind::HashJoin { left, right, .. }
+            | QueryPlanKind::Compound { left, right, .. } => {
                 vec![left, right]
             }
+            QueryPlanKind::Recursive {
+                base, recursive, ..
+            } => vec![base, recursive],
             QueryPlanKind::Explain { explained } => vec![explained],
             QueryPlanKind::GroupBy { grouped, .. } => vec![grouped],
             QueryPlanKind::GetPage { base, .. } => vec![base],
             QueryPlanKind::OrderedBy { ordered, .. } => vec![ordered],
+            QueryPlanKind::Window { windowed, .. } => vec![windowed],
+            QueryPlanKind::Distinct { deduplicated, .. } => vec![deduplicated],
+            QueryPlanKind::Insert { source, .. } => vec![source],
+            QueryPlanKind::Apply {
+                applied,
+                sub_queries,
+            } => [&**applied]
+                .into_iter()
+                .chain(sub_queries.iter().map(|sub_query| &sub_query.plan))
+                .collect(),
             _ => {
                 vec![]
             }
@@ -404,18 +659,132 @@ impl QueryPlanNode {
             QueryPlanKind::Project {
                 projected: node, ..
             } => vec![&mut *node],
-            QueryPlanKind::HashJoin { left, right, .. } => {
+            QueryPlanKind::HashJoin { left, right, .. }
+            | QueryPlanKind::Compound { left, right, .. } => {
                 vec![&mut *left, &mut *right]
             }
+            QueryPlanKind::Recursive {
+                base, recursive, ..
+            } => vec![&mut *base, &mut *recursive],
             QueryPlanKind::Explain { explained } => vec![&mut *explained],
             QueryPlanKind::GetPage { base, .. } => vec![&mut *base],
             QueryPlanKind::OrderedBy { ordered, .. } => vec![&mut *ordered],
             QueryPlanKind::GroupBy { grouped, .. } => vec![&mut *grouped],
+            QueryPlanKind::Window { windowed, .. } => vec![&mut *windowed],
+            QueryPlanKind::Distinct { deduplicated, .. } => vec![&mut *deduplicated],
+            QueryPlanKind::Insert { source, .. } => vec![&mut *source],
+            QueryPlanKind::Apply {
+                applied,
+                sub_queries,
+            } => [&mut **applied]
+                .into_iter()
+                .chain(sub_queries.iter_mut().map(|sub_query| &mut sub_query.plan))
+                .collect(),
             _ => {
                 vec![]
             }
         }
     }
+
+    /// Converts the query plan node tree into a pre order list of the nodes that are executed
+    /// with it, which excludes the subqueries of apply nodes and the recursive part of recursive
+    /// nodes as they are run separately.
+    pub fn execution_order(&self) -> Vec<&QueryPlanNode> {
+        let mut output = vec![self];
+        let inputs = match &self.kind {
+            QueryPlanKind::Apply { applied, .. } => vec![&**applied],
+            QueryPlanKind::Recursive { base, .. } => vec![&**base],
+            _ => self.children(),
+        };
+        for node in inputs {
+            output.extend(node.execution_order());
+        }
+
+        output
+    }
+
+    /// Replaces every reference to one of the given columns within this node and its children by
+    /// the column's value
+    pub fn bind_columns(&mut self, values: &HashMap<ResolvedColumnRef, DbVal>) {
+        let exprs: Vec<&mut Expr> = match &mut self.kind {
+            QueryPlanKind::Filter { condition, .. } => vec![condition],
+            QueryPlanKind::Project { columns, .. } => columns.iter_mut().collect(),
+            QueryPlanKind::GroupBy {
+                grouped_by,
+                result_columns,
+                ..
+            } => grouped_by.iter_mut().chain(result_columns).collect(),
+            QueryPlanKind::OrderedBy { order, .. } => {
+                order.iter_mut().map(|(expr, _)| expr).collect()
+            }
+            QueryPlanKind::HashJoin {
+                on: JoinConstraint { on },
+                ..
+            } => vec![on],
+            QueryPlanKind::Values { values } => values.iter_mut().flatten().collect(),
+            _ => vec![],
+        };
+        let mut binder = ColumnBinder { values };
+        for expr in exprs {
+            binder
+                .visit_expr_mut(expr)
+                .unwrap_or_else(|never| match never {});
+        }
+
+        for child in self.children_mut() {
+            child.bind_columns(values);
+        }
+    }
+
+    /// Replaces every scan of the working table of a recursive common table expression by the
+    /// rows found by its previous iteration
+    pub fn bind_working_table(&mut self, name: &str, rows: &[Vec<DbVal>]) {
+        if let QueryPlanKind::WorkingTable { name: table } = &self.kind {
+            if table == name {
+                self.kind = QueryPlanKind::Values {
+                    values: rows
+                        .iter()
+                        .map(|row| {
+                            row.iter()
+                                .map(|value| Expr::Literal {
+                                    literal: value.clone().into(),
+                                })
+                                .collect()
+                        })
+                        .collect(),
+                };
+            }
+            return;
+        }
+
+        for child in self.children_mut() {
+            child.bind_working_table(name, rows);
+        }
+    }
+}
+
+/// Replaces references to columns with their values
+struct ColumnBinder<'a> {
+    values: &'a HashMap<ResolvedColumnRef, DbVal>,
+}
+
+impl VisitorMut for ColumnBinder<'_> {
+    type Err = Infallible;
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> Result<(), Self::Err> {
+        if let Expr::Column {
+            column: ColumnRef::Resolved(column),
+        } = expr
+        {
+            if let Some(value) = self.values.get(column) {
+                *expr = Expr::Literal {
+                    literal: value.clone().into(),
+                };
+                return Ok(());
+            }
+        }
+        visit_expr_mut(self, expr)
+    }
 }
 
 impl HasSchema for QueryPlanNode {
@@ -537,12 +906,121 @@ pub enum QueryPlanKind {
         join_kind: JoinOperator,
         on: JoinConstraint,
     },
+    /// Combines the rows of two nodes with a set operation. Rows are deduplicated, unless the
+    /// rows of both nodes are kept with `union all`.
+    Compound {
+        left: Box<QueryPlanNode>,
+        right: Box<QueryPlanNode>,
+        op: CompoundOperator,
+    },
+    /// Runs a recursive common table expression, starting from the rows of `base`. Each iteration
+    /// runs `recursive` over the rows found by the previous one, which it scans as the working
+    /// table, until no new rows are found.
+    Recursive {
+        name: String,
+        base: Box<QueryPlanNode>,
+        recursive: Box<QueryPlanNode>,
+        /// Whether rows that were already found are discarded
+        distinct: bool,
+    },
+    /// The rows found by the previous iteration of a recursive common table expression
+    WorkingTable { name: String },
+    /// Adds the value of each subquery as a column of every row, which for `in` and `exists`
+    /// subqueries is whether the row is in the subquery or it has any rows. A subquery only runs
+    /// once for each distinct value of the outer columns it uses.
+    Apply {
+        applied: Box<QueryPlanNode>,
+        sub_queries: Vec<SubQueryPlan>,
+    },
+    /// Adds the value of each window function as a column of every row
+    Window {
+        windowed: Box<QueryPlanNode>,
+        functions: Vec<Expr>,
+    },
+    /// Removes duplicate rows, keeping the first of each
+    Distinct {
+        deduplicated: Box<QueryPlanNode>,
+        /// Whether the rows are ordered so that duplicates are next to each other, so only the
+        /// previous row needs to be compared instead of hashing every row
+        sorted: bool,
+    },
 
     /// Creates a table
     CreateTable { table_def: CreateTable },
+    /// Creates a schema
+    CreateSchema { schema: String },
+    /// Creates a secondary index on a table
+    CreateIndex {
+        schema: String,
+        table: String,
+        name: String,
+        columns: Vec<IndexColumn>,
+        unique: bool,
+    },
+    /// Sets the default schema of the current connection
+    Use { schema: String },
     /// Load data
     LoadData { load_data: LoadData },
+    /// Inserts the rows produced by the source node into a table
+    Insert {
+        schema: String,
+        table: String,
+        /// The columns of the table each source column is inserted into
+        columns: Vec<String>,
+        source: Box<QueryPlanNode>,
+    },
+    /// Rows of constant expressions
+    Values { values: Vec<Vec<Expr>> },
+    /// Updates the rows of a table in place
+    Update {
+        schema: String,
+        table: String,
+        /// The key used to find the rows that may be updated
+        key_index: KeyIndex,
+        /// Only rows matching this condition are updated
+        condition: Option<Expr>,
+        /// The columns being set, and the expressions used to compute their new values
+        set: Vec<(String, Expr)>,
+    },
+    /// Drops a table
+    DropTable {
+        schema: String,
+        table: String,
+        /// Dropping a table that doesn't exist is not an error
+        if_exists: bool,
+    },
+    /// Drops a schema
+    DropSchema {
+        schema: String,
+        /// Drop all tables within the schema as well
+        cascade: bool,
+    },
+    /// Alters the definition of a table
+    AlterTable {
+        schema: String,
+        table: String,
+        actions: Vec<AlterTableAction>,
+    },
+    /// Deletes rows from a table
+    Delete {
+        schema: String,
+        table: String,
+        /// The key used to find the rows that may be deleted
+        key_index: KeyIndex,
+        /// Only rows matching this condition are deleted
+        condition: Option<Expr>,
+    },
 
     /// Kill a process
     KillProcess { pid: WeaverPid },
 }
+
+/// A subquery run by an [apply](QueryPlanKind::Apply) node
+#[derive(Debug, Clone)]
+pub struct SubQueryPlan {
+    /// The subquery expression that was planned
+    pub expr: Expr,
+    /// The columns of the applied rows used by the subquery, which are bound before it runs
+    pub outer_columns: Vec<ResolvedColumnRef>,
+    pub plan: QueryPlanNode,
+}
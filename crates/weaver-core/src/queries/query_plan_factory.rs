@@ -2,46 +2,58 @@
 
 use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap};
-use std::convert::From;
+use std::convert::{From, Infallible};
 use std::marker::PhantomData;
+use std::ops::Bound;
 
 use tracing::{debug, debug_span, error_span, trace};
 
 use weaver_ast::ast;
 use weaver_ast::ast::visitor::{
-    visit_result_column_mut, visit_select_mut, visit_table_or_sub_query_mut, VisitorMut,
+    visit_create_mut, visit_expr_mut, visit_result_column_mut, visit_select_mut,
+    visit_table_or_sub_query_mut, VisitorMut,
 };
 use weaver_ast::ast::Select;
 use weaver_ast::ast::{
-    BinaryOp, ColumnRef, Create, Expr, FromClause, FunctionArgs, Identifier, JoinClause,
-    JoinOperator, OrderBy, Query, ReferencesCols, ResolvedColumnRef, ResultColumn, TableOrSubQuery,
-    UnresolvedColumnRef,
+    AlterTable, BinaryOp, ColumnRef, CommonTableExpr, CompoundOperator, Create, CreateIndex,
+    CreateSchema, CreateTable, Delete, DropSchema, DropTable, Expr, FromClause, FunctionArgs,
+    Identifier, Insert, InsertSource, JoinClause, JoinConstraint, JoinOperator, Literal, OrderBy,
+    Query, ReferencesCols, ResolvedColumnRef, ResultColumn, TableOrSubQuery, UnresolvedColumnRef,
+    Update,
 };
 
-use crate::data::types::DbTypeOf;
+use crate::data::charset::Collation;
+use crate::data::types::{DbTypeOf, Type};
 use crate::db::server::processes::WeaverProcessInfo;
 use crate::db::server::socket::DbSocket;
 use crate::db::server::WeakWeaverDb;
 use crate::dynamic_table::{DynamicTable, HasSchema, Table};
 use crate::error::WeaverError;
-use crate::key::KeyData;
+use crate::key::{KeyData, KeyDataRange};
 use crate::queries::execution::evaluation::functions::FunctionRegistry;
 use crate::queries::execution::evaluation::{find_function, FunctionKind};
 use crate::queries::execution::strategies::join::JoinStrategySelector;
 use crate::queries::query_cost::{Cost, CostTable};
-use crate::queries::query_plan::{QueryPlan, QueryPlanKind, QueryPlanNode};
+use crate::queries::query_plan::{QueryPlan, QueryPlanKind, QueryPlanNode, SubQueryPlan};
+use crate::queries::query_plan_optimizer::key_index_matches_types;
 use crate::rows::{KeyIndex, KeyIndexKind};
+use crate::storage::tables::shared_table::SharedTable;
 use crate::storage::tables::table_schema::{
-    ColumnDefinition, Key, TableSchema, TableSchemaBuilder,
+    json_path_column, ColumnDefinition, Key, TableSchema, TableSchemaBuilder,
 };
 use crate::storage::tables::TableRef;
 use crate::tx::Tx;
 
+/// The most values an `in` list can have to still be looked up key by key
+pub const MAX_IN_LIST_LOOKUPS: usize = 32;
+
 #[derive(Debug)]
 pub struct QueryPlanFactory {
     db: WeakWeaverDb,
     join_strategy_selector: JoinStrategySelector,
     cost_table: RefCell<CostTable>,
+    /// The plans of the common table expressions of the query being planned
+    common_table_exprs: RefCell<HashMap<TableRef, QueryPlanNode>>,
 }
 
 impl QueryPlanFactory {
@@ -51,6 +63,7 @@ impl QueryPlanFactory {
             db,
             join_strategy_selector: selector,
             cost_table: Default::default(),
+            common_table_exprs: Default::default(),
         }
     }
 
@@ -76,6 +89,7 @@ impl QueryPlanFactory {
             if cost_table != *self.cost_table.borrow() {
                 *self.cost_table.borrow_mut() = cost_table;
             }
+            self.common_table_exprs.borrow_mut().clear();
 
             self.to_plan_node(query, &socket, function_registry, plan_context.into())
                 .map(QueryPlan::new)
@@ -94,6 +108,10 @@ impl QueryPlanFactory {
         let db_socket = core.connect();
         let mut table = HashMap::new();
         for table_ref in involved {
+            if table_ref.0 == "<cte>" {
+                // common table expressions are planned with the query
+                continue;
+            }
             let schema = db_socket.get_table(&table_ref)?.schema().clone();
             table.insert(table_ref, schema);
         }
@@ -111,18 +129,56 @@ impl QueryPlanFactory {
 
         while let Some(query) = stack.pop() {
             match query {
-                Query::Select(Select {
-                    from: Some(ast::FromClause(table_ref)),
-                    ..
-                }) => self.get_involved_table_refs_helper(
-                    table_ref,
-                    &mut emit,
-                    &mut stack,
-                    plan_context,
-                )?,
+                Query::Select(select) => {
+                    stack.extend(
+                        select
+                            .columns
+                            .iter()
+                            .filter_map(|column| match column {
+                                ResultColumn::Expr { expr, .. } => Some(expr),
+                                _ => None,
+                            })
+                            .chain(&select.condition)
+                            .chain(&select.having)
+                            .flat_map(|expr| expr.sub_queries())
+                            .map(|sub_query| Query::Select(sub_query.clone())),
+                    );
+                    stack.extend(
+                        select
+                            .compound
+                            .iter()
+                            .map(|(_, select)| Query::Select(select.clone())),
+                    );
+                    stack.extend(select.with.iter().flat_map(|with| {
+                        with.tables
+                            .iter()
+                            .map(|cte| Query::Select(cte.select.clone()))
+                    }));
+                    if let Some(ast::FromClause(table_ref)) = select.from {
+                        self.get_involved_table_refs_helper(
+                            table_ref,
+                            &mut emit,
+                            &mut stack,
+                            plan_context,
+                        )?
+                    }
+                }
                 Query::Explain(e) => {
                     stack.push(*e);
                 }
+                Query::Insert(Insert {
+                    source: InsertSource::Select(select),
+                    ..
+                }) => {
+                    stack.push(Query::Select(*select));
+                }
+                Query::Update(Update { schema, table, .. })
+                | Query::Delete(Delete { schema, table, .. }) => {
+                    emit.push(self.table_ref(
+                        (schema.as_ref().map(|s| s.as_ref()), table.as_ref()),
+                        plan_context,
+                    )?);
+                }
                 _ => {}
             }
         }
@@ -175,40 +231,78 @@ impl QueryPlanFactory {
         plan_context: Option<&WeaverProcessInfo>,
     ) -> Result<QueryPlanNode, WeaverError> {
         debug!("creating query plan from {}", query);
-        let tables = debug_span!("finding involved tables")
-            .in_scope(|| self.get_involved_tables(query, plan_context))?;
+        if let Query::Explain(explained) = query {
+            let query = self.to_plan_node(explained, db, function_registry, plan_context)?;
+            return QueryPlanNode::builder()
+                .rows(0)
+                .cost(Cost::new(1.0, 0, None))
+                .kind(QueryPlanKind::Explain {
+                    explained: Box::new(query),
+                })
+                .schema(QueryPlan::explain_schema())
+                .build();
+        }
+
+        let mut query = query.clone();
+        // only a with clause at the start of a query is supported
+        let with = match &mut query {
+            Query::Select(select) => select.with.take(),
+            _ => None,
+        };
+        let names = with
+            .iter()
+            .flat_map(|with| with.tables.iter().map(|cte| cte.name.clone()))
+            .collect::<Vec<_>>();
+        let mut cte_selects = vec![];
+        for (idx, cte) in with.iter().flat_map(|with| with.tables.iter()).enumerate() {
+            // a recursive common table expression can also reference itself
+            let visible = match with.as_ref().is_some_and(|with| with.recursive) {
+                true => &names[..=idx],
+                false => &names[..idx],
+            };
+            let mut select = cte.select.clone();
+            CommonTableExprMarker { names: visible }.visit_select_mut(&mut select)?;
+            cte_selects.push(select);
+        }
+        CommonTableExprMarker { names: &names }.visit_query_mut(&mut query)?;
+
+        let mut tables = debug_span!("finding involved tables").in_scope(|| {
+            let mut tables = self.get_involved_tables(&query, plan_context)?;
+            for select in &cte_selects {
+                tables.extend(
+                    self.get_involved_tables(&Query::Select(select.clone()), plan_context)?,
+                );
+            }
+            Ok::<_, WeaverError>(tables)
+        })?;
         debug!("collected tables: {:?}", tables.keys());
-        debug!("resolving all identifiers");
-        let query = &{
-            let mut query = query.clone();
 
-            let in_use = plan_context
-                .and_then(|info| info.using.as_ref())
-                .map(Identifier::new);
+        for (cte, select) in with
+            .iter()
+            .flat_map(|with| with.tables.iter())
+            .zip(cte_selects)
+        {
+            debug_span!("common table expression", name = %cte.name).in_scope(|| {
+                self.common_table_expr_to_plan_node(
+                    db,
+                    plan_context,
+                    &mut tables,
+                    cte,
+                    select,
+                    function_registry,
+                )
+            })?;
+        }
 
-            let mut resolved = IdentifierResolver::new(in_use, |column_ref| {
-                self.resolve_column_ref(column_ref, &tables, plan_context)
-            });
-            debug_span!("column references resolver")
-                .in_scope(|| resolved.visit_query_mut(&mut query))?;
-            query
-        };
+        debug!("resolving all identifiers");
+        debug_span!("column references resolver")
+            .in_scope(|| self.resolve_identifiers(&mut query, &tables, plan_context))?;
+        let query = &query;
 
         let node = match query {
             Query::Select(select) => {
                 self.select_to_plan_node(db, plan_context, &tables, select, function_registry)
             }
-            Query::Explain(explained) => {
-                let query = self.to_plan_node(explained, db, function_registry, plan_context)?;
-                QueryPlanNode::builder()
-                    .rows(0)
-                    .cost(Cost::new(1.0, 0, None))
-                    .kind(QueryPlanKind::Explain {
-                        explained: Box::new(query),
-                    })
-                    .schema(QueryPlan::explain_schema())
-                    .build()
-            }
             Query::QueryList(_) => {
                 todo!("query list")
             }
@@ -231,6 +325,46 @@ impl QueryPlanFactory {
                     .schema(QueryPlan::ddl_result_schema())
                     .build()
             }
+            Query::Create(Create::Schema(CreateSchema { name })) => QueryPlanNode::builder()
+                .rows(0)
+                .cost(Cost::new(1.0, 0, None))
+                .kind(QueryPlanKind::CreateSchema {
+                    schema: name.to_string(),
+                })
+                .schema(QueryPlan::ddl_result_schema())
+                .build(),
+            Query::Create(Create::Index(CreateIndex {
+                name,
+                unique,
+                schema,
+                table,
+                columns,
+            })) => {
+                let (schema, table) = self.table_ref(
+                    (schema.as_ref().map(|s| s.as_ref()), table.as_ref()),
+                    plan_context,
+                )?;
+                QueryPlanNode::builder()
+                    .rows(0)
+                    .cost(Cost::new(1.0, 0, None))
+                    .kind(QueryPlanKind::CreateIndex {
+                        schema,
+                        table,
+                        name: name.to_string(),
+                        columns: columns.clone(),
+                        unique: *unique,
+                    })
+                    .schema(QueryPlan::ddl_result_schema())
+                    .build()
+            }
+            Query::Use(schema) => QueryPlanNode::builder()
+                .rows(0)
+                .cost(Cost::new(1.0, 0, None))
+                .kind(QueryPlanKind::Use {
+                    schema: schema.to_string(),
+                })
+                .schema(QueryPlan::ddl_result_schema())
+                .build(),
             Query::LoadData(load_data) => {
                 let mut load_data = load_data.clone();
                 if load_data.schema.is_none() {
@@ -247,6 +381,60 @@ impl QueryPlanFactory {
                     .schema(QueryPlan::ddl_result_schema())
                     .build()
             }
+            Query::Insert(insert) => {
+                self.insert_to_plan_node(db, plan_context, &tables, insert, function_registry)
+            }
+            Query::Update(update) => self.update_to_plan_node(db, plan_context, &tables, update),
+            Query::Delete(delete) => self.delete_to_plan_node(db, plan_context, &tables, delete),
+            Query::DropTable(DropTable {
+                schema,
+                name,
+                if_exists,
+            }) => {
+                let (schema, table) = self.table_ref(
+                    (schema.as_ref().map(|s| s.as_ref()), name.as_ref()),
+                    plan_context,
+                )?;
+                QueryPlanNode::builder()
+                    .rows(0)
+                    .cost(Cost::new(1.0, 0, None))
+                    .kind(QueryPlanKind::DropTable {
+                        schema,
+                        table,
+                        if_exists: *if_exists,
+                    })
+                    .schema(QueryPlan::ddl_result_schema())
+                    .build()
+            }
+            Query::DropSchema(DropSchema { name, cascade }) => QueryPlanNode::builder()
+                .rows(0)
+                .cost(Cost::new(1.0, 0, None))
+                .kind(QueryPlanKind::DropSchema {
+                    schema: name.to_string(),
+                    cascade: *cascade,
+                })
+                .schema(QueryPlan::ddl_result_schema())
+                .build(),
+            Query::AlterTable(AlterTable {
+                schema,
+                name,
+                actions,
+            }) => {
+                let (schema, table) = self.table_ref(
+                    (schema.as_ref().map(|s| s.as_ref()), name.as_ref()),
+                    plan_context,
+                )?;
+                QueryPlanNode::builder()
+                    .rows(0)
+                    .cost(Cost::new(1.0, 0, None))
+                    .kind(QueryPlanKind::AlterTable {
+                        schema,
+                        table,
+                        actions: actions.clone(),
+                    })
+                    .schema(QueryPlan::ddl_result_schema())
+                    .build()
+            }
             Query::KillProcess(pid) => QueryPlanNode::builder()
                 .rows(0)
                 .cost(Cost::new(0.0, 0, None))
@@ -261,6 +449,207 @@ impl QueryPlanFactory {
         node
     }
 
+    /// Resolves every column referenced by a query
+    fn resolve_identifiers(
+        &self,
+        query: &mut Query,
+        tables: &HashMap<TableRef, TableSchema>,
+        plan_context: Option<&WeaverProcessInfo>,
+    ) -> Result<(), WeaverError> {
+        let in_use = plan_context
+            .and_then(|info| info.using.as_ref())
+            .map(Identifier::new);
+
+        let mut resolved = IdentifierResolver::new(in_use, |column_ref, scope| match scope {
+            None => self.resolve_column_ref(column_ref, tables, plan_context),
+            Some(scope) => self.resolve_column_ref(
+                column_ref,
+                tables
+                    .iter()
+                    .filter(|(table_ref, _)| scope.contains(table_ref)),
+                plan_context,
+            ),
+        });
+        resolved.visit_query_mut(query)
+    }
+
+    /// Plans a common table expression, adding its schema to the tables of the query. Scans of
+    /// it are planned as copies of this plan.
+    ///
+    /// A recursive common table expression is a compound select whose last select references it.
+    /// That select is run against the rows found by its previous run until it finds none.
+    fn common_table_expr_to_plan_node(
+        &self,
+        db: &DbSocket,
+        plan_context: Option<&WeaverProcessInfo>,
+        tables: &mut HashMap<TableRef, TableSchema>,
+        cte: &CommonTableExpr,
+        select: Select,
+        function_registry: &FunctionRegistry,
+    ) -> Result<(), WeaverError> {
+        let table_ref = ("<cte>".to_string(), cte.name.to_string());
+        let unsupported = |reason: &str| {
+            WeaverError::UnsupportedCommonTableExpr(cte.name.to_string(), reason.to_string())
+        };
+        let references_itself = |select: &Select| -> Result<bool, WeaverError> {
+            Ok(self
+                .get_involved_table_refs(&Query::Select(select.clone()), plan_context)?
+                .contains(&table_ref))
+        };
+
+        let mut query = Query::Select(select);
+        let recursive = match &mut query {
+            Query::Select(select) if references_itself(select)? => {
+                let Some((op, recursive)) = select.compound.pop() else {
+                    return Err(unsupported("must be a compound select to reference itself"));
+                };
+                if !matches!(op, CompoundOperator::Union | CompoundOperator::UnionAll) {
+                    return Err(unsupported(
+                        "must use union or union all to reference itself",
+                    ));
+                }
+                if select.order_by.is_some() || select.limit.is_some() {
+                    return Err(unsupported("can not be ordered or limited when recursive"));
+                }
+                if references_itself(select)? {
+                    return Err(unsupported("only the last select can reference itself"));
+                }
+                Some((op, recursive))
+            }
+            _ => None,
+        };
+
+        self.resolve_identifiers(&mut query, tables, plan_context)?;
+        let Query::Select(select) = &query else {
+            unreachable!("common table expressions are selects")
+        };
+        let base = self.select_to_plan_node(db, plan_context, tables, select, function_registry)?;
+
+        // renames the columns of the select to those of the common table expression
+        let base_schema = base.schema();
+        let names = match &cte.columns {
+            Some(columns) if columns.len() != base_schema.columns().len() => {
+                return Err(WeaverError::BadColumnCount {
+                    expected: columns.len(),
+                    actual: base_schema.columns().len(),
+                });
+            }
+            Some(columns) => columns.iter().map(ToString::to_string).collect(),
+            None => base_schema
+                .columns()
+                .iter()
+                .map(|column| match column.source_column() {
+                    Some(source) if column.name() == source.to_string() => {
+                        source.column().to_string()
+                    }
+                    _ => column.name().to_string(),
+                })
+                .collect::<Vec<_>>(),
+        };
+        let mut schema_builder = TableSchemaBuilder::new(&table_ref.0, &table_ref.1);
+        let mut columns = vec![];
+        for (name, column) in names.iter().zip(base_schema.columns()) {
+            let mut definition = ColumnDefinition::new(
+                name,
+                column.data_type(),
+                column.non_null() && recursive.is_none(),
+                None,
+                None,
+            )?;
+            definition.set_source_column(ResolvedColumnRef::new(
+                Identifier::new(&table_ref.0),
+                Identifier::new(&table_ref.1),
+                Identifier::new(name),
+            ));
+            schema_builder = schema_builder.column_definition(definition);
+
+            let source = column.source_column().cloned().unwrap_or_else(|| {
+                ResolvedColumnRef::new(
+                    Identifier::new(base_schema.schema()),
+                    Identifier::new(base_schema.name()),
+                    Identifier::new(column.name()),
+                )
+            });
+            columns.push(Expr::Column {
+                column: source.into(),
+            });
+        }
+        let schema = schema_builder.build()?;
+        let rows = base.rows;
+        let base = QueryPlanNode::builder()
+            .cost(self.get_cost("PROJECT")?)
+            .rows(rows)
+            .kind(QueryPlanKind::Project {
+                columns,
+                projected: Box::new(base),
+            })
+            .schema(schema.clone())
+            .build()?;
+        tables.insert(table_ref.clone(), schema.clone());
+
+        let node = match recursive {
+            None => base,
+            Some((op, recursive)) => {
+                // while planning the recursive select, the common table expression is the rows
+                // found by the previous run
+                let working_table = QueryPlanNode::builder()
+                    .cost(self.get_cost("LOAD_TABLE")?)
+                    .rows(rows)
+                    .kind(QueryPlanKind::WorkingTable {
+                        name: cte.name.to_string(),
+                    })
+                    .schema(schema.clone())
+                    .build()?;
+                self.common_table_exprs
+                    .borrow_mut()
+                    .insert(table_ref.clone(), working_table);
+
+                let mut query = Query::Select(recursive);
+                self.resolve_identifiers(&mut query, tables, plan_context)?;
+                let Query::Select(recursive) = &query else {
+                    unreachable!("common table expressions are selects")
+                };
+                let recursive = self.select_to_plan_node(
+                    db,
+                    plan_context,
+                    tables,
+                    recursive,
+                    function_registry,
+                )?;
+
+                let recursive_columns = recursive.schema().columns();
+                if recursive_columns.len() != schema.columns().len() {
+                    return Err(WeaverError::BadColumnCount {
+                        expected: schema.columns().len(),
+                        actual: recursive_columns.len(),
+                    });
+                }
+                for (column, recursive_column) in schema.columns().iter().zip(recursive_columns) {
+                    if !recursive_column.data_type().widens_to(&column.data_type()) {
+                        return Err(WeaverError::IncompatibleTypes(
+                            column.data_type(),
+                            recursive_column.data_type(),
+                        ));
+                    }
+                }
+
+                QueryPlanNode::builder()
+                    .cost(self.get_cost("COMPOUND")?)
+                    .rows(rows + recursive.rows)
+                    .kind(QueryPlanKind::Recursive {
+                        name: cte.name.to_string(),
+                        base: Box::new(base),
+                        recursive: Box::new(recursive),
+                        distinct: op == CompoundOperator::Union,
+                    })
+                    .schema(schema)
+                    .build()?
+            }
+        };
+        self.common_table_exprs.borrow_mut().insert(table_ref, node);
+        Ok(())
+    }
+
     #[allow(clippy::wrong_self_convention)]
     fn from_to_plan_node(
         &self,
@@ -292,6 +681,11 @@ impl QueryPlanFactory {
                     (schema.as_ref().map(|s| s.as_ref()), table_name.as_ref()),
                     plan_context,
                 )?;
+                if let Some(cte) = self.common_table_exprs.borrow().get(&table_ref) {
+                    let mut node = cte.clone();
+                    node.alias = alias.as_ref().map(|i| i.to_string());
+                    return Ok(node);
+                }
                 let table_schema = real_tables
                     .get(&table_ref)
                     .expect("could not get schema")
@@ -353,12 +747,16 @@ impl QueryPlanFactory {
     ) -> Result<QueryPlanNode, WeaverError> {
         error_span!("SELECT").in_scope(|| -> Result<QueryPlanNode, WeaverError> {
             let Select {
+                with: _,
+                distinct,
                 columns,
                 from,
                 condition,
                 group_by,
+                having,
                 order_by,
                 limit,
+                compound,
                 offset,
             } = select;
 
@@ -367,17 +765,29 @@ impl QueryPlanFactory {
                     todo!("no from")
                 }
                 Some(from) => {
+                    let (from, condition) =
+                        self.sub_queries_to_joins(from, condition.as_ref(), plan_context)?;
                     let from_node = self.from_to_plan_node(
                         db,
                         plan_context,
                         real_tables,
-                        from,
+                        &from,
                         function_registry,
                     )?;
                     // let keys = self.get_keys_from_condition(plan_context, &real_tables, condition, &from_node)?;
 
                     let from_node_rows = from_node.rows;
 
+                    let mut condition = condition;
+                    let from_node = self.apply_sub_queries(
+                        db,
+                        plan_context,
+                        real_tables,
+                        from_node,
+                        condition.iter_mut().collect(),
+                        function_registry,
+                    )?;
+
                     let filtered = match condition {
                         None => from_node,
                         Some(condition) => {
@@ -391,18 +801,59 @@ impl QueryPlanFactory {
                                 )
                                 .kind(QueryPlanKind::Filter {
                                     filtered: Box::new(from_node),
-                                    condition: condition.clone(),
+                                    condition,
                                 })
                                 .schema(schema)
                                 .build()?
                         }
                     };
 
-                    let mut outer = match group_by {
-                        None => {
+                    let mut outer = match (group_by, having) {
+                        (None, None)
+                            if !self.has_aggregate(
+                                columns,
+                                filtered.schema(),
+                                function_registry,
+                            )? =>
+                        {
                             // no grouping allows for normal projection
+                            let mut columns = columns.clone();
+                            let filtered = self.apply_sub_queries(
+                                db,
+                                plan_context,
+                                real_tables,
+                                filtered,
+                                columns
+                                    .iter_mut()
+                                    .filter_map(|column| match column {
+                                        ResultColumn::Expr { expr, alias } => {
+                                            if alias.is_none()
+                                                && (!expr.sub_queries().is_empty()
+                                                    || has_window_function(expr))
+                                            {
+                                                // keeps the name of the column as written
+                                                *alias = Some(Identifier::new(expr.to_string()));
+                                            }
+                                            Some(expr)
+                                        }
+                                        _ => None,
+                                    })
+                                    .collect(),
+                                function_registry,
+                            )?;
+                            let filtered = self.apply_window_functions(
+                                filtered,
+                                columns
+                                    .iter_mut()
+                                    .filter_map(|column| match column {
+                                        ResultColumn::Expr { expr, .. } => Some(expr),
+                                        _ => None,
+                                    })
+                                    .collect(),
+                                function_registry,
+                            )?;
                             let (projected_schema, columns) = self.table_schema_for_projection(
-                                columns,
+                                &columns,
                                 &filtered,
                                 function_registry,
                             )?;
@@ -417,14 +868,41 @@ impl QueryPlanFactory {
                                 .schema(projected_schema)
                                 .build()?
                         }
-                        Some(grouped) => self.group_by_to_plan_node(
+                        // without a group by, aggregates and having treat all rows as a single
+                        // group
+                        (grouped, having) => self.group_by_to_plan_node(
                             columns,
-                            grouped,
+                            grouped.as_deref().unwrap_or_default(),
+                            having.as_ref(),
                             filtered,
                             function_registry,
                         )?,
                     };
 
+                    if *distinct {
+                        let outer_schema = outer.schema().clone();
+                        outer = QueryPlanNode::builder()
+                            .cost(self.get_cost("DISTINCT")?)
+                            .rows(outer.rows)
+                            .kind(QueryPlanKind::Distinct {
+                                deduplicated: Box::new(outer),
+                                sorted: false,
+                            })
+                            .schema(outer_schema)
+                            .build()?;
+                    }
+
+                    for (op, select) in compound {
+                        let right = self.select_to_plan_node(
+                            db,
+                            plan_context,
+                            real_tables,
+                            select,
+                            function_registry,
+                        )?;
+                        outer = self.compound_to_plan_node(outer, *op, right)?;
+                    }
+
                     if let Some(order) = order_by {
                         let outer_schema = outer.schema().clone();
                         outer = QueryPlanNode::builder()
@@ -463,6 +941,652 @@ impl QueryPlanFactory {
         })
     }
 
+    /// Combines the rows of two selects with a set operation. The columns are named after the
+    /// left select, and each has the wider of the types of the selects.
+    fn compound_to_plan_node(
+        &self,
+        left: QueryPlanNode,
+        op: CompoundOperator,
+        right: QueryPlanNode,
+    ) -> Result<QueryPlanNode, WeaverError> {
+        let (left_columns, right_columns) = (left.schema().columns(), right.schema().columns());
+        if left_columns.len() != right_columns.len() {
+            return Err(WeaverError::BadColumnCount {
+                expected: left_columns.len(),
+                actual: right_columns.len(),
+            });
+        }
+
+        let mut schema_builder = TableSchemaBuilder::new("<query>", "<compound>");
+        for (left_column, right_column) in left_columns.iter().zip(right_columns) {
+            let (left_type, right_type) = (left_column.data_type(), right_column.data_type());
+            let data_type = if right_type.widens_to(&left_type) {
+                left_type
+            } else if left_type.widens_to(&right_type) {
+                right_type
+            } else {
+                return Err(WeaverError::IncompatibleTypes(left_type, right_type));
+            };
+            let mut column = ColumnDefinition::new(
+                left_column.name(),
+                data_type,
+                left_column.non_null() && right_column.non_null(),
+                None,
+                None,
+            )?;
+            if let Some(source) = left_column.source_column() {
+                column.set_source_column(source.clone());
+            }
+            schema_builder = schema_builder.column_definition(column);
+        }
+
+        let rows = match op {
+            CompoundOperator::Union | CompoundOperator::UnionAll => left.rows + right.rows,
+            CompoundOperator::Intersect | CompoundOperator::Except => left.rows,
+        };
+        QueryPlanNode::builder()
+            .cost(self.get_cost("COMPOUND")?)
+            .rows(rows)
+            .kind(QueryPlanKind::Compound {
+                left: Box::new(left),
+                right: Box::new(right),
+                op,
+            })
+            .schema(schema_builder.build()?)
+            .build()
+    }
+
+    /// Turns the `in` and `exists` subqueries that are conjuncts of a where clause into semi and
+    /// anti joins against the from clause, so each subquery only runs once instead of once per row.
+    /// Subqueries that can't be joined are left in the condition to be applied.
+    ///
+    /// Returns the new from clause and what remains of the condition.
+    fn sub_queries_to_joins(
+        &self,
+        from: &FromClause,
+        condition: Option<&Expr>,
+        plan_context: Option<&WeaverProcessInfo>,
+    ) -> Result<(FromClause, Option<Expr>), WeaverError> {
+        let mut from = from.0.clone();
+        let mut remaining = vec![];
+        for conjunct in condition.map(conjuncts).unwrap_or_default() {
+            let joined = match conjunct {
+                Expr::InSubQuery {
+                    expr,
+                    query,
+                    negated,
+                } => self
+                    .in_sub_query_join_condition(expr, query, plan_context)?
+                    .map(|on| {
+                        let op = if *negated {
+                            JoinOperator::NullAwareAnti
+                        } else {
+                            JoinOperator::Semi
+                        };
+                        (query.as_ref().clone(), on, op)
+                    }),
+                Expr::Exists { query, negated } => self
+                    .decorrelate_exists(query, plan_context)?
+                    .map(|(query, on)| {
+                        let op = if *negated {
+                            JoinOperator::Anti
+                        } else {
+                            JoinOperator::Semi
+                        };
+                        (query, on, op)
+                    }),
+                _ => None,
+            };
+            // any other subqueries are left to be applied
+            let Some((query, on, op)) = joined else {
+                remaining.push(conjunct.clone());
+                continue;
+            };
+
+            from = TableOrSubQuery::JoinClause(JoinClause {
+                left: Box::new(from),
+                op,
+                right: Box::new(TableOrSubQuery::Select {
+                    select: Box::new(query),
+                    alias: None,
+                }),
+                constraint: JoinConstraint { on },
+            });
+        }
+
+        Ok((FromClause(from), conjunction(remaining)))
+    }
+
+    /// Gets the condition joining an `in` subquery with the outer query, which can only be done
+    /// when an outer column is compared with the single column selected by an uncorrelated
+    /// subquery, as the subquery is hash joined on the values of the two columns.
+    fn in_sub_query_join_condition(
+        &self,
+        expr: &Expr,
+        query: &Select,
+        plan_context: Option<&WeaverProcessInfo>,
+    ) -> Result<Option<Expr>, WeaverError> {
+        let [ResultColumn::Expr { expr: column, .. }] = query.columns.as_slice() else {
+            return Ok(None);
+        };
+        if !matches!(expr, Expr::Column { .. }) || !matches!(column, Expr::Column { .. }) {
+            return Ok(None);
+        }
+        let inner = self.get_involved_table_refs(&Query::Select(query.clone()), plan_context)?;
+        if query
+            .condition
+            .iter()
+            .chain([column])
+            .any(|expr| !is_within_tables(expr, &inner))
+        {
+            return Ok(None);
+        }
+        Ok(Some(Expr::Binary {
+            left: Box::new(expr.clone()),
+            op: BinaryOp::Eq,
+            right: Box::new(column.clone()),
+        }))
+    }
+
+    /// Splits an `exists` subquery into an uncorrelated subquery and the condition joining it with
+    /// the outer query, which requires every equality between an outer column and one of its own
+    /// columns to hold.
+    ///
+    /// Returns nothing if the subquery is uncorrelated, as it only has to run once anyway, or if it
+    /// is correlated by anything besides equalities between columns.
+    fn decorrelate_exists(
+        &self,
+        query: &Select,
+        plan_context: Option<&WeaverProcessInfo>,
+    ) -> Result<Option<(Select, Expr)>, WeaverError> {
+        let inner = self.get_involved_table_refs(&Query::Select(query.clone()), plan_context)?;
+        let mut correlations = vec![];
+        let mut remaining = vec![];
+        for conjunct in query.condition.as_ref().map(conjuncts).unwrap_or_default() {
+            if is_within_tables(conjunct, &inner) {
+                remaining.push(conjunct.clone());
+                continue;
+            }
+            match conjunct {
+                Expr::Binary {
+                    left,
+                    op: BinaryOp::Eq,
+                    right,
+                } if matches!(**left, Expr::Column { .. })
+                    && matches!(**right, Expr::Column { .. })
+                    && is_within_tables(left, &inner) != is_within_tables(right, &inner) =>
+                {
+                    correlations.push(if is_within_tables(left, &inner) {
+                        (right.clone(), left.clone())
+                    } else {
+                        (left.clone(), right.clone())
+                    });
+                }
+                _ => return Ok(None),
+            }
+        }
+        if correlations.is_empty() {
+            return Ok(None);
+        }
+
+        // only whether any row exists matters, so the selected columns and page are replaced
+        let mut columns = vec![];
+        for (_, inner_column) in &correlations {
+            let column = ResultColumn::Expr {
+                expr: *inner_column.clone(),
+                alias: None,
+            };
+            if !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+        let query = Select {
+            columns,
+            condition: conjunction(remaining),
+            order_by: None,
+            limit: None,
+            offset: None,
+            ..query.clone()
+        };
+        let on = conjunction(
+            correlations
+                .into_iter()
+                .map(|(outer, inner_column)| Expr::Binary {
+                    left: outer,
+                    op: BinaryOp::Eq,
+                    right: inner_column,
+                })
+                .collect(),
+        )
+        .expect("there is at least one correlation");
+        Ok(Some((query, on)))
+    }
+
+    /// Plans the subqueries within expressions as an [apply](QueryPlanKind::Apply) node over
+    /// `applied`, which adds the value of each subquery as a column of its rows. The value of an
+    /// `in` or `exists` subquery is the value of the whole `in` or `exists` expression. The
+    /// subqueries are replaced by references to these columns.
+    ///
+    /// Returns `applied` unchanged if there are no subqueries.
+    fn apply_sub_queries(
+        &self,
+        db: &DbSocket,
+        plan_context: Option<&WeaverProcessInfo>,
+        real_tables: &HashMap<TableRef, TableSchema>,
+        applied: QueryPlanNode,
+        exprs: Vec<&mut Expr>,
+        function_registry: &FunctionRegistry,
+    ) -> Result<QueryPlanNode, WeaverError> {
+        // subqueries of an earlier apply node are already columns of the applied rows
+        let offset = applied
+            .schema()
+            .columns()
+            .iter()
+            .filter(|column| {
+                column
+                    .source_column()
+                    .is_some_and(|source| source.table().as_ref() == "<sub_query>")
+            })
+            .count();
+        let mut replacer = SubQueryReplacer {
+            offset,
+            found: vec![],
+        };
+        for expr in exprs {
+            replacer.visit_expr_mut(expr)?;
+        }
+        if replacer.found.is_empty() {
+            return Ok(applied);
+        }
+
+        let applied_schema = applied.schema();
+        let mut schema_builder = TableSchemaBuilder::new("<query>", "<apply>");
+        for column in applied_schema.columns() {
+            let mut column = column.clone();
+            if column.source_column().is_none() {
+                column.set_source_column(ResolvedColumnRef::new(
+                    Identifier::new(applied_schema.schema()),
+                    Identifier::new(applied_schema.name()),
+                    Identifier::new(column.name()),
+                ));
+            }
+            schema_builder = schema_builder.column_definition(column);
+        }
+
+        let mut sub_queries = vec![];
+        for (idx, expr) in replacer.found.into_iter().enumerate() {
+            let (Expr::SubQuery { query }
+            | Expr::InSubQuery { query, .. }
+            | Expr::Exists { query, .. }) = &expr
+            else {
+                unreachable!("only subqueries are replaced")
+            };
+            // only whether an exists subquery has any rows matters
+            if !matches!(expr, Expr::Exists { .. })
+                && !matches!(query.columns.as_slice(), [ResultColumn::Expr { .. }])
+            {
+                return Err(WeaverError::UnsupportedSubQuery(
+                    expr.clone(),
+                    "must select exactly one column".to_string(),
+                ));
+            }
+            let plan =
+                self.select_to_plan_node(db, plan_context, real_tables, query, function_registry)?;
+
+            let inner =
+                self.get_involved_table_refs(&Query::Select(query.as_ref().clone()), plan_context)?;
+            let mut collector = ColumnCollector::default();
+            collector
+                .visit_select_mut(&mut query.as_ref().clone())
+                .unwrap_or_else(|never| match never {});
+            let mut outer_columns = vec![];
+            for column in collector.columns {
+                // columns of common table expressions can be used like those of any table
+                if (column.schema().as_ref().starts_with('<')
+                    && column.schema().as_ref() != "<cte>")
+                    || inner.iter().any(|(schema, table)| {
+                        column.schema().as_ref() == schema && column.table().as_ref() == table
+                    })
+                    || outer_columns.contains(&column)
+                {
+                    continue;
+                }
+                if applied_schema.column_index_by_source(&column).is_none() {
+                    return Err(WeaverError::UnsupportedSubQuery(
+                        expr.clone(),
+                        format!("can not reference {column} from outside of the outer query"),
+                    ));
+                }
+                outer_columns.push(column);
+            }
+
+            let source = sub_query_column(offset + idx);
+            let data_type = match expr {
+                Expr::SubQuery { .. } => plan.schema().columns()[0].data_type(),
+                _ => Type::Boolean,
+            };
+            let mut column = ColumnDefinition::new(source.column(), data_type, false, None, None)?;
+            column.set_source_column(source);
+            schema_builder = schema_builder.column_definition(column);
+
+            sub_queries.push(SubQueryPlan {
+                expr,
+                outer_columns,
+                plan,
+            });
+        }
+
+        let rows = applied.rows;
+        QueryPlanNode::builder()
+            .cost(self.get_cost("APPLY")?)
+            .rows(rows)
+            .kind(QueryPlanKind::Apply {
+                applied: Box::new(applied),
+                sub_queries,
+            })
+            .schema(schema_builder.build()?)
+            .build()
+    }
+
+    /// Evaluates the window functions within expressions with a [window](QueryPlanKind::Window)
+    /// node over `windowed`, which adds the value of each window function as a column of its rows.
+    /// The window functions are replaced by references to these columns.
+    ///
+    /// Returns `windowed` unchanged if there are no window functions.
+    fn apply_window_functions(
+        &self,
+        windowed: QueryPlanNode,
+        exprs: Vec<&mut Expr>,
+        function_registry: &FunctionRegistry,
+    ) -> Result<QueryPlanNode, WeaverError> {
+        let mut replacer = WindowFunctionReplacer { found: vec![] };
+        for expr in exprs {
+            replacer.visit_expr_mut(expr)?;
+        }
+        if replacer.found.is_empty() {
+            return Ok(windowed);
+        }
+
+        let windowed_schema = windowed.schema();
+        let mut schema_builder = TableSchemaBuilder::new("<query>", "<window>");
+        for column in windowed_schema.columns() {
+            let mut column = column.clone();
+            if column.source_column().is_none() {
+                column.set_source_column(ResolvedColumnRef::new(
+                    Identifier::new(windowed_schema.schema()),
+                    Identifier::new(windowed_schema.name()),
+                    Identifier::new(column.name()),
+                ));
+            }
+            schema_builder = schema_builder.column_definition(column);
+        }
+        for (idx, expr) in replacer.found.iter().enumerate() {
+            let source = window_function_column(idx);
+            let data_type = expr.type_of(function_registry, Some(windowed_schema))?;
+            let mut column = ColumnDefinition::new(source.column(), data_type, false, None, None)?;
+            column.set_source_column(source);
+            schema_builder = schema_builder.column_definition(column);
+        }
+
+        let rows = windowed.rows;
+        QueryPlanNode::builder()
+            .cost(self.get_cost("WINDOW")?)
+            .rows(rows)
+            .kind(QueryPlanKind::Window {
+                windowed: Box::new(windowed),
+                functions: replacer.found,
+            })
+            .schema(schema_builder.build()?)
+            .build()
+    }
+
+    /// Checks if any of the selected columns use an aggregate, outside of any subqueries
+    fn has_aggregate(
+        &self,
+        columns: &[ResultColumn],
+        schema: &TableSchema,
+        function_registry: &FunctionRegistry,
+    ) -> Result<bool, WeaverError> {
+        let mut finder = AggregateFinder {
+            schema,
+            function_registry,
+            found: false,
+        };
+        for column in columns {
+            if let ResultColumn::Expr { expr, .. } = column {
+                finder.visit_expr_mut(&mut expr.clone())?;
+            }
+        }
+        Ok(finder.found)
+    }
+
+    fn insert_to_plan_node(
+        &self,
+        db: &DbSocket,
+        plan_context: Option<&WeaverProcessInfo>,
+        real_tables: &HashMap<TableRef, TableSchema>,
+        insert: &Insert,
+        function_registry: &FunctionRegistry,
+    ) -> Result<QueryPlanNode, WeaverError> {
+        error_span!("INSERT").in_scope(|| -> Result<QueryPlanNode, WeaverError> {
+            let Insert {
+                schema,
+                table,
+                columns,
+                source,
+            } = insert;
+
+            let schema = match schema {
+                Some(schema) => schema.to_string(),
+                None => plan_context
+                    .and_then(|ctx| ctx.using.clone())
+                    .ok_or(WeaverError::NoDefaultSchema)?,
+            };
+            let table_ref = (schema, table.to_string());
+            let table_schema = db.get_table(&table_ref)?.schema().clone();
+
+            let columns = if columns.is_empty() {
+                table_schema
+                    .columns()
+                    .iter()
+                    .map(|col| col.name().to_string())
+                    .collect::<Vec<_>>()
+            } else {
+                columns.iter().map(|col| col.to_string()).collect()
+            };
+
+            let mut source_schema = TableSchemaBuilder::new("<query>", "<values>");
+            for (idx, column) in columns.iter().enumerate() {
+                if columns[..idx].contains(column) {
+                    return Err(WeaverError::DuplicateColumn(column.clone()));
+                }
+                let col = table_schema
+                    .columns()
+                    .iter()
+                    .find(|col| col.name() == column)
+                    .ok_or_else(|| WeaverError::ColumnNotFound(column.clone()))?;
+                source_schema = source_schema.column(column, col.data_type(), false, None, None)?;
+            }
+
+            let source = match source {
+                InsertSource::Values(values) => {
+                    if let Some(row) = values.iter().find(|row| row.len() != columns.len()) {
+                        return Err(WeaverError::BadColumnCount {
+                            expected: columns.len(),
+                            actual: row.len(),
+                        });
+                    }
+                    QueryPlanNode::builder()
+                        .cost(self.get_cost("VALUES")?)
+                        .rows(values.len() as u64)
+                        .kind(QueryPlanKind::Values {
+                            values: values.clone(),
+                        })
+                        .schema(source_schema.build()?)
+                        .build()?
+                }
+                InsertSource::Select(select) => {
+                    let node = self.select_to_plan_node(
+                        db,
+                        plan_context,
+                        real_tables,
+                        select,
+                        function_registry,
+                    )?;
+                    if node.schema().columns().len() != columns.len() {
+                        return Err(WeaverError::BadColumnCount {
+                            expected: columns.len(),
+                            actual: node.schema().columns().len(),
+                        });
+                    }
+                    node
+                }
+            };
+
+            let (schema, table) = table_ref;
+            QueryPlanNode::builder()
+                .cost(self.get_cost("INSERT")?)
+                .rows(source.rows)
+                .kind(QueryPlanKind::Insert {
+                    schema,
+                    table,
+                    columns,
+                    source: Box::new(source),
+                })
+                .schema(QueryPlan::ddl_result_schema())
+                .build()
+        })
+    }
+
+    fn update_to_plan_node(
+        &self,
+        db: &DbSocket,
+        plan_context: Option<&WeaverProcessInfo>,
+        real_tables: &HashMap<TableRef, TableSchema>,
+        update: &Update,
+    ) -> Result<QueryPlanNode, WeaverError> {
+        error_span!("UPDATE").in_scope(|| -> Result<QueryPlanNode, WeaverError> {
+            let Update {
+                schema,
+                table,
+                set,
+                condition,
+            } = update;
+
+            let table_ref = self.table_ref(
+                (schema.as_ref().map(|s| s.as_ref()), table.as_ref()),
+                plan_context,
+            )?;
+            let table = db.get_table(&table_ref)?;
+            let table_schema = table.schema();
+
+            let set = set
+                .iter()
+                .map(|(column, expr)| {
+                    if table_schema
+                        .columns()
+                        .iter()
+                        .any(|col| col.name() == column.as_ref())
+                    {
+                        Ok((column.to_string(), expr.clone()))
+                    } else {
+                        Err(WeaverError::ColumnNotFound(column.to_string()))
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let key_index =
+                self.best_key_index(&table, condition.as_ref(), real_tables, plan_context)?;
+            let rows = table.size_estimate(&key_index)?;
+
+            let (schema, table) = table_ref;
+            QueryPlanNode::builder()
+                .cost(self.get_cost("UPDATE")?)
+                .rows(rows)
+                .kind(QueryPlanKind::Update {
+                    schema,
+                    table,
+                    key_index,
+                    condition: condition.clone(),
+                    set,
+                })
+                .schema(QueryPlan::ddl_result_schema())
+                .build()
+        })
+    }
+
+    fn delete_to_plan_node(
+        &self,
+        db: &DbSocket,
+        plan_context: Option<&WeaverProcessInfo>,
+        real_tables: &HashMap<TableRef, TableSchema>,
+        delete: &Delete,
+    ) -> Result<QueryPlanNode, WeaverError> {
+        error_span!("DELETE").in_scope(|| -> Result<QueryPlanNode, WeaverError> {
+            let Delete {
+                schema,
+                table,
+                condition,
+            } = delete;
+
+            let table_ref = self.table_ref(
+                (schema.as_ref().map(|s| s.as_ref()), table.as_ref()),
+                plan_context,
+            )?;
+            let table = db.get_table(&table_ref)?;
+            let key_index =
+                self.best_key_index(&table, condition.as_ref(), real_tables, plan_context)?;
+            let rows = table.size_estimate(&key_index)?;
+            let result_schema = QueryPlan::delete_result_schema(table.schema())?;
+
+            let (schema, table) = table_ref;
+            QueryPlanNode::builder()
+                .cost(self.get_cost("DELETE")?)
+                .rows(rows)
+                .kind(QueryPlanKind::Delete {
+                    schema,
+                    table,
+                    key_index,
+                    condition: condition.clone(),
+                })
+                .schema(result_schema)
+                .build()
+        })
+    }
+
+    /// Gets the key index of a table that's expected to touch the least amount of rows while still
+    /// covering every row that matches the condition.
+    fn best_key_index(
+        &self,
+        table: &SharedTable,
+        condition: Option<&Expr>,
+        real_tables: &HashMap<TableRef, TableSchema>,
+        plan_context: Option<&WeaverProcessInfo>,
+    ) -> Result<KeyIndex, WeaverError> {
+        let table_schema = table.schema();
+        let mut key_indices = vec![];
+        for key in table_schema.keys() {
+            key_indices.extend(
+                self.to_key_index(key, condition, real_tables, plan_context)?
+                    .into_iter()
+                    .filter(|key_index| key_index_matches_types(key, key_index, table_schema)),
+            );
+        }
+        key_indices
+            .into_iter()
+            .map(|key_index| {
+                table
+                    .size_estimate(&key_index)
+                    .map(|rows| (rows, key_index))
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .min_by_key(|(rows, _)| *rows)
+            .map(|(_, key_index)| key_index)
+            .map(Ok)
+            .unwrap_or_else(|| table_schema.full_index())
+    }
+
     fn table_schema_for_projection(
         &self,
         columns: &Vec<ResultColumn>,
@@ -478,6 +1602,13 @@ impl QueryPlanFactory {
                 ResultColumn::Wildcard => {
                     // all columns from previous node
                     for col in from_node.schema.columns() {
+                        if col.source_column().is_some_and(|source| {
+                            matches!(source.table().as_ref(), "<sub_query>" | "<window>")
+                        }) {
+                            // the values of applied subqueries and window functions are never
+                            // selected by a wildcard
+                            continue;
+                        }
                         let resolved = if let Some(source_column) = col.source_column() {
                             source_column.clone()
                         } else {
@@ -527,6 +1658,7 @@ impl QueryPlanFactory {
         &self,
         columns: &Vec<ResultColumn>,
         groups: &[Expr],
+        having: Option<&Expr>,
         grouped: QueryPlanNode,
         function_registry: &FunctionRegistry,
     ) -> Result<QueryPlanNode, WeaverError> {
@@ -554,9 +1686,7 @@ impl QueryPlanFactory {
             }
         }
 
-        let _source_schema = &grouped.schema;
-        let mut schema_builder = TableSchemaBuilder::new("<query>", "<grouped_by>");
-        let mut cols = vec![];
+        let mut named_columns = vec![];
         for result_column in columns {
             match result_column {
                 ResultColumn::Expr { expr, alias } => {
@@ -564,38 +1694,132 @@ impl QueryPlanFactory {
                         None => expr.to_string(),
                         Some(alias) => alias.to_string(),
                     };
-                    let non_null = matches!(expr, Expr::Column { column: _ });
+                    named_columns.push((name, expr.clone()));
+                }
+                _ => return Err(WeaverError::WildcardIsNeverFunctionallyDependent),
+            }
+        }
+        let selected = named_columns.len();
+
+        let having = match having {
+            None => None,
+            Some(having) => {
+                if !self.is_functionally_dependent(
+                    grouped.schema(),
+                    groups,
+                    having,
+                    function_registry,
+                )? {
+                    return Err(WeaverError::ExpressionNotFunctionallyDependentOnGroupBy(
+                        having.clone(),
+                        groups.to_owned(),
+                    ));
+                }
+                let mut having = having.clone();
+                self.resolve_having(
+                    &mut having,
+                    grouped.schema(),
+                    &mut named_columns,
+                    function_registry,
+                )?;
+                Some(having)
+            }
+        };
 
-                    let db_type = expr.type_of(function_registry, Some(grouped.schema()))?;
+        let mut schema_builder = TableSchemaBuilder::new("<query>", "<grouped_by>");
+        let mut cols = vec![];
+        for (name, expr) in named_columns {
+            let non_null = matches!(expr, Expr::Column { column: _ });
 
-                    let mut cd = ColumnDefinition::new(name, db_type, non_null, None, None)?;
-                    if let Expr::Column { column } = expr {
-                        if let Some(resolved) = column.resolved() {
-                            cd.set_source_column(resolved.clone());
-                        }
-                    }
+            let db_type = expr.type_of(function_registry, Some(grouped.schema()))?;
 
-                    cols.push(expr.clone());
-                    schema_builder = schema_builder.column_definition(cd);
+            let mut cd = ColumnDefinition::new(name, db_type, non_null, None, None)?;
+            if let Expr::Column { column } = &expr {
+                if let Some(resolved) = column.resolved() {
+                    cd.set_source_column(resolved.clone());
                 }
-                _ => return Err(WeaverError::WildcardIsNeverFunctionallyDependent),
             }
+
+            cols.push(expr);
+            schema_builder = schema_builder.column_definition(cd);
         }
 
         let schema = schema_builder.build()?;
+        let rows = grouped.rows;
 
-        QueryPlanNode::builder()
+        let grouped = QueryPlanNode::builder()
             .cost(self.get_cost("GROUP_BY")?)
-            .rows(grouped.rows)
+            .rows(rows)
             .kind(QueryPlanKind::GroupBy {
                 grouped: Box::new(grouped),
                 grouped_by: groups.to_owned(),
                 result_columns: cols,
             })
-            .schema(schema)
+            .schema(schema.clone())
+            .build()?;
+
+        let Some(having) = having else {
+            return Ok(grouped);
+        };
+        let filtered = QueryPlanNode::builder()
+            .cost(self.get_cost("FILTER")?)
+            .rows(rows)
+            .kind(QueryPlanKind::Filter {
+                filtered: Box::new(grouped),
+                condition: having,
+            })
+            .schema(schema.clone())
+            .build()?;
+        if schema.columns().len() == selected {
+            return Ok(filtered);
+        }
+
+        // removes the columns only computed for the having clause
+        let projected_schema = schema
+            .columns()
+            .iter()
+            .take(selected)
+            .fold(
+                TableSchemaBuilder::new("<query>", "<grouped_by>"),
+                |builder, column| builder.column_definition(column.clone()),
+            )
+            .build()?;
+        let columns = schema
+            .columns()
+            .iter()
+            .take(selected)
+            .map(|column| grouped_column(column.name()))
+            .collect();
+        QueryPlanNode::builder()
+            .cost(self.get_cost("PROJECT")?)
+            .rows(rows)
+            .kind(QueryPlanKind::Project {
+                columns,
+                projected: Box::new(filtered),
+            })
+            .schema(projected_schema)
             .build()
     }
 
+    /// Makes a having clause evaluable over the rows of a group by, replacing every aggregate
+    /// within it by a reference to the grouped column computing it.
+    ///
+    /// Aggregates and grouped columns that aren't selected are added to `columns`.
+    fn resolve_having(
+        &self,
+        having: &mut Expr,
+        schema: &TableSchema,
+        columns: &mut Vec<(String, Expr)>,
+        function_registry: &FunctionRegistry,
+    ) -> Result<(), WeaverError> {
+        HavingResolver {
+            schema,
+            function_registry,
+            columns,
+        }
+        .visit_expr_mut(having)
+    }
+
     /// checks if a given expressions is functionally dependent on another.
     /// This should mean that all references to columns not part of the `sources` parameter
     /// are within an aggregating function.
@@ -614,65 +1838,6 @@ impl QueryPlanFactory {
         dependent: &Expr,
         function_registry: &FunctionRegistry,
     ) -> Result<bool, WeaverError> {
-        fn is_functionally_dependent_helper(
-            schema: &TableSchema,
-            source_columns: &[ResolvedColumnRef],
-            dependent: &Expr,
-            function_registry: &FunctionRegistry,
-        ) -> Result<bool, WeaverError> {
-            Ok(match dependent {
-                Expr::Column { column } => source_columns
-                    .contains(column.resolved().expect("all columns must be resolved")),
-                Expr::Literal { .. } => true,
-                Expr::BindParameter { .. } => {
-                    panic!("bind parameter in invalid locaction")
-                }
-                Expr::Unary { expr, .. } => is_functionally_dependent_helper(
-                    schema,
-                    source_columns,
-                    expr,
-                    function_registry,
-                )?,
-                Expr::Binary { left, right, .. } => {
-                    is_functionally_dependent_helper(
-                        schema,
-                        source_columns,
-                        left,
-                        function_registry,
-                    )? && is_functionally_dependent_helper(
-                        schema,
-                        source_columns,
-                        right,
-                        function_registry,
-                    )?
-                }
-                Expr::FunctionCall { function, args } => {
-                    let FunctionKind { normal, aggregate } =
-                        find_function(function_registry, function, args, schema)?;
-
-                    debug!("normal: {normal:?}, aggregate: {aggregate:?}");
-                    match (normal, aggregate) {
-                        (_, Some(_)) => true,
-                        (Some(_), None) => match args {
-                            FunctionArgs::Params { exprs, .. } => {
-                                exprs.iter().try_fold(true, |state, expr| {
-                                    is_functionally_dependent_helper(
-                                        schema,
-                                        source_columns,
-                                        expr,
-                                        function_registry,
-                                    )
-                                    .map(|output| output && state)
-                                })?
-                            }
-                            FunctionArgs::Wildcard { .. } => false,
-                        },
-                        (None, None) => false,
-                    }
-                }
-            })
-        }
-
         let source_columns = sources
             .iter()
             .flat_map(|e| e.columns().into_iter().flat_map(|i| i.resolved().cloned()))
@@ -681,12 +1846,14 @@ impl QueryPlanFactory {
             "seeing if {:?} is functionally dependent on {:?}",
             dependent, source_columns
         );
-        is_functionally_dependent_helper(
+        let mut checker = FunctionalDependencyChecker {
             schema,
-            source_columns.as_slice(),
-            dependent,
             function_registry,
-        )
+            source_columns: &source_columns,
+            dependent: true,
+        };
+        checker.visit_expr_mut(&mut dependent.clone())?;
+        Ok(checker.dependent)
     }
 
     fn join_to_plan_node(
@@ -734,17 +1901,27 @@ impl QueryPlanFactory {
                 JoinOperator::Inner => left.rows.max(right.rows),
                 JoinOperator::Cross => left.rows * right.rows,
                 JoinOperator::Outer => left.rows + right.rows,
+                JoinOperator::Semi | JoinOperator::Anti | JoinOperator::NullAwareAnti => left.rows,
             };
             strategy.join_node(rows, left, right, join_clause)
         })
     }
 
     /// When given some conditional expression `cond` and a known `key`, we can get key indices to query against the table
-    /// in a more efficient manner than just doing an `all` search.
+    /// in a more efficient manner than just doing an `all` search. Each returned key index
+    /// contains every row that may satisfy the condition.
     ///
     /// This requires the `cond` condition follows certain patterns:
     /// - `{column} = literal` (and reverse)
-    /// - `{column} < literal`
+    /// - `{column} < literal`, `{column} <= literal`, `{column} > literal`, `{column} >= literal` (and reverse)
+    /// - `{column} != literal` (and reverse)
+    /// - `{column} like 'prefix%'`
+    /// - `{column} between literal and literal`
+    /// - `{column} in (literal, ...)`, with at most [`MAX_IN_LIST_LOOKUPS`] literals
+    /// - conjunctions and disjunctions of the above
+    ///
+    /// Json paths like `{column} ->> 'key'` can be used in place of a column when the key was
+    /// created on them.
     pub fn to_key_index(
         &self,
         key: &Key,
@@ -763,46 +1940,96 @@ impl QueryPlanFactory {
                     | BinaryOp::Less
                     | BinaryOp::GreaterEq
                     | BinaryOp::LessEq => {
-                        let (col, const_v) = if left.is_const() && !right.is_const() {
-                            if let Expr::Column { column } = &**right {
-                                (column, left)
-                            } else {
-                                return Ok(vec![]);
-                            }
+                        let (operand, const_v, op) = if left.is_const() && !right.is_const() {
+                            // flip the operator so the column is on the left
+                            let flipped = match op {
+                                BinaryOp::Greater => BinaryOp::Less,
+                                BinaryOp::Less => BinaryOp::Greater,
+                                BinaryOp::GreaterEq => BinaryOp::LessEq,
+                                BinaryOp::LessEq => BinaryOp::GreaterEq,
+                                other => other.clone(),
+                            };
+                            (right, left, flipped)
                         } else if right.is_const() && !left.is_const() {
-                            if let Expr::Column { column } = &**left {
-                                (column, right)
-                            } else {
-                                return Ok(vec![]);
-                            }
+                            (left, right, op.clone())
                         } else {
                             return Ok(vec![]);
                         };
 
-                        let col = match col {
-                            ColumnRef::Unresolved(col) => {
-                                self.resolve_column_ref(col, involved_tables, ctx)?
-                            }
-                            ColumnRef::Resolved(resolved) => resolved.clone(),
+                        // constants that couldn't be reduced, such as `1/0`, are left for the
+                        // evaluator to report
+                        let Some(value) = const_v.literal() else {
+                            return Ok(vec![]);
                         };
 
-                        let Expr::Literal { literal: value } = &**const_v else {
-                            panic!("expr is constant but not a literal: {left:?}")
+                        if self.is_key_operand(key, operand, involved_tables, ctx)? {
+                            let key_data = KeyData::from([value.clone()]);
+                            let kind = match op {
+                                BinaryOp::Eq => KeyIndexKind::One(key_data),
+                                BinaryOp::Neq => KeyIndexKind::Ranges(vec![
+                                    KeyDataRange(
+                                        Bound::Unbounded,
+                                        Bound::Excluded(key_data.clone()),
+                                    ),
+                                    KeyDataRange(Bound::Excluded(key_data), Bound::Unbounded),
+                                ]),
+                                BinaryOp::Less => KeyIndexKind::Range {
+                                    low: Bound::Unbounded,
+                                    high: Bound::Excluded(key_data),
+                                },
+                                BinaryOp::LessEq => KeyIndexKind::Range {
+                                    low: Bound::Unbounded,
+                                    high: Bound::Included(key_data),
+                                },
+                                BinaryOp::Greater => KeyIndexKind::Range {
+                                    low: Bound::Excluded(key_data),
+                                    high: Bound::Unbounded,
+                                },
+                                BinaryOp::GreaterEq => KeyIndexKind::Range {
+                                    low: Bound::Included(key_data),
+                                    high: Bound::Unbounded,
+                                },
+                                _ => unreachable!(),
+                            };
+                            return Ok(vec![KeyIndex::new(key.name(), kind, None, None)]);
+                        }
+                        Ok(vec![])
+                    }
+                    BinaryOp::Like { escape } => {
+                        let Expr::Literal {
+                            literal: Literal::String(pattern),
+                        } = &**right
+                        else {
+                            return Ok(vec![]);
                         };
+                        let prefix = Collation::like_prefix(pattern, escape.unwrap_or('\\'));
+                        if prefix.is_empty() {
+                            return Ok(vec![]);
+                        }
 
-                        if key.columns().len() == 1
-                            && key.columns().contains(&col.column().to_string())
-                        {
-                            return Ok(vec![KeyIndex::new(
-                                key.name(),
-                                KeyIndexKind::One(KeyData::from([value.clone()])),
-                                None,
-                                None,
-                            )]);
+                        if self.is_key_operand(key, left, involved_tables, ctx)? {
+                            // keys are ordered by their chars, so every match sorts between the
+                            // lowest way of writing the prefix in the column's collation and the
+                            // highest with its last char incremented
+                            let collation = self.operand_collation(left, involved_tables, ctx)?;
+                            let (low, mut high) = collation.char_order_bounds(&prefix);
+                            let last = high.pop().expect("prefix isn't empty");
+                            let high = match char::from_u32(last as u32 + 1) {
+                                Some(next) => {
+                                    high.push(next);
+                                    Bound::Excluded(KeyData::from([Literal::from(high)]))
+                                }
+                                None => Bound::Unbounded,
+                            };
+                            let kind = KeyIndexKind::Range {
+                                low: Bound::Included(KeyData::from([Literal::from(low)])),
+                                high,
+                            };
+                            return Ok(vec![KeyIndex::new(key.name(), kind, None, None)]);
                         }
                         Ok(vec![])
                     }
-                    BinaryOp::And | BinaryOp::Or => self
+                    BinaryOp::And => self
                         .to_key_index(key, Some(left), involved_tables, ctx)
                         .and_then(|mut left| {
                             self.to_key_index(key, Some(right), involved_tables, ctx)
@@ -811,13 +2038,123 @@ impl QueryPlanFactory {
                                     left
                                 })
                         }),
+                    BinaryOp::Or => {
+                        // both sides must be narrowed by this key, and each pairing of their
+                        // indices covers every row matching either side
+                        let left = self.to_key_index(key, Some(left), involved_tables, ctx)?;
+                        let right = self.to_key_index(key, Some(right), involved_tables, ctx)?;
+                        Ok(left
+                            .iter()
+                            .flat_map(|left| {
+                                right.iter().map(|right| {
+                                    KeyIndex::new(
+                                        key.name(),
+                                        left.kind().union(right.kind()),
+                                        None,
+                                        None,
+                                    )
+                                })
+                            })
+                            .collect())
+                    }
                     _ => Ok(vec![]),
                 },
+                Expr::Between {
+                    expr,
+                    low,
+                    high,
+                    negated: false,
+                } => {
+                    let (Some(low), Some(high)) = (low.literal(), high.literal()) else {
+                        return Ok(vec![]);
+                    };
+                    if self.is_key_operand(key, expr, involved_tables, ctx)? {
+                        let kind = KeyIndexKind::Range {
+                            low: Bound::Included(KeyData::from([low.clone()])),
+                            high: Bound::Included(KeyData::from([high.clone()])),
+                        };
+                        return Ok(vec![KeyIndex::new(key.name(), kind, None, None)]);
+                    }
+                    Ok(vec![])
+                }
+                Expr::InList {
+                    expr,
+                    list,
+                    negated: false,
+                } if list.len() <= MAX_IN_LIST_LOOKUPS => {
+                    let Some(values) = list.iter().map(Expr::literal).collect::<Option<Vec<_>>>()
+                    else {
+                        return Ok(vec![]);
+                    };
+                    if self.is_key_operand(key, expr, involved_tables, ctx)? {
+                        let mut key_data = values
+                            .into_iter()
+                            .map(|value| KeyData::from([value.clone()]))
+                            .collect::<Vec<_>>();
+                        key_data.sort();
+                        key_data.dedup();
+                        let kind = KeyIndexKind::Many(key_data);
+                        return Ok(vec![KeyIndex::new(key.name(), kind, None, None)]);
+                    }
+                    Ok(vec![])
+                }
                 _ => Ok(vec![]),
             },
         }
     }
 
+    /// Checks if a key is made of only the given column, or of only the generated column holding the
+    /// values of the given expression
+    fn is_key_operand(
+        &self,
+        key: &Key,
+        operand: &Expr,
+        involved_tables: &HashMap<TableRef, TableSchema>,
+        ctx: Option<&WeaverProcessInfo>,
+    ) -> Result<bool, WeaverError> {
+        match operand {
+            Expr::Column { column } => self.is_key_column(key, column, involved_tables, ctx),
+            expr => Ok(json_path_column(expr).is_some_and(|col| key.columns() == [col])),
+        }
+    }
+
+    /// Gets the collation of the column an operand reads, or the default collation if it doesn't
+    /// read one
+    fn operand_collation(
+        &self,
+        operand: &Expr,
+        involved_tables: &HashMap<TableRef, TableSchema>,
+        ctx: Option<&WeaverProcessInfo>,
+    ) -> Result<&'static Collation, WeaverError> {
+        let Expr::Column { column } = operand else {
+            return Ok(Collation::default_collation());
+        };
+        let col = match column {
+            ColumnRef::Unresolved(col) => self.resolve_column_ref(col, involved_tables, ctx)?,
+            ColumnRef::Resolved(resolved) => resolved.clone(),
+        };
+        Ok(involved_tables
+            .values()
+            .find_map(|schema| schema.column_by_source(&col))
+            .map(|column| column.collation())
+            .unwrap_or_else(Collation::default_collation))
+    }
+
+    /// Checks if a key is made of only the given column
+    fn is_key_column(
+        &self,
+        key: &Key,
+        column: &ColumnRef,
+        involved_tables: &HashMap<TableRef, TableSchema>,
+        ctx: Option<&WeaverProcessInfo>,
+    ) -> Result<bool, WeaverError> {
+        let col = match column {
+            ColumnRef::Unresolved(col) => self.resolve_column_ref(col, involved_tables, ctx)?,
+            ColumnRef::Resolved(resolved) => resolved.clone(),
+        };
+        Ok(key.columns().len() == 1 && key.columns().contains(&col.column().to_string()))
+    }
+
     /// Convert to a table reference
     pub fn table_ref(
         &self,
@@ -855,10 +2192,10 @@ impl QueryPlanFactory {
         }
     }
 
-    pub fn resolve_column_ref(
+    pub fn resolve_column_ref<'t>(
         &self,
         column_ref: &UnresolvedColumnRef,
-        involved_tables: &HashMap<TableRef, TableSchema>,
+        involved_tables: impl IntoIterator<Item = (&'t TableRef, &'t TableSchema)>,
         _ctx: Option<&WeaverProcessInfo>,
     ) -> Result<ResolvedColumnRef, WeaverError> {
         match (column_ref.table(), column_ref.column()) {
@@ -935,6 +2272,106 @@ pub fn column_reference(table: &Table, column: &String) -> (String, String, Stri
     )
 }
 
+/// Splits a condition into the expressions that must all be true for it to be true
+fn conjuncts(condition: &Expr) -> Vec<&Expr> {
+    match condition {
+        Expr::Binary {
+            left,
+            op: BinaryOp::And,
+            right,
+        } => {
+            let mut ret = conjuncts(left);
+            ret.extend(conjuncts(right));
+            ret
+        }
+        other => vec![other],
+    }
+}
+
+/// Joins expressions into a single condition that's true when all of them are
+fn conjunction(exprs: Vec<Expr>) -> Option<Expr> {
+    exprs.into_iter().reduce(|left, right| Expr::Binary {
+        left: Box::new(left),
+        op: BinaryOp::And,
+        right: Box::new(right),
+    })
+}
+
+/// Checks if every column used by an expression belongs to one of the given tables
+fn is_within_tables(expr: &Expr, tables: &[TableRef]) -> bool {
+    expr.columns().iter().all(|column| {
+        column.resolved().is_some_and(|column| {
+            tables.iter().any(|(schema, table)| {
+                column.schema().as_ref() == schema && column.table().as_ref() == table
+            })
+        })
+    })
+}
+
+/// Refers to a column of the rows created by a group by
+fn grouped_column(name: &str) -> Expr {
+    Expr::Column {
+        column: ColumnRef::Resolved(ResolvedColumnRef::new(
+            Identifier::new("<query>"),
+            Identifier::new("<grouped_by>"),
+            Identifier::new(name),
+        )),
+    }
+}
+
+/// Refers to the column holding the value of an applied subquery
+fn sub_query_column(idx: usize) -> ResolvedColumnRef {
+    ResolvedColumnRef::new(
+        Identifier::new("<query>"),
+        Identifier::new("<sub_query>"),
+        Identifier::new(idx.to_string()),
+    )
+}
+
+fn window_function_column(idx: usize) -> ResolvedColumnRef {
+    ResolvedColumnRef::new(
+        Identifier::new("<query>"),
+        Identifier::new("<window>"),
+        Identifier::new(idx.to_string()),
+    )
+}
+
+/// Checks if an expression uses a window function, outside of any subqueries
+fn has_window_function(expr: &Expr) -> bool {
+    let mut finder = WindowFunctionFinder { found: false };
+    finder
+        .visit_expr_mut(&mut expr.clone())
+        .unwrap_or_else(|never| match never {});
+    finder.found
+}
+
+/// Collects the tables of a from clause, not including the tables of its subqueries
+fn from_clause_tables(
+    from: &TableOrSubQuery,
+    in_use_schema: Option<&Identifier>,
+    tables: &mut Vec<TableRef>,
+) {
+    match from {
+        TableOrSubQuery::Table {
+            schema, table_name, ..
+        } => {
+            if let Some(schema) = schema.as_ref().or(in_use_schema) {
+                tables.push((schema.to_string(), table_name.to_string()));
+            }
+        }
+        TableOrSubQuery::Select { .. } => {}
+        TableOrSubQuery::Multiple(many) => {
+            for table_or_sub_query in many {
+                from_clause_tables(table_or_sub_query, in_use_schema, tables);
+            }
+        }
+        TableOrSubQuery::JoinClause(JoinClause { left, right, .. }) => {
+            from_clause_tables(left, in_use_schema, tables);
+            from_clause_tables(right, in_use_schema, tables);
+        }
+    }
+}
+
 pub fn to_col_ref(
     input: &(Option<String>, Option<String>, String),
 ) -> (Option<&str>, Option<&str>, &str) {
@@ -942,12 +2379,17 @@ pub fn to_col_ref(
     (schema.as_deref(), table.as_deref(), column.as_str())
 }
 
+/// Resolves the columns of a query. Columns are searched for in the tables of the select using
+/// them first, then in those of the selects containing it, and finally in every table used by the
+/// query. The resolver is given the tables to search, or `None` for every table.
 struct IdentifierResolver<'a, F>
 where
-    F: Fn(&UnresolvedColumnRef) -> Result<ResolvedColumnRef, WeaverError> + 'a,
+    F: Fn(&UnresolvedColumnRef, Option<&[TableRef]>) -> Result<ResolvedColumnRef, WeaverError> + 'a,
 {
-    _in_use_schema: Option<Identifier>,
+    in_use_schema: Option<Identifier>,
     select_level: usize,
+    /// The tables of each select being visited, from the outermost
+    scopes: Vec<Vec<TableRef>>,
     column_aliases: BTreeMap<usize, Vec<Identifier>>,
     aliases: HashMap<Identifier, (Identifier, Identifier)>,
     resolver: F,
@@ -956,12 +2398,13 @@ where
 
 impl<'a, F> IdentifierResolver<'a, F>
 where
-    F: Fn(&UnresolvedColumnRef) -> Result<ResolvedColumnRef, WeaverError> + 'a,
+    F: Fn(&UnresolvedColumnRef, Option<&[TableRef]>) -> Result<ResolvedColumnRef, WeaverError> + 'a,
 {
     fn new(in_use_schema: Option<Identifier>, resolver: F) -> Self {
         Self {
-            _in_use_schema: in_use_schema,
+            in_use_schema,
             select_level: 0,
+            scopes: vec![],
             column_aliases: Default::default(),
             aliases: Default::default(),
             resolver,
@@ -972,7 +2415,7 @@ where
 
 impl<'a, F> VisitorMut for IdentifierResolver<'a, F>
 where
-    F: Fn(&UnresolvedColumnRef) -> Result<ResolvedColumnRef, WeaverError> + 'a,
+    F: Fn(&UnresolvedColumnRef, Option<&[TableRef]>) -> Result<ResolvedColumnRef, WeaverError> + 'a,
 {
     type Err = WeaverError;
 
@@ -1007,7 +2450,21 @@ where
                     }
                 }
 
-                let resolved = (self.resolver)(unresolved)?;
+                let mut resolved = None;
+                for scope in self.scopes.iter().rev() {
+                    match (self.resolver)(unresolved, Some(scope)) {
+                        Ok(found) => {
+                            resolved = Some(found);
+                            break;
+                        }
+                        Err(WeaverError::ColumnNotFound(_)) => {}
+                        Err(e) => return Err(e),
+                    }
+                }
+                let resolved = match resolved {
+                    Some(resolved) => resolved,
+                    None => (self.resolver)(unresolved, None)?,
+                };
                 debug!("resolved = {resolved}");
                 *column = ColumnRef::Resolved(resolved);
             }
@@ -1065,9 +2522,305 @@ where
     }
 
     fn visit_select_mut(&mut self, select: &mut Select) -> Result<(), Self::Err> {
+        let mut tables = vec![];
+        if let Some(FromClause(from)) = &select.from {
+            from_clause_tables(from, self.in_use_schema.as_ref(), &mut tables);
+        }
+        self.scopes.push(tables);
         self.select_level += 1;
         let ret = visit_select_mut(self, select);
         self.select_level -= 1;
+        self.scopes.pop();
         ret
     }
+
+    /// Columns used by check constraints belong to the table being created, so they are resolved
+    /// when its schema is built instead
+    fn visit_create_table_mut(&mut self, _create_table: &mut CreateTable) -> Result<(), Self::Err> {
+        Ok(())
+    }
+
+    /// The same goes for the columns used by the expressions of an index
+    fn visit_create_mut(&mut self, create: &mut Create) -> Result<(), Self::Err> {
+        match create {
+            Create::Index(_) => Ok(()),
+            create => visit_create_mut(self, create),
+        }
+    }
+}
+
+/// Marks the tables of a query that are common table expressions by giving them the `<cte>`
+/// schema. Only unqualified tables can be common table expressions.
+struct CommonTableExprMarker<'a> {
+    names: &'a [Identifier],
+}
+
+impl VisitorMut for CommonTableExprMarker<'_> {
+    type Err = WeaverError;
+
+    fn visit_table_or_sub_query_mut(
+        &mut self,
+        table_or_sub_query: &mut TableOrSubQuery,
+    ) -> Result<(), Self::Err> {
+        if let TableOrSubQuery::Table {
+            schema: schema @ None,
+            table_name,
+            ..
+        } = table_or_sub_query
+        {
+            if self.names.contains(table_name) {
+                *schema = Some(Identifier::new("<cte>"));
+            }
+        }
+        visit_table_or_sub_query_mut(self, table_or_sub_query)
+    }
+
+    fn visit_select_mut(&mut self, select: &mut Select) -> Result<(), Self::Err> {
+        if let Some(with) = &select.with {
+            return Err(WeaverError::UnsupportedCommonTableExpr(
+                with.tables
+                    .iter()
+                    .map(|cte| cte.name.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                "only supported at the start of a query".to_string(),
+            ));
+        }
+        visit_select_mut(self, select)
+    }
+}
+
+/// Replaces scalar subqueries with references to the columns that will hold their values,
+/// collecting each distinct subquery
+struct SubQueryReplacer {
+    offset: usize,
+    found: Vec<Expr>,
+}
+
+impl VisitorMut for SubQueryReplacer {
+    type Err = WeaverError;
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> Result<(), Self::Err> {
+        match expr {
+            Expr::SubQuery { .. } | Expr::InSubQuery { .. } | Expr::Exists { .. } => {
+                let idx = match self.found.iter().position(|found| found == expr) {
+                    Some(idx) => idx,
+                    None => {
+                        self.found.push(expr.clone());
+                        self.found.len() - 1
+                    }
+                };
+                *expr = Expr::Column {
+                    column: sub_query_column(self.offset + idx).into(),
+                };
+                Ok(())
+            }
+            _ => visit_expr_mut(self, expr),
+        }
+    }
+}
+
+/// Replaces window functions with references to the columns that will hold their values,
+/// collecting each distinct window function
+struct WindowFunctionReplacer {
+    found: Vec<Expr>,
+}
+
+impl VisitorMut for WindowFunctionReplacer {
+    type Err = WeaverError;
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> Result<(), Self::Err> {
+        match expr {
+            Expr::WindowFunction { args, window, .. } => {
+                let mut nested = WindowFunctionReplacer { found: vec![] };
+                nested.visit_function_args_mut(args)?;
+                nested.visit_window_mut(window)?;
+                if !nested.found.is_empty() {
+                    return Err(WeaverError::UnsupportedWindowFunction(
+                        expr.clone(),
+                        "can not contain another window function".to_string(),
+                    ));
+                }
+                let idx = match self.found.iter().position(|found| found == expr) {
+                    Some(idx) => idx,
+                    None => {
+                        self.found.push(expr.clone());
+                        self.found.len() - 1
+                    }
+                };
+                *expr = Expr::Column {
+                    column: window_function_column(idx).into(),
+                };
+                Ok(())
+            }
+            _ => visit_expr_mut(self, expr),
+        }
+    }
+}
+
+/// Collects every resolved column used within a query, including its subqueries
+#[derive(Default)]
+struct ColumnCollector {
+    columns: Vec<ResolvedColumnRef>,
+}
+
+impl VisitorMut for ColumnCollector {
+    type Err = Infallible;
+
+    fn visit_column_ref_mut(&mut self, column: &mut ColumnRef) -> Result<(), Self::Err> {
+        if let Some(resolved) = column.resolved() {
+            self.columns.push(resolved.clone());
+        }
+        Ok(())
+    }
+}
+
+/// Looks for aggregate function calls in an expression, outside of any subqueries
+struct AggregateFinder<'a> {
+    schema: &'a TableSchema,
+    function_registry: &'a FunctionRegistry,
+    found: bool,
+}
+
+impl VisitorMut for AggregateFinder<'_> {
+    type Err = WeaverError;
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> Result<(), Self::Err> {
+        if let Expr::FunctionCall { function, args } = expr {
+            let FunctionKind { aggregate, .. } =
+                find_function(self.function_registry, function, args, self.schema)?;
+            self.found |= aggregate.is_some();
+        }
+        visit_expr_mut(self, expr)
+    }
+
+    fn visit_select_mut(&mut self, _select: &mut Select) -> Result<(), Self::Err> {
+        Ok(())
+    }
+}
+
+/// Looks for window functions in an expression, outside of any subqueries
+struct WindowFunctionFinder {
+    found: bool,
+}
+
+impl VisitorMut for WindowFunctionFinder {
+    type Err = Infallible;
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> Result<(), Self::Err> {
+        if let Expr::WindowFunction { .. } = expr {
+            self.found = true;
+            return Ok(());
+        }
+        visit_expr_mut(self, expr)
+    }
+
+    fn visit_select_mut(&mut self, _select: &mut Select) -> Result<(), Self::Err> {
+        Ok(())
+    }
+}
+
+/// Checks if an expression only uses the source columns outside of aggregates, so it has a single
+/// value for each group
+struct FunctionalDependencyChecker<'a> {
+    schema: &'a TableSchema,
+    function_registry: &'a FunctionRegistry,
+    source_columns: &'a [ResolvedColumnRef],
+    dependent: bool,
+}
+
+impl VisitorMut for FunctionalDependencyChecker<'_> {
+    type Err = WeaverError;
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> Result<(), Self::Err> {
+        match expr {
+            Expr::Column { column } => {
+                self.dependent &= self
+                    .source_columns
+                    .contains(column.resolved().expect("all columns must be resolved"));
+                Ok(())
+            }
+            Expr::BindParameter { .. } => {
+                panic!("bind parameter in invalid locaction")
+            }
+            Expr::FunctionCall { function, args } => {
+                let FunctionKind { normal, aggregate } =
+                    find_function(self.function_registry, function, args, self.schema)?;
+
+                debug!("normal: {normal:?}, aggregate: {aggregate:?}");
+                match (normal, aggregate) {
+                    (_, Some(_)) => Ok(()),
+                    (Some(_), None) if matches!(args, FunctionArgs::Params { .. }) => {
+                        visit_expr_mut(self, expr)
+                    }
+                    _ => {
+                        self.dependent = false;
+                        Ok(())
+                    }
+                }
+            }
+            Expr::SubQuery { .. } | Expr::InSubQuery { .. } | Expr::Exists { .. } => {
+                Err(WeaverError::UnsupportedSubQuery(
+                    expr.clone(),
+                    "not supported in grouped queries".to_string(),
+                ))
+            }
+            Expr::WindowFunction { .. } => Err(WeaverError::UnsupportedWindowFunction(
+                expr.clone(),
+                "not supported in grouped queries".to_string(),
+            )),
+            _ => visit_expr_mut(self, expr),
+        }
+    }
+}
+
+/// Replaces every aggregate within a having clause by a reference to the grouped column computing
+/// it, adding the aggregates and grouped columns that aren't selected to `columns`
+struct HavingResolver<'a> {
+    schema: &'a TableSchema,
+    function_registry: &'a FunctionRegistry,
+    columns: &'a mut Vec<(String, Expr)>,
+}
+
+impl VisitorMut for HavingResolver<'_> {
+    type Err = WeaverError;
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> Result<(), Self::Err> {
+        if let Some((name, _)) = self.columns.iter().find(|(_, column)| column == expr) {
+            if !matches!(expr, Expr::Column { .. }) {
+                *expr = grouped_column(name);
+            }
+            return Ok(());
+        }
+
+        match expr {
+            Expr::Column { .. } => {
+                self.columns.push((expr.to_string(), expr.clone()));
+                Ok(())
+            }
+            Expr::FunctionCall { function, args } => {
+                let FunctionKind { aggregate, .. } =
+                    find_function(self.function_registry, function, args, self.schema)?;
+                if aggregate.is_some() {
+                    let name = expr.to_string();
+                    self.columns.push((name.clone(), expr.clone()));
+                    *expr = grouped_column(&name);
+                    Ok(())
+                } else {
+                    visit_expr_mut(self, expr)
+                }
+            }
+            Expr::SubQuery { .. } | Expr::InSubQuery { .. } | Expr::Exists { .. } => {
+                Err(WeaverError::UnsupportedSubQuery(
+                    expr.clone(),
+                    "not supported in grouped queries".to_string(),
+                ))
+            }
+            Expr::WindowFunction { .. } => Err(WeaverError::UnsupportedWindowFunction(
+                expr.clone(),
+                "not supported in grouped queries".to_string(),
+            )),
+            _ => visit_expr_mut(self, expr),
+        }
+    }
 }
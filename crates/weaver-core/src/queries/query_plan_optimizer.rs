@@ -1,8 +1,9 @@
 //! Query plan optimization
 
 use std::cell::RefCell;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
+use std::ops::Bound;
 
 use static_assertions::assert_obj_safe;
 use tracing::{debug, debug_span, trace};
@@ -16,7 +17,9 @@ use crate::dynamic_table::{DynamicTable, HasSchema};
 use crate::error::WeaverError;
 use crate::queries::query_cost::CostTable;
 use crate::queries::query_plan::{QueryPlan, QueryPlanKind, QueryPlanNode};
-use crate::storage::tables::table_schema::TableSchema;
+use crate::queries::query_plan_factory::QueryPlanFactory;
+use crate::rows::{KeyIndex, KeyIndexKind};
+use crate::storage::tables::table_schema::{json_path_column, Key, TableSchema};
 use crate::tx::Tx;
 
 /// An optimizer
@@ -68,7 +71,9 @@ impl QueryPlanOptimizer {
         // sigma cascade to seperate all binops
         sigma_cascade(query.root_mut())?;
         // push down expressions
-        push_down_filters(query, &socket)?;
+        push_down_filters(query, &socket, &QueryPlanFactory::new(self.db.clone()))?;
+        // deduplicate already sorted rows by comparing neighbours
+        sort_before_distinct(query.root_mut())?;
 
         let new_cost = query.root().cost();
         debug!("optimization changed cost from {initial_cost} to {new_cost}");
@@ -122,8 +127,55 @@ fn sigma_cascade(query: &mut QueryPlanNode) -> Result<(), WeaverError> {
     Ok(())
 }
 
+/// Orders rows before removing duplicates instead of after when they are ordered by every column,
+/// as duplicates are then next to each other and only need to be compared with the previous row
+fn sort_before_distinct(query: &mut QueryPlanNode) -> Result<(), WeaverError> {
+    query
+        .children_mut()
+        .into_iter()
+        .try_for_each(sort_before_distinct)?;
+
+    let QueryPlanKind::OrderedBy { ordered, order } = &query.kind else {
+        return Ok(());
+    };
+    let QueryPlanKind::Distinct {
+        deduplicated,
+        sorted: false,
+    } = &ordered.kind
+    else {
+        return Ok(());
+    };
+    let schema = ordered.schema();
+    let all_ordered = (0..schema.columns().len()).all(|idx| {
+        order.iter().any(|(expr, _)| match expr {
+            Expr::Column {
+                column: ColumnRef::Resolved(resolved),
+            } => schema.column_index_by_source(resolved) == Some(idx),
+            _ => false,
+        })
+    });
+    if !all_ordered {
+        return Ok(());
+    }
+
+    let mut distinct = *ordered.clone();
+    let mut ordered = query.clone();
+    ordered.rows = deduplicated.rows;
+    *ordered.children_mut()[0] = *deduplicated.clone();
+    distinct.kind = QueryPlanKind::Distinct {
+        deduplicated: Box::new(ordered),
+        sorted: true,
+    };
+    *query = distinct;
+    Ok(())
+}
+
 /// tries to push down filters as far down as possible
-fn push_down_filters(query: &mut QueryPlan, socket: &DbSocket) -> Result<(), WeaverError> {
+fn push_down_filters(
+    query: &mut QueryPlan,
+    socket: &DbSocket,
+    factory: &QueryPlanFactory,
+) -> Result<(), WeaverError> {
     let mut visited = HashSet::<Uuid>::new();
 
     while let Some(node_id) = query
@@ -136,14 +188,18 @@ fn push_down_filters(query: &mut QueryPlan, socket: &DbSocket) -> Result<(), Wea
         .map(|node| node.id())
     {
         let plan_node = query.get_mut(&node_id).unwrap();
-        push_down_filter(plan_node, socket)?;
+        push_down_filter(plan_node, socket, factory)?;
         visited.insert(node_id);
     }
 
     Ok(())
 }
 
-fn push_down_filter(parent: &mut QueryPlanNode, socket: &DbSocket) -> Result<(), WeaverError> {
+fn push_down_filter(
+    parent: &mut QueryPlanNode,
+    socket: &DbSocket,
+    factory: &QueryPlanFactory,
+) -> Result<(), WeaverError> {
     let QueryPlanKind::Filter {
         filtered: child,
         condition,
@@ -162,7 +218,7 @@ fn push_down_filter(parent: &mut QueryPlanNode, socket: &DbSocket) -> Result<(),
             let grandchild = *grandchild.clone();
             *parent.children_mut()[0] = grandchild;
             *child.children_mut()[0] = parent;
-            push_down_filter(child.children_mut()[0], socket)?;
+            push_down_filter(child.children_mut()[0], socket, factory)?;
             Some(child)
         }
         QueryPlanKind::Project {
@@ -172,13 +228,33 @@ fn push_down_filter(parent: &mut QueryPlanNode, socket: &DbSocket) -> Result<(),
             if columns
                 .iter()
                 .all(|expr| expr_exclusively_in_schema(expr, grandchild.schema()))
+                && expr_exclusively_in_schema(condition, grandchild.schema())
             {
                 let mut parent = parent.clone();
                 let mut child = *child.clone();
                 let grandchild = *grandchild.clone();
                 *parent.children_mut()[0] = grandchild;
                 *child.children_mut()[0] = parent;
-                push_down_filter(child.children_mut()[0], socket)?;
+                push_down_filter(child.children_mut()[0], socket, factory)?;
+                Some(child)
+            } else {
+                None
+            }
+        }
+        QueryPlanKind::Apply {
+            applied: grandchild,
+            ..
+        } => {
+            if expr_exclusively_in_schema(condition, grandchild.schema()) {
+                let mut parent = parent.clone();
+                let mut child = *child.clone();
+                let grandchild = *grandchild.clone();
+                parent.rows = grandchild.rows;
+                parent.schema = grandchild.schema.clone();
+                *parent.children_mut()[0] = grandchild;
+                child.rows = parent.rows;
+                *child.children_mut()[0] = parent;
+                push_down_filter(child.children_mut()[0], socket, factory)?;
                 Some(child)
             } else {
                 None
@@ -198,7 +274,7 @@ fn push_down_filter(parent: &mut QueryPlanNode, socket: &DbSocket) -> Result<(),
 
                 child.rows = parent.rows;
                 *child.children_mut()[0] = parent;
-                push_down_filter(child.children_mut()[0], socket)?;
+                push_down_filter(child.children_mut()[0], socket, factory)?;
                 Some(child)
             } else if expr_exclusively_in_schema(condition, right.schema()) {
                 let mut parent = parent.clone();
@@ -210,7 +286,7 @@ fn push_down_filter(parent: &mut QueryPlanNode, socket: &DbSocket) -> Result<(),
                 child.rows = parent.rows;
                 *child.children_mut()[1] = parent;
 
-                push_down_filter(child.children_mut()[1], socket)?;
+                push_down_filter(child.children_mut()[1], socket, factory)?;
                 Some(child)
             } else {
                 None
@@ -222,6 +298,7 @@ fn push_down_filter(parent: &mut QueryPlanNode, socket: &DbSocket) -> Result<(),
             keys: Option::None,
         } => {
             let child_schema = child.schema();
+            let filter_condition = condition;
             let condition: HashSet<ResolvedColumnRef> = condition
                 .columns()
                 .iter()
@@ -232,7 +309,18 @@ fn push_down_filter(parent: &mut QueryPlanNode, socket: &DbSocket) -> Result<(),
                 })
                 .collect();
             trace!("columns used in condition: {condition:?}");
+            // json paths are matched by the generated columns holding their values
+            let generated: HashSet<String> = filter_condition
+                .postfix()
+                .into_iter()
+                .filter_map(json_path_column)
+                .collect();
 
+            // the scanned table is the only one the condition can involve
+            let involved_tables = HashMap::from([(
+                (schema.to_string(), table.to_string()),
+                child_schema.clone(),
+            )]);
             let mut applicable_keys = child_schema
                 .keys()
                 .iter()
@@ -243,19 +331,36 @@ fn push_down_filter(parent: &mut QueryPlanNode, socket: &DbSocket) -> Result<(),
                         condition
                     );
                     key.columns().iter().all(|column| {
-                        let contains = condition.contains(&ResolvedColumnRef::new(
-                            Identifier::new(child_schema.schema()),
-                            Identifier::new(child_schema.name()),
-                            Identifier::new(column),
-                        ));
+                        let contains = generated.contains(column)
+                            || condition.contains(&ResolvedColumnRef::new(
+                                Identifier::new(child_schema.schema()),
+                                Identifier::new(child_schema.name()),
+                                Identifier::new(column),
+                            ));
                         trace!("does {condition:?} contain {column:?}? -> {contains}");
                         contains
                     })
                 })
                 .map(|key| {
-                    // key to key_index
-                    key.all()
+                    // narrow the key using the condition when possible
+                    factory
+                        .to_key_index(key, Some(filter_condition), &involved_tables, None)
+                        .map(|key_indices| {
+                            let mut key_indices = key_indices
+                                .into_iter()
+                                .filter(|key_index| {
+                                    key_index_matches_types(key, key_index, child_schema)
+                                })
+                                .collect::<Vec<_>>();
+                            if key_indices.is_empty() {
+                                key_indices.push(key.all());
+                            }
+                            key_indices
+                        })
                 })
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .flatten()
                 .collect::<Vec<_>>();
 
             applicable_keys.sort_by_cached_key(|key_index| {
@@ -285,7 +390,11 @@ fn push_down_filter(parent: &mut QueryPlanNode, socket: &DbSocket) -> Result<(),
                 child.rows = min_rows;
                 *keys = Some(applicable_keys);
                 trace!("merged into {child:#?}");
-                Some(child)
+                // the keys only narrow the scan, so the filter itself must be kept
+                let mut parent = parent.clone();
+                parent.rows = min_rows;
+                *parent.children_mut()[0] = child;
+                Some(parent)
             } else {
                 None
             }
@@ -300,6 +409,45 @@ fn push_down_filter(parent: &mut QueryPlanNode, socket: &DbSocket) -> Result<(),
     Ok(())
 }
 
+/// checks if the values bounding a key index have the same types as the columns of the key, as
+/// searching a key using values of other types could skip matching rows
+pub(crate) fn key_index_matches_types(
+    key: &Key,
+    key_index: &KeyIndex,
+    schema: &TableSchema,
+) -> bool {
+    let bounds = match key_index.kind() {
+        KeyIndexKind::All => vec![],
+        KeyIndexKind::One(key_data) => vec![key_data],
+        KeyIndexKind::Many(key_data) => key_data.iter().collect(),
+        KeyIndexKind::Range { low, high } => [low, high]
+            .into_iter()
+            .filter_map(|bound| match bound {
+                Bound::Included(key_data) | Bound::Excluded(key_data) => Some(key_data),
+                Bound::Unbounded => None,
+            })
+            .collect(),
+        KeyIndexKind::Ranges(ranges) => ranges
+            .iter()
+            .flat_map(|range| [range.start_bound(), range.end_bound()])
+            .filter_map(|bound| match bound {
+                Bound::Included(key_data) | Bound::Excluded(key_data) => Some(key_data),
+                Bound::Unbounded => None,
+            })
+            .collect(),
+    };
+    bounds.into_iter().all(|key_data| {
+        key.columns()
+            .iter()
+            .zip(key_data.iter())
+            .all(|(column, value)| {
+                schema
+                    .get_column(column)
+                    .is_some_and(|column| column.data_type().validate(value))
+            })
+    })
+}
+
 /// checks if an expression contains attributes exclusively in one schema
 fn expr_exclusively_in_schema(expr: &Expr, schema: &TableSchema) -> bool {
     for col in expr.columns() {
@@ -1,7 +1,7 @@
 //! A window over some rows
 
 use crate::data::row::{OwnedRow, Row};
-use crate::key::KeyData;
+use crate::key::{KeyData, KeyDataRange};
 use crate::storage::tables::table_schema::{ColumnizedRow, TableSchema};
 
 use std::collections::VecDeque;
@@ -65,6 +65,45 @@ pub enum KeyIndexKind {
         high: Bound<KeyData>,
     },
     One(KeyData),
+    /// Any of the given keys
+    Many(Vec<KeyData>),
+    /// Any key within one of the given disjoint ranges
+    Ranges(Vec<KeyDataRange>),
+}
+
+impl KeyIndexKind {
+    /// Gets the ranges of keys this covers, or `None` if it covers every key
+    pub fn ranges(&self) -> Option<Vec<KeyDataRange>> {
+        match self {
+            KeyIndexKind::All => None,
+            KeyIndexKind::Range { low, high } => {
+                Some(vec![KeyDataRange(low.clone(), high.clone())])
+            }
+            KeyIndexKind::One(id) => Some(vec![KeyDataRange(
+                Bound::Included(id.clone()),
+                Bound::Included(id.clone()),
+            )]),
+            KeyIndexKind::Many(ids) => Some(
+                ids.iter()
+                    .map(|id| {
+                        KeyDataRange(Bound::Included(id.clone()), Bound::Included(id.clone()))
+                    })
+                    .collect(),
+            ),
+            KeyIndexKind::Ranges(ranges) => Some(ranges.clone()),
+        }
+    }
+
+    /// Creates a key index kind covering every key covered by either of two kinds
+    pub fn union(&self, other: &Self) -> Self {
+        match (self.ranges(), other.ranges()) {
+            (Some(mut ranges), Some(other)) => {
+                ranges.extend(other);
+                KeyIndexKind::Ranges(KeyDataRange::merge(ranges))
+            }
+            _ => KeyIndexKind::All,
+        }
+    }
 }
 
 /// A rows result
@@ -42,6 +42,8 @@ pub enum ReadDataError {
     UnknownTypeDiscriminant(u8),
     #[error("Need a type in order to continue deserialization")]
     NoTypeGiven,
+    #[error("Stored date or time is out of range")]
+    TemporalOutOfRange,
     #[error(transparent)]
     FromUtf8Error(#[from] FromUtf8Error),
     #[error("Page {0} already locked")]
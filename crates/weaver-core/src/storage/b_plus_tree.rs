@@ -6,6 +6,7 @@ use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::fmt::{Debug, Formatter};
 use std::io::Write;
+use std::mem::size_of;
 use std::ops::Bound;
 use std::sync::atomic::AtomicUsize;
 use std::sync::{atomic, Arc, OnceLock};
@@ -20,10 +21,14 @@ use crate::error::WeaverError;
 use crate::key::{KeyData, KeyDataRange};
 use crate::monitoring::{Monitor, Monitorable, Stats};
 use crate::storage::cells::{Cell, KeyCell, KeyValueCell, PageId};
-use crate::storage::paging::slotted_pager::{PageType, SlottedPager};
-use crate::storage::paging::traits::Pager;
+use crate::storage::paging::slotted_pager::{PageType, SlottedPageMut, SlottedPager};
+use crate::storage::paging::traits::{PageMut, Pager};
 use crate::storage::{ReadDataError, WriteDataError};
 
+/// Records whose cell would take up more than this fraction of a page are partially moved into
+/// overflow pages, so that a full leaf always has enough cells to be split
+const OVERFLOW_FRACTION: usize = 4;
+
 /// A BPlusTree that uses a given pager.
 ///
 /// This is used for primary indices.
@@ -60,18 +65,25 @@ where
     /// Creates a new bplus tree around a pager
     pub fn new(pager: P) -> Self {
         let allocator = SlottedPager::new(pager);
-        let root = if allocator.allocated() > 0 {
-            let mut ptr = Pager::get(&allocator, 0)
-                .unwrap_or_else(|_| panic!("should not fail because len > 0"));
-            while let Some(parent) = ptr.parent() {
-                ptr = allocator
-                    .get(parent)
-                    .expect("parent set but does not exist")
+        // recycled pages are emptied and detached from the tree, so the root is the only tree page
+        // without a parent that can still contain cells
+        let detached = Pager::iter(&allocator)
+            .filter_map(Result::ok)
+            .map(|(page, _)| page)
+            .filter(|page| page.page_type() != PageType::Overflow && page.parent().is_none())
+            .map(|page| (page.page_id(), page.count() > 0, page.page_type()))
+            .collect::<Vec<_>>();
+        let root = detached
+            .iter()
+            .max_by_key(|&&(_, filled, page_type)| (filled, page_type == PageType::KeyValue))
+            .map(|&(page_id, ..)| page_id);
+        for &(page_id, ..) in &detached {
+            if Some(page_id) != root {
+                allocator
+                    .recycle(page_id)
+                    .expect("detached pages are not in use");
             }
-            Some(ptr.page_id())
-        } else {
-            None
-        };
+        }
 
         Self {
             allocator: Arc::new(allocator),
@@ -103,7 +115,12 @@ where
         }
 
         let leaf = self.find_leaf(&key, true)?;
-        let cell: Cell = KeyValueCell::new(key.clone(), value).into();
+        let replaced = self
+            .allocator
+            .get(leaf)?
+            .get(&key)?
+            .and_then(Cell::into_key_value_cell);
+        let cell: Cell = self.key_value_cell(key.clone(), value)?.into();
         let insert_result = self.insert_cell(cell.clone(), leaf);
         let split = match insert_result {
             Ok(split) => split,
@@ -115,15 +132,355 @@ where
         if split {
             // split occurred, retry
             let leaf = self.find_leaf(&key, true)?;
-            self.insert_cell(cell, leaf).map(|_| ()).map_err(|err| {
+            self.insert_cell(cell, leaf).map_err(|err| {
                 error!("error occurred during insert after split: {err}");
                 err
-            })
+            })?;
+        }
+        if let Some(replaced) = replaced {
+            self.free_overflow(&replaced)?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the value of an existing key in the bplus tree.
+    ///
+    /// Fails with [`WeaverError::NotFound`] if the key is not already present.
+    pub fn update<K: Into<KeyData> + Debug, V: Into<OwnedRow> + Debug>(
+        &self,
+        k: K,
+        v: V,
+    ) -> Result<(), WeaverError> {
+        let key = k.into();
+        let value = v.into();
+
+        let leaf = self.find_leaf(&key, false)?;
+        if self.allocator.get(leaf)?.get(&key)?.is_none() {
+            return Err(WeaverError::NotFound(key));
+        }
+        let new_cell = self.key_value_cell(key.clone(), value)?;
+        let cell: Cell = new_cell.clone().into();
+        let removed = (|| {
+            let mut page = self.allocator.get_mut(leaf)?;
+            let replaced = page
+                .delete(&key)?
+                .and_then(Cell::into_key_value_cell)
+                .ok_or_else(|| WeaverError::NotFound(key.clone()))?;
+            Ok((replaced, page.insert(cell.clone())))
+        })();
+        let (replaced, inserted) = match removed {
+            Ok(removed) => removed,
+            Err(err) => {
+                self.free_overflow(&new_cell)?;
+                return Err(err);
+            }
+        };
+        let result = match inserted {
+            Ok(()) => Ok(()),
+            Err(WeaverError::WriteDataError(WriteDataError::AllocationFailed { .. })) => {
+                // the new value no longer fits in the page
+                self.split(leaf).and_then(|()| {
+                    let leaf = self.find_leaf(&key, true)?;
+                    if self.insert_cell(cell.clone(), leaf)? {
+                        let leaf = self.find_leaf(&key, true)?;
+                        self.insert_cell(cell, leaf)?;
+                    }
+                    Ok(())
+                })
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(err) = result {
+            error!("error occurred during update: {err}");
+            self.restore_replaced(&key, replaced, &new_cell)?;
+            return Err(err);
+        }
+        self.free_overflow(&replaced)?;
+        if let Some(monitor) = self.monitor.get() {
+            monitor.updates.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    /// Undoes an update that failed after the old record of a key was removed, putting the old
+    /// record back and recycling the overflow pages of the new one
+    fn restore_replaced(
+        &self,
+        key: &KeyData,
+        replaced: KeyValueCell,
+        new_cell: &KeyValueCell,
+    ) -> Result<(), WeaverError> {
+        let replaced: Cell = replaced.into();
+        let leaf = self.find_leaf(key, true)?;
+        if self.insert_cell(replaced.clone(), leaf)? {
+            let leaf = self.find_leaf(key, true)?;
+            self.insert_cell(replaced, leaf)?;
+        }
+        self.free_overflow(new_cell)
+    }
+
+    /// Removes a key from the bplus tree, returning the removed record if it was present.
+    ///
+    /// Pages left under-full by the removal either borrow cells from an adjacent sibling or are
+    /// merged into it, fixing up the separator keys of the parent as needed. Pages emptied by a merge
+    /// are unlinked from the tree and recycled, so later splits can reuse them.
+    pub fn delete(&self, key_data: &KeyData) -> Result<Option<Box<[u8]>>, WeaverError> {
+        let Some(path) = self.find_path(key_data)? else {
+            return Ok(None);
+        };
+        let leaf = *path.last().expect("path always contains the root");
+        let removed = {
+            let mut page = self.allocator.get_mut(leaf)?;
+            match page.delete(key_data)? {
+                None => return Ok(None),
+                Some(Cell::Key(_)) => {
+                    return Err(WeaverError::CellTypeMismatch {
+                        page_id: page.page_id(),
+                        expected: PageType::KeyValue,
+                        actual: PageType::Key,
+                    })
+                }
+                Some(Cell::KeyValue(cell)) => cell,
+            }
+        };
+        let record = self.read_record(&removed)?;
+        self.free_overflow(&removed)?;
+        self.rebalance(&path)?;
+        self.verify_integrity();
+        if let Some(monitor) = self.monitor.get() {
+            monitor.deletes.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+        Ok(Some(record))
+    }
+
+    /// Finds the pages from the root to the leaf that can contain the given key.
+    ///
+    /// Returns `None` if the key is greater than every key in the tree.
+    fn find_path(&self, key_data: &KeyData) -> Result<Option<Vec<PageId>>, WeaverError> {
+        let Some(mut ptr) = *self.root.read() else {
+            return Ok(None);
+        };
+        let mut path = vec![ptr];
+        loop {
+            let page = self.allocator.get(ptr)?;
+            match page.page_type() {
+                PageType::Key => {
+                    let cells = to_ranges(page.all()?);
+                    let Some((_, cell)) = cells.iter().find(|(kdr, _)| kdr.contains(key_data))
+                    else {
+                        return Ok(None);
+                    };
+                    ptr = cell
+                        .as_key_cell()
+                        .expect("key cell pages only contain key cells")
+                        .page_id();
+                    path.push(ptr);
+                }
+                PageType::KeyValue => return Ok(Some(path)),
+                PageType::Overflow => unreachable!("overflow pages are not part of the tree"),
+            }
+        }
+    }
+
+    /// Rebalances the last page of a root to page path, recursing upwards when pages are merged.
+    fn rebalance(&self, path: &[PageId]) -> Result<(), WeaverError> {
+        let Some((&node, ancestors)) = path.split_last() else {
+            return Ok(());
+        };
+        let Some(&parent) = ancestors.last() else {
+            return self.collapse_root(node);
+        };
+        {
+            let page = self.allocator.get(node)?;
+            // pages are balanced while at least a quarter of their space is in use
+            if page.count() > 0 && page.used() * 3 >= page.free_space() {
+                return Ok(());
+            }
+        }
+        let children = self
+            .allocator
+            .get(parent)?
+            .all()?
+            .into_iter()
+            .filter_map(Cell::into_key_cell)
+            .collect::<Vec<_>>();
+        if children.len() < 2 {
+            return self.rebalance(ancestors);
+        }
+        let index = children
+            .iter()
+            .position(|cell| cell.page_id() == node)
+            .ok_or_else(|| WeaverError::ReadDataError(ReadDataError::PageNotFound(node)))?;
+        let (left, right) = if index > 0 {
+            (&children[index - 1], &children[index])
+        } else {
+            (&children[0], &children[1])
+        };
+        if self.merge_or_borrow(parent, left, right)? {
+            self.rebalance(ancestors)
         } else {
             Ok(())
         }
     }
 
+    /// Redistributes the cells of two adjacent siblings.
+    ///
+    /// If all cells fit within a single page, the left page is merged into the right page and its
+    /// key cell is removed from the parent, returning `true`. Otherwise the cells are split evenly
+    /// between the two pages and the separator of the left page is updated.
+    fn merge_or_borrow(
+        &self,
+        parent: PageId,
+        left: &KeyCell,
+        right: &KeyCell,
+    ) -> Result<bool, WeaverError> {
+        let left_id = left.page_id();
+        let right_id = right.page_id();
+        let mut left_page = self.allocator.get_mut(left_id)?;
+        let mut right_page = self.allocator.get_mut(right_id)?;
+        let page_type = left_page.page_type();
+
+        let mut cells = drain_all(&mut left_page)?;
+        cells.extend(drain_all(&mut right_page)?);
+        let sizes = cells
+            .iter()
+            .map(|cell| cell.len() + size_of::<u64>())
+            .collect::<Vec<_>>();
+        let needed = sizes.iter().sum::<usize>();
+
+        if needed < right_page.free_space() {
+            for cell in cells {
+                right_page.insert(cell)?;
+            }
+            right_page.set_left_sibling(left_page.left_sibling());
+            if let Some(left_sibling) = left_page.left_sibling() {
+                self.allocator
+                    .get_mut(left_sibling)?
+                    .set_right_sibling(right_id);
+            }
+            left_page.set_left_sibling(None);
+            left_page.set_right_sibling(None);
+            left_page.set_parent(None);
+            drop(left_page);
+            drop(right_page);
+
+            if page_type == PageType::Key {
+                self.adopt_children(right_id)?;
+            }
+            self.allocator.get_mut(parent)?.delete(&left.key_data())?;
+            self.allocator.recycle(left_id)?;
+            if let Some(monitor) = self.monitor.get() {
+                monitor.merges.fetch_add(1, atomic::Ordering::Relaxed);
+            }
+            Ok(true)
+        } else {
+            let mut on_left = 0;
+            let mut split = 0;
+            while split < cells.len() - 1 && (on_left + sizes[split]) * 2 <= needed {
+                on_left += sizes[split];
+                split += 1;
+            }
+            let split = split.max(1);
+            let right_cells = cells.split_off(split);
+            for cell in cells {
+                left_page.insert(cell)?;
+            }
+            for cell in right_cells {
+                right_page.insert(cell)?;
+            }
+            let separator = left_page
+                .max_key()?
+                .expect("left page should not be empty after borrowing");
+            drop(left_page);
+            drop(right_page);
+
+            if page_type == PageType::Key {
+                self.adopt_children(left_id)?;
+                self.adopt_children(right_id)?;
+            }
+            self.allocator.get_mut(parent)?.delete(&left.key_data())?;
+            self.insert_key_cell(KeyCell::new(left_id.as_u32(), separator), parent)?;
+            Ok(false)
+        }
+    }
+
+    /// Replaces internal roots that only have a single child with that child
+    fn collapse_root(&self, mut root: PageId) -> Result<(), WeaverError> {
+        loop {
+            let child = {
+                let page = self.allocator.get(root)?;
+                if page.page_type() != PageType::Key || page.count() != 1 {
+                    return Ok(());
+                }
+                page.all()?
+                    .into_iter()
+                    .filter_map(Cell::into_key_cell)
+                    .map(|cell| cell.page_id())
+                    .next()
+                    .expect("page has one cell")
+            };
+            drain_all(&mut self.allocator.get_mut(root)?)?;
+            self.allocator.get_mut(child)?.set_parent(None);
+            *self.root.write() = Some(child);
+            self.allocator.recycle(root)?;
+            root = child;
+        }
+    }
+
+    /// Creates the cell storing a record, moving the end of the record into overflow pages when the
+    /// cell and its slot would take up more than [`1 / OVERFLOW_FRACTION`](OVERFLOW_FRACTION) of a page.
+    fn key_value_cell(&self, key: KeyData, record: OwnedRow) -> Result<KeyValueCell, WeaverError> {
+        let mut cell = KeyValueCell::new(key, record);
+        let max_len = self.allocator.body_size() / OVERFLOW_FRACTION - size_of::<u64>();
+        if let Some(spilled) = cell.spill(max_len) {
+            let first = self.write_overflow(&spilled)?;
+            cell.set_overflow_page(first);
+        }
+        Ok(cell)
+    }
+
+    /// Writes data into a chain of overflow pages linked by their right siblings, returning the
+    /// first page of the chain
+    fn write_overflow(&self, data: &[u8]) -> Result<PageId, WeaverError> {
+        let (mut page, _) = self.allocator.new_with_type(PageType::Overflow)?;
+        let first = page.page_id();
+        let mut rest = &data[page.write_overflow_data(data)?..];
+        while !rest.is_empty() {
+            let (mut next, _) = self.allocator.new_with_type(PageType::Overflow)?;
+            page.set_right_sibling(next.page_id());
+            rest = &rest[next.write_overflow_data(rest)?..];
+            page = next;
+        }
+        Ok(first)
+    }
+
+    /// Reads the record of a cell, including the part of it stored in overflow pages
+    fn read_record(&self, cell: &KeyValueCell) -> Result<Box<[u8]>, WeaverError> {
+        let Some((first, len)) = cell.overflow() else {
+            return Ok(Box::from(cell.record()));
+        };
+        let mut record = Vec::with_capacity(len);
+        record.extend_from_slice(cell.record());
+        let mut next = Some(first);
+        while record.len() < len {
+            let page_id = next.ok_or(ReadDataError::UnexpectedEof)?;
+            let page = self.allocator.get(page_id)?;
+            record.extend_from_slice(page.overflow_data()?);
+            next = page.right_sibling();
+        }
+        Ok(record.into_boxed_slice())
+    }
+
+    /// Recycles the overflow pages of a cell that's no longer in the tree
+    fn free_overflow(&self, cell: &KeyValueCell) -> Result<(), WeaverError> {
+        let mut next = cell.overflow().map(|(first, _)| first);
+        while let Some(page_id) = next {
+            next = self.allocator.get(page_id)?.right_sibling();
+            self.allocator.recycle(page_id)?;
+        }
+        Ok(())
+    }
+
     fn insert_cell(&self, cell: Cell, page_id: PageId) -> Result<bool, WeaverError> {
         let mut page = self.allocator.get_mut(page_id).expect("no page found");
         trace!(
@@ -215,30 +572,58 @@ where
         split_page.set_parent(parent);
         let key_ptr_cell = KeyCell::new(split_page_id.as_u32(), median_key.clone());
         /* trace!("created ptr {}", key_ptr_cell); */
-        let ptr_cell = Cell::Key(key_ptr_cell.clone());
 
         drop(page);
         drop(split_page);
 
+        if page_type == PageType::Key {
+            self.adopt_children(split_page_id)?;
+        }
+
         let parent = self.get_new_parent(&median_key, parent)?;
 
         /* trace!("inserting split page into {parent:?}"); */
 
-        let emit = if self.insert_cell(ptr_cell.clone(), parent)? {
-            let parent = self.get_new_parent(&median_key, parent)?;
-            self.insert_cell(ptr_cell, parent).map(|split| {
-                if split {
-                    panic!("second split")
-                }
-            })
-        } else {
-            Ok(())
-        };
+        let parent = self.insert_key_cell(key_ptr_cell, parent)?;
+        self.allocator.get_mut(split_page_id)?.set_parent(parent);
         self.verify_integrity();
         if let Some(monitor) = self.monitor.get() {
             monitor.splits.fetch_add(1, atomic::Ordering::Relaxed);
         }
-        emit
+        Ok(())
+    }
+
+    /// Inserts a key cell into an internal page, splitting the page if required.
+    ///
+    /// Returns the id of the page the cell was eventually inserted into.
+    fn insert_key_cell(&self, key_cell: KeyCell, parent: PageId) -> Result<PageId, WeaverError> {
+        let key_data = key_cell.key_data();
+        let cell = Cell::Key(key_cell);
+        if self.insert_cell(cell.clone(), parent)? {
+            let parent = self.get_new_parent(&key_data, parent)?;
+            if self.insert_cell(cell, parent)? {
+                panic!("second split")
+            }
+            Ok(parent)
+        } else {
+            Ok(parent)
+        }
+    }
+
+    /// Sets the parent of every child of an internal page to that page
+    fn adopt_children(&self, page_id: PageId) -> Result<(), WeaverError> {
+        let children = self
+            .allocator
+            .get(page_id)?
+            .all()?
+            .into_iter()
+            .filter_map(Cell::into_key_cell)
+            .map(|cell| cell.page_id())
+            .collect::<Vec<_>>();
+        for child in children {
+            self.allocator.get_mut(child)?.set_parent(page_id);
+        }
+        Ok(())
     }
 
     fn get_new_parent(&self, median_key: &KeyData, parent: PageId) -> Result<PageId, WeaverError> {
@@ -309,7 +694,7 @@ where
                 expected: PageType::KeyValue,
                 actual: PageType::Key,
             }),
-            Some(Cell::KeyValue(value)) => Ok(Some(Box::from(value.record()))),
+            Some(Cell::KeyValue(value)) => self.read_record(&value).map(Some),
         }
     }
     pub fn count<T: Into<KeyDataRange>>(&self, key_data_range: T) -> Result<u64, WeaverError> {
@@ -318,11 +703,21 @@ where
             return Ok(0);
         };
         let start_node = match range.start_bound() {
-            Bound::Included(k) | Bound::Excluded(k) => self.find_leaf(k, false)?,
+            Bound::Included(k) | Bound::Excluded(k) => match self.find_leaf(k, false) {
+                Ok(leaf) => leaf,
+                // start is past the maximum key
+                Err(WeaverError::NotFound(_)) => return Ok(Default::default()),
+                Err(e) => return Err(e),
+            },
             Bound::Unbounded => self.left_most(root)?,
         };
         let end_node = match range.end_bound() {
-            Bound::Included(k) | Bound::Excluded(k) => self.find_leaf(k, false)?,
+            Bound::Included(k) | Bound::Excluded(k) => match self.find_leaf(k, false) {
+                Ok(leaf) => leaf,
+                // end is past the maximum key
+                Err(WeaverError::NotFound(_)) => self.right_most(root)?,
+                Err(e) => return Err(e),
+            },
             Bound::Unbounded => self.right_most(root)?,
         };
 
@@ -360,11 +755,21 @@ where
             return Ok(vec![]);
         };
         let start_node = match range.start_bound() {
-            Bound::Included(k) | Bound::Excluded(k) => self.find_leaf(k, false)?,
+            Bound::Included(k) | Bound::Excluded(k) => match self.find_leaf(k, false) {
+                Ok(leaf) => leaf,
+                // start is past the maximum key
+                Err(WeaverError::NotFound(_)) => return Ok(Default::default()),
+                Err(e) => return Err(e),
+            },
             Bound::Unbounded => self.left_most(root)?,
         };
         let end_node = match range.end_bound() {
-            Bound::Included(k) | Bound::Excluded(k) => self.find_leaf(k, false)?,
+            Bound::Included(k) | Bound::Excluded(k) => match self.find_leaf(k, false) {
+                Ok(leaf) => leaf,
+                // end is past the maximum key
+                Err(WeaverError::NotFound(_)) => self.right_most(root)?,
+                Err(e) => return Err(e),
+            },
             Bound::Unbounded => self.right_most(root)?,
         };
         let mut pages = vec![];
@@ -386,12 +791,13 @@ where
                 let page = self.allocator.get(page_id)?;
                 let page_range = page.key_range()?;
                 if let Some(on_page) = page_range.intersection(&range) {
-                    let page_cells = page
+                    for cell in page
                         .get_range(on_page)?
                         .into_iter()
                         .flat_map(|cell| cell.into_key_value_cell())
-                        .map(|cell| Box::from(cell.record()));
-                    vec.extend(page_cells);
+                    {
+                        vec.push(self.read_record(&cell)?);
+                    }
                 }
                 Ok(vec)
             })
@@ -404,6 +810,95 @@ where
             })
     }
 
+    /// Gets the set of rows whose keys start with a value within the given range.
+    ///
+    /// Each key is truncated to the length of the bound it's compared against, so that a range over
+    /// the leading columns of a composite key can be used. This is what secondary indices use, as their
+    /// keys have the primary key appended to them.
+    pub fn prefix_range<T: Into<KeyDataRange>>(
+        &self,
+        key_data_range: T,
+    ) -> Result<Vec<Box<[u8]>>, WeaverError> {
+        let mut records = vec![];
+        self.visit_prefix_range(&key_data_range.into(), |cell| {
+            records.push(self.read_record(&cell)?);
+            Ok(())
+        })?;
+        if let Some(monitor) = self.monitor.get() {
+            monitor
+                .reads
+                .fetch_add(records.len(), atomic::Ordering::Relaxed);
+        }
+        Ok(records)
+    }
+
+    /// Counts the rows whose keys start with a value within the given range, the same as
+    /// [`prefix_range`](Self::prefix_range) but without reading any records.
+    pub fn prefix_count<T: Into<KeyDataRange>>(
+        &self,
+        key_data_range: T,
+    ) -> Result<u64, WeaverError> {
+        let mut count = 0_u64;
+        self.visit_prefix_range(&key_data_range.into(), |_| {
+            count += 1;
+            Ok(())
+        })?;
+        Ok(count)
+    }
+
+    /// Visits the cells whose keys start with a value within the given range, in order
+    fn visit_prefix_range<F: FnMut(KeyValueCell) -> Result<(), WeaverError>>(
+        &self,
+        range: &KeyDataRange,
+        mut visit: F,
+    ) -> Result<(), WeaverError> {
+        let Some(root) = *self.root.read() else {
+            return Ok(());
+        };
+        let mut page_ptr = match range.start_bound() {
+            Bound::Included(k) | Bound::Excluded(k) => match self.find_leaf(k, false) {
+                Ok(leaf) => leaf,
+                // start is past the maximum key
+                Err(WeaverError::NotFound(_)) => return Ok(()),
+                Err(e) => return Err(e),
+            },
+            Bound::Unbounded => self.left_most(root)?,
+        };
+        let end_node = self.right_most(root)?;
+
+        loop {
+            let page = self.allocator.get(page_ptr)?;
+            for cell in page
+                .all()?
+                .into_iter()
+                .flat_map(|cell| cell.into_key_value_cell())
+            {
+                let key_data = cell.key_data();
+                let after_start = match range.start_bound() {
+                    Bound::Included(k) => key_data.prefix(k.len()) >= *k,
+                    Bound::Excluded(k) => key_data.prefix(k.len()) > *k,
+                    Bound::Unbounded => true,
+                };
+                if !after_start {
+                    continue;
+                }
+                let before_end = match range.end_bound() {
+                    Bound::Included(k) => key_data.prefix(k.len()) <= *k,
+                    Bound::Excluded(k) => key_data.prefix(k.len()) < *k,
+                    Bound::Unbounded => true,
+                };
+                if !before_end {
+                    return Ok(());
+                }
+                visit(cell)?;
+            }
+            if page_ptr == end_node {
+                return Ok(());
+            }
+            page_ptr = page.right_sibling().expect("siblings should always be set");
+        }
+    }
+
     /// Gets all rows
     #[inline]
     pub fn all(&self) -> Result<Vec<Box<[u8]>>, WeaverError> {
@@ -491,6 +986,7 @@ where
                 PageType::KeyValue => {
                     break;
                 }
+                PageType::Overflow => unreachable!("overflow pages are not part of the tree"),
             }
         }
         // trace!("find leaf for key {key_data:?} had traversal: {traversal:#?}");
@@ -551,6 +1047,7 @@ where
                 PageType::KeyValue => {
                     break;
                 }
+                PageType::Overflow => unreachable!("overflow pages are not part of the tree"),
             }
         }
         // trace!("find leaf for key {key_data:?} had traversal: {traversal:#?}");
@@ -607,6 +1104,7 @@ where
                 }
             }
             PageType::KeyValue => Ok(node),
+            PageType::Overflow => unreachable!("overflow pages are not part of the tree"),
         }
     }
 
@@ -628,6 +1126,7 @@ where
                 }
             }
             PageType::KeyValue => Ok(node),
+            PageType::Overflow => unreachable!("overflow pages are not part of the tree"),
         }
     }
 
@@ -656,6 +1155,16 @@ where
                     let key_cell = node.into_key_cell().unwrap();
                     let child = self.allocator.get(key_cell.page_id())?;
 
+                    if child.parent() != Some(page_id) {
+                        let _ = self.print();
+                        error!("verify failed, check backtrace for details");
+                        panic!(
+                            "verify failed because parent of child ({:?}) is not this page. page {page_id} -> {}",
+                            child.parent(),
+                            child.page_id()
+                        );
+                    }
+
                     if let Some(ref min) = child.min_key()? {
                         if !range.contains(min) {
                             let _ = self.print();
@@ -703,6 +1212,7 @@ where
                 Ok(())
             }
             PageType::KeyValue => Ok(()),
+            PageType::Overflow => unreachable!("overflow pages are not part of the tree"),
         }
     }
 
@@ -774,6 +1284,7 @@ where
                         .collect::<Vec<_>>()
                 ));
             }
+            PageType::Overflow => unreachable!("overflow pages are not part of the tree"),
         }
         builder.end_child();
         Ok(())
@@ -793,6 +1304,7 @@ where
                 .sum::<usize>()
                 + 1),
             PageType::KeyValue => Ok(1),
+            PageType::Overflow => unreachable!("overflow pages are not part of the tree"),
         }
     }
 
@@ -819,6 +1331,16 @@ impl<P: Pager> Monitorable for BPlusTree<P> {
     }
 }
 
+/// Removes every cell from a page
+fn drain_all<'a, P: PageMut<'a>>(
+    page: &mut SlottedPageMut<'a, P>,
+) -> Result<Vec<Cell>, WeaverError> {
+    match page.max_key()? {
+        Some(max) => page.drain(..=max),
+        None => Ok(vec![]),
+    }
+}
+
 fn to_ranges(cells: Vec<Cell>) -> Vec<(KeyDataRange, Cell)> {
     cells
         .into_iter()
@@ -841,6 +1363,7 @@ struct BPlusTreeMonitor {
     updates: Arc<AtomicUsize>,
     deletes: Arc<AtomicUsize>,
     splits: Arc<AtomicUsize>,
+    merges: Arc<AtomicUsize>,
 }
 
 impl Debug for BPlusTreeMonitor {
@@ -860,6 +1383,7 @@ impl BPlusTreeMonitor {
             updates: Arc::new(Default::default()),
             deletes: Arc::new(Default::default()),
             splits: Arc::new(Default::default()),
+            merges: Arc::new(Default::default()),
         }
     }
 }
@@ -875,6 +1399,7 @@ impl Monitor for BPlusTreeMonitor {
         let u = self.updates.load(std::sync::atomic::Ordering::Relaxed) as f64 / elapsed;
         let d = self.deletes.load(std::sync::atomic::Ordering::Relaxed) as f64 / elapsed;
         let splits = self.splits.load(std::sync::atomic::Ordering::Relaxed) as f64 / elapsed;
+        let merges = self.merges.load(std::sync::atomic::Ordering::Relaxed) as f64 / elapsed;
 
         Stats::from_iter([
             ("pager", self.pager_monitor.lock().stats()),
@@ -883,12 +1408,16 @@ impl Monitor for BPlusTreeMonitor {
             ("updates", Stats::Throughput(u)),
             ("deletes", Stats::Throughput(d)),
             ("splits", Stats::Throughput(splits)),
+            ("merges", Stats::Throughput(merges)),
         ])
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
     use rand::distributions::Alphanumeric;
     use rand::Rng;
     use test_log::test;
@@ -929,6 +1458,67 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_data_persists_after_deletes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("btree");
+        let create_pager = || BufferedPager::new(FilePager::open_or_create(&path).unwrap());
+        const MAX: i64 = 512;
+        {
+            let btree = BPlusTree::new(create_pager());
+            for i in 0..MAX {
+                btree.insert([i], [1 + i, 2 * i]).unwrap();
+            }
+            // empties the first pages of the tree, which get merged away and recycled
+            for i in 0..MAX - 8 {
+                btree.delete(&KeyData::from([i])).unwrap();
+            }
+        }
+        {
+            let btree = BPlusTree::new(create_pager());
+            btree.verify_integrity();
+            assert_eq!(btree.all().expect("could not get all").len(), 8);
+            let allocated = btree.allocator().allocated();
+            for i in 0..MAX - 8 {
+                btree.insert([i], [1 + i, 2 * i]).unwrap();
+            }
+            btree.verify_integrity();
+            assert_eq!(btree.all().expect("could not get all").len(), MAX as usize);
+            assert_eq!(
+                btree.allocator().allocated(),
+                allocated,
+                "pages recycled before closing the tree should be reused"
+            );
+        }
+    }
+
+    #[test]
+    fn test_overflow_persists() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("btree");
+        let record = |i: i64| -> [DbVal; 2] { [i.into(), "x".repeat(10_000 + i as usize).into()] };
+        const MAX: i64 = 16;
+        {
+            let btree = BPlusTree::new(FilePager::open_or_create(&path).unwrap());
+            for i in 0..MAX {
+                btree.insert([i], record(i)).unwrap();
+            }
+        }
+        {
+            let btree = BPlusTree::new(FilePager::open(&path).unwrap());
+            assert_eq!(btree.all().expect("could not get all").len(), MAX as usize);
+            for i in 0..MAX {
+                let raw = btree
+                    .get(&KeyData::from([i]))
+                    .unwrap()
+                    .expect("should have id");
+                let read = deserialize_data_untyped(raw, [Type::Integer, Type::String(u16::MAX)])
+                    .expect("could not deserialize");
+                assert_eq!(read, record(i));
+            }
+        }
+    }
+
     #[test]
     fn test_data_buffered_persists() {
         let dir = tempfile::TempDir::new().unwrap();
@@ -1008,6 +1598,199 @@ mod tests {
         assert_eq!(&read[2], &3.into());
     }
 
+    #[test]
+    fn large_records_use_overflow_pages() {
+        let btree = BPlusTree::new(VecPager::new(256));
+        let record = |i: i64, c: char| -> [DbVal; 2] {
+            [i.into(), c.to_string().repeat(1000 + i as usize).into()]
+        };
+        let read = |i: i64| {
+            let raw = btree
+                .get(&[i].into())
+                .unwrap()
+                .unwrap_or_else(|| panic!("could not get record for key {i}"));
+            deserialize_data_untyped(raw, [Type::Integer, Type::String(u16::MAX)])
+                .expect("could not deserialize")
+        };
+
+        const MAX: i64 = 16;
+        for i in 0..MAX {
+            btree.insert([i], record(i, 'x')).expect("could not insert");
+        }
+        btree.verify_integrity();
+        for i in 0..MAX {
+            assert_eq!(read(i), record(i, 'x'));
+        }
+        assert_eq!(btree.all().unwrap().len(), MAX as usize);
+
+        // an update writes the new overflow pages before recycling the old ones, so only the first
+        // update needs to allocate pages
+        for i in 0..MAX {
+            btree.update([i], record(i, 'y')).expect("could not update");
+        }
+        let allocated = btree.allocator().allocated();
+        for i in 0..MAX {
+            btree.update([i], record(i, 'z')).expect("could not update");
+        }
+        assert_eq!(btree.allocator().allocated(), allocated);
+        for i in 0..MAX {
+            assert_eq!(read(i), record(i, 'z'));
+        }
+
+        let removed = btree
+            .delete(&[0].into())
+            .unwrap()
+            .expect("record should be present");
+        let removed = deserialize_data_untyped(removed, [Type::Integer, Type::String(u16::MAX)])
+            .expect("could not deserialize");
+        assert_eq!(removed, record(0, 'z'));
+        assert!(btree.get(&[0].into()).unwrap().is_none());
+    }
+
+    #[test]
+    fn update_b_plus_tree_many() {
+        let btree = BPlusTree::new(VecPager::new(180));
+
+        const MAX: i64 = 128;
+        for i in 0..MAX {
+            btree.insert([i], [i]).expect("could not insert");
+        }
+        for i in (0..MAX).step_by(2) {
+            btree
+                .update([i], [i, 2 * i, 3 * i])
+                .expect("could not update");
+        }
+        btree.verify_integrity();
+        assert!(matches!(
+            btree.update([MAX], [MAX]),
+            Err(WeaverError::NotFound(_))
+        ));
+
+        for i in 0..MAX {
+            let raw = btree
+                .get(&[i].into())
+                .unwrap()
+                .unwrap_or_else(|| panic!("could not get record for key {i}"));
+            if i % 2 == 0 {
+                let read = deserialize_data_untyped(raw, vec![Type::Integer; 3])
+                    .expect("could not deserialize");
+                assert_eq!(&read[2], &(3 * i).into());
+            } else {
+                let read = deserialize_data_untyped(raw, vec![Type::Integer; 1])
+                    .expect("could not deserialize");
+                assert_eq!(&read[0], &i.into());
+            }
+        }
+    }
+
+    #[test]
+    fn prefix_count_b_plus_tree() {
+        let btree = BPlusTree::new(VecPager::new(180));
+        const MAX: i64 = 128;
+        for i in 0..MAX {
+            btree.insert([i % 4, i], [i]).expect("could not insert");
+        }
+
+        let prefix = |low: i64, high: i64| {
+            KeyDataRange(
+                Bound::Included([low].into()),
+                Bound::Included([high].into()),
+            )
+        };
+        for (low, high) in [(0, 0), (1, 2), (3, 3), (4, 8)] {
+            let range = btree.prefix_range(prefix(low, high)).unwrap();
+            assert_eq!(
+                btree.prefix_count(prefix(low, high)).unwrap(),
+                range.len() as u64
+            );
+        }
+        assert_eq!(btree.prefix_count(prefix(1, 2)).unwrap(), 64);
+        assert_eq!(
+            btree.prefix_count(KeyDataRange::from(..)).unwrap(),
+            MAX as u64
+        );
+    }
+
+    /// A pager that refuses to create pages once exhausted
+    #[derive(Debug)]
+    struct ExhaustiblePager {
+        inner: VecPager,
+        exhausted: Arc<AtomicBool>,
+    }
+
+    impl Monitorable for ExhaustiblePager {
+        fn monitor(&self) -> Box<dyn Monitor> {
+            self.inner.monitor()
+        }
+    }
+
+    impl Pager for ExhaustiblePager {
+        type Page<'a> = <VecPager as Pager>::Page<'a>;
+        type PageMut<'a> = <VecPager as Pager>::PageMut<'a>;
+        type Err = std::io::Error;
+
+        fn page_size(&self) -> usize {
+            self.inner.page_size()
+        }
+
+        fn get(&self, index: usize) -> Result<Self::Page<'_>, Self::Err> {
+            self.inner.get(index).map_err(|never| match never {})
+        }
+
+        fn get_mut(&self, index: usize) -> Result<Self::PageMut<'_>, Self::Err> {
+            self.inner.get_mut(index).map_err(|never| match never {})
+        }
+
+        fn new_page(&self) -> Result<(Self::PageMut<'_>, usize), Self::Err> {
+            if self.exhausted.load(Ordering::SeqCst) {
+                return Err(std::io::Error::other("out of pages"));
+            }
+            self.inner.new_page().map_err(|never| match never {})
+        }
+
+        fn free(&self, index: usize) -> Result<(), Self::Err> {
+            self.inner.free(index).map_err(|never| match never {})
+        }
+
+        fn allocated(&self) -> usize {
+            self.inner.allocated()
+        }
+
+        fn reserved(&self) -> usize {
+            self.inner.reserved()
+        }
+    }
+
+    #[test]
+    fn failed_update_keeps_old_record() {
+        let exhausted = Arc::new(AtomicBool::new(false));
+        let btree = BPlusTree::new(ExhaustiblePager {
+            inner: VecPager::new(1024),
+            exhausted: exhausted.clone(),
+        });
+        let record = |i: i64, len: usize| -> [DbVal; 2] { [i.into(), "x".repeat(len).into()] };
+        const MAX: i64 = 5;
+        for i in 0..MAX {
+            btree.insert([i], record(i, 150)).expect("could not insert");
+        }
+        exhausted.store(true, Ordering::SeqCst);
+
+        // the new record no longer fits in the leaf, and the leaf can not be split
+        let result = btree.update([0], record(0, 200));
+        assert!(result.is_err(), "update should fail without new pages");
+
+        btree.verify_integrity();
+        for i in 0..MAX {
+            let raw = btree
+                .get(&[i].into())
+                .unwrap()
+                .unwrap_or_else(|| panic!("could not get record for key {i}"));
+            let read = deserialize_data_untyped(raw, [Type::Integer, Type::String(u16::MAX)])
+                .expect("could not deserialize");
+            assert_eq!(read, record(i, 150));
+        }
+    }
+
     #[test]
     fn insert_into_b_plus_tree_many() {
         let btree = BPlusTree::new(VecPager::new(180));
@@ -223,17 +223,72 @@ impl KeyValueCell {
 
     /// Returns the raw record.
     ///
-    /// Records are stored untyped, and therefore we do not know what it's actually made of.
+    /// Records are stored untyped, and therefore we do not know what it's actually made of. If the
+    /// record [overflows](Self::overflow), only the part of it stored within this cell is returned.
     pub fn record(&self) -> &[u8] {
-        self.data_record.as_ref()
+        if self.flags.overflow() {
+            &self.data_record[OVERFLOW_HEADER_LEN..]
+        } else {
+            self.data_record.as_ref()
+        }
+    }
+
+    /// Gets the first overflow page and the total length of the record, if the record doesn't fit
+    /// within this cell.
+    pub fn overflow(&self) -> Option<(PageId, usize)> {
+        if !self.flags.overflow() {
+            return None;
+        }
+        let page_id = PageId::read(&self.data_record).ok()?;
+        let len = u32::read(&self.data_record[size_of::<u32>()..]).ok()?;
+        Some((page_id, len as usize))
+    }
+
+    /// Moves the end of the record out of this cell so that the cell is at most `max_len` bytes long,
+    /// returning the removed bytes.
+    ///
+    /// Returns `None` if the cell already fits. Otherwise, the removed bytes must be written to overflow
+    /// pages, and the first of those set with [`set_overflow_page`](Self::set_overflow_page).
+    pub fn spill(&mut self, max_len: usize) -> Option<Box<[u8]>> {
+        if self.flags.overflow() || self.len() <= max_len {
+            return None;
+        }
+        let inline = max_len
+            .saturating_sub(self.len() - self.data_record.len() + OVERFLOW_HEADER_LEN)
+            .min(self.data_record.len());
+        let (kept, spilled) = self.data_record.split_at(inline);
+        let mut data_record = Vec::with_capacity(OVERFLOW_HEADER_LEN + inline);
+        data_record.extend(0_u32.to_be_bytes());
+        data_record.extend((self.data_record.len() as u32).to_be_bytes());
+        data_record.extend(kept);
+        let spilled = Box::from(spilled);
+
+        self.flags.set_overflow(true);
+        self.value_size = data_record.len() as u32;
+        self.data_record = data_record.into_boxed_slice();
+        Some(spilled)
+    }
+
+    /// Sets the first overflow page of a cell whose record has been [spilled](Self::spill)
+    pub fn set_overflow_page(&mut self, page_id: PageId) {
+        assert!(self.flags.overflow(), "cell does not overflow");
+        page_id
+            .write(&mut self.data_record)
+            .expect("overflow header is always present");
     }
 }
 
+/// The length of the first overflow page id and the total record length, which prefix the record of
+/// a cell that overflows
+const OVERFLOW_HEADER_LEN: usize = 2 * size_of::<u32>();
+
 bitfield! {
     #[derive(Copy, Clone, Eq, PartialEq)]
     #[repr(transparent)]
     pub struct Flags(u8);
     impl Debug;
+    /// Whether the end of the record is stored in overflow pages
+    pub overflow, set_overflow: 0;
 }
 
 impl StorageBackedData for KeyValueCell {
@@ -281,7 +336,7 @@ impl StorageBackedData for KeyValueCell {
 
 #[cfg(test)]
 mod tests {
-    use crate::storage::cells::{Flags, KeyCell, KeyValueCell};
+    use crate::storage::cells::{Flags, KeyCell, KeyValueCell, PageId};
     use crate::storage::StorageBackedData;
 
     #[test]
@@ -319,6 +374,40 @@ mod tests {
         let read_key_cell = KeyValueCell::read(&buffer).expect("could not read key cell");
         assert_eq!(read_key_cell, key_cell);
     }
+
+    #[test]
+    fn spill_key_value_cell() {
+        let record = (0..100).collect::<Vec<u8>>();
+        let mut key_value_cell = KeyValueCell {
+            flags: Flags(0),
+            key_size: 4,
+            value_size: record.len() as u32,
+            key: Box::new([1, 2, 3, 4]),
+            data_record: record.clone().into_boxed_slice(),
+        };
+        assert!(key_value_cell.spill(200).is_none());
+
+        let spilled = key_value_cell.spill(50).expect("should spill");
+        key_value_cell.set_overflow_page(PageId::new(7.try_into().unwrap()));
+        assert_eq!(key_value_cell.len(), 50);
+        assert_eq!(
+            key_value_cell.overflow(),
+            Some((PageId::new(7.try_into().unwrap()), 100))
+        );
+        assert_eq!(
+            [key_value_cell.record(), &spilled].concat(),
+            record,
+            "inline and spilled parts should make up the record"
+        );
+
+        let mut buffer = vec![0_u8; 50];
+        key_value_cell
+            .write(&mut buffer)
+            .expect("could not write key cell");
+        let read_key_cell = KeyValueCell::read(&buffer).expect("could not read key cell");
+        assert_eq!(read_key_cell, key_value_cell);
+        assert!(key_value_cell.spill(20).is_none(), "can only spill once");
+    }
 }
 
 /// A page id
@@ -79,6 +79,9 @@ impl<'a, P: Page<'a>> SlottedPageShared<'a, P> {
     /// If present, `Ok(index)` is returned, and if not present `Err(index)` is returned, where the index
     /// is where the key data could be inserted to maintain sort order.
     pub fn binary_search(&self, key_data: &KeyData) -> Result<Result<usize, usize>, WeaverError> {
+        if self.count() == 0 {
+            return Ok(Err(0));
+        }
         let mut l: usize = 0;
         let mut r: usize = self.count().saturating_sub(1);
 
@@ -128,28 +131,32 @@ impl<'a, P: Page<'a>> SlottedPageShared<'a, P> {
         }
         let range = key_data.into();
         let l = match range.start_bound() {
-            Bound::Included(i) => match self.binary_search(i)? {
-                Ok(ok) => ok,
-                Err(err) => err,
-            },
-            Bound::Excluded(e) => match self.binary_search(e)? {
-                Ok(ok) => ok + 1,
-                Err(err) => err,
-            },
+            Bound::Included(i) => self.partition_point(|key_data| key_data < i)?,
+            Bound::Excluded(e) => self.partition_point(|key_data| key_data <= e)?,
             Bound::Unbounded => 0,
         };
         let r = match range.end_bound() {
-            Bound::Included(i) => match self.binary_search(i)? {
-                Ok(ok) => ok,
-                Err(err) => err,
-            },
-            Bound::Excluded(e) => match self.binary_search(e)? {
-                Ok(ok) => ok - 1,
-                Err(err) => err,
-            },
-            Bound::Unbounded => self.count() - 1,
+            Bound::Included(i) => self.partition_point(|key_data| key_data <= i)?,
+            Bound::Excluded(e) => self.partition_point(|key_data| key_data < e)?,
+            Bound::Unbounded => self.count(),
         };
-        (l..=r).map(|index| self.get_cell(index)).collect()
+        (l..r).map(|index| self.get_cell(index)).collect()
+    }
+
+    /// Gets the index of the first cell whose key data doesn't match the predicate, which must
+    /// match every cell before that index and none after it
+    fn partition_point<F: Fn(&KeyData) -> bool>(&self, predicate: F) -> Result<usize, WeaverError> {
+        let mut l: usize = 0;
+        let mut r: usize = self.count();
+        while l < r {
+            let m = l + (r - l) / 2;
+            if predicate(&self.get_key_data(m)?) {
+                l = m + 1;
+            } else {
+                r = m;
+            }
+        }
+        Ok(l)
     }
 
     /// Gets all the cells within this page
@@ -190,6 +197,10 @@ impl<'a, P: Page<'a>> SlottedPageShared<'a, P> {
         match self.page_type() {
             PageType::Key => Ok(KeyCell::read(&slice)?.into()),
             PageType::KeyValue => Ok(KeyValueCell::read(&slice)?.into()),
+            PageType::Overflow => Err(WeaverError::custom(format!(
+                "overflow page {} does not contain cells",
+                self.page_id()
+            ))),
         }
     }
 
@@ -242,6 +253,14 @@ impl<'a, P: Page<'a>> SlottedPageShared<'a, P> {
                 expected: PageType::Key,
                 actual: PageType::KeyValue,
             }),
+            (cell, PageType::Overflow) => Err(WeaverError::CellTypeMismatch {
+                page_id: self.page_id(),
+                expected: PageType::Overflow,
+                actual: match cell {
+                    Cell::Key(_) => PageType::Key,
+                    Cell::KeyValue(_) => PageType::KeyValue,
+                },
+            }),
             _ => Ok(()),
         }
     }
@@ -302,6 +321,45 @@ impl<'a, P: Page<'a>> SlottedPageShared<'a, P> {
         self.get_key_data(mid).map(Some)
     }
 
+    /// Gets the space used by the header, the slots, and the cells
+    pub fn used(&self) -> usize {
+        self.all()
+            .into_iter()
+            .flatten()
+            .map(|cell| cell.len())
+            .sum::<usize>()
+            + self.count() * size_of::<u64>()
+            + size_of::<SlottedPageHeader>()
+    }
+
+    /// Gets the free space left over in this page
+    pub fn free_space(&self) -> usize {
+        self.len() - self.used()
+    }
+
+    /// Gets the part of an overflowing record stored in this overflow page.
+    ///
+    /// The rest of the record continues in the right sibling of this page, if it has one.
+    pub fn overflow_data(&self) -> Result<&[u8], WeaverError> {
+        self.assert_overflow()?;
+        let body = self.page.as_slice();
+        let len = u32::read(body)? as usize;
+        body.get(size_of::<u32>()..)
+            .and_then(|data| data.get(..len))
+            .ok_or_else(|| ReadDataError::UnexpectedEof.into())
+    }
+
+    fn assert_overflow(&self) -> Result<(), WeaverError> {
+        match self.page_type() {
+            PageType::Overflow => Ok(()),
+            actual => Err(WeaverError::CellTypeMismatch {
+                page_id: self.page_id(),
+                expected: PageType::Overflow,
+                actual,
+            }),
+        }
+    }
+
     /// Gets the range of the key data
     pub fn key_range(&self) -> Result<KeyDataRange, WeaverError> {
         let min = self
@@ -317,6 +375,17 @@ impl<'a, P: Page<'a>> SlottedPageShared<'a, P> {
 }
 
 impl<'a, P: PageMut<'a>> SlottedPageShared<'a, P> {
+    /// Writes as much of the given data as fits into this overflow page, returning the number of
+    /// bytes written.
+    pub fn write_overflow_data(&mut self, data: &[u8]) -> Result<usize, WeaverError> {
+        self.assert_overflow()?;
+        let body = self.page.as_mut_slice();
+        let len = data.len().min(body.len() - size_of::<u32>());
+        (len as u32).write(body)?;
+        body[size_of::<u32>()..][..len].copy_from_slice(&data[..len]);
+        Ok(len)
+    }
+
     /// Insert a cell into a slotted page. Must lock the cell
     pub fn insert(&mut self, cell: Cell) -> Result<(), WeaverError> {
         self.assert_cell_type(&cell)?;
@@ -420,22 +489,6 @@ impl<'a, P: PageMut<'a>> SlottedPageShared<'a, P> {
         Ok(cells)
     }
 
-    /// Gets the space used by the header, the slots, and the cells
-    pub fn used(&self) -> usize {
-        self.all()
-            .into_iter()
-            .flatten()
-            .map(|cell| cell.len())
-            .sum::<usize>()
-            + self.count() * size_of::<u64>()
-            + size_of::<SlottedPageHeader>()
-    }
-
-    /// Gets the free space left over in this page
-    pub fn free_space(&self) -> usize {
-        self.len() - self.used()
-    }
-
     /// allocate a given length within the slotted page
     ///
     /// If successful, returns the offset where the cell should be inserted, otherwise
@@ -902,6 +955,8 @@ impl StorageBackedData for SlottedPageHeader {
 pub enum PageType {
     Key = 1,
     KeyValue = 2,
+    /// Stores the part of a record that doesn't fit in its key value cell
+    Overflow = 3,
 }
 
 impl StorageBackedData for PageType {
@@ -910,6 +965,7 @@ impl StorageBackedData for PageType {
         match buf.first() {
             Some(1) => Ok(PageType::Key),
             Some(2) => Ok(PageType::KeyValue),
+            Some(3) => Ok(PageType::Overflow),
             Some(_) => Err(ReadDataError::BadMagicNumber),
             None => Err(ReadDataError::UnexpectedEof),
         }
@@ -969,7 +1025,7 @@ impl<P: Pager> SlottedPager<P> {
             .map(|p| p.page_id())
             .max()
         {
-            paged.next_page_id = AtomicU32::new(max.as_u32());
+            paged.next_page_id = AtomicU32::new(max.as_u32() + 1);
         }
 
         for (page, index) in (0..paged.allocated())
@@ -1003,6 +1059,33 @@ impl<P: Pager> SlottedPager<P> {
         Ok((new, index))
     }
 
+    /// Gets the space available for cells and their slots within each page
+    pub fn body_size(&self) -> usize {
+        self.page_size() - size_of::<SlottedPageHeader>()
+    }
+
+    /// Makes a page that is no longer used available to [`new_with_type`](Self::new_with_type) again.
+    ///
+    /// The page keeps its place in the underlying pager, and is only reused by this slotted pager.
+    pub fn recycle(&self, id: PageId) -> Result<(), WeaverError> {
+        if self
+            .usage
+            .lock()
+            .get(&id)
+            .is_some_and(|usage| usage.load(Ordering::SeqCst) != 0)
+        {
+            return Err(WeaverError::ReadDataError(ReadDataError::PageLocked(id)));
+        }
+        let index = self
+            .page_id_to_index
+            .write()
+            .remove(&id)
+            .ok_or(WeaverError::ReadDataError(ReadDataError::PageNotFound(id)))?;
+        self.usage.lock().remove(&id);
+        self.free_list.write().push_back(index);
+        Ok(())
+    }
+
     /// Gets the page by a given page_id
     pub fn get(&self, id: PageId) -> Result<SlottedPage<P::Page<'_>>, WeaverError> {
         let lock = self.usage.lock().entry(id).or_default().clone();
@@ -1392,6 +1475,16 @@ mod tests {
             .get_range(KeyData::from([8])..=KeyData::from([24]))
             .unwrap();
         assert_eq!(cells.len(), 17);
+        assert!(page
+            .get_range(KeyData::from([40])..=KeyData::from([40]))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            page.get_range(KeyData::from([30])..KeyData::from([40]))
+                .unwrap()
+                .len(),
+            2
+        );
         println!(
             "cells: {:#?}",
             cells.iter().map(Cell::key_data).collect::<Vec<_>>()
@@ -1,6 +1,7 @@
 //! Table in a file
 
 use std::fs::File;
+use std::io;
 use std::path::{Path, PathBuf};
 
 use cfg_if::cfg_if;
@@ -115,8 +116,27 @@ impl BptfTableFactory {
         }
     }
 
+    /// The file a table is stored in
+    fn file_location(&self, schema: &TableSchema) -> PathBuf {
+        self.base_dir.join(schema.schema()).join(schema.name())
+    }
+
+    /// The file a table is staged in before it replaces the table's own file
+    fn staging_location(&self, schema: &TableSchema) -> PathBuf {
+        self.base_dir
+            .join(schema.schema())
+            .join(format!("{}.staging", schema.name()))
+    }
+
     fn open(&self, schema: &TableSchema) -> Result<BptfTable, WeaverError> {
-        let file_location = self.base_dir.join(schema.schema()).join(schema.name());
+        self.open_at(schema, self.file_location(schema))
+    }
+
+    fn open_at(
+        &self,
+        schema: &TableSchema,
+        file_location: PathBuf,
+    ) -> Result<BptfTable, WeaverError> {
         if let Some(parent) = file_location.parent() {
             std::fs::create_dir_all(parent)?;
         }
@@ -161,6 +181,65 @@ impl DynamicTableFactory for BptfTableFactory {
     fn open(&self, schema: &TableSchema, _core: &WeaverDbCore) -> Result<Table, WeaverError> {
         self.open(schema).map(|s| Box::new(s) as Table)
     }
+
+    fn destroy(&self, schema: &TableSchema) -> Result<(), WeaverError> {
+        remove_table_file(&self.file_location(schema))
+    }
+
+    fn open_staged(
+        &self,
+        schema: &TableSchema,
+        _core: &WeaverDbCore,
+    ) -> Result<Table, WeaverError> {
+        let staging_location = self.staging_location(schema);
+        // staging left behind by an interrupted alteration must not leak into the new table
+        if staging_location.exists() {
+            std::fs::remove_file(&staging_location)?;
+        }
+        self.open_at(schema, staging_location)
+            .map(|s| Box::new(s) as Table)
+    }
+
+    fn promote_staged(
+        &self,
+        staged: Table,
+        schema: &TableSchema,
+        _core: &WeaverDbCore,
+    ) -> Result<Table, WeaverError> {
+        drop(staged);
+        let staging_location = self.staging_location(schema);
+        if !staging_location.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no staged table at {staging_location:?}"),
+            )
+            .into());
+        }
+        // the staged table is reopened before it replaces the file of the table, so the file is
+        // left untouched if it can't be reopened. the open file follows the rename.
+        let table = self.open_at(schema, staging_location.clone())?;
+        std::fs::rename(&staging_location, self.file_location(schema))?;
+        Ok(Box::new(table))
+    }
+
+    fn destroy_staged(&self, schema: &TableSchema) -> Result<(), WeaverError> {
+        remove_table_file(&self.staging_location(schema))
+    }
+}
+
+/// Deletes the file of a table, along with its schema directory if it no longer contains any
+/// tables
+fn remove_table_file(file_location: &Path) -> Result<(), WeaverError> {
+    debug!("deleting Bptf table at {file_location:?}");
+    if file_location.exists() {
+        std::fs::remove_file(file_location)?;
+    }
+    if let Some(parent) = file_location.parent() {
+        if parent.exists() && parent.read_dir()?.next().is_none() {
+            std::fs::remove_dir(parent)?;
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -176,7 +255,9 @@ mod tests {
     use crate::data::row::Row;
     use crate::data::types::Type;
     use crate::data::values::DbVal;
+    use crate::db::core::WeaverDbCore;
     use crate::dynamic_table::{DynamicTable, EngineKey};
+    use crate::dynamic_table_factory::DynamicTableFactory;
     use crate::error::WeaverError;
     use crate::key::KeyData;
     use crate::monitoring::Monitorable;
@@ -299,4 +380,72 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn promote_staged_replaces_table() -> Result<(), WeaverError> {
+        let temp_dir = tempdir().expect("could not create temp dir");
+        let core = WeaverDbCore::with_path(temp_dir.path())?;
+        let factory = BptfTableFactory::new(temp_dir.path());
+        let schema = TableSchema::builder("test", "test")
+            .column("name", Type::String(16), true, None, None)?
+            .primary(&["name"])?
+            .build()?;
+        let names = |table: &dyn DynamicTable| -> Result<Vec<DbVal>, WeaverError> {
+            let tx = Tx::default();
+            let names = table
+                .all(&tx)?
+                .into_iter()
+                .map(|row| row[0].clone().into_owned())
+                .collect();
+            Ok(names)
+        };
+        let tx = Tx::default();
+
+        let table = factory.open(&schema)?;
+        table.insert(&tx, Row::from(["josh"]))?;
+        table.commit(&tx);
+        drop(table);
+
+        let staged = DynamicTableFactory::open_staged(&factory, &schema, &core)?;
+        staged.insert(&tx, Row::from(["chris"]))?;
+        staged.commit(&tx);
+        std::fs::remove_file(factory.staging_location(&schema))?;
+        assert!(
+            factory.promote_staged(staged, &schema, &core).is_err(),
+            "staging storage that's gone can't be promoted"
+        );
+        let table = factory.open(&schema)?;
+        assert_eq!(
+            names(&table)?,
+            [DbVal::from("josh")],
+            "a failed promotion should leave the table untouched"
+        );
+        drop(table);
+
+        let staged = DynamicTableFactory::open_staged(&factory, &schema, &core)?;
+        drop(staged);
+        std::fs::write(factory.staging_location(&schema), b"not a table")?;
+        assert!(
+            factory
+                .promote_staged(Box::new(factory.open(&schema)?), &schema, &core)
+                .is_err(),
+            "staging storage that can't be reopened can't be promoted"
+        );
+        let table = factory.open(&schema)?;
+        assert_eq!(
+            names(&table)?,
+            [DbVal::from("josh")],
+            "a staged table that can't be reopened should leave the table untouched"
+        );
+        drop(table);
+
+        let staged = DynamicTableFactory::open_staged(&factory, &schema, &core)?;
+        staged.insert(&tx, Row::from(["chris"]))?;
+        staged.commit(&tx);
+        let table = factory.promote_staged(staged, &schema, &core)?;
+        assert_eq!(names(&*table)?, [DbVal::from("chris")]);
+        assert!(!factory.staging_location(&schema).exists());
+
+        Ok(())
+    }
 }
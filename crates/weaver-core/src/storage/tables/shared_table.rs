@@ -4,53 +4,81 @@ use crate::data::row::Row;
 use crate::dynamic_table::{Col, DynamicTable, HasSchema, Table};
 use crate::error::WeaverError;
 use crate::monitoring::{Monitor, Monitorable};
-use crate::rows::{KeyIndex, Rows};
+use crate::rows::{KeyIndex, OwnedRows, Rows};
 use crate::storage::tables::table_schema::TableSchema;
 use crate::tx::Tx;
-use std::sync::Arc;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
 
-/// A shared table
+mod batches;
+pub(crate) mod foreign_keys;
+
+/// The open tables of a core, by schema and name
+pub type OpenTables = RwLock<HashMap<(String, String), SharedTable>>;
+
+/// A shared table.
+///
+/// Writes to a shared table enforce the foreign keys between it and the other tables open
+/// alongside it.
 #[derive(Debug, Clone)]
-pub struct SharedTable(Arc<Table>);
+pub struct SharedTable {
+    table: Arc<Table>,
+    open_tables: Weak<OpenTables>,
+}
 
 impl SharedTable {
     /// A shared table
     pub fn new(table: Table) -> Self {
-        Self(Arc::new(table))
+        Self {
+            table: Arc::new(table),
+            open_tables: Weak::new(),
+        }
+    }
+
+    /// A shared table that's open alongside the given tables, which its foreign keys can
+    /// reference
+    pub fn with_open_tables(table: Table, open_tables: &Arc<OpenTables>) -> Self {
+        Self {
+            table: Arc::new(table),
+            open_tables: Arc::downgrade(open_tables),
+        }
     }
 }
 
 impl HasSchema for SharedTable {
     fn schema(&self) -> &TableSchema {
-        self.0.schema()
+        self.table.schema()
     }
 }
 
 impl Monitorable for SharedTable {
     fn monitor(&self) -> Box<dyn Monitor> {
-        self.0.monitor()
+        self.table.monitor()
     }
 }
 
 impl DynamicTable for SharedTable {
     fn auto_increment(&self, col: Col) -> i64 {
-        self.0.auto_increment(col)
+        self.table.auto_increment(col)
     }
 
     fn next_row_id(&self) -> i64 {
-        self.0.next_row_id()
+        self.table.next_row_id()
     }
 
     fn commit(&self, tx: &Tx) {
-        self.0.commit(tx)
+        self.table.commit(tx)
     }
 
     fn rollback(&self, tx: &Tx) {
-        self.0.rollback(tx)
+        self.table.rollback(tx)
     }
 
+    /// Inserts a row, checking that its foreign keys reference existing rows first
     fn insert(&self, tx: &Tx, row: Row) -> Result<(), WeaverError> {
-        self.0.insert(tx, row)
+        self.check_references(tx, &row)?;
+        self.table.insert(tx, row)
     }
 
     fn read<'tx, 'table: 'tx>(
@@ -58,25 +86,50 @@ impl DynamicTable for SharedTable {
         tx: &'tx Tx,
         key: &KeyIndex,
     ) -> Result<Box<dyn Rows<'tx> + 'tx + Send>, WeaverError> {
-        self.0.read(tx, key)
+        self.table.read(tx, key)
     }
 
     fn all<'tx, 'table: 'tx>(
         &'table self,
         tx: &'tx Tx,
     ) -> Result<Box<dyn Rows<'tx> + 'tx + Send>, WeaverError> {
-        self.0.all(tx)
+        self.table.all(tx)
     }
 
     fn size_estimate(&self, key_index: &KeyIndex) -> Result<u64, WeaverError> {
-        self.0.size_estimate(key_index)
+        self.table.size_estimate(key_index)
     }
 
+    /// Updates a row, enforcing the foreign keys of the values it changes
     fn update(&self, tx: &Tx, row: Row) -> Result<(), WeaverError> {
-        self.0.update(tx, row)
+        if !self.constrained() {
+            return self.table.update(tx, row);
+        }
+        match self.stored(tx, &row)? {
+            Some(old) => self.update_row(tx, &Row::from(old), row),
+            None => self.table.update(tx, row),
+        }
     }
 
+    /// Deletes every row matching a key, applying the `on delete` action of every foreign key
+    /// referencing them
     fn delete(&self, tx: &Tx, key: &KeyIndex) -> Result<Box<dyn Rows>, WeaverError> {
-        self.0.delete(tx, key)
+        if !self.constrained() {
+            return self.table.delete(tx, key);
+        }
+        let rows = {
+            let mut read = self.table.read(tx, key)?;
+            let mut rows = vec![];
+            while let Some(row) = read.next() {
+                rows.push(row.to_owned());
+            }
+            rows
+        };
+
+        let mut deleted = vec![];
+        for row in rows {
+            deleted.extend(self.delete_row(tx, &Row::from(row))?);
+        }
+        Ok(Box::new(OwnedRows::new(self.schema().clone(), deleted)))
     }
 }
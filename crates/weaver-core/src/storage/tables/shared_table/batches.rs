@@ -0,0 +1,83 @@
+//! Writes of many rows to shared tables, which either all succeed or leave the tables as they were
+
+use tracing::trace;
+
+use crate::data::row::{OwnedRow, Row};
+use crate::data::values::DbVal;
+use crate::dynamic_table::{DynamicTable, HasSchema, ROW_ID_COLUMN};
+use crate::error::WeaverError;
+use crate::rows::Rows;
+use crate::storage::tables::shared_table::SharedTable;
+use crate::tx::{Tx, TX_ID_COLUMN};
+
+impl SharedTable {
+    /// Inserts rows in order, returning how many were inserted.
+    ///
+    /// If any row can't be inserted, the rows already inserted are deleted again before the error
+    /// is returned.
+    pub(crate) fn insert_rows(&self, tx: &Tx, rows: Vec<OwnedRow>) -> Result<usize, WeaverError> {
+        // every inserted row is given a larger row id than this one
+        let first_row_id = self.next_row_id();
+        for (inserted, row) in rows.iter().enumerate() {
+            if let Err(e) = self.insert(tx, Row::from(row.clone())) {
+                if inserted > 0 {
+                    trace!("undoing {inserted} inserted rows after {e}");
+                    self.delete_inserted(tx, first_row_id)?;
+                }
+                return Err(e);
+            }
+        }
+        Ok(rows.len())
+    }
+
+    /// Replaces every old row with its new version in order, returning how many were replaced.
+    ///
+    /// If any row can't be replaced, the rows already replaced are put back before the error is
+    /// returned.
+    pub(crate) fn update_rows(
+        &self,
+        tx: &Tx,
+        updates: Vec<(OwnedRow, OwnedRow)>,
+    ) -> Result<usize, WeaverError> {
+        let mut updated: Vec<(OwnedRow, OwnedRow)> = vec![];
+        for (old, new) in updates {
+            if let Err(e) = self.update_row(tx, &Row::from(old.clone()), Row::from(new.clone())) {
+                trace!("undoing {} updated rows after {e}", updated.len());
+                for (old, new) in updated.into_iter().rev() {
+                    self.update_row(tx, &Row::from(new), Row::from(old))?;
+                }
+                return Err(e);
+            }
+            updated.push((old, new));
+        }
+        Ok(updated.len())
+    }
+
+    /// Deletes the rows this transaction inserted with a row id of at least the given one, newest
+    /// first
+    fn delete_inserted(&self, tx: &Tx, first_row_id: i64) -> Result<(), WeaverError> {
+        let table_schema = self.schema();
+        let row_id_index = table_schema
+            .column_index(ROW_ID_COLUMN)
+            .expect("every table has a row id");
+        let tx_id_index = table_schema.column_index(TX_ID_COLUMN);
+        let tx_id = DbVal::Integer(tx.id().into());
+
+        let mut inserted = vec![];
+        let mut all = self.all(tx)?;
+        while let Some(row) = all.next() {
+            let row_id = row[row_id_index].int_value().unwrap_or(i64::MIN);
+            let own = tx_id_index.map_or(true, |idx| *row[idx] == tx_id);
+            if row_id >= first_row_id && own {
+                inserted.push((row_id, row.to_owned()));
+            }
+        }
+        drop(all);
+
+        inserted.sort_by_key(|(row_id, _)| -row_id);
+        for (_, row) in inserted {
+            self.delete_row(tx, &Row::from(row))?;
+        }
+        Ok(())
+    }
+}
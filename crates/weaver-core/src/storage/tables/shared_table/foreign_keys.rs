@@ -0,0 +1,449 @@
+//! Enforces the referential integrity of foreign keys on writes to shared tables
+
+use std::borrow::Cow;
+
+use tracing::trace;
+
+use weaver_ast::ast::ReferentialAction;
+
+use crate::data::row::{OwnedRow, Row};
+use crate::data::values::DbVal;
+use crate::dynamic_table::{DynamicTable, HasSchema};
+use crate::error::WeaverError;
+use crate::key::KeyData;
+use crate::rows::{KeyIndex, KeyIndexKind, Rows};
+use crate::storage::tables::shared_table::SharedTable;
+use crate::storage::tables::table_schema::{ForeignKey, Key, TableSchema};
+use crate::tx::Tx;
+
+/// The rows referencing a parent row through a single foreign key
+struct References {
+    table: SharedTable,
+    foreign_key: ForeignKey,
+    value: KeyData,
+    rows: Vec<OwnedRow>,
+}
+
+impl SharedTable {
+    /// Replaces a row of this table with an updated version of it.
+    ///
+    /// Foreign keys whose values changed are checked, and the `on update` action of every foreign
+    /// key referencing changed values is applied. Rows with a changed primary key are moved. If the
+    /// row or any row it cascades to can't be updated, every one of them is put back.
+    pub(crate) fn update_row(&self, tx: &Tx, old: &Row, new: Row) -> Result<(), WeaverError> {
+        let table_schema = self.schema();
+        for foreign_key in table_schema.foreign_keys() {
+            if table_schema.foreign_key_data(foreign_key, old)
+                != table_schema.foreign_key_data(foreign_key, &new)
+            {
+                self.check_reference(tx, foreign_key, &new)?;
+            }
+        }
+
+        let references = self.references(tx, old, |foreign_key| {
+            table_schema.referenced_data(foreign_key, old)
+                != table_schema.referenced_data(foreign_key, &new)
+        })?;
+        for references in &references {
+            if references.foreign_key.on_update() == ReferentialAction::Restrict {
+                return Err(WeaverError::RowReferenced {
+                    foreign_key: references.foreign_key.name().to_string(),
+                    value: references.value.clone(),
+                });
+            }
+        }
+
+        let new_values = |foreign_key: &ForeignKey| {
+            foreign_key
+                .parent_columns()
+                .iter()
+                .map(|col| {
+                    table_schema
+                        .column_index(col)
+                        .map_or(DbVal::Null, |idx| new[idx].as_ref().clone())
+                })
+                .collect::<Vec<_>>()
+        };
+        let updates = references
+            .iter()
+            .map(|references| match references.foreign_key.on_update() {
+                ReferentialAction::Cascade => new_values(&references.foreign_key),
+                _ => vec![DbVal::Null; references.foreign_key.columns().len()],
+            })
+            .collect::<Vec<_>>();
+        let mut cascades = vec![];
+        for (
+            References {
+                table: child,
+                foreign_key,
+                rows,
+                ..
+            },
+            values,
+        ) in references.into_iter().zip(updates)
+        {
+            for child_row in rows {
+                let new_row = replace_values(
+                    child.schema(),
+                    foreign_key.columns(),
+                    values.clone(),
+                    &Row::from(child_row.clone()),
+                )?;
+                cascades.push((child.clone(), child_row, OwnedRow::from(new_row)));
+            }
+        }
+
+        let stored = OwnedRow::from(&new);
+        self.store_update(tx, old, new)?;
+        let mut cascaded: Vec<(SharedTable, OwnedRow, OwnedRow)> = vec![];
+        for (child, child_row, new_row) in cascades {
+            trace!("cascading update to {:?}", new_row);
+            if let Err(e) = child.update_row(
+                tx,
+                &Row::from(child_row.clone()),
+                Row::from(new_row.clone()),
+            ) {
+                // this row is put back first, so the rows put back after it reference it again
+                self.store_update(tx, &Row::from(stored), old.slice(..))?;
+                for (child, child_row, new_row) in cascaded.into_iter().rev() {
+                    child.update_row(tx, &Row::from(new_row), Row::from(child_row))?;
+                }
+                return Err(e);
+            }
+            cascaded.push((child, child_row, new_row));
+        }
+        Ok(())
+    }
+
+    /// Deletes a row of this table by its primary key, returning the deleted row if it existed.
+    ///
+    /// The `on delete` action of every foreign key referencing the row is applied.
+    pub(crate) fn delete_row(&self, tx: &Tx, row: &Row) -> Result<Option<OwnedRow>, WeaverError> {
+        let table_schema = self.schema();
+        let references = self.references(tx, row, |_| true)?;
+        for references in &references {
+            if references.foreign_key.on_delete() == ReferentialAction::Restrict {
+                return Err(WeaverError::RowReferenced {
+                    foreign_key: references.foreign_key.name().to_string(),
+                    value: references.value.clone(),
+                });
+            }
+        }
+
+        let primary_key = table_schema.primary_key()?;
+        let mut removed = self.table.delete(
+            tx,
+            &KeyIndex::new(
+                primary_key.name(),
+                KeyIndexKind::One(table_schema.key_data(primary_key, row)),
+                None,
+                None,
+            ),
+        )?;
+        let deleted = removed.next().map(|row| row.to_owned());
+        while removed.next().is_some() {}
+        drop(removed);
+
+        for References {
+            table: child,
+            foreign_key,
+            rows,
+            ..
+        } in references
+        {
+            for child_row in rows {
+                let child_row = Row::from(child_row);
+                if foreign_key.on_delete() == ReferentialAction::Cascade {
+                    trace!("cascading delete to {:?}", child_row);
+                    child.delete_row(tx, &child_row)?;
+                } else {
+                    let nulls = vec![DbVal::Null; foreign_key.columns().len()];
+                    let new_row =
+                        replace_values(child.schema(), foreign_key.columns(), nulls, &child_row)?;
+                    child.update_row(tx, &child_row, new_row)?;
+                }
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// Checks whether this table has foreign keys, or is referenced by the foreign keys of any table
+    /// open alongside it
+    pub(super) fn constrained(&self) -> bool {
+        let table_schema = self.schema();
+        !table_schema.foreign_keys().is_empty()
+            || !referencing(
+                self.open_tables(),
+                table_schema.schema(),
+                table_schema.name(),
+            )
+            .is_empty()
+    }
+
+    /// Gets every table open alongside this one, including this table itself
+    fn open_tables(&self) -> Vec<SharedTable> {
+        match self.open_tables.upgrade() {
+            Some(open_tables) => open_tables.read().values().cloned().collect(),
+            None => vec![self.clone()],
+        }
+    }
+
+    /// Gets the stored version of a row, found by its primary key
+    pub(super) fn stored(&self, tx: &Tx, row: &Row) -> Result<Option<OwnedRow>, WeaverError> {
+        let table_schema = self.schema();
+        let primary_key = table_schema.primary_key()?;
+        let primary = table_schema.key_data(primary_key, row);
+        // reads by key may return more than the matching rows, so their keys are compared again
+        let mut read = self.table.read(
+            tx,
+            &KeyIndex::new(
+                primary_key.name(),
+                KeyIndexKind::One(primary.clone()),
+                None,
+                None,
+            ),
+        )?;
+        while let Some(stored) = read.next() {
+            if table_schema.key_data(primary_key, &stored) == primary {
+                return Ok(Some(stored.to_owned()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Stores an updated row. Rows with a changed primary key are moved by deleting the old row
+    /// and inserting the new one.
+    fn store_update(&self, tx: &Tx, old: &Row, new: Row) -> Result<(), WeaverError> {
+        let table_schema = self.schema();
+        let primary_key = table_schema.primary_key()?;
+        let old_primary = table_schema.key_data(primary_key, old);
+        let new_primary = table_schema.key_data(primary_key, &new);
+        if old_primary == new_primary {
+            return self.table.update(tx, new);
+        }
+
+        let primary_index = |key_data: KeyData| {
+            KeyIndex::new(primary_key.name(), KeyIndexKind::One(key_data), None, None)
+        };
+        let mut existing = self.table.read(tx, &primary_index(new_primary.clone()))?;
+        let taken = existing.next().is_some();
+        drop(existing);
+        if taken {
+            return Err(WeaverError::UniqueKeyViolation {
+                key: primary_key.name().to_string(),
+                value: new_primary,
+            });
+        }
+
+        let mut removed = self.table.delete(tx, &primary_index(old_primary))?;
+        while removed.next().is_some() {}
+        drop(removed);
+        if let Err(e) = self.table.insert(tx, table_schema.public_only(new)) {
+            // the old row was valid, so it can always be put back
+            self.table
+                .insert(tx, table_schema.public_only(old.slice(..)))?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Checks that every foreign key of a row references an existing row of its parent table.
+    ///
+    /// Foreign keys with any null value are not checked.
+    pub(super) fn check_references(&self, tx: &Tx, row: &Row) -> Result<(), WeaverError> {
+        self.schema()
+            .foreign_keys()
+            .iter()
+            .try_for_each(|foreign_key| self.check_reference(tx, foreign_key, row))
+    }
+
+    fn check_reference(
+        &self,
+        tx: &Tx,
+        foreign_key: &ForeignKey,
+        row: &Row,
+    ) -> Result<(), WeaverError> {
+        let table_schema = self.schema();
+        let Some(value) = table_schema.foreign_key_data(foreign_key, row) else {
+            return Ok(());
+        };
+        if foreign_key.references(table_schema.schema(), table_schema.name())
+            && table_schema.referenced_data(foreign_key, row).as_ref() == Some(&value)
+        {
+            // a row may reference itself
+            return Ok(());
+        }
+
+        let (schema, name) = foreign_key.parent();
+        let parent = self.open_table(schema, name)?;
+        let key = parent_key(parent.schema(), foreign_key)?;
+        // reads by key may return more than the matching rows, so their keys are compared again
+        let mut rows = parent.read(
+            tx,
+            &KeyIndex::new(key.name(), KeyIndexKind::One(value.clone()), None, None),
+        )?;
+        let mut exists = false;
+        while let Some(parent_row) = rows.next() {
+            if parent.schema().key_data(key, &parent_row) == value {
+                exists = true;
+                break;
+            }
+        }
+        drop(rows);
+        if exists {
+            Ok(())
+        } else {
+            Err(WeaverError::ForeignKeyViolation {
+                foreign_key: foreign_key.name().to_string(),
+                value,
+            })
+        }
+    }
+
+    /// Gets a table open alongside this one, which may be this table itself
+    fn open_table(&self, schema: &str, name: &str) -> Result<SharedTable, WeaverError> {
+        let table_schema = self.schema();
+        if table_schema.schema() == schema && table_schema.name() == name {
+            return Ok(self.clone());
+        }
+        self.open_tables
+            .upgrade()
+            .and_then(|open_tables| {
+                open_tables
+                    .read()
+                    .get(&(schema.to_string(), name.to_string()))
+                    .cloned()
+            })
+            .ok_or_else(|| WeaverError::NoTableFound {
+                table: name.to_string(),
+                schema: schema.to_string(),
+            })
+    }
+
+    /// Gets the rows referencing a row through every foreign key matching the filter. The row
+    /// itself is never included.
+    fn references<F: Fn(&ForeignKey) -> bool>(
+        &self,
+        tx: &Tx,
+        row: &Row,
+        filter: F,
+    ) -> Result<Vec<References>, WeaverError> {
+        let table_schema = self.schema();
+        let primary_key = table_schema.primary_key()?;
+        let primary = table_schema.key_data(primary_key, row);
+
+        let mut references = vec![];
+        for (table, foreign_key) in referencing(
+            self.open_tables(),
+            table_schema.schema(),
+            table_schema.name(),
+        ) {
+            if !filter(&foreign_key) {
+                continue;
+            }
+            let Some(value) = table_schema.referenced_data(&foreign_key, row) else {
+                continue;
+            };
+            let child_schema = table.schema();
+            let same_table = child_schema.schema() == table_schema.schema()
+                && child_schema.name() == table_schema.name();
+
+            // tables created before foreign keys were indexed have to be scanned
+            let key_index = match child_schema
+                .keys()
+                .iter()
+                .find(|key| key.columns() == foreign_key.columns())
+            {
+                Some(key) => {
+                    KeyIndex::new(key.name(), KeyIndexKind::One(value.clone()), None, None)
+                }
+                None => child_schema.full_index()?,
+            };
+            // reads by key may return more than the matching rows, so their keys are compared again
+            let mut rows = vec![];
+            let mut read = table.read(tx, &key_index)?;
+            while let Some(child_row) = read.next() {
+                if same_table && child_schema.key_data(primary_key, &child_row) == primary {
+                    continue;
+                }
+                if child_schema
+                    .foreign_key_data(&foreign_key, &child_row)
+                    .as_ref()
+                    == Some(&value)
+                {
+                    rows.push(child_row.to_owned());
+                }
+            }
+            drop(read);
+
+            if !rows.is_empty() {
+                references.push(References {
+                    table,
+                    foreign_key,
+                    value,
+                    rows,
+                });
+            }
+        }
+        Ok(references)
+    }
+}
+
+/// Gets every foreign key of the given tables referencing a table, along with the table it belongs
+/// to
+pub(crate) fn referencing<I: IntoIterator<Item = SharedTable>>(
+    tables: I,
+    schema: &str,
+    name: &str,
+) -> Vec<(SharedTable, ForeignKey)> {
+    tables
+        .into_iter()
+        .flat_map(|table| {
+            table
+                .schema()
+                .foreign_keys()
+                .iter()
+                .filter(|foreign_key| foreign_key.references(schema, name))
+                .map(|foreign_key| (table.clone(), foreign_key.clone()))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Finds the unique key of a parent table made up of exactly the columns referenced by a foreign
+/// key
+pub(crate) fn parent_key<'a>(
+    parent_schema: &'a TableSchema,
+    foreign_key: &ForeignKey,
+) -> Result<&'a Key, WeaverError> {
+    parent_schema
+        .keys()
+        .iter()
+        .find(|key| key.unique() && key.columns() == foreign_key.parent_columns())
+        .ok_or_else(|| WeaverError::IllegalForeignKey {
+            name: foreign_key.name().to_string(),
+            reason: format!(
+                "({}) is not a unique key of {}.{}",
+                foreign_key.parent_columns().join(", "),
+                parent_schema.schema(),
+                parent_schema.name()
+            ),
+        })
+}
+
+/// Creates a copy of a row with the given columns replaced
+fn replace_values<'a>(
+    table_schema: &TableSchema,
+    columns: &[String],
+    values: Vec<DbVal>,
+    row: &Row<'a>,
+) -> Result<Row<'a>, WeaverError> {
+    let mut new_row = row.slice(..);
+    for (col, value) in columns.iter().zip(values) {
+        let idx = table_schema
+            .column_index(col)
+            .ok_or_else(|| WeaverError::ColumnNotFound(col.clone()))?;
+        new_row[idx] = Cow::Owned(value);
+    }
+    Ok(new_row)
+}
@@ -11,16 +11,24 @@ use std::rc::Rc;
 use serde::{Deserialize, Serialize};
 use tracing::{trace, warn};
 
-use weaver_ast::ast::{Identifier, ResolvedColumnRef};
+use weaver_ast::ast;
+use weaver_ast::ast::visitor::VisitorMut;
+use weaver_ast::ast::{
+    BinaryOp, ColumnRef, Expr, Identifier, Literal, ReferencesCols, ReferentialAction,
+    ResolvedColumnRef,
+};
 use weaver_ast::ToSql;
 
+use crate::data::charset::Collation;
 use crate::data::row::{OwnedRow, Row};
 use crate::data::serde::{deserialize_data_untyped, serialize_data_untyped};
-use crate::data::types::Type;
+use crate::data::types::{DbTypeOf, Type};
 use crate::data::values::DbVal;
 use crate::dynamic_table::{Col, DynamicTable, EngineKey, ROW_ID_COLUMN};
 use crate::error::WeaverError;
 use crate::key::KeyData;
+use crate::queries::execution::evaluation::builtins::BUILTIN_FUNCTIONS_REGISTRY;
+use crate::queries::execution::evaluation::ExpressionEvaluator;
 use crate::rows::KeyIndex;
 use crate::storage::tables::in_memory_table::IN_MEMORY_KEY;
 use crate::tx::{Tx, TX_ID_COLUMN};
@@ -33,6 +41,10 @@ pub struct TableSchema {
     columns: Vec<ColumnDefinition>,
     sys_columns: Vec<ColumnDefinition>,
     keys: Vec<Key>,
+    #[serde(default)]
+    foreign_keys: Vec<ForeignKey>,
+    #[serde(default)]
+    checks: Vec<Check>,
     engine: EngineKey,
 }
 
@@ -44,6 +56,8 @@ impl TableSchema {
             columns: vec![],
             sys_columns: vec![],
             keys: vec![],
+            foreign_keys: vec![],
+            checks: vec![],
             engine: EngineKey::new(IN_MEMORY_KEY),
         }
     }
@@ -113,6 +127,16 @@ impl TableSchema {
         &self.keys
     }
 
+    /// Gets the foreign keys of this table
+    pub fn foreign_keys(&self) -> &[ForeignKey] {
+        &self.foreign_keys
+    }
+
+    /// Gets the check constraints of this table
+    pub fn checks(&self) -> &[Check] {
+        &self.checks
+    }
+
     /// Gets all non-primary keys
     pub fn secondary_keys(&self) -> Vec<&Key> {
         if let Ok(primary) = self.primary_key() {
@@ -246,6 +270,7 @@ impl TableSchema {
             sys_modified_row
         };
 
+        let evaluator = self.evaluator();
         row.iter_mut()
             .zip(self.all_columns())
             .map(|(val, col)| {
@@ -259,18 +284,120 @@ impl TableSchema {
                     _ => {}
                 }
 
-                if **val == DbVal::Null && col.default_value.is_some() {
-                    *val.to_mut() = col.default_value.as_ref().cloned().unwrap();
-                } else if **val == DbVal::Null && col.auto_increment.is_some() {
+                if **val == DbVal::Null && col.auto_increment.is_some() {
                     *val.to_mut() = DbVal::Integer(table.auto_increment(col.name()));
                 }
                 col.validate(val)
             })
             .collect::<Result<Vec<_>, _>>()?;
+        self.fill_generated(&mut row)?;
+        self.check_constraints(&row, evaluator.as_ref())?;
+
+        Ok(row)
+    }
+
+    /// Sets every public column of a row that wasn't supplied a value to its default value, if it
+    /// has one. Supplied values are kept as they are, even when they are null.
+    pub fn fill_defaults(&self, row: &mut Row, supplied: &[usize]) -> Result<(), WeaverError> {
+        let evaluator = self.evaluator();
+        for (idx, col) in self.columns().iter().enumerate() {
+            if supplied.contains(&idx) {
+                continue;
+            }
+            if let Some(default) = &col.default_value {
+                row[idx] = Cow::Owned(default.clone());
+            } else if let (Some(default), Some(evaluator)) = (&col.default_expr, &evaluator) {
+                row[idx] = Cow::Owned(
+                    col.coerce_default(
+                        evaluator
+                            .evaluate_one_row(default, &Row::new(0), self, None)?
+                            .into_owned(),
+                    )?,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates a row that replaces an already existing row.
+    ///
+    /// Unlike [`validate`](Self::validate), the row must contain every column, including system
+    /// columns. System columns are kept as is, except for the transaction id.
+    pub fn validate_update<'a>(&self, mut row: Row<'a>, tx: &Tx) -> Result<Row<'a>, WeaverError> {
+        trace!("validating update: {:?}", row);
+        if row.len() != self.all_columns().len() {
+            return Err(WeaverError::BadColumnCount {
+                expected: self.all_columns().len(),
+                actual: row.len(),
+            });
+        }
+
+        row.iter_mut()
+            .zip(self.all_columns())
+            .map(|(val, col)| {
+                if col.name() == TX_ID_COLUMN {
+                    *val.to_mut() = DbVal::Integer(tx.id().into());
+                }
+                col.validate(val)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.fill_generated(&mut row)?;
+        self.check_constraints(&row, self.evaluator().as_ref())?;
 
         Ok(row)
     }
 
+    /// Computes the values of the generated columns of a row that has every column of this schema
+    pub(crate) fn fill_generated(&self, row: &mut Row) -> Result<(), WeaverError> {
+        let generated = self
+            .all_columns()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, col)| Some((idx, col, col.generated.as_ref()?)))
+            .collect::<Vec<_>>();
+        if generated.is_empty() {
+            return Ok(());
+        }
+        let evaluator = ExpressionEvaluator::new(None);
+        for (idx, col, expr) in generated {
+            let value = evaluator
+                .evaluate_one_row(expr, row, self, None)?
+                .into_owned();
+            row[idx] = Cow::Owned(col.data_type.coerce(value)?);
+        }
+        Ok(())
+    }
+
+    /// Creates an evaluator for default expressions and checks, if this table uses any
+    fn evaluator(&self) -> Option<ExpressionEvaluator> {
+        (!self.checks.is_empty() || self.columns.iter().any(|col| col.default_expr.is_some()))
+            .then(|| ExpressionEvaluator::new(None))
+    }
+
+    /// Checks that a row satisfies every check constraint.
+    ///
+    /// Like in SQL, a check is only violated when it's false, so a check that evaluates to null is
+    /// satisfied.
+    fn check_constraints(
+        &self,
+        row: &Row,
+        evaluator: Option<&ExpressionEvaluator>,
+    ) -> Result<(), WeaverError> {
+        let Some(evaluator) = evaluator else {
+            return Ok(());
+        };
+        for check in &self.checks {
+            let result = evaluator.evaluate_one_row(&check.expr, row, self, None)?;
+            if result.bool_value() == Some(false) {
+                return Err(WeaverError::CheckViolation {
+                    name: check.name.clone(),
+                    check: check.expr.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Gets all key data for a given row.
     ///
     /// This included primary and secondary keys.
@@ -297,6 +424,29 @@ impl TableSchema {
         KeyData::from(row)
     }
 
+    /// Gets the values of a row that must match a parent row of a foreign key, or `None` if any of
+    /// them is null
+    pub fn foreign_key_data(&self, foreign_key: &ForeignKey, row: &Row) -> Option<KeyData> {
+        self.key_data_of(foreign_key.columns(), row)
+    }
+
+    /// Gets the values of a row that the given foreign key, which must reference this table, refers
+    /// to. `None` is returned if any of them is null.
+    pub fn referenced_data(&self, foreign_key: &ForeignKey, row: &Row) -> Option<KeyData> {
+        self.key_data_of(foreign_key.parent_columns(), row)
+    }
+
+    fn key_data_of(&self, columns: &[String], row: &Row) -> Option<KeyData> {
+        columns
+            .iter()
+            .map(|col| {
+                let val = &row[self.column_index(col)?];
+                (**val != DbVal::Null).then(|| val.clone())
+            })
+            .collect::<Option<Row>>()
+            .map(KeyData::from)
+    }
+
     /// Join two table schemas, one after eachother
     pub fn join(&self, other: &Self) -> TableSchema {
         let mut ret = TableSchema::builder("<query>", "<join>");
@@ -350,6 +500,8 @@ impl ToSql for TableSchema {
             .iter()
             .map(|col| col.to_sql())
             .chain(self.keys.iter().map(|key| key.to_sql()))
+            .chain(self.foreign_keys.iter().map(|fk| fk.to_sql()))
+            .chain(self.checks.iter().map(|check| check.to_sql()))
             .map(|s| format!("  {s}"))
             .collect::<Vec<_>>()
             .join(",\n");
@@ -369,8 +521,17 @@ pub struct ColumnDefinition {
     data_type: Type,
     non_null: bool,
     default_value: Option<DbVal>,
+    /// A default that isn't constant, evaluated for every inserted row
+    #[serde(default)]
+    default_expr: Option<Expr>,
     auto_increment: Option<i64>,
+    /// The name of the collation strings in this column are compared in, if not the default
+    #[serde(default)]
+    collation: Option<String>,
     source_column: Option<ResolvedColumnRef>,
+    /// An expression over the other columns of a row that this column is always set to
+    #[serde(default)]
+    generated: Option<Expr>,
 }
 
 impl ColumnDefinition {
@@ -411,8 +572,11 @@ impl ColumnDefinition {
                 data_type,
                 non_null,
                 default_value,
+                default_expr: None,
                 auto_increment,
+                collation: None,
                 source_column: None,
+                generated: None,
             })
         })()
         .map_err(|e| WeaverError::IllegalColumnDefinition {
@@ -434,18 +598,115 @@ impl ColumnDefinition {
     pub fn default_value(&self) -> Option<&DbVal> {
         self.default_value.as_ref()
     }
+    pub fn default_expr(&self) -> Option<&Expr> {
+        self.default_expr.as_ref()
+    }
+    pub fn generated(&self) -> Option<&Expr> {
+        self.generated.as_ref()
+    }
+
+    /// Checks if a value is used when inserting null into this column
+    pub fn has_default(&self) -> bool {
+        self.default_value.is_some() || self.default_expr.is_some()
+    }
+
+    /// Sets the default of this column to an expression.
+    ///
+    /// Constant expressions are evaluated right away, while others are evaluated for every inserted
+    /// row. Defaults can not use any columns.
+    pub fn with_default(mut self, default: Expr) -> Result<Self, WeaverError> {
+        let name = self.name.clone();
+        (|| -> Result<Self, WeaverError> {
+            if self.auto_increment.is_some() {
+                return Err(WeaverError::IllegalAutoIncrement {
+                    reason: "can not specify both auto increment and default value".to_string(),
+                });
+            }
+            if !default.columns().is_empty() {
+                return Err(WeaverError::IllegalDefault {
+                    reason: "defaults can not use columns".to_string(),
+                });
+            }
+            if let Some(literal) = default.literal() {
+                let default = self.data_type.coerce(DbVal::from(literal.clone()))?;
+                self.default_value = Some(default);
+                self.default_expr = None;
+            } else {
+                let default_type = default.type_of(&BUILTIN_FUNCTIONS_REGISTRY, None)?;
+                let point_in_time =
+                    default_type.is_point_in_time() && self.data_type.is_point_in_time();
+                if !point_in_time && !default_type.coerces_to(&self.data_type) {
+                    return Err(WeaverError::IllegalDefault {
+                        reason: format!(
+                            "a default of type {default_type} can not be stored as {}",
+                            self.data_type
+                        ),
+                    });
+                }
+                self.default_value = None;
+                self.default_expr = Some(default);
+            }
+            Ok(self)
+        })()
+        .map_err(|e| WeaverError::IllegalColumnDefinition {
+            col: name,
+            reason: Box::new(e),
+        })
+    }
+
+    /// Converts the value of the default expression of this column to the type of this column.
+    ///
+    /// Dates and timestamps are converted into each other like a cast would, so `now()` can be the
+    /// default of any of them.
+    fn coerce_default(&self, val: DbVal) -> Result<DbVal, WeaverError> {
+        match val.value_type() {
+            Some(ty) if ty.is_point_in_time() && self.data_type.is_point_in_time() => {
+                self.data_type.cast(val)
+            }
+            _ => self.data_type.coerce(val),
+        }
+    }
 
     pub fn auto_increment(&self) -> Option<i64> {
         self.auto_increment
     }
 
-    /// Validates a value
+    /// Gets the collation strings in this column are compared in
+    pub fn collation(&self) -> &'static Collation {
+        self.collation
+            .as_deref()
+            .and_then(Collation::named)
+            .unwrap_or_else(Collation::default_collation)
+    }
+
+    /// Sets the collation strings in this column are compared in. Only string columns have a
+    /// collation.
+    pub fn with_collation(mut self, collation: impl AsRef<str>) -> Result<Self, WeaverError> {
+        let name = self.name.clone();
+        (|| -> Result<Self, WeaverError> {
+            if !matches!(self.data_type, Type::String(_)) {
+                return Err(WeaverError::IllegalCollation {
+                    reason: format!("{} has no collation", self.data_type),
+                });
+            }
+            let collation = Collation::named(collation.as_ref()).ok_or_else(|| {
+                WeaverError::IllegalCollation {
+                    reason: format!("unknown collation `{}`", collation.as_ref()),
+                }
+            })?;
+            self.collation = Some(collation.name().to_string());
+            Ok(self)
+        })()
+        .map_err(|e| WeaverError::IllegalColumnDefinition {
+            col: name,
+            reason: Box::new(e),
+        })
+    }
+
+    /// Validates a value, [coercing](Type::coerce) it to the type of this column if necessary
     pub fn validate(&self, value: &mut Cow<DbVal>) -> Result<(), WeaverError> {
         if !self.data_type().validate(value) {
-            return Err(WeaverError::TypeError {
-                expected: self.data_type,
-                actual: (**value).clone(),
-            });
+            *value = Cow::Owned(self.data_type.coerce((**value).clone())?);
         }
         Ok(())
     }
@@ -465,6 +726,28 @@ impl ColumnDefinition {
     }
 }
 
+impl TryFrom<&ast::ColumnDefinition> for ColumnDefinition {
+    type Error = WeaverError;
+
+    fn try_from(value: &ast::ColumnDefinition) -> Result<Self, Self::Error> {
+        let column = ColumnDefinition::new(
+            &value.id,
+            value.data_type.into(),
+            value.non_null,
+            None,
+            value.auto_increment.then_some(0),
+        )?;
+        let column = match &value.collation {
+            None => column,
+            Some(collation) => column.with_collation(collation)?,
+        };
+        match &value.default {
+            None => Ok(column),
+            Some(default) => column.with_default(default.clone()),
+        }
+    }
+}
+
 impl Debug for ColumnDefinition {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "column {}", self.to_sql())
@@ -474,6 +757,9 @@ impl Debug for ColumnDefinition {
 impl ToSql for ColumnDefinition {
     fn write_sql<W: Write>(&self, f: &mut W) -> io::Result<()> {
         write!(f, "`{}` {}", self.name, self.data_type)?;
+        if let Some(collation) = self.collation.as_ref() {
+            write!(f, " collate {collation}")?;
+        }
         if self.non_null {
             write!(f, " not null")?;
         }
@@ -488,6 +774,11 @@ impl ToSql for ColumnDefinition {
         }
         if let Some(default) = self.default_value.as_ref() {
             write!(f, " default {default}")?;
+        } else if let Some(default) = self.default_expr.as_ref() {
+            write!(f, " default {default}")?;
+        }
+        if let Some(generated) = self.generated.as_ref() {
+            write!(f, " generated always as ({generated})")?;
         }
         if let Some(source_column) = self.source_column() {
             write!(f, " comment \"source-column: {source_column}\"")?;
@@ -581,12 +872,227 @@ impl Key {
     }
 }
 
+/// A foreign key, requiring the values of its columns to match a row of the parent table
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct ForeignKey {
+    name: String,
+    columns: Vec<String>,
+    parent_schema: String,
+    parent_table: String,
+    parent_columns: Vec<String>,
+    on_delete: ReferentialAction,
+    on_update: ReferentialAction,
+}
+
+impl Debug for ForeignKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "foreign key `{}` ({}) references {}.{} ({}) on delete {} on update {}",
+            self.name,
+            self.columns.join(", "),
+            self.parent_schema,
+            self.parent_table,
+            self.parent_columns.join(", "),
+            self.on_delete,
+            self.on_update
+        )
+    }
+}
+
+impl ToSql for ForeignKey {
+    fn write_sql<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write!(writer, "{self:?}")
+    }
+}
+
+impl ForeignKey {
+    /// Create a new foreign key definition
+    pub fn new(
+        name: impl AsRef<str>,
+        columns: Vec<String>,
+        parent: (impl AsRef<str>, impl AsRef<str>),
+        parent_columns: Vec<String>,
+        on_delete: ReferentialAction,
+        on_update: ReferentialAction,
+    ) -> Result<Self, WeaverError> {
+        if columns.len() != parent_columns.len() {
+            return Err(WeaverError::IllegalForeignKey {
+                name: name.as_ref().to_string(),
+                reason: format!(
+                    "{} columns can not reference {} columns",
+                    columns.len(),
+                    parent_columns.len()
+                ),
+            });
+        }
+
+        Ok(Self {
+            name: name.as_ref().to_string(),
+            columns,
+            parent_schema: parent.0.as_ref().to_string(),
+            parent_table: parent.1.as_ref().to_string(),
+            parent_columns,
+            on_delete,
+            on_update,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Gets the referencing columns
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+    /// Gets the schema and name of the referenced table
+    pub fn parent(&self) -> (&str, &str) {
+        (&self.parent_schema, &self.parent_table)
+    }
+    /// Gets the referenced columns of the parent table
+    pub fn parent_columns(&self) -> &[String] {
+        &self.parent_columns
+    }
+    pub fn on_delete(&self) -> ReferentialAction {
+        self.on_delete
+    }
+    pub fn on_update(&self) -> ReferentialAction {
+        self.on_update
+    }
+
+    /// Checks if this foreign key references the given table
+    pub fn references(&self, schema: &str, table: &str) -> bool {
+        self.parent_schema == schema && self.parent_table == table
+    }
+}
+
+/// A check constraint, requiring an expression over the columns of a row to not be false
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Check {
+    name: String,
+    expr: Expr,
+}
+
+impl Debug for Check {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "constraint `{}` check ({})", self.name, self.expr)
+    }
+}
+
+impl ToSql for Check {
+    fn write_sql<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write!(writer, "{self:?}")
+    }
+}
+
+impl Check {
+    /// Create a new check definition
+    pub fn new(name: impl AsRef<str>, expr: Expr) -> Self {
+        Self {
+            name: name.as_ref().to_string(),
+            expr,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    /// Checks if this check uses the given column
+    fn uses_column(&self, column: &str) -> bool {
+        expr_uses_column(&self.expr, column)
+    }
+}
+
+/// Points every column used by the expression of a check at a column of its table
+struct CheckColumns<'a, F: FnMut(&Identifier) -> Result<Identifier, WeaverError>> {
+    schema: &'a str,
+    table: &'a str,
+    column: F,
+}
+
+impl<F: FnMut(&Identifier) -> Result<Identifier, WeaverError>> VisitorMut for CheckColumns<'_, F> {
+    type Err = WeaverError;
+
+    fn visit_column_ref_mut(&mut self, column: &mut ColumnRef) -> Result<(), Self::Err> {
+        let name = match column {
+            ColumnRef::Unresolved(unresolved) => {
+                if let Some(table) = unresolved.table() {
+                    if table.as_ref() != self.table {
+                        return Err(WeaverError::ColumnNotFound(unresolved.to_string()));
+                    }
+                }
+                unresolved.column()
+            }
+            ColumnRef::Resolved(resolved) => resolved.column(),
+        };
+        let name = (self.column)(name)?;
+        *column = ColumnRef::Resolved(ResolvedColumnRef::new(
+            Identifier::new(self.schema),
+            Identifier::new(self.table),
+            name,
+        ));
+        Ok(())
+    }
+}
+
+/// Gets the name of the generated column that holds the values of an indexable expression, such as
+/// `@@payload ->> 'kind'` for `t.payload ->> 'kind'`. Only text extracted from a json column with
+/// `->>` can be indexed, and columns are named without their table so the same column is found
+/// however the expression refers to it.
+pub fn json_path_column(expr: &Expr) -> Option<String> {
+    fn json_path(expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Column { column } => Some(match column {
+                ColumnRef::Unresolved(col) => col.column().to_string(),
+                ColumnRef::Resolved(col) => col.column().to_string(),
+            }),
+            Expr::Binary {
+                left,
+                op: op @ (BinaryOp::JsonGet | BinaryOp::JsonGetText),
+                right,
+            } => {
+                let key = match right.literal()? {
+                    Literal::String(key) => format!("'{}'", key.replace('\'', "''")),
+                    Literal::Integer(index) => index.to_string(),
+                    _ => return None,
+                };
+                Some(format!("{} {op} {key}", json_path(left)?))
+            }
+            _ => None,
+        }
+    }
+
+    match expr {
+        Expr::Binary {
+            op: BinaryOp::JsonGetText,
+            ..
+        } => Some(format!("@@{}", json_path(expr)?)),
+        _ => None,
+    }
+}
+
+/// Checks if an expression uses the given column of its table
+fn expr_uses_column(expr: &Expr, column: &str) -> bool {
+    expr.columns().iter().any(|col| match col {
+        ColumnRef::Unresolved(col) => col.column().as_ref() == column,
+        ColumnRef::Resolved(col) => col.column().as_ref() == column,
+    })
+}
+
 #[derive(Debug)]
 pub struct TableSchemaBuilder {
     schema: String,
     name: String,
     columns: Vec<ColumnDefinition>,
     keys: Vec<Key>,
+    foreign_keys: Vec<ForeignKey>,
+    checks: Vec<Check>,
+    /// System columns holding the values of indexed expressions
+    generated: Vec<ColumnDefinition>,
     engine: Option<EngineKey>,
 }
 
@@ -599,6 +1105,9 @@ impl TableSchemaBuilder {
             name: name.as_ref().to_string(),
             columns: vec![],
             keys: vec![],
+            foreign_keys: vec![],
+            checks: vec![],
+            generated: vec![],
             engine: None,
         }
     }
@@ -625,8 +1134,209 @@ impl TableSchemaBuilder {
         self
     }
 
+    /// Adds the keys declared as part of a column definition
+    pub fn column_keys(
+        self,
+        column_definition: &ast::ColumnDefinition,
+    ) -> Result<Self, WeaverError> {
+        let ast::ColumnDefinition {
+            id,
+            unique,
+            key,
+            primary,
+            ..
+        } = column_definition;
+        if *unique || *key && !*primary {
+            self.index(&format!("SK_{}", id), &[id.as_ref()], *unique)
+        } else if *primary {
+            self.primary(&[id.as_ref()])
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Adds the key declared by a table level constraint.
+    ///
+    /// Keys that weren't given a name are named after their columns.
+    pub fn constraint(
+        self,
+        constraint_definition: &ast::ConstraintDefinition,
+    ) -> Result<Self, WeaverError> {
+        let columns = constraint_definition
+            .constraint
+            .columns()
+            .iter()
+            .map(|col| col.as_ref())
+            .collect::<Vec<_>>();
+        let name = constraint_definition
+            .key_name()
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| format!("SK_{}", columns.join("_")));
+        match &constraint_definition.constraint {
+            ast::Constraint::PrimaryKey { .. } => self.primary(&columns),
+            ast::Constraint::Unique { .. } => self.index(&name, &columns, true),
+            ast::Constraint::Key { .. } => self.index(&name, &columns, false),
+            ast::Constraint::ForeignKey { references, .. } => {
+                let name = constraint_definition
+                    .key_name()
+                    .map(|name| name.to_string())
+                    .unwrap_or_else(|| format!("FK_{}", columns.join("_")));
+                let parent_schema = references
+                    .schema
+                    .as_ref()
+                    .map(|schema| schema.to_string())
+                    .unwrap_or_else(|| self.schema.clone());
+                let foreign_key = ForeignKey::new(
+                    name,
+                    columns.iter().map(ToString::to_string).collect(),
+                    (parent_schema, references.table.as_ref()),
+                    references.columns.iter().map(ToString::to_string).collect(),
+                    references.on_delete,
+                    references.on_update,
+                )?;
+                self.foreign_key(foreign_key)
+            }
+            ast::Constraint::Check { expr } => {
+                let name = constraint_definition
+                    .key_name()
+                    .map(|name| name.to_string())
+                    .unwrap_or_else(|| format!("CK_{}", self.checks.len() + 1));
+                self.check(Check::new(name, expr.clone()))
+            }
+        }
+    }
+
+    /// Drops a column, removing it from every key that uses it. Keys left without any columns are
+    /// dropped as well, along with every foreign key and check using the column.
+    ///
+    /// Columns of the primary key can't be dropped, as that would silently change how rows are
+    /// identified.
+    pub fn drop_column(mut self, name: &str) -> Result<Self, WeaverError> {
+        let index = self.column_position(name)?;
+        if self
+            .keys
+            .iter()
+            .any(|key| key.primary() && key.columns.iter().any(|col| col == name))
+        {
+            return Err(WeaverError::PrimaryKeyColumn(name.to_string()));
+        }
+        self.columns.remove(index);
+        let mut dropped = vec![name.to_string()];
+        self.generated.retain(|col| {
+            let uses_column = col
+                .generated
+                .as_ref()
+                .is_some_and(|expr| expr_uses_column(expr, name));
+            if uses_column {
+                dropped.push(col.name.clone());
+            }
+            !uses_column
+        });
+        for key in &mut self.keys {
+            key.columns.retain(|col| !dropped.contains(col));
+        }
+        self.keys.retain(|key| !key.columns.is_empty());
+        let (schema, table) = (self.schema.clone(), self.name.clone());
+        let uses_column = |columns: &[String]| columns.iter().any(|col| col == name);
+        self.foreign_keys.retain(|fk| {
+            !(uses_column(&fk.columns)
+                || fk.references(&schema, &table) && uses_column(&fk.parent_columns))
+        });
+        self.checks.retain(|check| !check.uses_column(name));
+        Ok(self)
+    }
+
+    /// Renames a column, including within every key that uses it
+    pub fn rename_column(mut self, from: &str, to: &str) -> Result<Self, WeaverError> {
+        if self.columns.iter().any(|col| col.name == to) {
+            return Err(WeaverError::ColumnAlreadyExists(to.to_string()));
+        }
+        let index = self.column_position(from)?;
+        self.columns[index].name = to.to_string();
+        for col in self.keys.iter_mut().flat_map(|key| key.columns.iter_mut()) {
+            if col == from {
+                *col = to.to_string();
+            }
+        }
+        for fk in &mut self.foreign_keys {
+            let mut columns = fk.columns.iter_mut().collect::<Vec<_>>();
+            if fk.parent_schema == self.schema && fk.parent_table == self.name {
+                columns.extend(fk.parent_columns.iter_mut());
+            }
+            for col in columns {
+                if col == from {
+                    *col = to.to_string();
+                }
+            }
+        }
+        let mut renamed = CheckColumns {
+            schema: &self.schema,
+            table: &self.name,
+            column: |col: &Identifier| {
+                Ok(if col.as_ref() == from {
+                    Identifier::new(to)
+                } else {
+                    col.clone()
+                })
+            },
+        };
+        for check in &mut self.checks {
+            renamed.visit_expr_mut(&mut check.expr)?;
+        }
+        for col in &mut self.generated {
+            let Some(expr) = col.generated.as_mut() else {
+                continue;
+            };
+            renamed.visit_expr_mut(expr)?;
+            let name = json_path_column(expr).expect("generated columns hold json paths");
+            let old_name = std::mem::replace(&mut col.name, name);
+            for key_col in self.keys.iter_mut().flat_map(|key| key.columns.iter_mut()) {
+                if *key_col == old_name {
+                    key_col.clone_from(&col.name);
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Replaces the definition of the column with the same name.
+    ///
+    /// The type of the column can only be changed to one its current type
+    /// [widens to](Type::widens_to).
+    pub fn modify_column(
+        mut self,
+        column_definition: ColumnDefinition,
+    ) -> Result<Self, WeaverError> {
+        let index = self.column_position(column_definition.name())?;
+        let current = &self.columns[index];
+        if !current.data_type.widens_to(&column_definition.data_type) {
+            return Err(WeaverError::IllegalTypeChange {
+                column: current.name.clone(),
+                from: current.data_type,
+                to: column_definition.data_type,
+            });
+        }
+        self.columns[index] = column_definition;
+        Ok(self)
+    }
+
+    fn column_position(&self, name: &str) -> Result<usize, WeaverError> {
+        self.columns
+            .iter()
+            .position(|col| col.name == name)
+            .ok_or_else(|| WeaverError::ColumnNotFound(name.to_string()))
+    }
+
     /// Sets the primary key
+    ///
+    /// A primary key using only the row id is replaced.
     pub fn primary(mut self, cols: &[&str]) -> Result<Self, WeaverError> {
+        if let Some(index) = self.keys.iter().position(|key| key.primary()) {
+            if self.keys[index].columns != [ROW_ID_COLUMN] {
+                return Err(WeaverError::KeyAlreadyExists(self.keys[index].name.clone()));
+            }
+            self.keys.remove(index);
+        }
         self.keys.push(Key::new(
             "PRIMARY",
             cols.iter().map(ToString::to_string).collect(),
@@ -638,10 +1348,18 @@ impl TableSchemaBuilder {
         Ok(self)
     }
 
-    /// Sets the primary key
-    pub fn index(mut self, name: &str, cols: &[&str], _unique: bool) -> Result<Self, WeaverError> {
+    /// Adds a secondary key
+    pub fn index(mut self, name: &str, cols: &[&str], unique: bool) -> Result<Self, WeaverError> {
+        if self.keys.iter().any(|key| key.name() == name) {
+            return Err(WeaverError::KeyAlreadyExists(name.to_string()));
+        }
         let non_null = cols.iter().try_fold(true, |accum, col| {
-            if let Some(col) = self.columns.iter().find(|column| &column.name == col) {
+            if let Some(col) = self
+                .columns
+                .iter()
+                .chain(&self.generated)
+                .find(|column| &column.name == col)
+            {
                 Ok(col.non_null && accum)
             } else {
                 Err(WeaverError::ColumnNotFound(col.to_string()))
@@ -652,13 +1370,136 @@ impl TableSchemaBuilder {
             name,
             cols.iter().map(ToString::to_string).collect(),
             non_null,
-            false,
+            unique,
             false,
         )?);
 
         Ok(self)
     }
 
+    /// Adds a secondary key over columns and expressions.
+    ///
+    /// The values of each expression are kept in a generated system column, so that they can be
+    /// found through the key like the values of any other column. Only text extracted from json
+    /// with `->>` can be indexed this way.
+    pub fn index_on(
+        mut self,
+        name: &str,
+        columns: &[ast::IndexColumn],
+        unique: bool,
+    ) -> Result<Self, WeaverError> {
+        let mut cols = vec![];
+        for column in columns {
+            match column {
+                ast::IndexColumn::Column(col) => cols.push(col.to_string()),
+                ast::IndexColumn::Expr(expr) => cols.push(self.generate(name, expr)?),
+            }
+        }
+        self.index(
+            name,
+            &cols.iter().map(String::as_str).collect::<Vec<_>>(),
+            unique,
+        )
+    }
+
+    /// Gets the name of a generated column holding the values of an expression used by the given
+    /// key, adding the column if there is none yet
+    fn generate(&mut self, key: &str, expr: &Expr) -> Result<String, WeaverError> {
+        let illegal = |reason: String| WeaverError::IllegalIndex {
+            name: key.to_string(),
+            reason,
+        };
+        let Some(name) = json_path_column(expr) else {
+            return Err(illegal(format!(
+                "can not index {expr}, only text extracted from json with `->>` can be indexed"
+            )));
+        };
+        if self.generated.iter().any(|col| col.name == name) {
+            return Ok(name);
+        }
+        let mut expr = expr.clone();
+        let columns = &self.columns;
+        let mut resolver = CheckColumns {
+            schema: &self.schema,
+            table: &self.name,
+            column: |col: &Identifier| {
+                if columns.iter().any(|column| column.name == col.as_ref()) {
+                    Ok(col.clone())
+                } else {
+                    Err(WeaverError::ColumnNotFound(col.to_string()))
+                }
+            },
+        };
+        resolver
+            .visit_expr_mut(&mut expr)
+            .map_err(|e| illegal(e.to_string()))?;
+        let mut column = ColumnDefinition::new(&name, Type::String(u16::MAX), false, None, None)?;
+        column.generated = Some(expr);
+        self.generated.push(column);
+        Ok(name)
+    }
+
+    /// Adds a foreign key, along with a key named after it over its columns if no such key exists
+    /// yet. The key is used to find the rows referencing a parent row.
+    ///
+    /// Only the columns of this table are checked, as the parent table may not be known yet.
+    pub fn foreign_key(mut self, foreign_key: ForeignKey) -> Result<Self, WeaverError> {
+        if self
+            .foreign_keys
+            .iter()
+            .any(|fk| fk.name == foreign_key.name)
+        {
+            return Err(WeaverError::KeyAlreadyExists(foreign_key.name));
+        }
+        for col in &foreign_key.columns {
+            self.column_position(col)?;
+        }
+        if !self
+            .keys
+            .iter()
+            .any(|key| key.columns() == foreign_key.columns())
+        {
+            let columns = foreign_key
+                .columns()
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>();
+            self = self.index(foreign_key.name(), &columns, false)?;
+        }
+        self.foreign_keys.push(foreign_key);
+        Ok(self)
+    }
+
+    /// Adds a check, whose columns must be part of this table
+    pub fn check(mut self, mut check: Check) -> Result<Self, WeaverError> {
+        if self.checks.iter().any(|other| other.name == check.name) {
+            return Err(WeaverError::IllegalCheck {
+                name: check.name,
+                reason: "a check with the same name already exists".to_string(),
+            });
+        }
+        let columns = &self.columns;
+        let mut resolver = CheckColumns {
+            schema: &self.schema,
+            table: &self.name,
+            column: |col: &Identifier| {
+                if columns.iter().any(|column| column.name == col.as_ref()) {
+                    Ok(col.clone())
+                } else {
+                    Err(WeaverError::ColumnNotFound(col.to_string()))
+                }
+            },
+        };
+        resolver
+            .visit_expr_mut(&mut check.expr)
+            .map_err(|e| WeaverError::IllegalCheck {
+                name: check.name.clone(),
+                reason: e.to_string(),
+            })?;
+        self.checks.push(check);
+        Ok(self)
+    }
+
     /// Sets the used engine
     pub fn engine(mut self, engine_key: EngineKey) -> Self {
         self.engine = Some(engine_key);
@@ -682,6 +1523,7 @@ impl TableSchemaBuilder {
             None,
             0,
         )?);
+        sys_columns.extend(self.generated);
 
         if !keys.iter().any(|key| key.primary_eligible()) {
             keys.push(Key::new(
@@ -712,12 +1554,34 @@ impl TableSchemaBuilder {
             }
         }
 
+        // keys are encoded and compared by their bytes, which only agrees with the default
+        // collation
+        for key in &keys {
+            if let Some(column) = key.columns().iter().find_map(|col| {
+                columns.iter().find(|column| {
+                    &column.name == col
+                        && column.collation().name() != Collation::default_collation().name()
+                })
+            }) {
+                return Err(WeaverError::IllegalCollation {
+                    reason: format!(
+                        "column `{}` is part of key `{}`, so it can not have collation `{}`",
+                        column.name,
+                        key.name(),
+                        column.collation()
+                    ),
+                });
+            }
+        }
+
         Ok(TableSchema {
             schema: self.schema,
             name: self.name,
             columns,
             sys_columns,
             keys,
+            foreign_keys: self.foreign_keys,
+            checks: self.checks,
             engine: self.engine.unwrap_or(EngineKey::new(IN_MEMORY_KEY)),
         })
     }
@@ -736,6 +1600,14 @@ impl From<&TableSchema> for TableSchemaBuilder {
             name: value.name.clone(),
             columns: value.columns.clone(),
             keys: value.keys.clone(),
+            foreign_keys: value.foreign_keys.clone(),
+            checks: value.checks.clone(),
+            generated: value
+                .sys_columns
+                .iter()
+                .filter(|col| col.generated.is_some())
+                .cloned()
+                .collect(),
             engine: Some(value.engine.clone()),
         }
     }
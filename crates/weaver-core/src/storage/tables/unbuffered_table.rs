@@ -2,6 +2,8 @@
 
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::Bound;
 
 use std::sync::atomic::{AtomicI64, Ordering};
 use std::sync::OnceLock;
@@ -9,16 +11,18 @@ use std::sync::OnceLock;
 use tracing::{instrument, trace};
 
 use crate::data::row::{OwnedRow, Row};
+use crate::data::serde::deserialize_data_untyped;
 use crate::data::types::Type;
-use crate::dynamic_table::{Col, DynamicTable, HasSchema, OwnedCol};
+use crate::data::values::DbVal;
+use crate::dynamic_table::{Col, DynamicTable, HasSchema, OwnedCol, ROW_ID_COLUMN};
 use crate::error::WeaverError;
-use crate::key::KeyDataRange;
+use crate::key::{KeyData, KeyDataRange};
 use crate::monitoring::{monitor_fn, Monitor, MonitorCollector, Monitorable};
 use crate::rows::{KeyIndex, KeyIndexKind, OwnedRows, Rows};
 use crate::storage::b_plus_tree::BPlusTree;
 use crate::storage::paging::buffered_pager::BufferedPager;
 use crate::storage::paging::virtual_pager::{VirtualPager, VirtualPagerTable};
-use crate::storage::tables::table_schema::{ColumnDefinition, TableSchema};
+use crate::storage::tables::table_schema::{ColumnDefinition, Key, TableSchema};
 use crate::storage::Pager;
 use crate::tx::{Tx, TxId, TX_ID_COLUMN};
 
@@ -26,12 +30,34 @@ use crate::tx::{Tx, TxId, TX_ID_COLUMN};
 struct RowId(u64);
 
 const MAIN_ROOT: u8 = 0;
-const _SECONDARY_ROOT: u8 = 1;
+
+/// The root of a tree within a table's virtual pager table
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum TreeRoot {
+    Main,
+    /// Secondary keys are rooted by their name, so adding or removing other keys doesn't move them
+    Secondary(String),
+}
+
+impl Hash for TreeRoot {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            // the main tree keeps the root it was always stored at
+            TreeRoot::Main => MAIN_ROOT.hash(state),
+            TreeRoot::Secondary(name) => name.hash(state),
+        }
+    }
+}
+
+type Tree<P> = BPlusTree<VirtualPager<TreeRoot, BufferedPager<P>>>;
 
 /// An in memory table that immediately flushes to storage
 pub struct UnbufferedTable<P: Pager + Sync + Send> {
     schema: TableSchema,
-    main_buffer: BPlusTree<VirtualPager<u8, BufferedPager<P>>>,
+    main_buffer: Tree<P>,
+    /// Secondary indices by key name. Entries are keyed by the secondary key data followed by the
+    /// primary key data, and store the primary key data.
+    secondary_buffers: HashMap<String, Tree<P>>,
     auto_incremented: HashMap<OwnedCol, OnceLock<AtomicI64>>,
     row_id: AtomicI64,
 }
@@ -44,6 +70,10 @@ impl<P: Pager + Sync + Send> Debug for UnbufferedTable<P> {
                 "primary_index_nodes",
                 &self.main_buffer.nodes().unwrap_or(0),
             )
+            .field(
+                "secondary_indices",
+                &self.secondary_buffers.keys().collect::<Vec<_>>(),
+            )
             .field("auto_incremented", &self.auto_incremented)
             .field("row_id", &self.row_id)
             .finish()
@@ -72,8 +102,37 @@ impl<P: Pager + Sync + Send> UnbufferedTable<P> {
             )?)?;
         }
 
-        let virtual_pager_table = VirtualPagerTable::<u8, _>::new(BufferedPager::new(paged))?;
-        let root = virtual_pager_table.get_or_init(MAIN_ROOT)?;
+        let virtual_pager_table = VirtualPagerTable::new(BufferedPager::new(paged))?;
+        let root = virtual_pager_table.get_or_init(TreeRoot::Main)?;
+        let main_buffer = BPlusTree::new(root);
+
+        let mut secondary_buffers = HashMap::new();
+        for key in schema.secondary_keys() {
+            let root_key = TreeRoot::Secondary(key.name().to_string());
+            let tree = match virtual_pager_table.get(root_key.clone())? {
+                Some(root) => BPlusTree::new(root),
+                None => {
+                    // keys added to an existing table have to be backfilled
+                    let tree = BPlusTree::new(virtual_pager_table.get_or_init(root_key)?);
+                    for bytes in main_buffer.all()? {
+                        let row = schema.decode(&bytes)?;
+                        let (entry, primary) = secondary_entry(&schema, key, &row)?;
+                        tree.insert(entry, OwnedRow::clone(&primary))?;
+                    }
+                    tree
+                }
+            };
+            secondary_buffers.insert(key.name().to_string(), tree);
+        }
+
+        // row ids must continue from the rows already stored
+        let row_id = match schema.primary_key()?.columns() {
+            [col] if col == ROW_ID_COLUMN => main_buffer
+                .max_key()?
+                .and_then(|max| max[0].int_value())
+                .map_or(0, |max| max + 1),
+            _ => 0,
+        };
 
         let auto_incremented = schema
             .all_columns()
@@ -83,9 +142,10 @@ impl<P: Pager + Sync + Send> UnbufferedTable<P> {
             .collect();
         Ok(Self {
             schema,
-            main_buffer: BPlusTree::new(root),
+            main_buffer,
+            secondary_buffers,
             auto_incremented,
-            row_id: Default::default(),
+            row_id: AtomicI64::new(row_id),
         })
     }
 
@@ -99,28 +159,146 @@ impl<P: Pager + Sync + Send> UnbufferedTable<P> {
             .all()?
             .into_iter()
             .map(|bytes| self.schema.decode(&bytes))
-            .filter(|row| {
-                if let Ok(row) = row {
-                    let tx_id = self
-                        .schema
-                        .column_index(TX_ID_COLUMN)
-                        .and_then(|tx_col| row.get(tx_col))
-                        .and_then(|tx| tx.int_value())
-                        .map(TxId::from);
-                    let can_see = tx_id.map(|ref i| tx.can_see(i)).unwrap_or(true);
-                    trace!(
-                        "checking if row {:?} (tx_id: {tx_id:?}) can be seen by tx {} -> {can_see}",
-                        row,
-                        tx
-                    );
-                    can_see
-                } else {
-                    true
-                }
-            })
+            .filter(|row| self.can_see(tx, row))
             .collect::<Result<Vec<_>, _>>()
             .map(|rows| OwnedRows::new(self.schema.clone(), rows))
     }
+
+    /// Checks if a row can be seen by the given transaction
+    fn can_see(&self, tx: &Tx, row: &Result<OwnedRow, WeaverError>) -> bool {
+        if let Ok(row) = row {
+            let tx_id = self
+                .schema
+                .column_index(TX_ID_COLUMN)
+                .and_then(|tx_col| row.get(tx_col))
+                .and_then(|tx| tx.int_value())
+                .map(TxId::from);
+            let can_see = tx_id.map(|ref i| tx.can_see(i)).unwrap_or(true);
+            trace!(
+                "checking if row {:?} (tx_id: {tx_id:?}) can be seen by tx {} -> {can_see}",
+                row,
+                tx
+            );
+            can_see
+        } else {
+            true
+        }
+    }
+
+    /// Gets the row stored with the given primary key, if present
+    fn get_row(&self, primary: &KeyData) -> Result<Option<OwnedRow>, WeaverError> {
+        match self.main_buffer.get(primary) {
+            Ok(Some(bytes)) => self.schema.decode(&bytes).map(Some),
+            Ok(None) | Err(WeaverError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Decodes the primary key data stored in a secondary index
+    fn decode_primary(&self, bytes: &[u8]) -> Result<KeyData, WeaverError> {
+        let primary = self.schema.primary_key()?;
+        let types = primary.columns().iter().map(|col| {
+            self.schema
+                .get_column(col)
+                .or_else(|| {
+                    self.schema
+                        .sys_columns()
+                        .iter()
+                        .find(|sys_col| sys_col.name() == col)
+                })
+                .map(|col| col.data_type())
+                .expect("primary key columns must exist")
+        });
+        Ok(KeyData::from(Row::from(deserialize_data_untyped(
+            bytes, types,
+        )?)))
+    }
+
+    /// Gets the primary keys of every row whose secondary key is within the given range
+    fn secondary_range(
+        &self,
+        index: &Tree<P>,
+        range: KeyDataRange,
+    ) -> Result<Vec<KeyData>, WeaverError> {
+        index
+            .prefix_range(range)?
+            .iter()
+            .map(|bytes| self.decode_primary(bytes))
+            .collect()
+    }
+
+    /// Makes sure a row doesn't duplicate any unique secondary key held by another row
+    fn check_unique(&self, row: &Row, primary: &KeyData) -> Result<(), WeaverError> {
+        for key in self.schema.secondary_keys() {
+            if !key.unique() {
+                continue;
+            }
+            let key_data = self.schema.key_data(key, row);
+            // nulls are never equal to each other, so they can't conflict
+            if key_data.iter().any(|val| **val == DbVal::Null) {
+                continue;
+            }
+            let index = &self.secondary_buffers[key.name()];
+            let existing = self.secondary_range(
+                index,
+                KeyDataRange(
+                    Bound::Included(key_data.clone()),
+                    Bound::Included(key_data.clone()),
+                ),
+            )?;
+            if existing.iter().any(|other| other != primary) {
+                return Err(WeaverError::UniqueKeyViolation {
+                    key: key.name().to_string(),
+                    value: key_data,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a row to every secondary index
+    fn index_row(&self, row: &Row) -> Result<(), WeaverError> {
+        for key in self.schema.secondary_keys() {
+            let (entry, primary) = secondary_entry(&self.schema, key, row)?;
+            self.secondary_buffers[key.name()].insert(entry, OwnedRow::clone(&primary))?;
+        }
+        Ok(())
+    }
+
+    /// Removes a row from every secondary index
+    fn unindex_row(&self, row: &Row) -> Result<(), WeaverError> {
+        for key in self.schema.secondary_keys() {
+            let (entry, _) = secondary_entry(&self.schema, key, row)?;
+            self.secondary_buffers[key.name()].delete(&entry)?;
+        }
+        Ok(())
+    }
+
+    /// Gets the largest value of an integer column
+    fn max_value(&self, col: Col) -> Result<Option<i64>, WeaverError> {
+        let Some(col_idx) = self.schema.column_index(col) else {
+            return Ok(None);
+        };
+        self.main_buffer
+            .all()?
+            .into_iter()
+            .try_fold(None, |max: Option<i64>, bytes| {
+                let row = self.schema.decode(&bytes)?;
+                let value = row.get(col_idx).and_then(|val| val.int_value());
+                Ok(max.max(value))
+            })
+    }
+}
+
+/// Gets the entry of a row within a secondary index, along with the row's primary key data
+fn secondary_entry(
+    schema: &TableSchema,
+    key: &Key,
+    row: &Row,
+) -> Result<(KeyData, KeyData), WeaverError> {
+    let primary = schema.key_data(schema.primary_key()?, row);
+    let secondary = schema.key_data(key, row);
+    Ok((KeyData::from(secondary.join(&primary)), primary))
 }
 
 impl<P: Pager + Sync + Send> Monitorable for UnbufferedTable<P> {
@@ -144,13 +322,18 @@ where
             .get(col)
             .expect("auto incremented should be initialized");
         lock.get_or_init(|| {
-            AtomicI64::new(
-                self.schema
-                    .get_column(col)
-                    .unwrap()
-                    .auto_increment()
-                    .unwrap_or(0),
-            )
+            let start = self
+                .schema
+                .get_column(col)
+                .unwrap()
+                .auto_increment()
+                .unwrap_or(0);
+            // continue from the rows already stored
+            let next = match self.max_value(col) {
+                Ok(Some(max)) => start.max(max + 1),
+                _ => start,
+            };
+            AtomicI64::new(next)
         })
         .fetch_add(1, Ordering::SeqCst)
     }
@@ -178,7 +361,17 @@ where
         let key_data = self.schema.all_key_data(&row);
         let primary = key_data.primary().clone();
         trace!("validated row primary key: {:?}", primary);
+        if self.get_row(&primary)?.is_some() {
+            return Err(WeaverError::UniqueKeyViolation {
+                key: self.schema.primary_key()?.name().to_string(),
+                value: primary,
+            });
+        }
+        if !self.secondary_buffers.is_empty() {
+            self.check_unique(&row, &primary)?;
+        }
         self.main_buffer.insert(primary, row.to_owned())?;
+        self.index_row(&row)?;
         Ok(())
     }
 
@@ -189,72 +382,118 @@ where
     ) -> Result<Box<dyn Rows<'tx> + 'tx + Send>, WeaverError> {
         let key_def = self.schema.get_key(key.key_name())?;
 
+        let Some(ranges) = key.kind().ranges() else {
+            return Ok(Box::new(self.all_rows(tx)?));
+        };
         if key_def.primary() {
-            match key.kind() {
-                KeyIndexKind::All => Ok(Box::new(self.all_rows(tx)?)),
-                KeyIndexKind::Range { low, high } => {
-                    let rows = self.main_buffer.range(KeyDataRange(low.clone(), high.clone()))?
-                        .into_iter()
-                        .map(|bytes| self.schema.decode(&bytes))
-                        .filter(|row: &Result<OwnedRow, WeaverError>| {
-                            if let Ok(row) = row {
-                                println!("row: {row:?}");
-                                let tx_id = self
-                                    .schema
-                                    .column_index(TX_ID_COLUMN)
-                                    .and_then(|tx_col| row.get(tx_col))
-                                    .and_then(|tx| tx.int_value())
-                                    .map(TxId::from);
-                                let can_see = tx_id.map(|ref i| tx.can_see(i)).unwrap_or(true);
-                                trace!(
-                            "checking if row {:?} (tx_id: {tx_id:?}) can be seen by tx {} -> {can_see}",
-                            row,
-                            tx
-                        );
-                                can_see
-                            } else {
-                                true
-                            }
-                        })
-                        .collect::<Result<Vec<_>, _>>()
-                        .map(|rows| OwnedRows::new(self.schema.clone(), rows))?;
-                    Ok(Box::new(rows))
-                }
-                KeyIndexKind::One(_id) => todo!(),
+            let mut entries = vec![];
+            for range in ranges {
+                entries.extend(self.main_buffer.range(range)?);
             }
+            let rows = entries
+                .into_iter()
+                .map(|bytes| self.schema.decode(&bytes))
+                .filter(|row| self.can_see(tx, row))
+                .collect::<Result<Vec<_>, _>>()
+                .map(|rows| OwnedRows::new(self.schema.clone(), rows))?;
+            Ok(Box::new(rows))
         } else {
-            let mut all = self.all_rows(tx)?;
-            all.retain(|row| {
-                let row_key_data = &self.schema.key_data(key_def, row);
-                match key.kind() {
-                    KeyIndexKind::All => true,
-                    KeyIndexKind::Range { low, high } => {
-                        KeyDataRange(low.clone(), high.clone()).contains(row_key_data)
-                    }
-                    KeyIndexKind::One(id) => id == row_key_data,
-                }
-            });
-
-            Ok(Box::new(all))
+            let index = self
+                .secondary_buffers
+                .get(key_def.name())
+                .ok_or_else(|| WeaverError::BadKeyName(key_def.name().to_string()))?;
+            let mut primaries = vec![];
+            for range in ranges {
+                primaries.extend(self.secondary_range(index, range)?);
+            }
+            let rows = primaries
+                .iter()
+                .filter_map(|primary| self.get_row(primary).transpose())
+                .filter(|row| self.can_see(tx, row))
+                .collect::<Result<Vec<_>, _>>()
+                .map(|rows| OwnedRows::new(self.schema.clone(), rows))?;
+            Ok(Box::new(rows))
         }
     }
 
     fn size_estimate(&self, key_index: &KeyIndex) -> Result<u64, WeaverError> {
+        if let Some(index) = self.secondary_buffers.get(key_index.key_name()) {
+            return match key_index.kind() {
+                KeyIndexKind::All => self.main_buffer.count(KeyDataRange::from(..)),
+                KeyIndexKind::Range { low, high } => {
+                    index.prefix_count(KeyDataRange(low.clone(), high.clone()))
+                }
+                KeyIndexKind::One(id) => index.prefix_count(KeyDataRange(
+                    Bound::Included(id.clone()),
+                    Bound::Included(id.clone()),
+                )),
+                KeyIndexKind::Many(ids) => ids.iter().try_fold(0, |count, id| {
+                    index
+                        .prefix_count(KeyDataRange(
+                            Bound::Included(id.clone()),
+                            Bound::Included(id.clone()),
+                        ))
+                        .map(|entries| count + entries)
+                }),
+                KeyIndexKind::Ranges(ranges) => ranges.iter().try_fold(0, |count, range| {
+                    index
+                        .prefix_count(range.clone())
+                        .map(|entries| count + entries)
+                }),
+            };
+        }
         match key_index.kind() {
             KeyIndexKind::All => self.main_buffer.count(KeyDataRange::from(..)),
             KeyIndexKind::Range { low, high } => self
                 .main_buffer
                 .count(KeyDataRange::from((low.clone(), high.clone()))),
             KeyIndexKind::One(_) => Ok(1),
+            KeyIndexKind::Many(ids) => Ok(ids.len() as u64),
+            KeyIndexKind::Ranges(ranges) => ranges.iter().try_fold(0, |count, range| {
+                self.main_buffer
+                    .count(range.clone())
+                    .map(|rows| count + rows)
+            }),
         }
     }
 
-    fn update(&self, _tx: &Tx, _row: Row) -> Result<(), crate::error::WeaverError> {
-        todo!()
+    fn update(&self, tx: &Tx, row: Row) -> Result<(), WeaverError> {
+        let row = self.schema.validate_update(row, tx)?;
+        trace!("validated updated row: {:?}", row);
+        let primary = self.schema.key_data(self.schema.primary_key()?, &row);
+        let old = if !self.secondary_buffers.is_empty() {
+            self.check_unique(&row, &primary)?;
+            self.get_row(&primary)?
+        } else {
+            None
+        };
+        self.main_buffer.update(primary, row.to_owned())?;
+        if let Some(old) = old {
+            self.unindex_row(&old)?;
+        }
+        self.index_row(&row)?;
+        Ok(())
     }
 
-    fn delete(&self, _tx: &Tx, _key: &KeyIndex) -> Result<Box<dyn Rows>, WeaverError> {
-        todo!()
+    fn delete(&self, tx: &Tx, key: &KeyIndex) -> Result<Box<dyn Rows>, WeaverError> {
+        let primary = self.schema.primary_key()?;
+        let mut matching = vec![];
+        let mut rows = self.read(tx, key)?;
+        while let Some(row) = rows.next() {
+            matching.push(self.schema.key_data(primary, &row));
+        }
+        drop(rows);
+
+        let deleted = matching
+            .into_iter()
+            .filter_map(|key_data| self.main_buffer.delete(&key_data).transpose())
+            .map(|bytes| bytes.and_then(|bytes| self.schema.decode(&bytes)))
+            .collect::<Result<Vec<_>, _>>()?;
+        for row in &deleted {
+            self.unindex_row(row)?;
+        }
+        trace!("deleted {} rows", deleted.len());
+        Ok(Box::new(OwnedRows::new(self.schema.clone(), deleted)))
     }
 }
 
@@ -275,10 +514,12 @@ mod tests {
     use crate::data::values::DbVal;
     use crate::dynamic_table::DynamicTable;
     use crate::error::WeaverError;
+    use crate::key::KeyData;
+    use crate::rows::{KeyIndex, KeyIndexKind, Rows};
     use crate::storage::devices::ram_file::RandomAccessFile;
     use crate::storage::paging::file_pager::FilePager;
     use crate::storage::tables::bpt_file_table::BptfTableFactory;
-    use crate::storage::tables::table_schema::TableSchema;
+    use crate::storage::tables::table_schema::{TableSchema, TableSchemaBuilder};
     use crate::storage::tables::unbuffered_table::UnbufferedTable;
     use crate::storage::VecPager;
     use crate::tx::Tx;
@@ -328,4 +569,119 @@ mod tests {
         info!("{:#?}", HexDump::new(&bytes));
         Ok(())
     }
+
+    #[test]
+    fn secondary_index_backfills() -> Result<(), WeaverError> {
+        let temp_dir = tempdir().expect("could not create temp dir");
+        let path = temp_dir.path().join("test.tbl");
+        let builder = TableSchema::builder("test", "test")
+            .column("id", Type::Integer, true, None, None)?
+            .column("name", Type::String(32), true, None, None)?
+            .column("age", Type::Integer, true, None, None)?
+            .primary(&["id"])?
+            .index("SK_age", &["age"], false)?;
+        let schema = builder.build()?;
+
+        let count = |table: &UnbufferedTable<FilePager<_>>, key_index: &KeyIndex| {
+            let tx = Tx::default();
+            let mut rows = table.read(&tx, key_index).expect("could not read");
+            let mut count = 0;
+            while rows.next().is_some() {
+                count += 1;
+            }
+            count
+        };
+
+        {
+            let table =
+                UnbufferedTable::new(schema.clone(), FilePager::open_or_create(&path)?, true)?;
+            let tx = Tx::default();
+            for (id, name) in ["josh", "chris", "jordan", "jon", "desi", "seth"]
+                .into_iter()
+                .enumerate()
+            {
+                let id = id as i64;
+                table.insert(
+                    &tx,
+                    Row::from([id.into(), name.into(), DbVal::from(20 + id % 2)]),
+                )?;
+            }
+            table.commit(&tx);
+            let twenty =
+                KeyIndex::new("SK_age", KeyIndexKind::One(KeyData::from([20])), None, None);
+            assert_eq!(count(&table, &twenty), 3);
+        }
+
+        let schema = TableSchemaBuilder::from(&schema)
+            .index("SK_name", &["name"], true)?
+            .build()?;
+        let table = UnbufferedTable::new(schema, FilePager::open_or_create(&path)?, true)?;
+        let josh = KeyIndex::new(
+            "SK_name",
+            KeyIndexKind::One(KeyData::from(["josh"])),
+            None,
+            None,
+        );
+        assert_eq!(count(&table, &josh), 1, "new index should be backfilled");
+
+        let tx = Tx::default();
+        let result = table.insert(&tx, Row::from([DbVal::from(10), "josh".into(), 30.into()]));
+        assert!(
+            matches!(result, Err(WeaverError::UniqueKeyViolation { .. })),
+            "names must be unique"
+        );
+        table.delete(&tx, &josh)?;
+        table.insert(&tx, Row::from([DbVal::from(10), "josh".into(), 30.into()]))?;
+        let thirty = KeyIndex::new("SK_age", KeyIndexKind::One(KeyData::from([30])), None, None);
+        assert_eq!(count(&table, &thirty), 1);
+        assert_eq!(count(&table, &josh), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn secondary_index_roots_are_stable() -> Result<(), WeaverError> {
+        let temp_dir = tempdir().expect("could not create temp dir");
+        let path = temp_dir.path().join("test.tbl");
+        let table_schema = |keys: &[(&str, &str)]| -> Result<TableSchema, WeaverError> {
+            keys.iter()
+                .try_fold(
+                    TableSchema::builder("test", "test")
+                        .column("id", Type::Integer, true, None, None)?
+                        .column("name", Type::String(32), true, None, None)?
+                        .column("age", Type::Integer, true, None, None)?
+                        .primary(&["id"])?,
+                    |builder, (name, column)| builder.index(name, &[column], false),
+                )?
+                .build()
+        };
+
+        {
+            let schema = table_schema(&[("SK_age", "age"), ("SK_name", "name")])?;
+            let table = UnbufferedTable::new(schema, FilePager::open_or_create(&path)?, true)?;
+            let tx = Tx::default();
+            for (id, name) in ["josh", "chris", "jordan"].into_iter().enumerate() {
+                let id = id as i64;
+                table.insert(
+                    &tx,
+                    Row::from([id.into(), name.into(), DbVal::from(20 + id)]),
+                )?;
+            }
+            table.commit(&tx);
+        }
+
+        let schema = table_schema(&[("SK_name", "name")])?;
+        let table = UnbufferedTable::new(schema, FilePager::open_or_create(&path)?, true)?;
+        let tx = Tx::default();
+        let chris = KeyIndex::new(
+            "SK_name",
+            KeyIndexKind::One(KeyData::from(["chris"])),
+            None,
+            None,
+        );
+        let mut rows = table.read(&tx, &chris)?;
+        let row = rows.next().expect("chris should be found by name");
+        assert_eq!(row[0].int_value(), Some(1));
+        assert!(rows.next().is_none());
+        Ok(())
+    }
 }
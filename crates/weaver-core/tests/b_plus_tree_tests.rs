@@ -1,5 +1,6 @@
 use rand::distributions::Alphanumeric;
 use rand::rngs::ThreadRng;
+use rand::seq::SliceRandom;
 use rand::Rng;
 
 use tracing::level_filters::LevelFilter;
@@ -7,7 +8,7 @@ use weaver_core::data::row::Row;
 use weaver_core::data::values::DbVal;
 use weaver_core::key::KeyData;
 use weaver_core::storage::b_plus_tree::BPlusTree;
-use weaver_core::storage::VecPager;
+use weaver_core::storage::{Pager, VecPager};
 
 fn insert_rand(count: usize) {
     insert((0..count).map(|_| rand::thread_rng().gen_range(-10_000..=10_000)))
@@ -111,3 +112,167 @@ fn insert_0_to_10000() {
 fn insert_10000_to_0() {
     insert((0..=10000).rev());
 }
+
+fn insert_then_delete<I: IntoIterator<Item = i64>, D: IntoIterator<Item = i64>>(
+    inserted: I,
+    deleted: D,
+) -> BPlusTree<VecPager> {
+    let btree = BPlusTree::new(VecPager::new(4096));
+    let mut remaining = std::collections::BTreeSet::new();
+    for id in inserted {
+        remaining.insert(id);
+        btree
+            .insert(KeyData::from([id]), Row::from([id, id]).to_owned())
+            .expect("could not insert");
+    }
+
+    for id in deleted {
+        let removed = btree
+            .delete(&KeyData::from([id]))
+            .unwrap_or_else(|e| panic!("failed to delete {id}: {e}"));
+        assert_eq!(
+            removed.is_some(),
+            remaining.remove(&id),
+            "only present keys should be removed (key = {id})"
+        );
+        btree.verify_integrity();
+    }
+
+    btree.print().expect("could not print");
+    assert_eq!(
+        btree.all().expect("could not get all").len(),
+        remaining.len()
+    );
+    for id in remaining {
+        assert!(
+            btree
+                .get(&KeyData::from([id]))
+                .expect("could not get")
+                .is_some(),
+            "remaining key {id} should still be present"
+        );
+    }
+    btree
+}
+
+#[test]
+fn delete_0_to_1000() {
+    let btree = insert_then_delete(0..1000, 0..1000);
+    assert_eq!(
+        btree.nodes().unwrap(),
+        1,
+        "emptied tree should only have a root"
+    );
+}
+
+#[test]
+fn delete_1000_to_0() {
+    let btree = insert_then_delete(0..1000, (0..1000).rev());
+    assert_eq!(
+        btree.nodes().unwrap(),
+        1,
+        "emptied tree should only have a root"
+    );
+}
+
+#[test]
+fn delete_rand_half() {
+    let mut ids = (0..2000).collect::<Vec<_>>();
+    ids.shuffle(&mut rand::thread_rng());
+    let _ = insert_then_delete(ids.clone(), ids.into_iter().take(1000));
+}
+
+#[test]
+fn delete_missing() {
+    let _ = insert_then_delete(0..500, (-100..0).chain(500..600));
+}
+
+#[test]
+fn delete_then_reinsert() {
+    let btree = BPlusTree::new(VecPager::new(4096));
+    for id in 0..1000_i64 {
+        btree
+            .insert(KeyData::from([id]), Row::from([id, id]).to_owned())
+            .expect("could not insert");
+    }
+    for id in (0..1000_i64).filter(|id| id % 3 != 0) {
+        btree
+            .delete(&KeyData::from([id]))
+            .expect("could not delete")
+            .expect("key should be present");
+    }
+    btree.verify_integrity();
+    for id in (0..1000_i64).filter(|id| id % 3 != 0) {
+        btree
+            .insert(KeyData::from([id]), Row::from([id, id]).to_owned())
+            .expect("could not insert");
+    }
+    btree.verify_integrity();
+    assert_eq!(btree.all().expect("could not get all").len(), 1000);
+}
+
+#[test]
+fn deleted_pages_are_reused() {
+    let btree = BPlusTree::new(VecPager::new(4096));
+    let fill = |btree: &BPlusTree<VecPager>| {
+        for id in 0..1000_i64 {
+            btree
+                .insert(KeyData::from([id]), Row::from([id, id]).to_owned())
+                .expect("could not insert");
+        }
+    };
+    let empty = |btree: &BPlusTree<VecPager>| {
+        for id in 0..1000_i64 {
+            btree
+                .delete(&KeyData::from([id]))
+                .expect("could not delete")
+                .expect("key should be present");
+        }
+    };
+
+    fill(&btree);
+    let allocated = btree.allocator().allocated();
+    for _ in 0..3 {
+        empty(&btree);
+        fill(&btree);
+        btree.verify_integrity();
+        assert_eq!(
+            btree.allocator().allocated(),
+            allocated,
+            "pages freed by deletes should be reused"
+        );
+    }
+}
+
+#[test]
+fn prefix_range() {
+    let btree = BPlusTree::new(VecPager::new(4096));
+    let mut ids = (0..2000_i64).collect::<Vec<_>>();
+    ids.shuffle(&mut rand::thread_rng());
+    for id in ids {
+        btree
+            .insert(KeyData::from([id % 10, id]), Row::from([id]).to_owned())
+            .expect("could not insert");
+    }
+
+    let one = KeyData::from([3_i64]);
+    let found = btree
+        .prefix_range(one.clone()..=one)
+        .expect("could not get prefix range");
+    assert_eq!(found.len(), 200);
+
+    let found = btree
+        .prefix_range(KeyData::from([2_i64])..KeyData::from([5_i64]))
+        .expect("could not get prefix range");
+    assert_eq!(found.len(), 600);
+
+    let found = btree
+        .prefix_range(KeyData::from([8_i64])..)
+        .expect("could not get prefix range");
+    assert_eq!(found.len(), 400);
+
+    let found = btree
+        .prefix_range(KeyData::from([10_i64])..)
+        .expect("could not get prefix range");
+    assert!(found.is_empty(), "no keys start past the maximum");
+}
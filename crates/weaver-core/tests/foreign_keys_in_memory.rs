@@ -0,0 +1,86 @@
+use tempfile::TempDir;
+use weaver_core::access_control::auth::init::AuthConfig;
+use weaver_core::ast::ReferentialAction;
+use weaver_core::data::row::Row;
+use weaver_core::data::types::Type;
+use weaver_core::data::values::DbVal;
+use weaver_core::db::core::WeaverDbCore;
+use weaver_core::db::server::layers::packets::{DbReqBody, DbResp, IntoDbResponse};
+use weaver_core::db::server::WeaverDb;
+use weaver_core::dynamic_table::DynamicTable;
+use weaver_core::error::WeaverError;
+use weaver_core::rows::Rows;
+use weaver_core::storage::tables::table_schema::{ForeignKey, TableSchema};
+
+#[test]
+fn foreign_keys_in_memory() -> Result<(), WeaverError> {
+    let dir = TempDir::new()?;
+    let core = WeaverDbCore::with_path(dir.path())?;
+    let db = WeaverDb::new(core, AuthConfig::in_path(dir.path()))?;
+    let mut service = db.lifecycle_service();
+    service.startup()?;
+
+    let socket = db.connect();
+    socket
+        .send(DbReqBody::on_core_write(|db, _| {
+            Ok((|| -> Result<_, WeaverError> {
+                let parents = &(TableSchema::builder("default", "parents")
+                    .column("id", Type::Integer, true, None, None)?
+                    .primary(&["id"])?
+                    .in_memory()
+                    .build()?);
+                let children = &(TableSchema::builder("default", "children")
+                    .column("id", Type::Integer, true, None, None)?
+                    .column("parent", Type::Integer, false, None, None)?
+                    .primary(&["id"])?
+                    .foreign_key(ForeignKey::new(
+                        "FK_parent",
+                        vec!["parent".to_string()],
+                        ("default", "parents"),
+                        vec!["id".to_string()],
+                        ReferentialAction::Cascade,
+                        ReferentialAction::Restrict,
+                    )?)?
+                    .in_memory()
+                    .build()?);
+                db.open_table(parents)?;
+                db.open_table(children)?;
+                let parent_table = db.get_open_table("default", "parents")?;
+                let child_table = db.get_open_table("default", "children")?;
+
+                let tx = db.start_transaction();
+                parent_table.insert(&tx, Row::from([DbVal::Integer(1)]))?;
+                child_table.insert(&tx, Row::from([DbVal::Integer(1), DbVal::Integer(1)]))?;
+                assert!(
+                    matches!(
+                        child_table.insert(&tx, Row::from([DbVal::Integer(2), DbVal::Integer(2)])),
+                        Err(WeaverError::ForeignKeyViolation { .. })
+                    ),
+                    "inserting through the table should check its foreign keys"
+                );
+
+                let mut deleted = parent_table.delete(&tx, &parents.primary_key()?.all())?;
+                let mut count = 0;
+                while deleted.next().is_some() {
+                    count += 1;
+                }
+                drop(deleted);
+                assert_eq!(count, 1);
+                let mut remaining = child_table.all(&tx)?;
+                assert!(
+                    remaining.next().is_none(),
+                    "deleting through the table should cascade to referencing rows"
+                );
+                drop(remaining);
+                tx.commit();
+
+                Ok(DbResp::Ok)
+            })()
+            .into_db_resp())
+        }))
+        .join()
+        .unwrap()
+        .expect("socket failed");
+
+    Ok(())
+}
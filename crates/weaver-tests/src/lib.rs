@@ -8,17 +8,20 @@ use std::thread::JoinHandle;
 use crossbeam::channel::{bounded, Sender};
 use eyre::eyre;
 use tracing::level_filters::LevelFilter;
-use tracing::{debug, error, error_span, warn};
+use tracing::{debug, error, error_span, info, warn};
 
 use weaver_client::WeaverClient;
 use weaver_core::access_control::auth::init::AuthConfig;
 use weaver_core::access_control::auth::LoginContext;
+use weaver_core::ast::Query;
 use weaver_core::cnxn::interprocess::LocalSocketStream;
 use weaver_core::common::dual_result::DualResult;
+use weaver_core::data::values::DbVal;
 use weaver_core::db::core::WeaverDbCore;
 use weaver_core::db::server::WeaverDb;
 
 use weaver_core::monitoring::{Monitor, Monitorable};
+use weaver_core::rows::Rows;
 
 pub fn init_tracing(
     level_filter: impl Into<Option<LevelFilter>>,
@@ -32,6 +35,81 @@ pub fn init_tracing(
         .try_init()
 }
 
+/// Runs a ddl statement, returning whether it succeeded
+pub fn ddl(client: &mut WeaverClient<LocalSocketStream>, query: &str) -> eyre::Result<bool> {
+    let (mut rows, _) = client.query(&Query::parse(query)?)?;
+    let result = rows.next().expect("should have a result row");
+    let shown = query
+        .char_indices()
+        .nth(64)
+        .map_or(query, |(end, _)| &query[..end]);
+    info!("{shown} -> ok: {}, err: {}", result[0], result[1]);
+    Ok(*result[1] == DbVal::Null)
+}
+
+/// Gets every row returned by a query, with each value as a string
+pub fn rows(
+    client: &mut WeaverClient<LocalSocketStream>,
+    query: &str,
+) -> eyre::Result<Vec<String>> {
+    let (rows, _) = client.query(&Query::parse(query)?)?;
+    Ok(rows
+        .to_owned()
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|val| val.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect())
+}
+
+/// Creates a table from its definition, such as `weaver.people (id int primary key, age int)`,
+/// then inserts rows of values for every one of its columns, such as `(1, 20)`
+pub fn create_table(
+    client: &mut WeaverClient<LocalSocketStream>,
+    definition: &str,
+    values: &[&str],
+) -> eyre::Result<()> {
+    assert!(ddl(client, &format!("create table {definition}"))?);
+    if !values.is_empty() {
+        let table = definition.split_whitespace().next().unwrap_or(definition);
+        assert!(ddl(
+            client,
+            &format!("insert into {table} values {}", values.join(", "))
+        )?);
+    }
+    Ok(())
+}
+
+/// Creates `weaver.people`, with an id, name and age, containing the given rows
+pub fn create_people(
+    client: &mut WeaverClient<LocalSocketStream>,
+    values: &[&str],
+) -> eyre::Result<()> {
+    create_table(
+        client,
+        "weaver.people (id int primary key, name varchar(16), age int)",
+        values,
+    )
+}
+
+/// Creates `weaver.employees`, where alex, chris and josh work in eng and kim and sam in ops
+pub fn create_employees(client: &mut WeaverClient<LocalSocketStream>) -> eyre::Result<()> {
+    create_table(
+        client,
+        "weaver.employees (id int primary key, dept varchar(8), name varchar(16), salary int)",
+        &[
+            "(1, 'eng', 'alex', 100)",
+            "(2, 'eng', 'chris', 80)",
+            "(3, 'eng', 'josh', 80)",
+            "(4, 'ops', 'kim', 60)",
+            "(5, 'ops', 'sam', 70)",
+        ],
+    )
+}
+
 pub fn start_server(
     port: u16,
     in_path: &Path,
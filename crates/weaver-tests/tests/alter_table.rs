@@ -0,0 +1,166 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_core::ast::Query;
+use weaver_tests::{ddl, init_tracing, rows, run_full_stack_local_socket};
+
+#[test]
+fn alter_table_columns() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        client.query(&Query::parse(
+            "create table weaver.people ( id INT primary key, name varchar(8) not null, age INT )",
+        )?)?;
+        client.query(&Query::parse(
+            "insert into weaver.people (id, name, age) values (1, 'josh', 25), (2, 'chris', 30)",
+        )?)?;
+
+        assert!(ddl(
+            client,
+            "alter table weaver.people add column height float, drop column age, \
+            rename column name to full_name, modify column full_name varchar(32) not null"
+        )?);
+        assert!(
+            !ddl(client, "alter table weaver.people modify id varchar(32)")?,
+            "int can not be narrowed to a string"
+        );
+        assert!(
+            !ddl(client, "alter table weaver.people add height float")?,
+            "column names must be unique"
+        );
+        assert!(
+            !ddl(client, "alter table weaver.people drop column age")?,
+            "dropped columns no longer exist"
+        );
+        assert!(
+            !ddl(
+                client,
+                "alter table weaver.people add column nick varchar(8) not null"
+            )?,
+            "existing rows have no value for the new column"
+        );
+        assert!(
+            !ddl(client, "alter table weaver.tables add column extra int")?,
+            "protected tables can not be altered"
+        );
+        assert!(
+            !ddl(
+                client,
+                "alter table weaver.people add column code int default 7 unique key"
+            )?,
+            "existing rows would share the default of a unique column"
+        );
+        assert_eq!(
+            rows(client, "select * from weaver.people order by id")?,
+            ["1,josh,", "2,chris,"],
+            "a failed alteration should leave the table untouched"
+        );
+        assert!(
+            !temp_dir
+                .path()
+                .join("weaver")
+                .join("people.staging")
+                .exists(),
+            "staging storage should be removed"
+        );
+
+        client.query(&Query::parse(
+            "insert into weaver.people (id, full_name, height) values (3, 'jordan the great', 1.8)",
+        )?)?;
+        assert_eq!(
+            rows(
+                client,
+                "select id, full_name, height from weaver.people order by id"
+            )?,
+            ["1,josh,", "2,chris,", "3,jordan the great,1.8"]
+        );
+
+        Ok(())
+    })?;
+
+    info!("altered definition is used after a restart");
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert_eq!(
+            rows(
+                client,
+                "select id, full_name, height from weaver.people order by id"
+            )?,
+            ["1,josh,", "2,chris,", "3,jordan the great,1.8"]
+        );
+        assert!(ddl(
+            client,
+            "alter table weaver.people modify column id float"
+        )?);
+        let ddl = rows(
+            client,
+            "select table_ddl from weaver.tables where name = 'people'",
+        )?;
+        assert!(
+            ddl[0].contains("`id` float"),
+            "stored ddl should be updated"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn alter_table_keys() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        client.query(&Query::parse(
+            "create table weaver.people ( id INT primary key, first varchar(32), last varchar(32) )",
+        )?)?;
+        client.query(&Query::parse(
+            "insert into weaver.people (id, first, last) values (1, 'josh', 'a'), (2, 'josh', 'b')",
+        )?)?;
+
+        assert!(ddl(
+            client,
+            "create unique index idx_name on weaver.people (first, last)"
+        )?);
+        assert!(
+            !ddl(client, "alter table weaver.people drop column last")?,
+            "the remaining columns of the unique key contain duplicates"
+        );
+        assert!(ddl(
+            client,
+            "alter table weaver.people rename column first to given"
+        )?);
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.people where given = 'josh' and last = 'b'"
+            )?,
+            ["2"]
+        );
+
+        info!("columns of the primary key can not be dropped");
+        assert!(!ddl(client, "alter table weaver.people drop column id")?);
+        assert!(ddl(
+            client,
+            "create table weaver.pairs (a int, b int, c int, primary key (a, b))"
+        )?);
+        assert!(ddl(
+            client,
+            "insert into weaver.pairs (a, b, c) values (1, 1, 1), (1, 2, 2)"
+        )?);
+        assert!(
+            !ddl(client, "alter table weaver.pairs drop column b")?,
+            "a composite primary key should not shrink"
+        );
+        assert!(ddl(client, "alter table weaver.pairs drop column c")?);
+        assert_eq!(
+            rows(client, "select a, b from weaver.pairs order by b")?,
+            ["1,1", "1,2"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use tempfile::TempDir;
+
+use weaver_tests::{create_table, ddl, init_tracing, rows, run_full_stack_local_socket};
+
+#[test]
+fn casts() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        // the readings and flags are inserted as ints, and coerced to floats and booleans
+        create_table(
+            client,
+            "weaver.measurements (id int primary key, label varchar(8), reading float, \
+            flag boolean)",
+            &["(1, 'a', 1, 1)", "(2, 'b', 2.5, 0)", "(3, 'c', null, 0)"],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select id, reading / 2, flag from weaver.measurements order by id"
+            )?,
+            ["1,0.5,true", "2,1.25,false", "3,,false"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id, cast(reading as int), id::float / 2, cast(id as varchar(4)) + label, \
+                '4'::int * id from weaver.measurements order by id"
+            )?,
+            ["1,1,0.5,1a,4", "2,2,1,2b,8", "3,,1.5,3c,12"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id, cast(flag as int) + 1, cast(' true ' as boolean) from \
+                weaver.measurements where cast(id as boolean) and label < 'c' order by id"
+            )?,
+            ["1,2,true", "2,1,true"]
+        );
+        Ok(())
+    })?;
+    Ok(())
+}
+
+#[test]
+fn promotions() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        // the readings and flags are inserted as ints, and coerced to floats and booleans
+        create_table(
+            client,
+            "weaver.measurements (id int primary key, reading float, flag boolean)",
+            &["(1, 1, 1)", "(2, 2.5, 0)", "(3, null, 0)"],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select id, id + reading, reading * 2 from weaver.measurements order by id"
+            )?,
+            ["1,2,2", "2,4.5,5", "3,,"],
+            "ints should be promoted to floats"
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.measurements where reading >= 2"
+            )?,
+            ["2"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id, flag + 1 from weaver.measurements order by id"
+            )?,
+            ["1,2", "2,1", "3,1"],
+            "booleans should be promoted to ints"
+        );
+        Ok(())
+    })?;
+    Ok(())
+}
+
+#[test]
+fn type_errors() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.measurements (id int primary key, label varchar(8), reading float, \
+            flag boolean)",
+            &["(1, 'a', 1, 1)", "(2, 'b', 2.5, 0)", "(3, 'c', null, 0)"],
+        )?;
+
+        assert!(
+            rows(client, "select label + id from weaver.measurements").is_err(),
+            "strings and ints have no common type"
+        );
+        assert!(
+            rows(client, "select id from weaver.measurements where label = 1").is_err(),
+            "strings and ints can not be compared"
+        );
+        assert!(
+            rows(client, "select cast(label as int) from weaver.measurements").is_err(),
+            "labels are not numbers"
+        );
+        assert!(
+            rows(client, "select id / 0 from weaver.measurements").is_err(),
+            "ints can not be divided by zero"
+        );
+        let err = rows(
+            client,
+            "select id from weaver.measurements where id = 1 / 0",
+        )
+        .expect_err("constants that can not be reduced should fail when evaluated");
+        assert_eq!(err.to_string(), "Division by zero");
+        assert!(
+            rows(client, "select -flag from weaver.measurements").is_err(),
+            "booleans can not be negated"
+        );
+        assert!(
+            !matches!(
+                ddl(
+                    client,
+                    "insert into weaver.measurements (id, label, reading, flag) values \
+                    (4, 'd', 1.5, 2)"
+                ),
+                Ok(true)
+            ),
+            "only 0 and 1 can be assigned to booleans"
+        );
+        assert!(
+            !matches!(
+                ddl(
+                    client,
+                    "insert into weaver.measurements (id, label, reading, flag) values \
+                    (4, 'd', 'abc', 1)"
+                ),
+                Ok(true)
+            ),
+            "strings are not implicitly parsed"
+        );
+        assert_eq!(
+            rows(client, "select id from weaver.measurements where id > 3")?,
+            Vec::<String>::new(),
+            "failed inserts should not write any rows"
+        );
+        Ok(())
+    })?;
+    Ok(())
+}
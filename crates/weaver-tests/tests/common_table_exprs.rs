@@ -0,0 +1,184 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_core::ast::Query;
+use weaver_core::rows::Rows;
+use weaver_tests::{create_table, ddl, init_tracing, rows, run_full_stack_local_socket};
+
+#[test]
+fn with() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.people (id int primary key, name varchar(16), age int, manager_id int)",
+            &[
+                "(1, 'alex', 50, null)",
+                "(2, 'chris', 35, 1)",
+                "(3, 'josh', 20, 2)",
+                "(4, 'kim', 41, 1)",
+                "(5, 'sam', 28, 3)",
+            ],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "with adults as (select name, age from weaver.people where age > 30) \
+                select name from adults where age < 45 order by name"
+            )?,
+            ["chris", "kim"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "with managers (id, manager) as \
+                (select id, name from weaver.people where id in (1, 2)) \
+                select name, manager from weaver.people \
+                join managers on people.manager_id = managers.id order by name"
+            )?,
+            ["chris,alex", "josh,chris", "kim,alex"]
+        );
+
+        info!("common table expressions can use the ones before them");
+        assert_eq!(
+            rows(
+                client,
+                "with young as (select id, name, age from weaver.people where age < 40), \
+                youngest as (select name from young where age < 25) \
+                select * from youngest"
+            )?,
+            ["josh"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "with old as (select age from weaver.people where age > 40) \
+                select name from weaver.people where age = (select max(age) from old)"
+            )?,
+            ["alex"]
+        );
+
+        assert!(
+            rows(
+                client,
+                "with named (a, b) as (select name from weaver.people) select * from named"
+            )
+            .is_err(),
+            "column list must match the select"
+        );
+        assert!(
+            rows(
+                client,
+                "select name from weaver.people where age > \
+                (with ages as (select age from weaver.people) select min(age) from ages)"
+            )
+            .is_err(),
+            "with is only supported at the start of a query"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn with_recursive() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        info!("chris reports to alex, josh to chris and sam to josh");
+        create_table(
+            client,
+            "weaver.people (id int primary key, name varchar(16), manager_id int)",
+            &[
+                "(1, 'alex', null)",
+                "(2, 'chris', 1)",
+                "(3, 'josh', 2)",
+                "(5, 'sam', 3)",
+            ],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "with recursive reports (id, name, depth) as ( \
+                select id, name, 0 from weaver.people where id = 2 \
+                union all \
+                select people.id, people.name, depth + 1 from weaver.people \
+                join reports on people.manager_id = reports.id) \
+                select name, depth from reports order by depth"
+            )?,
+            ["chris,0", "josh,1", "sam,2"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "with recursive counter (n) as ( \
+                select 1 from weaver.people where id = 1 \
+                union all \
+                select n + 1 from counter where n < 5) \
+                select n from counter"
+            )?,
+            ["1", "2", "3", "4", "5"]
+        );
+
+        info!("union stops once no new rows are found");
+        assert!(ddl(
+            client,
+            "update weaver.people set manager_id = 5 where id = 1"
+        )?);
+        assert_eq!(
+            rows(
+                client,
+                "with recursive chain (id) as ( \
+                select id from weaver.people where id = 3 \
+                union \
+                select manager_id from weaver.people join chain on people.id = chain.id) \
+                select id from chain order by id"
+            )?,
+            ["1", "2", "3", "5"]
+        );
+        assert!(
+            rows(
+                client,
+                "with recursive chain (id) as ( \
+                select id from weaver.people where id = 3 \
+                union all \
+                select manager_id from weaver.people join chain on people.id = chain.id) \
+                select id from chain"
+            )
+            .is_err(),
+            "a cycle never ends with union all"
+        );
+
+        let (explained, _) = client.query(&Query::parse(
+            "explain with recursive counter (n) as ( \
+            select 1 from weaver.people where id = 1 \
+            union all select n + 1 from counter where n < 5) \
+            select n from counter",
+        )?)?;
+        assert!(explained
+            .to_owned()
+            .iter()
+            .any(|row| row[3].to_string() == "recursive"));
+
+        assert!(
+            rows(
+                client,
+                "with recursive counter (n) as ( \
+                select 1 from weaver.people where id = 1 \
+                intersect select n + 1 from counter where n < 5) \
+                select n from counter"
+            )
+            .is_err(),
+            "only union can be recursive"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
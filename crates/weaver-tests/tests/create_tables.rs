@@ -5,7 +5,7 @@ use tracing::info;
 
 use weaver_client::write_rows::write_rows;
 use weaver_core::ast::Query;
-use weaver_tests::{init_tracing, run_full_stack_local_socket};
+use weaver_tests::{ddl, init_tracing, rows, run_full_stack_local_socket};
 
 #[test]
 fn explain_create_table() -> eyre::Result<()> {
@@ -40,3 +40,77 @@ fn create_simple_table() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn create_table_with_constraints() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            r#"
+            create table weaver.people (
+                first varchar(32) not null,
+                last varchar(32) not null,
+                email varchar(64),
+                age int,
+                primary key (first, last),
+                constraint uk_email unique (email),
+                key idx_age (age)
+            )
+        "#
+        )?);
+        assert!(ddl(
+            client,
+            "insert into weaver.people (first, last, email, age) values \
+            ('josh', 'a', 'josh@a', 25), ('josh', 'b', 'josh@b', 25), ('chris', 'a', null, 30)"
+        )?);
+
+        let ddl_string = rows(
+            client,
+            "select table_ddl from weaver.tables where name = 'people'",
+        )?;
+        info!("ddl: {}", ddl_string[0]);
+        assert!(ddl_string[0].contains("index `PRIMARY` (first, last) primary"));
+        assert!(ddl_string[0].contains("index `uk_email` (email) unique"));
+        assert!(ddl_string[0].contains("index `idx_age` (age)"));
+
+        assert!(
+            !ddl(
+                client,
+                "insert into weaver.people (first, last, email) values ('jordan', 'a', 'josh@a')"
+            )?,
+            "emails must be unique"
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select email from weaver.people where first = 'josh' and last = 'b'"
+            )?,
+            ["josh@b"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn create_table_with_bad_constraints() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        for query in [
+            "create table weaver.bad (id int primary key, other int, primary key (other))",
+            "create table weaver.bad (id int, key (missing))",
+            "create table weaver.bad (id int, key k (id), unique key k (id))",
+        ] {
+            assert!(!ddl(client, query)?, "{query} should fail");
+        }
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_tests::{create_table, ddl, init_tracing, rows, run_full_stack_local_socket};
+
+#[test]
+fn decimal_columns() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.ledger (id int primary key, amount decimal(10, 2), rate numeric(5, 4))",
+            &[
+                "(1, 19.99, 0.0725)",
+                "(2, '0.10', 0.1)",
+                "(3, 0.20, '1')",
+                "(4, -5, 0.0001)",
+            ],
+        )?;
+
+        assert_eq!(
+            rows(client, "select id, amount, rate from weaver.ledger")?,
+            [
+                "1,19.99,0.0725",
+                "2,0.10,0.1000",
+                "3,0.20,1.0000",
+                "4,-5.00,0.0001"
+            ],
+            "values should be stored with the scale of their column"
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select amount from weaver.ledger order by amount desc"
+            )?,
+            ["19.99", "0.20", "0.10", "-5.00"]
+        );
+        assert_eq!(
+            rows(client, "select id from weaver.ledger where amount = '0.1'")?,
+            ["2"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.ledger where amount > 0.15 order by id"
+            )?,
+            ["1", "3"]
+        );
+        assert!(
+            !ddl(
+                client,
+                "insert into weaver.ledger (id, amount) values (5, 123456789.5)"
+            )?,
+            "amounts should be at most 8 digits before the decimal point"
+        );
+
+        let ddl_string = rows(
+            client,
+            "select table_ddl from weaver.tables where name = 'ledger'",
+        )?;
+        assert!(ddl_string[0].contains("`amount` decimal(10, 2)"));
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn exact_arithmetic() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.ledger (id int primary key, amount decimal(10, 2), rate numeric(5, 4))",
+            &[
+                "(1, 19.99, 0.0725)",
+                "(2, '0.10', 0.1)",
+                "(3, 0.20, '1')",
+                "(4, -5, 0.0001)",
+            ],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select amount * rate, amount / 3, amount - 1, -amount, amount + amount \
+                from weaver.ledger where id = 1"
+            )?,
+            ["1.449275,6.6633333333333333333333,18.99,-19.99,39.98"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select cast(amount as int), cast('12.345' as decimal(5, 2)), cast(rate as float) \
+                from weaver.ledger where id = 1"
+            )?,
+            ["19,12.35,0.0725"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select sum(amount), avg(amount), min(rate), max(rate) from weaver.ledger"
+            )?,
+            ["15.29,3.822500,0.0001,1.0000"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select sum(amount) from weaver.ledger where id >= 2"
+            )?,
+            ["-4.70"],
+            "0.10 + 0.20 should be exactly 0.30"
+        );
+
+        for query in [
+            "select amount / 0 from weaver.ledger",
+            "select amount + 'ten' from weaver.ledger",
+            "select cast(amount as decimal(3, 2)) from weaver.ledger",
+        ] {
+            assert!(rows(client, query).is_err(), "{query} should fail");
+        }
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn decimal_keys() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            "create table weaver.prices (price decimal(6, 3) primary key, label varchar(8))"
+        )?);
+        assert!(ddl(
+            client,
+            "insert into weaver.prices (price, label) values \
+            (10.5, 'ten'), (-2.125, 'minus'), (0.001, 'tiny'), (100, 'hundred')"
+        )?);
+        // equal decimals are the same primary key, whatever their scale
+        assert!(!ddl(
+            client,
+            "insert into weaver.prices (price, label) values (10.500, 'again')"
+        )?);
+
+        Ok(())
+    })?;
+
+    info!("decimal keys are kept in order after a restart");
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert_eq!(
+            rows(client, "select price, label from weaver.prices")?,
+            [
+                "-2.125,minus",
+                "0.001,tiny",
+                "10.500,ten",
+                "100.000,hundred"
+            ]
+        );
+        assert_eq!(
+            rows(client, "select label from weaver.prices where price = 10.5")?,
+            ["ten"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn exact_literals() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            "create table weaver.balances (id int primary key, balance decimal(30, 2))"
+        )?);
+        assert!(ddl(
+            client,
+            "insert into weaver.balances (id, balance) values \
+            (1, 1234567890123456789.01), (2, -99999999999999999999), (3, 0.1 + 0.2)"
+        )?);
+
+        assert_eq!(
+            rows(client, "select id, balance from weaver.balances")?,
+            [
+                "1,1234567890123456789.01",
+                "2,-99999999999999999999.00",
+                "3,0.30"
+            ],
+            "numeric literals should not be rounded through a float"
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.balances where balance = 1234567890123456789.01"
+            )?,
+            ["1"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select pow(1.5, 2.0), 1.5e0 * 2 from weaver.balances where id = 1"
+            )?,
+            ["2.25,3"],
+            "decimals are still passed to float functions"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
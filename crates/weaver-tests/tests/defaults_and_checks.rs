@@ -0,0 +1,170 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_tests::{ddl, init_tracing, rows, run_full_stack_local_socket};
+
+#[test]
+fn column_defaults() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            "create table weaver.people (id int primary key, name varchar(16) not null default 'anon', \
+            height float default 1, created timestamp with time zone default now())"
+        )?);
+        assert!(ddl(
+            client,
+            "insert into weaver.people (id, name) values (1, 'josh')"
+        )?);
+        assert!(ddl(
+            client,
+            "insert into weaver.people (id, height) values (2, 2.5)"
+        )?);
+        assert_eq!(
+            rows(
+                client,
+                "select id, name, height from weaver.people order by id"
+            )?,
+            ["1,josh,1", "2,anon,2.5"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.people where created > '2000-01-01' order by id"
+            )?,
+            ["1", "2"],
+            "now() should be evaluated on insert"
+        );
+
+        for query in [
+            "create table weaver.bad (id int, other int default id)",
+            "create table weaver.bad (id int default 'one')",
+            "create table weaver.bad (id int default now() auto_increment)",
+            "create table weaver.bad (id int default uuid())",
+            "create table weaver.bad (id int, at time default now())",
+        ] {
+            assert!(!ddl(client, query)?, "{query} should fail");
+        }
+
+        Ok(())
+    })?;
+
+    info!("defaults are kept after a restart");
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(client, "insert into weaver.people (id) values (3)")?);
+        assert_eq!(
+            rows(
+                client,
+                "select name, height from weaver.people where id = 3 and created > '2000-01-01'"
+            )?,
+            ["anon,1"]
+        );
+        let ddl_string = rows(
+            client,
+            "select table_ddl from weaver.tables where name = 'people'",
+        )?;
+        assert!(ddl_string[0].contains("`created` timestamp with time zone default now()"));
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn check_constraints() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            "create table weaver.people (id int primary key, age int, min_age int, \
+            constraint ck_age check (age >= 0 and age < 150), check (age >= min_age))"
+        )?);
+        assert!(ddl(
+            client,
+            "insert into weaver.people (id, age, min_age) values (1, 20, 18), (2, null, 18)"
+        )?);
+        assert!(
+            !ddl(client, "insert into weaver.people (id, age) values (3, -1)")?,
+            "age must not be negative"
+        );
+        assert!(
+            !ddl(
+                client,
+                "insert into weaver.people (id, age, min_age) values (3, 16, 18)"
+            )?,
+            "age must be at least the minimum age"
+        );
+        assert!(
+            !ddl(client, "update weaver.people set age = 200 where id = 1")?,
+            "updates are checked too"
+        );
+        assert_eq!(
+            rows(client, "select id, age from weaver.people order by id")?,
+            ["1,20", "2,"]
+        );
+
+        info!("checks handling null themselves are enforced on null values");
+        assert!(ddl(
+            client,
+            "create table weaver.scores (id int primary key, x int, check (coalesce(x, 0) > 5))"
+        )?);
+        assert!(ddl(
+            client,
+            "insert into weaver.scores (id, x) values (1, 6)"
+        )?);
+        assert!(
+            !ddl(client, "insert into weaver.scores (id, x) values (2, null)")?,
+            "a null x is treated as 0"
+        );
+
+        for query in [
+            "create table weaver.bad (id int, check (missing > 0))",
+            "create table weaver.bad (id int, constraint ck check (id > 0), constraint ck check (id < 5))",
+        ] {
+            assert!(!ddl(client, query)?, "{query} should fail");
+        }
+
+        Ok(())
+    })?;
+
+    info!("checks are kept after a restart and follow altered columns");
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(!ddl(
+            client,
+            "insert into weaver.people (id, age) values (3, -1)"
+        )?);
+        let ddl_string = rows(
+            client,
+            "select table_ddl from weaver.tables where name = 'people'",
+        )?;
+        assert!(ddl_string[0].contains("constraint `ck_age` check"));
+
+        assert!(ddl(
+            client,
+            "alter table weaver.people rename column age to years"
+        )?);
+        assert!(!ddl(
+            client,
+            "insert into weaver.people (id, years) values (3, -1)"
+        )?);
+        assert!(ddl(
+            client,
+            "alter table weaver.people drop column min_age"
+        )?);
+        assert!(ddl(
+            client,
+            "insert into weaver.people (id, years) values (3, 5)"
+        )?);
+        assert!(!ddl(
+            client,
+            "insert into weaver.people (id, years) values (4, 150)"
+        )?);
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use std::io::stdout;
+
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_client::write_rows::write_rows;
+use weaver_core::ast::Query;
+use weaver_core::rows::Rows;
+use weaver_tests::{ddl, init_tracing, rows, run_full_stack_local_socket};
+
+#[test]
+fn delete_rows() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        client.query(&Query::parse(
+            r#"create table weaver.people ( id INT primary key, name varchar(32) not null, age int )"#,
+        )?)?;
+        client.query(&Query::parse(
+            r#"insert into weaver.people (id, name, age) values (1, 'josh', 25), (2, 'chris', 3), (3, 'jane', 40), (4, 'john', 52)"#,
+        )?)?;
+
+        info!("delete by primary key");
+        let (mut rows, _) =
+            client.query(&Query::parse(r#"delete from weaver.people where id = 2"#)?)?;
+        let result = rows.next().expect("should have a result row");
+        assert_eq!(result[0].to_string(), "1", "one row should be deleted");
+        drop(rows);
+
+        info!("delete by condition");
+        let (mut rows, _) = client.query(&Query::parse(
+            r#"delete from weaver.people where age > 30"#,
+        )?)?;
+        let result = rows.next().expect("should have a result row");
+        assert_eq!(result[0].to_string(), "2", "two rows should be deleted");
+        let mut deleted = vec![];
+        while let Some(row) = rows.next() {
+            deleted.push((row[2].to_string(), row[3].to_string(), row[4].to_string()));
+        }
+        deleted.sort();
+        assert_eq!(
+            deleted,
+            [
+                ("3".to_string(), "jane".to_string(), "40".to_string()),
+                ("4".to_string(), "john".to_string(), "52".to_string()),
+            ],
+            "deleted rows should be returned after the count"
+        );
+        drop(rows);
+
+        let (rows, elapsed) = client.query(&Query::parse(
+            "select id, name from weaver.people order by id",
+        )?)?;
+        let rows = rows.to_owned();
+        let values = rows
+            .iter()
+            .map(|row| row[1].to_string())
+            .collect::<Vec<_>>();
+        write_rows(stdout(), rows, elapsed).expect("could not write rows");
+        assert_eq!(values, ["josh"]);
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn delete_all_rows() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        client.query(&Query::parse(
+            r#"create table weaver.numbers ( id INT primary key, value int )"#,
+        )?)?;
+        let values = (0..256)
+            .map(|i| format!("({i}, {})", i * 2))
+            .collect::<Vec<_>>()
+            .join(", ");
+        client.query(&Query::parse(&format!(
+            "insert into weaver.numbers (id, value) values {values}"
+        ))?)?;
+
+        let (mut rows, _) = client.query(&Query::parse(r#"delete from weaver.numbers"#)?)?;
+        let result = rows.next().expect("should have a result row");
+        assert_eq!(result[0].to_string(), "256", "every row should be deleted");
+        drop(rows);
+
+        let (rows, _) = client.query(&Query::parse("select * from weaver.numbers")?)?;
+        assert_eq!(rows.to_owned().iter().count(), 0, "table should be empty");
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn delete_columns_named_like_the_summary() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        client.query(&Query::parse(
+            r#"create table weaver.results ( id INT primary key, ok varchar(8), err varchar(8) )"#,
+        )?)?;
+        client.query(&Query::parse(
+            r#"insert into weaver.results (id, ok, err) values (1, 'yes', 'no')"#,
+        )?)?;
+
+        let (mut rows, _) =
+            client.query(&Query::parse(r#"delete from weaver.results where id = 1"#)?)?;
+        let columns = rows
+            .schema()
+            .columns()
+            .iter()
+            .map(|column| column.name().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(columns, ["@@ok", "@@err", "id", "ok", "err"]);
+        let result = rows.next().expect("should have a result row");
+        assert_eq!(result[0].to_string(), "1", "one row should be deleted");
+        let deleted = rows.next().expect("should return the deleted row");
+        assert_eq!(
+            (deleted[3].to_string(), deleted[4].to_string()),
+            ("yes".to_string(), "no".to_string())
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn delete_by_float_against_int_key() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(client, "create table weaver.t ( id INT primary key )")?);
+        assert!(ddl(
+            client,
+            "insert into weaver.t (id) values (1), (2), (3)"
+        )?);
+
+        assert!(ddl(client, "delete from weaver.t where id = 2.0")?);
+        assert_eq!(
+            rows(client, "select id from weaver.t order by id")?,
+            ["1", "3"]
+        );
+
+        assert!(ddl(client, "delete from weaver.t where id > 1.5")?);
+        assert_eq!(rows(client, "select id from weaver.t order by id")?, ["1"]);
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_client::WeaverClient;
+use weaver_core::ast::Query;
+use weaver_core::cnxn::interprocess::LocalSocketStream;
+use weaver_core::rows::Rows;
+use weaver_tests::{create_employees, ddl, init_tracing, rows, run_full_stack_local_socket};
+
+/// Gets the join kinds of the nodes of an explained query
+fn explained(
+    client: &mut WeaverClient<LocalSocketStream>,
+    query: &str,
+) -> eyre::Result<Vec<String>> {
+    let (explained, _) = client.query(&Query::parse(&format!("explain {query}"))?)?;
+    Ok(explained
+        .to_owned()
+        .iter()
+        .map(|row| row[3].to_string())
+        .collect())
+}
+
+#[test]
+fn select_distinct() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_employees(client)?;
+        assert!(ddl(
+            client,
+            "insert into weaver.employees (id, dept, name, salary) values (6, 'ops', 'lee', null)"
+        )?);
+
+        assert_eq!(
+            rows(
+                client,
+                "select distinct dept from weaver.employees order by dept"
+            )?,
+            ["eng", "ops"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select distinct dept from weaver.employees order by dept desc"
+            )?,
+            ["ops", "eng"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select distinct dept, salary from weaver.employees order by dept, salary"
+            )?,
+            ["eng,80", "eng,100", "ops,", "ops,60", "ops,70"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select distinct salary from weaver.employees order by salary limit 2"
+            )?,
+            ["", "60"]
+        );
+
+        let mut unordered = rows(client, "select distinct dept from weaver.employees")?;
+        unordered.sort();
+        assert_eq!(unordered, ["eng", "ops"]);
+
+        info!("duplicates are only removed from the select they belong to");
+        assert_eq!(
+            rows(
+                client,
+                "select distinct dept from weaver.employees union all \
+                select dept from weaver.employees where id = 1"
+            )?
+            .len(),
+            3
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn sorted_distinct() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_employees(client)?;
+        assert!(ddl(
+            client,
+            "insert into weaver.employees (id, dept, name, salary) values (6, 'ops', 'lee', null)"
+        )?);
+
+        let kinds = explained(
+            client,
+            "select distinct dept, salary from weaver.employees order by salary, dept",
+        )?;
+        assert!(kinds.contains(&"sorted-distinct".to_string()), "{kinds:?}");
+        let kinds = explained(client, "select distinct dept from weaver.employees")?;
+        assert!(kinds.contains(&"hash-distinct".to_string()), "{kinds:?}");
+
+        info!("rows ordered by only some of the columns may have duplicates apart");
+        let kinds = explained(
+            client,
+            "select distinct dept, salary from weaver.employees order by dept",
+        )?;
+        assert!(kinds.contains(&"hash-distinct".to_string()), "{kinds:?}");
+        assert_eq!(
+            rows(
+                client,
+                "select distinct dept, salary from weaver.employees order by salary, dept"
+            )?,
+            ["ops,", "ops,60", "ops,70", "eng,80", "eng,100"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn aggregate_distinct() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_employees(client)?;
+        assert!(ddl(
+            client,
+            "insert into weaver.employees (id, dept, name, salary) values (6, 'ops', 'lee', null)"
+        )?);
+
+        assert_eq!(
+            rows(
+                client,
+                "select dept, count(distinct salary), sum(distinct salary), count(*) \
+                from weaver.employees group by dept order by dept"
+            )?,
+            ["eng,2,180,3", "ops,2,130,3"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select count(distinct salary), sum(salary), avg(distinct salary) \
+                from weaver.employees"
+            )?,
+            ["4,390,77.5"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
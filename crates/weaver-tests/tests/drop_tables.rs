@@ -0,0 +1,74 @@
+use tempfile::TempDir;
+
+use weaver_core::ast::Query;
+use weaver_tests::{ddl, init_tracing, run_full_stack_local_socket};
+
+#[test]
+fn drop_table() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    let table_file = temp_dir.path().join("weaver").join("people");
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        client.query(&Query::parse(
+            r#"create table weaver.people ( id INT primary key, name varchar(32) not null )"#,
+        )?)?;
+        client.query(&Query::parse(
+            r#"insert into weaver.people (id, name) values (1, 'josh'), (2, 'chris')"#,
+        )?)?;
+        assert!(table_file.exists(), "table should be backed by a file");
+
+        assert!(ddl(client, "drop table weaver.people")?);
+        assert!(!table_file.exists(), "backing file should be deleted");
+        assert!(
+            client
+                .query(&Query::parse("select * from weaver.people")?)
+                .is_err(),
+            "dropped table should no longer exist"
+        );
+
+        assert!(!ddl(client, "drop table weaver.people")?);
+        assert!(ddl(client, "drop table if exists weaver.people")?);
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn drop_protected_table_fails() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(!ddl(client, "drop table weaver.tables")?);
+        assert!(!ddl(client, "drop table weaver.processes")?);
+        client.query(&Query::parse("select * from weaver.tables")?)?;
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn drop_schema_fails() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        client.query(&Query::parse(
+            r#"create table weaver.people ( id INT primary key )"#,
+        )?)?;
+
+        assert!(!ddl(client, "drop schema weaver")?, "schema isn't empty");
+        assert!(
+            !ddl(client, "drop schema weaver cascade")?,
+            "schema contains protected tables"
+        );
+        client.query(&Query::parse("select * from weaver.people")?)?;
+        assert!(!ddl(client, "drop schema missing")?);
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
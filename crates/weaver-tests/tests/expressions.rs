@@ -0,0 +1,182 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_tests::{create_table, init_tracing, rows, run_full_stack_local_socket};
+
+#[test]
+fn case_expressions() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.people (id int primary key, age int, level int)",
+            &["(1, 12, 1)", "(2, 40, 2)", "(3, null, 2)"],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select id, case when age is null then 'unknown' when age < 18 then 'minor' \
+                else 'adult' end from weaver.people order by id"
+            )?,
+            ["1,minor", "2,adult", "3,unknown"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id, case level when 1 then 'low' end from weaver.people order by id"
+            )?,
+            ["1,low", "2,", "3,"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id, case age when null then 'null' else 'other' end from weaver.people \
+                order by id"
+            )?,
+            ["1,other", "2,other", "3,other"],
+            "a null operand should never match"
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.people where if(age is null, 0, age) < 18 order by id"
+            )?,
+            ["1", "3"]
+        );
+
+        info!("only the matching branch is evaluated");
+        assert_eq!(
+            rows(
+                client,
+                "select case when level = 2 then age else age / 0 end \
+                from weaver.people where id = 2"
+            )?,
+            ["40"]
+        );
+
+        info!("case can use aggregates in grouped queries");
+        assert_eq!(
+            rows(
+                client,
+                "select level, case when count(id) > 1 then 'many' else 'one' end \
+                from weaver.people group by level order by level"
+            )?,
+            ["1,one", "2,many"]
+        );
+
+        assert!(
+            rows(
+                client,
+                "select case when id = 1 then 1 else 'one' end from weaver.people"
+            )
+            .is_err(),
+            "branches must have compatible types"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn null_handling_functions() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.people (id int primary key, name varchar(16), nickname varchar(16), age int, \
+            level int)",
+            &[
+                "(1, 'josh', 'jj', 12, 1)",
+                "(2, 'chris', 'chris', 40, 2)",
+                "(3, 'alex', null, null, 2)",
+            ],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select id, coalesce(nickname, name), ifnull(age, -1) from weaver.people order by id"
+            )?,
+            ["1,jj,12", "2,chris,40", "3,alex,-1"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id, nullif(nickname, name) from weaver.people order by id"
+            )?,
+            ["1,jj", "2,", "3,"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select level, coalesce(max(age), 0) from weaver.people group by level order by level"
+            )?,
+            ["1,12", "2,40"]
+        );
+
+        info!("arguments are promoted to their common type");
+        assert_eq!(
+            rows(
+                client,
+                "select id, coalesce(age, 1.5), ifnull(age, cast(0.25 as decimal(3, 2))) \
+                from weaver.people order by id"
+            )?,
+            ["1,12.0,12.00", "2,40.0,40.00", "3,1.5,0.25"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id, nullif(age, 40.0) from weaver.people order by id"
+            )?,
+            ["1,12.0", "2,", "3,"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id, coalesce(nickname, null, null, null, null, null, null, null, null, name) \
+                from weaver.people order by id"
+            )?,
+            ["1,jj", "2,chris", "3,alex"]
+        );
+
+        info!("only the arguments up to the first non-null one are evaluated");
+        assert_eq!(
+            rows(
+                client,
+                "select id, coalesce(age, id / 0), ifnull(nickname, name) from weaver.people \
+                where id < 3 order by id"
+            )?,
+            ["1,12,jj", "2,40,chris"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select level, coalesce(max(age), max(id) / 0) from weaver.people \
+                group by level order by level"
+            )?,
+            ["1,12", "2,40"]
+        );
+        assert!(
+            rows(
+                client,
+                "select coalesce(age, id / 0) from weaver.people where id = 3"
+            )
+            .is_err(),
+            "the argument after a null should be evaluated"
+        );
+
+        assert!(
+            rows(client, "select coalesce(age, name) from weaver.people").is_err(),
+            "arguments must have a common type"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
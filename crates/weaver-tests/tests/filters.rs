@@ -0,0 +1,41 @@
+use std::io::stdout;
+
+use tempfile::TempDir;
+
+use weaver_client::write_rows::write_rows;
+use weaver_core::ast::Query;
+use weaver_core::rows::Rows;
+use weaver_tests::{init_tracing, run_full_stack_local_socket};
+
+#[test]
+fn filter_kept_above_keyed_scan() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        client.query(&Query::parse(
+            r#"create table weaver.people ( id INT primary key, name varchar(32) not null, age int )"#,
+        )?)?;
+        client.query(&Query::parse(
+            r#"insert into weaver.people (id, name, age) values (1, 'josh', 25), (2, 'chris', 3), (3, 'jane', 40), (4, 'john', 52)"#,
+        )?)?;
+
+        let (rows, elapsed) = client.query(&Query::parse(
+            "select id, name from weaver.people where id > 2 order by id",
+        )?)?;
+        let rows = rows.to_owned();
+        let values = rows
+            .iter()
+            .map(|row| row[1].to_string())
+            .collect::<Vec<_>>();
+        write_rows(stdout(), rows, elapsed).expect("could not write rows");
+        assert_eq!(
+            values,
+            ["jane", "john"],
+            "rows outside of the key range should not be returned"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,193 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_tests::{create_table, ddl, init_tracing, rows, run_full_stack_local_socket};
+
+#[test]
+fn foreign_keys_on_insert_and_update() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.people (id int primary key, email varchar(32) not null unique key)",
+            &["(1, 'a@a')", "(2, 'b@b')"],
+        )?;
+        create_table(
+            client,
+            "weaver.pets (id int primary key, owner int, \
+            constraint fk_owner foreign key (owner) references people (id) on delete cascade)",
+            &["(1, 1)", "(2, 2)", "(3, null)"],
+        )?;
+        create_table(
+            client,
+            "weaver.accounts (id int primary key, email varchar(32), \
+            foreign key (email) references people (email) on update cascade)",
+            &[],
+        )?;
+        assert!(
+            !ddl(client, "insert into weaver.pets (id, owner) values (4, 3)")?,
+            "owner 3 does not exist"
+        );
+        assert!(
+            !ddl(client, "update weaver.pets set owner = 5 where id = 1")?,
+            "owner 5 does not exist"
+        );
+
+        info!("loaded rows are checked too");
+        let csv = temp_dir.path().join("pets.csv");
+        let load = format!(
+            "load data infile '{}' into table weaver.pets (id, owner)",
+            csv.display()
+        );
+        std::fs::write(&csv, "id,owner\n4,2\n5,9\n")?;
+        assert!(!ddl(client, &load)?, "owner 9 does not exist");
+        std::fs::write(&csv, "id,owner\n6,2\n")?;
+        assert!(ddl(client, &load)?);
+        assert!(ddl(
+            client,
+            "update weaver.pets set owner = 2 where id = 1"
+        )?);
+
+        info!("updates of referenced keys cascade");
+        assert!(ddl(
+            client,
+            "insert into weaver.accounts (id, email) values (1, 'a@a')"
+        )?);
+        assert!(ddl(
+            client,
+            "update weaver.people set email = 'c@c' where id = 1"
+        )?);
+        assert_eq!(rows(client, "select email from weaver.accounts")?, ["c@c"]);
+
+        Ok(())
+    })?;
+
+    info!("foreign keys are enforced after a restart");
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(!ddl(
+            client,
+            "insert into weaver.pets (id, owner) values (4, 3)"
+        )?);
+        let ddl_string = rows(
+            client,
+            "select table_ddl from weaver.tables where name = 'pets'",
+        )?;
+        assert!(ddl_string[0].contains(
+            "foreign key `fk_owner` (owner) references weaver.people (id) on delete cascade"
+        ));
+        assert!(
+            ddl_string[0].contains("index `fk_owner` (owner)"),
+            "foreign keys should be indexed"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn foreign_keys_on_delete() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.people (id int primary key, email varchar(32) not null unique key)",
+            &["(1, 'a@a')", "(2, 'b@b')", "(3, 'c@c')"],
+        )?;
+        create_table(
+            client,
+            "weaver.pets (id int primary key, owner int, \
+            constraint fk_owner foreign key (owner) references people (id) on delete cascade)",
+            &["(1, 1)", "(2, 2)", "(3, 3)", "(4, 3)"],
+        )?;
+        create_table(
+            client,
+            "weaver.visits (id int primary key, pet int, \
+            foreign key (pet) references weaver.pets (id))",
+            &["(1, 2)"],
+        )?;
+        create_table(
+            client,
+            "weaver.toys (id int primary key, pet int, \
+            foreign key (pet) references pets (id) on delete set null)",
+            &["(1, 3)", "(2, 1)"],
+        )?;
+
+        assert!(
+            !ddl(client, "delete from weaver.pets where id = 2")?,
+            "pet 2 is still referenced by a visit"
+        );
+        assert!(
+            !ddl(client, "delete from weaver.people where id = 2")?,
+            "deleting owner 2 cascades to pet 2, which is still referenced by a visit"
+        );
+
+        assert!(ddl(client, "delete from weaver.people where id = 3")?);
+        assert_eq!(
+            rows(client, "select id from weaver.pets order by id")?,
+            ["1", "2"]
+        );
+        assert_eq!(
+            rows(client, "select id, pet from weaver.toys order by id")?,
+            ["1,", "2,1"]
+        );
+
+        assert!(
+            !ddl(client, "drop table weaver.pets")?,
+            "pets are referenced by visits and toys"
+        );
+        assert!(ddl(client, "drop table weaver.visits")?);
+        assert!(ddl(client, "drop table weaver.toys")?);
+        assert!(ddl(client, "drop table weaver.pets")?);
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn illegal_foreign_keys() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.people (id int primary key, email varchar(32) not null unique key)",
+            &[],
+        )?;
+        create_table(
+            client,
+            "weaver.pets (id int primary key, owner int, \
+            constraint fk_owner foreign key (owner) references people (id) on delete cascade)",
+            &[],
+        )?;
+        create_table(
+            client,
+            "weaver.accounts (id int primary key, email varchar(32), \
+            foreign key (email) references people (email) on update cascade)",
+            &[],
+        )?;
+        for query in [
+            "create table weaver.bad (id int, foreign key (id) references missing (id))",
+            "create table weaver.bad (id int, foreign key (id) references pets (owner))",
+            "create table weaver.bad (id varchar(8), foreign key (id) references people (id))",
+            "create table weaver.bad (id int not null, \
+            foreign key (id) references people (id) on delete set null)",
+            "create table weaver.bad (id int, foreign key (missing) references people (id))",
+        ] {
+            assert!(!ddl(client, query)?, "{query} should fail");
+        }
+        assert!(
+            !ddl(client, "alter table weaver.people drop column email")?,
+            "email is referenced by accounts"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
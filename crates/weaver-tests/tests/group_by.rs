@@ -0,0 +1,78 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_tests::{create_table, init_tracing, rows, run_full_stack_local_socket};
+
+#[test]
+fn having() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        info!("levels 1 and 3 have two people and level 2 has one");
+        create_table(
+            client,
+            "weaver.people (id int primary key, level int, age int)",
+            &[
+                "(1, 1, 20)",
+                "(2, 1, 25)",
+                "(3, 2, 40)",
+                "(4, 3, 30)",
+                "(5, 3, 50)",
+            ],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select level, count(id) from weaver.people group by level \
+                having count(id) > 1 order by level"
+            )?,
+            ["1,2", "3,2"]
+        );
+
+        info!("aggregates and grouped columns don't need to be selected");
+        assert_eq!(
+            rows(
+                client,
+                "select level from weaver.people group by level \
+                having max(age) >= 40 order by level"
+            )?,
+            ["2", "3"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select count(id) as total from weaver.people group by level \
+                having level < 3 and min(age) > 10"
+            )?,
+            ["2", "1"]
+        );
+
+        info!("without a group by, all rows are a single group");
+        assert_eq!(
+            rows(
+                client,
+                "select count(id) from weaver.people having max(age) = 50"
+            )?,
+            ["5"]
+        );
+        assert!(rows(
+            client,
+            "select count(id) from weaver.people having max(age) > 50"
+        )?
+        .is_empty());
+
+        assert!(
+            rows(
+                client,
+                "select level from weaver.people group by level having age > 20"
+            )
+            .is_err(),
+            "age is not functionally dependent on level"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
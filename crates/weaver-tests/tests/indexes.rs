@@ -0,0 +1,141 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_client::WeaverClient;
+use weaver_core::ast::Query;
+use weaver_core::cnxn::interprocess::LocalSocketStream;
+use weaver_core::rows::Rows;
+use weaver_tests::{ddl, init_tracing, run_full_stack_local_socket};
+
+/// Gets the first column of every row returned by a query
+fn names(client: &mut WeaverClient<LocalSocketStream>, query: &str) -> eyre::Result<Vec<String>> {
+    let (rows, _) = client.query(&Query::parse(query)?)?;
+    Ok(rows
+        .to_owned()
+        .iter()
+        .map(|row| row[0].to_string())
+        .collect())
+}
+
+#[test]
+fn create_index() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        client.query(&Query::parse(
+            "create table weaver.people ( id INT primary key, name varchar(32) not null, age INT not null )",
+        )?)?;
+        client.query(&Query::parse(
+            "insert into weaver.people (id, name, age) values (1, 'josh', 25), (2, 'chris', 30), (3, 'jordan', 25)",
+        )?)?;
+
+        info!("index is backfilled from existing rows");
+        assert!(ddl(client, "create index idx_age on weaver.people (age)")?);
+        assert!(
+            !ddl(client, "create index idx_age on weaver.people (name)")?,
+            "index names must be unique"
+        );
+        assert!(
+            !ddl(
+                client,
+                "create index idx_missing on weaver.people (missing)"
+            )?,
+            "indexed columns must exist"
+        );
+        assert_eq!(
+            names(
+                client,
+                "select name from weaver.people where age = 25 order by name"
+            )?,
+            ["jordan", "josh"]
+        );
+
+        let (rows, _) = client.query(&Query::parse(
+            "explain select name from weaver.people where age = 30",
+        )?)?;
+        let scan = rows
+            .to_owned()
+            .iter()
+            .find(|row| row[2].to_string() == "people")
+            .map(|row| (row[3].to_string(), row[4].to_string()))
+            .expect("should scan people");
+        assert_eq!(scan.0, "const", "should look up a single key");
+        assert!(scan.1.starts_with("idx_age"), "should use the index");
+
+        info!("index is maintained by inserts, updates and deletes");
+        client.query(&Query::parse(
+            "insert into weaver.people (id, name, age) values (4, 'jon', 30)",
+        )?)?;
+        client.query(&Query::parse(
+            "update weaver.people set age = 30 where id = 1",
+        )?)?;
+        client.query(&Query::parse("delete from weaver.people where id = 2")?)?;
+        assert_eq!(
+            names(
+                client,
+                "select name from weaver.people where age = 30 order by name"
+            )?,
+            ["jon", "josh"]
+        );
+        assert_eq!(
+            names(client, "select name from weaver.people where age = 25")?,
+            ["jordan"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn create_unique_index() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        client.query(&Query::parse(
+            "create table weaver.people ( id INT primary key, name varchar(32) not null )",
+        )?)?;
+        client.query(&Query::parse(
+            "insert into weaver.people (id, name) values (1, 'josh'), (2, 'chris'), (3, 'josh')",
+        )?)?;
+        assert!(
+            !ddl(
+                client,
+                "create unique index idx_name on weaver.people (name)"
+            )?,
+            "existing rows violate the unique key"
+        );
+
+        client.query(&Query::parse("delete from weaver.people where id = 3")?)?;
+        assert!(ddl(
+            client,
+            "create unique index idx_name on weaver.people (name)"
+        )?);
+        assert!(
+            !ddl(
+                client,
+                "insert into weaver.people (id, name) values (3, 'chris')"
+            )?,
+            "inserting a duplicate name should fail"
+        );
+        assert!(
+            !ddl(
+                client,
+                "update weaver.people set name = 'josh' where id = 2"
+            )?,
+            "updating to a duplicate name should fail"
+        );
+        assert_eq!(
+            names(
+                client,
+                "select name from weaver.people where name = 'chris'"
+            )?,
+            ["chris"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
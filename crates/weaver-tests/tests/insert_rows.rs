@@ -0,0 +1,226 @@
+use std::io::stdout;
+
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_client::write_rows::write_rows;
+use weaver_core::ast::Query;
+use weaver_core::rows::Rows;
+use weaver_tests::{create_table, ddl, init_tracing, rows, run_full_stack_local_socket};
+
+#[test]
+fn insert_values() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        info!("create table");
+        let (rows, elapsed) = client.query(&Query::parse(
+            r#"create table weaver.people ( id INT auto_increment primary key, name varchar(32) not null, age int )"#,
+        )?)?;
+        write_rows(stdout(), rows, elapsed).expect("could not write rows");
+
+        info!("insert into table");
+        let (mut rows, _) = client.query(&Query::parse(
+            r#"insert into weaver.people (name, age) values ('josh', 25), ('chris', 1 + 2)"#,
+        )?)?;
+        let result = rows.next().expect("should have a result row");
+        assert_eq!(result[0].to_string(), "2", "two rows should be inserted");
+        drop(rows);
+
+        let (rows, elapsed) = client.query(&Query::parse("select * from weaver.people")?)?;
+        let rows = rows.to_owned();
+        assert_eq!(rows.iter().count(), 2);
+        write_rows(stdout(), rows, elapsed).expect("could not write rows");
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn insert_select() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        client.query(&Query::parse(
+            r#"create table weaver.people ( id INT auto_increment primary key, name varchar(32) not null, age int )"#,
+        )?)?;
+        client.query(&Query::parse(
+            r#"insert into weaver.people (name, age) values ('josh', 25), ('chris', 3)"#,
+        )?)?;
+
+        let (mut rows, _) = client.query(&Query::parse(
+            r#"insert into weaver.people (name, age) select name, age from weaver.people"#,
+        )?)?;
+        let result = rows.next().expect("should have a result row");
+        assert_eq!(result[0].to_string(), "2", "two rows should be inserted");
+        drop(rows);
+
+        let (rows, _) = client.query(&Query::parse("select * from weaver.people")?)?;
+        assert_eq!(rows.to_owned().iter().count(), 4);
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn insert_duplicate_primary_key() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            "create table weaver.people ( id INT primary key, name varchar(32) not null )"
+        )?);
+        assert!(ddl(
+            client,
+            "insert into weaver.people (id, name) values (1, 'josh')"
+        )?);
+
+        assert!(
+            !ddl(
+                client,
+                "insert into weaver.people (id, name) values (1, 'chris')"
+            )?,
+            "a duplicate primary key should be rejected"
+        );
+        assert!(
+            !ddl(
+                client,
+                "insert into weaver.people (id, name) values (2, 'jordan'), (2, 'jon')"
+            )?,
+            "a primary key duplicated within the inserted rows should be rejected"
+        );
+        assert_eq!(
+            rows(client, "select id, name from weaver.people where id = 1")?,
+            ["1,josh"],
+            "the existing row should be kept"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn insert_explicit_null() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            "create table weaver.people ( id INT auto_increment primary key, name varchar(32) default 'anon', \
+            age int default 18 )"
+        )?);
+        assert!(ddl(
+            client,
+            "insert into weaver.people (name, age) values ('josh', null)"
+        )?);
+        assert!(ddl(client, "insert into weaver.people (age) values (25)")?);
+        assert!(ddl(
+            client,
+            "insert into weaver.people (id, name, age) values (null, null, 30)"
+        )?);
+
+        assert_eq!(
+            rows(
+                client,
+                "select id, name, age from weaver.people order by id"
+            )?,
+            ["0,josh,", "1,anon,25", "2,,30"],
+            "only omitted columns should get their defaults"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn insert_duplicate_columns() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            "create table weaver.people ( id INT primary key, name varchar(32) not null )"
+        )?);
+        assert!(ddl(
+            client,
+            "insert into weaver.people (id, name) values (1, 'josh')"
+        )?);
+
+        for query in [
+            "insert into weaver.people (id, name, id) values (3, 'jordan', 4)",
+            "insert into weaver.people (id, name, name) select id + 10, name, name from weaver.people",
+        ] {
+            assert!(
+                rows(client, query).is_err(),
+                "{query} should fail, as a column is given twice"
+            );
+        }
+        assert_eq!(
+            rows(client, "select id, name from weaver.people")?,
+            ["1,josh"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn failed_insert_changes_nothing() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.t (id int primary key, v int, check (v > 0))",
+            &["(1, 10)", "(2, 20)"],
+        )?;
+        create_table(
+            client,
+            "weaver.tags (name varchar(16), unique key (name))",
+            &["('a')"],
+        )?;
+
+        for insert in [
+            "insert into weaver.t (id, v) values (4, 40), (1, 99)",
+            "insert into weaver.t (id, v) values (4, 40), (5, 50), (6, -1)",
+            "insert into weaver.t (id, v) select id + 3, v - 15 from weaver.t",
+        ] {
+            assert!(!ddl(client, insert)?, "{insert} should fail");
+            assert_eq!(
+                rows(client, "select id, v from weaver.t")?,
+                ["1,10", "2,20"],
+                "no rows of {insert} should be kept"
+            );
+        }
+
+        info!("rows without a primary key are found by their row id");
+        assert!(!ddl(
+            client,
+            "insert into weaver.tags (name) values ('b'), ('c'), ('a')"
+        )?);
+        assert_eq!(rows(client, "select name from weaver.tags")?, ["a"]);
+        assert!(ddl(
+            client,
+            "insert into weaver.tags (name) values ('b'), ('c')"
+        )?);
+        assert_eq!(
+            rows(client, "select name from weaver.tags order by name")?,
+            ["a", "b", "c"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,196 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_core::ast::Query;
+use weaver_core::rows::Rows;
+use weaver_tests::{ddl, init_tracing, rows, run_full_stack_local_socket};
+
+#[test]
+fn json_columns() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            "create table weaver.events (id int primary key, payload json)"
+        )?);
+        assert!(ddl(
+            client,
+            r#"insert into weaver.events (id, payload) values
+            (1, '{ "kind": "click", "tags": ["a", "b"], "at": {"x": 1, "y": 2} }'),
+            (2, '{"kind": "view", "tags": []}'),
+            (3, '[1, 2, 3]'),
+            (4, null)"#
+        )?);
+        assert!(
+            !ddl(
+                client,
+                "insert into weaver.events (id, payload) values (5, '{not json')"
+            )?,
+            "invalid json should be rejected"
+        );
+
+        assert_eq!(
+            rows(client, "select id, payload from weaver.events order by id")?,
+            [
+                r#"1,{"at":{"x":1,"y":2},"kind":"click","tags":["a","b"]}"#,
+                r#"2,{"kind":"view","tags":[]}"#,
+                "3,[1,2,3]",
+                "4,"
+            ]
+        );
+
+        info!("path operators");
+        assert_eq!(
+            rows(
+                client,
+                "select id, payload -> 'kind', payload ->> 'kind', payload -> 'at' ->> 'y', \
+                payload -> 'tags' -> 0 from weaver.events order by id"
+            )?,
+            [
+                r#"1,"click",click,2,"a""#,
+                r#"2,"view",view,,"#,
+                "3,,,,",
+                "4,,,,"
+            ]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.events where payload ->> 'kind' = 'view'"
+            )?,
+            ["2"]
+        );
+
+        info!("json functions");
+        assert_eq!(
+            rows(
+                client,
+                "select id, json_extract(payload, '$.at.x'), json_array_length(payload -> 'tags'), \
+                json_object_keys(payload) from weaver.events order by id"
+            )?,
+            [
+                r#"1,1,2,["at","kind","tags"]"#,
+                r#"2,,0,["kind","tags"]"#,
+                "3,,,",
+                "4,,,"
+            ]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select json_valid('{\"a\": 1}'), json_valid('{a'), json_array_length('[1, 2]') \
+                from weaver.events where id = 1"
+            )?,
+            ["true,false,2"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn json_expression_index() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            "create table weaver.events (id int primary key, payload json)"
+        )?);
+        assert!(ddl(
+            client,
+            r#"insert into weaver.events (id, payload) values
+            (1, '{"kind": "click", "user": 10}'),
+            (2, '{"kind": "view", "user": 10}'),
+            (3, '{"kind": "click", "user": 11}'),
+            (4, '{"user": 12}')"#
+        )?);
+
+        assert!(
+            !ddl(
+                client,
+                "create index idx_bad on weaver.events ((payload -> 'kind'))"
+            )?,
+            "only text extracted from json can be indexed"
+        );
+        assert!(
+            !ddl(
+                client,
+                "create unique index idx_kind on weaver.events ((payload ->> 'kind'))"
+            )?,
+            "existing rows violate the unique key"
+        );
+        assert!(ddl(
+            client,
+            "create index idx_kind on weaver.events ((payload ->> 'kind'))"
+        )?);
+
+        let (plan, _) = client.query(&Query::parse(
+            "explain select id from weaver.events where payload ->> 'kind' = 'click'",
+        )?)?;
+        let scan = plan
+            .to_owned()
+            .iter()
+            .find(|row| row[2].to_string() == "events")
+            .map(|row| (row[3].to_string(), row[4].to_string()))
+            .expect("should scan events");
+        assert_eq!(scan.0, "const", "should look up a single key");
+        assert!(scan.1.starts_with("idx_kind"), "should use the index");
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.events where payload ->> 'kind' = 'click' order by id"
+            )?,
+            ["1", "3"]
+        );
+
+        info!("index is maintained by inserts and updates");
+        assert!(ddl(
+            client,
+            r#"insert into weaver.events (id, payload) values (5, '{"kind": "click"}')"#
+        )?);
+        assert!(ddl(
+            client,
+            r#"update weaver.events set payload = '{"kind": "view"}' where id = 1"#
+        )?);
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.events where payload ->> 'kind' = 'click' order by id"
+            )?,
+            ["3", "5"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.events where payload ->> 'kind' in ('view') order by id"
+            )?,
+            ["1", "2"]
+        );
+        assert_eq!(
+            rows(client, "select id, payload from weaver.events where id = 1")?,
+            [r#"1,{"kind":"view"}"#],
+            "generated columns aren't selected"
+        );
+
+        info!("unique expression keys");
+        assert!(ddl(
+            client,
+            "create unique index idx_user on weaver.events ((payload ->> 'user'))"
+        )?);
+        assert!(
+            !ddl(
+                client,
+                r#"insert into weaver.events (id, payload) values (6, '{"user": 12}')"#
+            )?,
+            "duplicate user should be rejected"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
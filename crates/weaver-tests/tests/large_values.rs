@@ -0,0 +1,148 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_core::ast::Query;
+use weaver_tests::{ddl, init_tracing, rows, run_full_stack_local_socket};
+
+/// A string many pages long, which differs for each seed
+fn large_string(seed: usize, len: usize) -> String {
+    (0..len)
+        .map(|i| char::from(b'a' + ((i + seed) % 26) as u8))
+        .collect()
+}
+
+#[test]
+fn large_text_values() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            "create table weaver.documents (id int primary key, title varchar(1000), body text, \
+            attachment blob)"
+        )?);
+        for id in 0..8 {
+            assert!(ddl(
+                client,
+                &format!(
+                    "insert into weaver.documents (id, title, body, attachment) values \
+                    ({id}, '{}', '{}', '{}')",
+                    large_string(id, 1000),
+                    large_string(id, 20_000 + id),
+                    large_string(id + 1, 60_000),
+                )
+            )?);
+        }
+
+        for id in 0..8 {
+            assert_eq!(
+                rows(
+                    client,
+                    &format!("select title, body from weaver.documents where id = {id}")
+                )?,
+                [format!(
+                    "{},{}",
+                    large_string(id, 1000),
+                    large_string(id, 20_000 + id)
+                )]
+            );
+        }
+        assert_eq!(
+            rows(
+                client,
+                "select cast(attachment as text) from weaver.documents where id = 3"
+            )?,
+            [large_string(4, 60_000)]
+        );
+        assert_eq!(
+            rows(client, "select id from weaver.documents where body = title")?,
+            Vec::<String>::new()
+        );
+
+        info!("updating and deleting rows with overflowing values");
+        assert!(ddl(
+            client,
+            &format!(
+                "update weaver.documents set body = '{}' where id = 2",
+                large_string(7, 30_000)
+            )
+        )?);
+        assert_eq!(
+            rows(client, "select body from weaver.documents where id = 2")?,
+            [large_string(7, 30_000)]
+        );
+        assert!(ddl(client, "delete from weaver.documents where id < 4")?);
+        assert_eq!(
+            rows(client, "select id from weaver.documents order by id")?,
+            ["4", "5", "6", "7"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn string_widths() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            "create table weaver.notes (id int primary key, short varchar(4), long varchar(65535))"
+        )?);
+        assert!(ddl(
+            client,
+            &format!(
+                "insert into weaver.notes (id, short, long) values (1, 'abcd', '{}')",
+                large_string(0, 65_535)
+            )
+        )?);
+        assert!(
+            !ddl(
+                client,
+                "insert into weaver.notes (id, short, long) values (2, 'abcde', '')"
+            )?,
+            "values longer than their column should be rejected"
+        );
+        assert!(Query::parse("create table weaver.wide (id int, name varchar(65536))").is_err());
+        assert!(Query::parse("create table weaver.empty (id int, name varchar(0))").is_err());
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn large_table_ddl() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        let columns = (0..100)
+            .map(|i| format!("a_rather_long_column_name_{i} varchar(255)"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let query = format!("create table weaver.wide (id int primary key, {columns})");
+        assert!(query.len() > 1 << 11);
+        assert!(ddl(client, &query)?);
+        assert!(ddl(
+            client,
+            "insert into weaver.wide (id, a_rather_long_column_name_99) values (1, 'last')"
+        )?);
+        assert_eq!(
+            rows(
+                client,
+                "select a_rather_long_column_name_99 from weaver.wide where id = 1"
+            )?,
+            ["last"]
+        );
+        assert_eq!(
+            rows(client, "select name from weaver.tables where name = 'wide'")?,
+            ["wide"]
+        );
+        Ok(())
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,217 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_core::ast::Query;
+use weaver_core::rows::Rows;
+use weaver_tests::{create_table, ddl, init_tracing, rows, run_full_stack_local_socket};
+
+#[test]
+fn like() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.people (id int primary key, name varchar(16), email varchar(32), \
+            key idx_name (name))",
+            &[
+                "(1, 'josh', 'josh@example.com')",
+                "(2, 'jordan', 'jordan@example.org')",
+                "(3, 'jon', null)",
+                "(4, 'chris', 'chris_1@example.com')",
+                "(5, 'kim', 'kim@example.com')",
+            ],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where name like 'jo%' order by name"
+            )?,
+            ["jon", "jordan", "josh"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where name like 'jo%n' order by name"
+            )?,
+            ["jon", "jordan"],
+            "rows in the prefix range must still match the whole pattern"
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where name like '_i%' order by name"
+            )?,
+            ["kim"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where name not like 'jo%' order by name"
+            )?,
+            ["chris", "kim"]
+        );
+
+        info!("escaped wildcards are matched literally");
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where email like '%\\\\_%'"
+            )?,
+            ["chris"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where email like '%!_1%' escape '!'"
+            )?,
+            ["chris"]
+        );
+
+        info!("null never matches");
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where email not like '%.com' order by name"
+            )?,
+            ["jordan"]
+        );
+
+        info!("prefix searches use the index");
+        let (rows, _) = client.query(&Query::parse(
+            "explain select name from weaver.people where name like 'jo%'",
+        )?)?;
+        let scan = rows
+            .to_owned()
+            .iter()
+            .find(|row| row[2].to_string() == "people")
+            .map(|row| (row[3].to_string(), row[4].to_string()))
+            .expect("should scan people");
+        assert_eq!(scan.0, "range", "should scan a range of keys");
+        assert!(scan.1.starts_with("idx_name"), "should use the index");
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn regexp() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.people (id int primary key, name varchar(16), email varchar(32))",
+            &[
+                "(1, 'josh', 'josh@example.com')",
+                "(2, 'jordan', 'jordan@example.org')",
+                "(3, 'chris', 'chris_1@example.com')",
+                "(4, 'kim', 'kim@example.com')",
+            ],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where email regexp '^[a-z]+@example\\\\.com$' \
+                order by name"
+            )?,
+            ["josh", "kim"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where name rlike 'r' order by name"
+            )?,
+            ["chris", "jordan"]
+        );
+        assert!(
+            rows(
+                client,
+                "select name from weaver.people where name regexp '('"
+            )
+            .is_err(),
+            "invalid regular expressions should fail"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn case_insensitive_collation() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.tags (id int primary key, name varchar(16) collate utf8_ci)",
+            &[
+                "(1, 'Rust')",
+                "(2, 'RUSTY')",
+                "(3, 'rustacean')",
+                "(4, 'ruby')",
+                "(5, 'Trust')",
+            ],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select id, name from weaver.tags where name like 'rUs%' order by id"
+            )?,
+            ["1,Rust", "2,RUSTY", "3,rustacean"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id, name from weaver.tags where name like '%ST' order by id"
+            )?,
+            ["1,Rust", "5,Trust"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id, name from weaver.tags where name regexp '^ru' order by id"
+            )?,
+            ["1,Rust", "2,RUSTY", "3,rustacean", "4,ruby"]
+        );
+
+        assert!(
+            !ddl(
+                client,
+                "create table weaver.bad (id int primary key, name varchar(16) collate latin1)"
+            )?,
+            "unknown collations should fail"
+        );
+        assert!(
+            !ddl(
+                client,
+                "create table weaver.bad (id int primary key, age int collate utf8_ci)"
+            )?,
+            "only strings have a collation"
+        );
+        for create in [
+            "create table weaver.bad (name varchar(16) collate utf8_ci primary key)",
+            "create table weaver.bad (id int primary key, name varchar(16) collate utf8_ci unique key)",
+            "create table weaver.bad (id int primary key, name varchar(16) collate utf8_ci, \
+            key idx_name (name))",
+            "create index idx_name on weaver.tags (name)",
+            "alter table weaver.tags add column tag varchar(16) collate utf8_ci unique key",
+        ] {
+            assert!(
+                !ddl(client, create)?,
+                "keys can not be over columns with a collation: {create}"
+            );
+        }
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
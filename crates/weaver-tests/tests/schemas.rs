@@ -0,0 +1,117 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_core::ast::Query;
+use weaver_core::rows::Rows;
+use weaver_tests::{init_tracing, run_full_stack_local_socket};
+
+#[test]
+fn create_and_use_schema() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(
+            client
+                .query(&Query::parse("select * from tables")?)
+                .is_err(),
+            "unqualified tables can't be used without a default schema"
+        );
+        assert!(
+            client.query(&Query::parse("use app")?).is_err(),
+            "can't use a schema that doesn't exist"
+        );
+
+        info!("create schema");
+        let (mut rows, _) = client.query(&Query::parse("create schema app")?)?;
+        let result = rows.next().expect("should have a result row");
+        assert_eq!(result[0].to_string(), "ok");
+        drop(rows);
+
+        let (mut rows, _) = client.query(&Query::parse("create schema app")?)?;
+        let result = rows.next().expect("should have a result row");
+        assert_ne!(result[0].to_string(), "ok", "schema already exists");
+        drop(rows);
+
+        info!("use schema");
+        client.query(&Query::parse("use app")?)?;
+        client.query(&Query::parse(
+            "create table people ( id INT primary key, name varchar(32) not null )",
+        )?)?;
+        client.query(&Query::parse(
+            "insert into people (id, name) values (1, 'josh'), (2, 'chris')",
+        )?)?;
+
+        let (rows, _) = client.query(&Query::parse(
+            "select id, name from app.people order by id",
+        )?)?;
+        let names = rows
+            .to_owned()
+            .iter()
+            .map(|row| row[1].to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["josh", "chris"]);
+
+        let (rows, _) = client.query(&Query::parse("select id, name from people where id = 2")?)?;
+        let names = rows
+            .to_owned()
+            .iter()
+            .map(|row| row[1].to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["chris"]);
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn drop_schema_cascade() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        client.query(&Query::parse("create schema scratch")?)?;
+        client.query(&Query::parse(
+            "create table scratch.first ( id INT primary key )",
+        )?)?;
+        client.query(&Query::parse(
+            "create table scratch.second ( id INT primary key )",
+        )?)?;
+        assert!(temp_dir.path().join("scratch").join("first").exists());
+        client.query(&Query::parse("use scratch")?)?;
+
+        let (mut rows, _) = client.query(&Query::parse("drop schema scratch")?)?;
+        let result = rows.next().expect("should have a result row");
+        assert_ne!(result[0].to_string(), "ok", "schema isn't empty");
+        drop(rows);
+
+        let (mut rows, _) = client.query(&Query::parse("drop schema scratch cascade")?)?;
+        let result = rows.next().expect("should have a result row");
+        assert_eq!(result[0].to_string(), "ok");
+        drop(rows);
+        assert!(
+            !temp_dir.path().join("scratch").exists(),
+            "schema directory should be deleted"
+        );
+        assert!(
+            client
+                .query(&Query::parse("select * from scratch.first")?)
+                .is_err(),
+            "dropped table should no longer exist"
+        );
+        assert!(client.query(&Query::parse("use scratch")?).is_err());
+
+        info!("dropped schema is no longer the default schema");
+        client.query(&Query::parse("create schema scratch")?)?;
+        assert!(
+            client
+                .query(&Query::parse("create table first ( id INT primary key )")?)
+                .is_err(),
+            "unqualified tables should not resolve to a dropped schema"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_tests::{create_people, create_table, init_tracing, rows, run_full_stack_local_socket};
+
+#[test]
+fn union() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        info!("josh and kim are in both people and staff");
+        create_people(
+            client,
+            &[
+                "(1, 'josh', 20)",
+                "(2, 'chris', 35)",
+                "(3, 'alex', 28)",
+                "(4, 'kim', 41)",
+            ],
+        )?;
+        create_table(
+            client,
+            "weaver.staff (id int primary key, name varchar(32), rating float)",
+            &[
+                "(1, 'josh', 4.5)",
+                "(2, 'kim', 20.0)",
+                "(3, 'sam', 3.0)",
+                "(4, 'sam', 3.0)",
+            ],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people union select name from weaver.staff order by name"
+            )?,
+            ["alex", "chris", "josh", "kim", "sam"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where age > 30 \
+                union all select name from weaver.staff order by name"
+            )?,
+            ["chris", "josh", "kim", "kim", "sam", "sam"]
+        );
+
+        info!("order by and limit apply to the whole compound select");
+        assert_eq!(
+            rows(
+                client,
+                "select name, age from weaver.people union select name, id from weaver.staff \
+                order by name desc limit 3"
+            )?,
+            ["sam,3", "sam,4", "kim,41"]
+        );
+
+        info!("values are compared after being widened to the type of the column");
+        assert_eq!(
+            rows(
+                client,
+                "select age from weaver.people union select rating from weaver.staff order by age"
+            )?,
+            ["3", "4.5", "20", "28", "35", "41"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn intersect_and_except() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        info!("josh and kim are in both people and staff");
+        create_people(
+            client,
+            &[
+                "(1, 'josh', 20)",
+                "(2, 'chris', 35)",
+                "(3, 'alex', 28)",
+                "(4, 'kim', 41)",
+            ],
+        )?;
+        create_table(
+            client,
+            "weaver.staff (id int primary key, name varchar(32), rating float)",
+            &[
+                "(1, 'josh', 4.5)",
+                "(2, 'kim', 20.0)",
+                "(3, 'sam', 3.0)",
+                "(4, 'sam', 3.0)",
+            ],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people intersect select name from weaver.staff \
+                order by name"
+            )?,
+            ["josh", "kim"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.staff except select name from weaver.people"
+            )?,
+            ["sam"],
+            "rows are deduplicated"
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people except select name from weaver.staff \
+                union select name from weaver.staff where rating < 4 order by name"
+            )?,
+            ["alex", "chris", "sam"],
+            "operators are applied from left to right"
+        );
+
+        assert!(
+            rows(
+                client,
+                "select name, age from weaver.people union select name from weaver.staff"
+            )
+            .is_err(),
+            "both selects must have the same number of columns"
+        );
+        assert!(
+            rows(
+                client,
+                "select name from weaver.people union select rating from weaver.staff"
+            )
+            .is_err(),
+            "columns must have compatible types"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
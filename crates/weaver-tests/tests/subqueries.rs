@@ -0,0 +1,499 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_client::WeaverClient;
+use weaver_core::ast::Query;
+use weaver_core::cnxn::interprocess::LocalSocketStream;
+use weaver_core::rows::Rows;
+use weaver_tests::{
+    create_people, create_table, ddl, init_tracing, rows, run_full_stack_local_socket,
+};
+
+/// Gets the kind and keys of the scan of a table in an explained query
+fn explain_scan(
+    client: &mut WeaverClient<LocalSocketStream>,
+    query: &str,
+    table: &str,
+) -> eyre::Result<(String, String)> {
+    let (rows, _) = client.query(&Query::parse(&format!("explain {query}"))?)?;
+    Ok(rows
+        .to_owned()
+        .iter()
+        .find(|row| row[2].to_string() == table)
+        .map(|row| (row[3].to_string(), row[4].to_string()))
+        .expect("should scan the table"))
+}
+
+#[test]
+fn in_lists_and_between() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_people(
+            client,
+            &[
+                "(1, 'josh', 20)",
+                "(2, 'chris', 35)",
+                "(3, 'alex', 28)",
+                "(4, 'kim', 41)",
+                "(5, 'sam', 17)",
+            ],
+        )?;
+        assert!(ddl(client, "create index idx_age on weaver.people (age)")?);
+
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where age between 20 and 35 order by name"
+            )?,
+            ["alex", "chris", "josh"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where age not between 20 and 35 order by name"
+            )?,
+            ["kim", "sam"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where id in (1, 3, 5, 7) order by name"
+            )?,
+            ["alex", "josh", "sam"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where name not in ('josh', 'kim') and age < 30 \
+                order by name"
+            )?,
+            ["alex", "sam"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id, age in (17, 41) from weaver.people where id < 3 order by id"
+            )?,
+            ["1,false", "2,false"]
+        );
+
+        info!("key columns are searched by range or by each value");
+        let scan = explain_scan(
+            client,
+            "select name from weaver.people where age between 20 and 35",
+            "people",
+        )?;
+        assert_eq!(scan.0, "range");
+        assert!(scan.1.starts_with("idx_age"), "should use the index");
+        let scan = explain_scan(
+            client,
+            "select name from weaver.people where age in (20, 41)",
+            "people",
+        )?;
+        assert_eq!(scan.0, "range");
+        assert!(scan.1.starts_with("idx_age"), "should use the index");
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where age in (41, 20, 41) order by name"
+            )?,
+            ["josh", "kim"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn null_in_lists_and_between() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.samples (id int primary key, x int)",
+            &["(1, 1)", "(2, 2)", "(3, null)"],
+        )?;
+
+        info!("a list with a null is unknown unless the value is found");
+        assert_eq!(
+            rows(
+                client,
+                "select id, x in (1, null), x not in (1, null), x in (1, 2) from weaver.samples \
+                order by id"
+            )?,
+            ["1,true,false,true", "2,,,true", "3,,,"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.samples where x not in (1, null) order by id"
+            )?,
+            Vec::<String>::new()
+        );
+
+        info!("between is unknown when the value or either bound is null");
+        assert_eq!(
+            rows(
+                client,
+                "select id, x between 1 and 2, x between 1 and null, x not between null and 1 \
+                from weaver.samples order by id"
+            )?,
+            ["1,true,,", "2,true,,", "3,,,"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.samples where x not between null and 1 order by id"
+            )?,
+            Vec::<String>::new()
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn in_and_exists_subqueries() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_people(
+            client,
+            &[
+                "(1, 'josh', 20)",
+                "(2, 'chris', 35)",
+                "(3, 'alex', 28)",
+                "(4, 'kim', 41)",
+                "(5, 'sam', 17)",
+            ],
+        )?;
+        info!("josh has placed two orders, and alex and kim one each");
+        create_table(
+            client,
+            "weaver.orders (id int primary key, person_id int, total int)",
+            &["(1, 1, 50)", "(2, 1, 150)", "(3, 3, 20)", "(4, 4, 300)"],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people \
+                where people.id in (select person_id from weaver.orders where total > 40) \
+                order by name"
+            )?,
+            ["josh", "kim"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people \
+                where people.id not in (select person_id from weaver.orders) and age > 18 \
+                order by name"
+            )?,
+            ["chris"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where exists \
+                (select * from weaver.orders where orders.person_id = people.id and total < 100) \
+                order by name"
+            )?,
+            ["alex", "josh"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where not exists \
+                (select 1 from weaver.orders where person_id = people.id limit 1) \
+                order by name"
+            )?,
+            ["chris", "sam"]
+        );
+
+        info!("subqueries are joined instead of being run for every row");
+        let (explained, _) = client.query(&Query::parse(
+            "explain select name from weaver.people \
+            where people.id in (select person_id from weaver.orders)",
+        )?)?;
+        assert!(explained
+            .to_owned()
+            .iter()
+            .any(|row| row[3].to_string() == "hash-semi-join"));
+
+        info!("an exists subquery correlated by many equalities is joined on all of them");
+        let query = "select name from weaver.people where exists (select * from weaver.orders \
+            where orders.person_id = people.id and orders.id = people.id) order by name";
+        assert_eq!(rows(client, query)?, ["alex", "josh", "kim"]);
+        let (explained, _) = client.query(&Query::parse(&format!("explain {query}"))?)?;
+        assert!(explained
+            .to_owned()
+            .iter()
+            .any(|row| row[3].to_string() == "hash-semi-join"));
+
+        info!("an uncorrelated exists subquery is the same for every row");
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where exists (select * from weaver.orders) \
+                order by name"
+            )?,
+            ["alex", "chris", "josh", "kim", "sam"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where not exists \
+                (select * from weaver.orders where total > 200)"
+            )?,
+            Vec::<String>::new()
+        );
+
+        info!("subqueries that can't be joined are applied to every row");
+        let query = "select name from weaver.people \
+            where age > 40 or people.id in (select person_id from weaver.orders) order by name";
+        assert_eq!(rows(client, query)?, ["alex", "josh", "kim"]);
+        let (explained, _) = client.query(&Query::parse(&format!("explain {query}"))?)?;
+        assert!(explained
+            .to_owned()
+            .iter()
+            .any(|row| row[3].to_string() == "apply"));
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people \
+                where people.id in (select person_id from weaver.orders where total > people.age) \
+                order by name"
+            )?,
+            ["josh", "kim"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where not exists \
+                (select * from weaver.orders where orders.person_id = people.id \
+                and total > people.age) order by name"
+            )?,
+            ["alex", "chris", "sam"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id, id in (select person_id from weaver.orders), \
+                exists (select * from weaver.orders where person_id = people.id and total > 100) \
+                from weaver.people where id < 4 order by id"
+            )?,
+            ["1,true,true", "2,false,false", "3,true,false"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn in_and_exists_subqueries_with_nulls() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.lefts (id int primary key, x int)",
+            &["(1, 1)", "(2, 2)", "(3, null)"],
+        )?;
+        create_table(
+            client,
+            "weaver.rights (id int primary key, y int)",
+            &["(1, 1)", "(2, null)"],
+        )?;
+
+        info!("null keys never match");
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.lefts where x in (select y from weaver.rights) order by id"
+            )?,
+            ["1"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.lefts where exists \
+                (select 1 from weaver.rights where rights.y = lefts.x) order by id"
+            )?,
+            ["1"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.lefts where not exists \
+                (select 1 from weaver.rights where rights.y = lefts.x) order by id"
+            )?,
+            ["2", "3"]
+        );
+
+        info!("not in is unknown when either side is null");
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.lefts where x not in (select y from weaver.rights) \
+                order by id"
+            )?,
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.lefts \
+                where x not in (select y from weaver.rights where y is not null) order by id"
+            )?,
+            ["2"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.lefts \
+                where x not in (select y from weaver.rights where id > 5) order by id"
+            )?,
+            ["1", "2", "3"],
+            "nothing is in an empty subquery"
+        );
+
+        info!("subqueries that can't be joined are just as unknown with nulls");
+        assert_eq!(
+            rows(
+                client,
+                "select id, x + 1 in (select y from weaver.rights), \
+                x in (select y + 1 from weaver.rights) from weaver.lefts order by id"
+            )?,
+            ["1,,", "2,,true", "3,,"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.lefts \
+                where x + 1 not in (select y from weaver.rights where y is not null) order by id"
+            )?,
+            ["1", "2"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn scalar_subqueries() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_people(
+            client,
+            &[
+                "(1, 'josh', 20)",
+                "(2, 'chris', 35)",
+                "(3, 'alex', 28)",
+                "(4, 'kim', 41)",
+                "(5, 'sam', 17)",
+            ],
+        )?;
+        info!("josh has placed two orders, and alex and kim one each");
+        create_table(
+            client,
+            "weaver.orders (id int primary key, person_id int, total int)",
+            &["(1, 1, 50)", "(2, 1, 150)", "(3, 3, 20)", "(4, 4, 300)"],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select name, (select max(total) from weaver.orders \
+                where orders.person_id = people.id) from weaver.people order by name"
+            )?,
+            ["alex,20", "chris,", "josh,150", "kim,300", "sam,"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name, (select count(orders.id) from weaver.orders \
+                where orders.person_id = people.id) as placed from weaver.people \
+                where age > 25 order by name"
+            )?,
+            ["alex,1", "chris,0", "kim,1"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people where age > 18 and \
+                (select count(orders.id) from weaver.orders where orders.person_id = people.id) > 1"
+            )?,
+            ["josh"]
+        );
+
+        info!("uncorrelated subqueries are compared like any other value");
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.orders where total = (select max(total) from weaver.orders)"
+            )?,
+            ["4"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.people \
+                where age < (select min(age) from weaver.people where age > 20) order by name"
+            )?,
+            ["josh", "sam"]
+        );
+
+        info!("a subquery returning no rows is null");
+        assert_eq!(
+            rows(
+                client,
+                "select orders.id, (select name from weaver.people where people.id = 9) \
+                from weaver.orders where orders.id = 1"
+            )?,
+            ["1,"]
+        );
+
+        let (explained, _) = client.query(&Query::parse(
+            "explain select name, (select max(total) from weaver.orders \
+            where orders.person_id = people.id) from weaver.people",
+        )?)?;
+        assert!(explained
+            .to_owned()
+            .iter()
+            .any(|row| row[3].to_string() == "apply"));
+
+        assert!(
+            rows(
+                client,
+                "select name, (select total from weaver.orders \
+                where orders.person_id = people.id) from weaver.people"
+            )
+            .is_err(),
+            "josh has placed more than one order"
+        );
+        assert!(
+            rows(
+                client,
+                "select max(age), (select max(total) from weaver.orders) from weaver.people"
+            )
+            .is_err(),
+            "subqueries are not supported in grouped queries"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
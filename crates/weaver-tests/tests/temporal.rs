@@ -0,0 +1,251 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_tests::{create_table, ddl, init_tracing, rows, run_full_stack_local_socket};
+
+#[test]
+fn temporal_columns() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.events (at timestamp primary key, day date, starts time, took interval, \
+            seen timestamp with time zone)",
+            &[
+                "('2024-03-01 09:30:00', '2024-03-01', '09:30', '1 hour 30 minutes', \
+                '2024-03-01 10:30:00+01')",
+                "('2023-12-31 23:59:59.5', '2023-12-31', '23:59:59', '2 days', \
+                '2024-01-01T00:00:00Z')",
+                "('2024-02-29 12:00:00', '2024-02-29', '12:00', '45 seconds', \
+                '2024-02-29 12:00:00')",
+            ],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select at, day, starts, took, seen from weaver.events"
+            )?,
+            [
+                "2023-12-31 23:59:59.500,2023-12-31,23:59:59,2 days,2024-01-01 00:00:00+00:00",
+                "2024-02-29 12:00:00,2024-02-29,12:00:00,00:00:45,2024-02-29 12:00:00+00:00",
+                "2024-03-01 09:30:00,2024-03-01,09:30:00,01:30:00,2024-03-01 09:30:00+00:00",
+            ],
+            "rows should be in timestamp order"
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select day from weaver.events where at > '2024-01-01' order by day desc"
+            )?,
+            ["2024-03-01", "2024-02-29"],
+            "strings should be compared as timestamps"
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select took from weaver.events where took >= interval '1 hour' order by took"
+            )?,
+            ["01:30:00", "2 days"]
+        );
+        assert!(
+            !ddl(
+                client,
+                "insert into weaver.events (at) values ('the day after tomorrow')"
+            )?,
+            "timestamps should be checked on insert"
+        );
+
+        Ok(())
+    })?;
+
+    info!("temporal keys are kept after a restart");
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert_eq!(
+            rows(
+                client,
+                "select day from weaver.events where at = timestamp '2024-02-29 12:00:00'"
+            )?,
+            ["2024-02-29"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn date_arithmetic() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.events (at timestamp primary key, day date, starts time, took interval, \
+            seen timestamp with time zone)",
+            &[
+                "('2024-03-01 09:30:00', '2024-03-01', '09:30', '1 hour 30 minutes', \
+                '2024-03-01 10:30:00+01')",
+                "('2023-12-31 23:59:59.5', '2023-12-31', '23:59:59', '2 days', \
+                '2024-01-01T00:00:00Z')",
+            ],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select day + 1, day - date '2024-01-01', at + interval '1 mon 1 day', \
+                at - timestamp '2024-02-29 00:00:00', starts - interval '10 hours', took * 2 \
+                from weaver.events where day = '2024-03-01'"
+            )?,
+            ["2024-03-02,60,2024-04-02 09:30:00,1 day 09:30:00,23:30:00,03:00:00"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select seen - at from weaver.events where day = '2023-12-31'"
+            )?,
+            ["00:00:00.5"],
+            "timestamps should be promoted to timestamps with time zones"
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select min(at), max(took), min(day) from weaver.events"
+            )?,
+            ["2023-12-31 23:59:59.500,2 days,2023-12-31"]
+        );
+
+        for query in [
+            "select day + day from weaver.events",
+            "select starts + 1 from weaver.events",
+            "select took * took from weaver.events",
+        ] {
+            assert!(rows(client, query).is_err(), "{query} should fail");
+        }
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn temporal_functions() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.events (at timestamp primary key, day date, took interval, \
+            seen timestamp with time zone)",
+            &[
+                "('2024-03-01 09:30:00', '2024-03-01', '1 hour 30 minutes', \
+                '2024-03-01 10:30:00+01')",
+                "('2023-12-31 23:59:59.5', '2023-12-31', '2 days', '2024-01-01T00:00:00Z')",
+                "('2024-02-29 12:00:00', '2024-02-29', '45 seconds', '2024-02-29 12:00:00')",
+            ],
+        )?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select date_trunc('month', at), extract(year from day), extract(hour from at), \
+                extract(epoch from took) from weaver.events where day = '2024-03-01'"
+            )?,
+            ["2024-03-01 00:00:00,2024,9,5400"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select to_timestamp(86400) from weaver.events where day = '2024-03-01'"
+            )?,
+            ["1970-01-02 00:00:00+00:00"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select count(*) from weaver.events where seen < now()"
+            )?,
+            ["3"]
+        );
+        assert!(
+            rows(
+                client,
+                "select date_trunc('fortnight', at) from weaver.events"
+            )
+            .is_err(),
+            "fortnights are not a unit"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn load_temporal_data() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    let csv = temp_dir.path().join("events.csv");
+    std::fs::write(
+        &csv,
+        "at,day,took\n\
+        2024-01-02T03:04:05,2024-01-02,1 day 02:00:00\n\
+        2024-01-01 00:00:00,2024-01-01,-30 minutes\n",
+    )?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            "create table weaver.loaded (at timestamp primary key, day date, took interval)"
+        )?);
+        assert!(ddl(
+            client,
+            &format!(
+                "load data infile '{}' into table weaver.loaded (at, day, took)",
+                csv.display()
+            )
+        )?);
+        assert_eq!(
+            rows(client, "select at, day, took from weaver.loaded")?,
+            [
+                "2024-01-01 00:00:00,2024-01-01,-00:30:00",
+                "2024-01-02 03:04:05,2024-01-02,1 day 02:00:00",
+            ]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn now_as_default() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            "create table weaver.visits (id int primary key, at timestamp default now(), \
+            day date default now(), seen timestamp with time zone default now())"
+        )?);
+        assert!(ddl(client, "insert into weaver.visits (id) values (1)")?);
+        assert_eq!(
+            rows(
+                client,
+                "select id from weaver.visits where at > '2000-01-01' and day > '2000-01-01' \
+                and seen > '2000-01-01'"
+            )?,
+            ["1"],
+            "now() should be converted to the type of each column"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,246 @@
+use std::io::stdout;
+
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_client::write_rows::write_rows;
+use weaver_core::ast::Query;
+use weaver_core::rows::Rows;
+use weaver_tests::{create_table, ddl, init_tracing, rows, run_full_stack_local_socket};
+
+#[test]
+fn update_rows() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        client.query(&Query::parse(
+            r#"create table weaver.people ( id INT primary key, name varchar(32) not null, age int )"#,
+        )?)?;
+        client.query(&Query::parse(
+            r#"insert into weaver.people (id, name, age) values (1, 'josh', 25), (2, 'chris', 3), (3, 'jane', 40)"#,
+        )?)?;
+
+        info!("update by primary key");
+        let (mut rows, _) = client.query(&Query::parse(
+            r#"update weaver.people set age = age + 1, name = 'joshua' where id = 1"#,
+        )?)?;
+        let result = rows.next().expect("should have a result row");
+        assert_eq!(result[0].to_string(), "1", "one row should be updated");
+        drop(rows);
+
+        info!("update by condition");
+        let (mut rows, _) = client.query(&Query::parse(
+            r#"update weaver.people set age = 0 where age > 30"#,
+        )?)?;
+        let result = rows.next().expect("should have a result row");
+        assert_eq!(result[0].to_string(), "1", "one row should be updated");
+        drop(rows);
+
+        let (rows, elapsed) = client.query(&Query::parse(
+            "select id, name, age from weaver.people order by id",
+        )?)?;
+        let rows = rows.to_owned();
+        let values = rows
+            .iter()
+            .map(|row| (row[1].to_string(), row[2].to_string()))
+            .collect::<Vec<_>>();
+        write_rows(stdout(), rows, elapsed).expect("could not write rows");
+        assert_eq!(
+            values,
+            [
+                ("joshua".to_string(), "26".to_string()),
+                ("chris".to_string(), "3".to_string()),
+                ("jane".to_string(), "0".to_string()),
+            ]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn update_primary_key() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        client.query(&Query::parse(
+            r#"create table weaver.people ( id INT primary key, name varchar(32) not null, age int )"#,
+        )?)?;
+        client.query(&Query::parse(
+            r#"insert into weaver.people (id, name, age) values (1, 'josh', 25), (2, 'chris', 3)"#,
+        )?)?;
+
+        info!("move a row to a new primary key");
+        let (mut rows, _) = client.query(&Query::parse(
+            r#"update weaver.people set id = 10, age = age + 1 where id = 1"#,
+        )?)?;
+        let result = rows.next().expect("should have a result row");
+        assert_eq!(result[0].to_string(), "1", "one row should be updated");
+        drop(rows);
+
+        info!("moving a row onto an existing primary key fails");
+        let (mut rows, _) = client.query(&Query::parse(
+            r#"update weaver.people set id = 10 where id = 2"#,
+        )?)?;
+        let result = rows.next().expect("should have a result row");
+        assert_ne!(
+            result[1].to_string(),
+            "null",
+            "primary keys must stay unique"
+        );
+        drop(rows);
+
+        let (rows, _) = client.query(&Query::parse(
+            "select id, name, age from weaver.people order by id",
+        )?)?;
+        let values = rows
+            .to_owned()
+            .iter()
+            .map(|row| (row[0].to_string(), row[1].to_string(), row[2].to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            [
+                ("2".to_string(), "chris".to_string(), "3".to_string()),
+                ("10".to_string(), "josh".to_string(), "26".to_string()),
+            ]
+        );
+
+        let (rows, _) = client.query(&Query::parse(
+            "select name from weaver.people where id = 1",
+        )?)?;
+        assert_eq!(
+            rows.to_owned().iter().count(),
+            0,
+            "old primary key should be gone"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn update_with_negated_and_disjunct_keys() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        client.query(&Query::parse(
+            r#"create table weaver.people ( id INT primary key, name varchar(32) not null, age int )"#,
+        )?)?;
+        client.query(&Query::parse(
+            r#"insert into weaver.people (id, name, age) values (1, 'josh', 25), (2, 'chris', 3), (3, 'jane', 40), (4, 'mary', 18)"#,
+        )?)?;
+
+        let (mut rows, _) = client.query(&Query::parse(
+            r#"update weaver.people set age = 0 where id != 2"#,
+        )?)?;
+        let result = rows.next().expect("should have a result row");
+        assert_eq!(result[0].to_string(), "3");
+        drop(rows);
+
+        let (mut rows, _) = client.query(&Query::parse(
+            r#"update weaver.people set age = 1 where id = 1 or id > 3"#,
+        )?)?;
+        let result = rows.next().expect("should have a result row");
+        assert_eq!(result[0].to_string(), "2");
+        drop(rows);
+
+        let (rows, _) = client.query(&Query::parse(
+            "select id, age from weaver.people order by id",
+        )?)?;
+        let values = rows
+            .to_owned()
+            .iter()
+            .map(|row| format!("{},{}", row[0], row[1]))
+            .collect::<Vec<_>>();
+        assert_eq!(values, ["1,1", "2,3", "3,0", "4,1"]);
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn update_by_float_against_int_key() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            "create table weaver.t ( id INT primary key, v int )"
+        )?);
+        assert!(ddl(
+            client,
+            "insert into weaver.t (id, v) values (1, 1), (2, 2), (3, 3)"
+        )?);
+
+        for condition in ["id = 3.0", "id > 1.5"] {
+            let selected = rows(
+                client,
+                &format!("select id from weaver.t where {condition} order by id"),
+            )?;
+            let (mut updated, _) = client.query(&Query::parse(&format!(
+                "update weaver.t set v = 99 where {condition}"
+            ))?)?;
+            let result = updated.next().expect("should have a result row");
+            assert_eq!(
+                result[0].to_string(),
+                selected.len().to_string(),
+                "update should touch every row selected by {condition}"
+            );
+            drop(updated);
+            assert_eq!(
+                rows(client, &format!("select v from weaver.t where {condition}"))?,
+                vec!["99"; selected.len()]
+            );
+        }
+        assert_eq!(
+            rows(client, "select id, v from weaver.t order by id")?,
+            ["1,1", "2,99", "3,99"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn failed_update_changes_nothing() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.t (id int primary key, v int, unique key (v))",
+            &["(1, 10)", "(2, 20)", "(3, 30)"],
+        )?;
+
+        for update in [
+            "update weaver.t set v = case when id = 1 then 5 else 30 end",
+            "update weaver.t set id = case when id = 1 then 9 else id + 1 end",
+            "update weaver.t set v = case when id < 3 then v + 1 else v / 0 end",
+        ] {
+            assert!(!ddl(client, update)?, "{update} should fail");
+            assert_eq!(
+                rows(client, "select id, v from weaver.t")?,
+                ["1,10", "2,20", "3,30"],
+                "no rows changed by {update} should be kept"
+            );
+        }
+        assert!(ddl(client, "update weaver.t set id = id + 3, v = v + 1")?);
+        assert_eq!(
+            rows(client, "select id, v from weaver.t")?,
+            ["4,11", "5,21", "6,31"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_tests::{ddl, init_tracing, rows, run_full_stack_local_socket};
+
+#[test]
+fn uuid_primary_keys() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            "create table weaver.sessions (id uuid default gen_random_uuid() primary key, \
+            name varchar(32))"
+        )?);
+        for name in ["ada", "grace", "linus"] {
+            assert!(ddl(
+                client,
+                &format!("insert into weaver.sessions (name) values ('{name}')")
+            )?);
+        }
+        assert_eq!(
+            rows(
+                client,
+                "select count(distinct id), count(*) from weaver.sessions"
+            )?,
+            ["3,3"],
+            "every row should get its own uuid"
+        );
+        for id in rows(client, "select id from weaver.sessions")? {
+            // the version is the first digit of the third group
+            assert_eq!(id.len(), 36);
+            assert_eq!(
+                id.chars().nth(14),
+                Some('4'),
+                "{id} should be a random uuid"
+            );
+        }
+
+        info!("uuids are parsed from and formatted as text");
+        assert!(ddl(
+            client,
+            "insert into weaver.sessions (id, name) values \
+            ('0F8FAD5B-D9CB-469F-A165-70867728950E', 'ken'), \
+            ('{00000000-0000-0000-0000-000000000001}', 'dennis')"
+        )?);
+        assert!(
+            !ddl(
+                client,
+                "insert into weaver.sessions (id, name) values ('not-a-uuid', 'bob')"
+            )?,
+            "invalid uuids should be rejected"
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id, name from weaver.sessions \
+                where id = '0f8fad5b-d9cb-469f-a165-70867728950e'"
+            )?,
+            ["0f8fad5b-d9cb-469f-a165-70867728950e,ken"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name from weaver.sessions \
+                where id = uuid '00000000000000000000000000000001'"
+            )?,
+            ["dennis"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select id, name from weaver.sessions where name = 'ken' or name = 'dennis' \
+                order by id"
+            )?,
+            [
+                "00000000-0000-0000-0000-000000000001,dennis",
+                "0f8fad5b-d9cb-469f-a165-70867728950e,ken"
+            ],
+            "uuids should be ordered by their bytes"
+        );
+
+        info!("uuids can be cast to and from binaries");
+        assert_eq!(
+            rows(
+                client,
+                "select cast(cast(id as varbinary(16)) as uuid) = id, cast(id as varchar(36)) \
+                from weaver.sessions where name = 'ken'"
+            )?,
+            ["true,0f8fad5b-d9cb-469f-a165-70867728950e"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn generated_uuids() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        assert!(ddl(
+            client,
+            "create table weaver.tokens (id int primary key, token uuid not null default uuid())"
+        )?);
+        assert!(ddl(
+            client,
+            "insert into weaver.tokens (id) values (1), (2)"
+        )?);
+        assert!(ddl(
+            client,
+            "update weaver.tokens set token = uuid() where id = 2"
+        )?);
+        let tokens = rows(client, "select token, id from weaver.tokens order by id")?;
+        assert_eq!(tokens.len(), 2);
+        assert_ne!(tokens[0][..36], tokens[1][..36]);
+        assert_eq!(
+            rows(
+                client,
+                "select uuid() = uuid(), gen_random_uuid() is null from weaver.tokens where id = 1"
+            )?,
+            ["false,false"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
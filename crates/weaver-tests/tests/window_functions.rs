@@ -0,0 +1,309 @@
+use tempfile::TempDir;
+use tracing::info;
+
+use weaver_core::ast::Query;
+use weaver_core::rows::Rows;
+use weaver_tests::{
+    create_employees, create_table, init_tracing, rows, run_full_stack_local_socket,
+};
+
+#[test]
+fn ranking() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_employees(client)?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select name, row_number() over (partition by dept order by salary desc, name) \
+                from weaver.employees order by name"
+            )?,
+            ["alex,1", "chris,2", "josh,3", "kim,2", "sam,1"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name, rank() over (order by salary desc), \
+                dense_rank() over (order by salary desc) from weaver.employees order by name"
+            )?,
+            ["alex,1,1", "chris,2,2", "josh,2,2", "kim,5,4", "sam,4,3"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name, rank() over (partition by dept order by salary desc) as ranked \
+                from weaver.employees order by name"
+            )?,
+            ["alex,1", "chris,2", "josh,2", "kim,2", "sam,1"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name, row_number() over (order by id) * 10 from weaver.employees \
+                where dept = 'ops' order by name"
+            )?,
+            ["kim,10", "sam,20"]
+        );
+
+        info!("columns are named after the window functions they use");
+        let (rows, _) = client.query(&Query::parse(
+            "select id, cast(row_number() over (order by id) as float), \
+            -rank() over (order by salary) from weaver.employees",
+        )?)?;
+        let columns = rows
+            .schema()
+            .columns()
+            .iter()
+            .map(|column| column.name().to_string())
+            .collect::<Vec<_>>();
+        drop(rows);
+        assert!(
+            columns.iter().all(|column| !column.contains("<window>")),
+            "{columns:?} should not name the window function columns"
+        );
+        assert!(
+            columns[1].starts_with("cast(row_number()"),
+            "{columns:?} should keep the cast as written"
+        );
+
+        let (explained, _) = client.query(&Query::parse(
+            "explain select name, row_number() over (order by id) from weaver.employees",
+        )?)?;
+        assert!(explained
+            .to_owned()
+            .iter()
+            .any(|row| row[3].to_string() == "window"));
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn offsets() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_employees(client)?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select name, lag(name) over (order by id), lead(salary, 2, 0) over (order by id) \
+                from weaver.employees order by name"
+            )?,
+            [
+                "alex,,80",
+                "chris,alex,60",
+                "josh,chris,70",
+                "kim,josh,0",
+                "sam,kim,0"
+            ]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name, lag(name) over (partition by dept order by id) \
+                from weaver.employees order by name"
+            )?,
+            ["alex,", "chris,alex", "josh,chris", "kim,", "sam,kim"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name, first_value(name) over (partition by dept order by salary, name) \
+                from weaver.employees order by name"
+            )?,
+            [
+                "alex,chris",
+                "chris,chris",
+                "josh,chris",
+                "kim,kim",
+                "sam,kim"
+            ]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn aggregates_over_frames() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_employees(client)?;
+
+        info!("without an order, the frame is the whole partition");
+        assert_eq!(
+            rows(
+                client,
+                "select name, count(id) over (partition by dept), sum(salary) over () \
+                from weaver.employees order by name"
+            )?,
+            [
+                "alex,3,390",
+                "chris,3,390",
+                "josh,3,390",
+                "kim,2,390",
+                "sam,2,390"
+            ]
+        );
+        info!("with an order, the frame ends with the last peer of the row");
+        assert_eq!(
+            rows(
+                client,
+                "select name, sum(salary) over (order by salary) from weaver.employees \
+                order by name"
+            )?,
+            ["alex,390", "chris,290", "josh,290", "kim,60", "sam,130"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name, sum(salary) over (order by id rows between 1 preceding and \
+                current row) from weaver.employees order by name"
+            )?,
+            ["alex,100", "chris,180", "josh,160", "kim,140", "sam,130"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name, max(salary) over (order by id rows between current row and \
+                unbounded following) from weaver.employees order by name"
+            )?,
+            ["alex,100", "chris,80", "josh,80", "kim,70", "sam,70"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name, sum(salary) over (order by salary range between 10 preceding and \
+                10 following) from weaver.employees order by name"
+            )?,
+            ["alex,100", "chris,230", "josh,230", "kim,130", "sam,290"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name, sum(salary) over (order by salary desc range 20 preceding) \
+                from weaver.employees order by name"
+            )?,
+            ["alex,100", "chris,260", "josh,260", "kim,290", "sam,230"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn ordered_aggregate_arguments() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_employees(client)?;
+
+        assert_eq!(
+            rows(
+                client,
+                "select max(salary order by name) from weaver.employees"
+            )?,
+            ["100"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select dept, count(distinct salary order by name), sum(salary order by id) \
+                from weaver.employees group by dept order by dept"
+            )?,
+            ["eng,2,260", "ops,2,130"]
+        );
+        assert_eq!(
+            rows(
+                client,
+                "select name, sum(salary order by id) over (partition by dept) \
+                from weaver.employees order by name"
+            )?,
+            ["alex,260", "chris,260", "josh,260", "kim,130", "sam,130"]
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn unsupported_window_functions() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_employees(client)?;
+
+        assert!(
+            rows(
+                client,
+                "select name from weaver.employees where row_number() over (order by id) = 1"
+            )
+            .is_err(),
+            "window functions can only be selected"
+        );
+        assert!(
+            rows(
+                client,
+                "select dept, rank() over (order by dept) from weaver.employees group by dept"
+            )
+            .is_err(),
+            "window functions are not supported in grouped queries"
+        );
+        assert!(
+            rows(client, "select rank(salary) over () from weaver.employees").is_err(),
+            "ranking functions take no arguments"
+        );
+        assert!(
+            rows(
+                client,
+                "select sum(salary) over (order by name range between 1 preceding and \
+                current row) from weaver.employees"
+            )
+            .is_err(),
+            "a range with an offset must be ordered by a number"
+        );
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn integer_sums_overflow() -> eyre::Result<()> {
+    let _ = init_tracing(None);
+    let temp_dir = TempDir::new()?;
+    run_full_stack_local_socket(temp_dir.path(), |_server, client| {
+        create_table(
+            client,
+            "weaver.big (id int primary key, v int)",
+            &["(1, 9223372036854775807)", "(2, 1)"],
+        )?;
+
+        for query in [
+            "select sum(v) from weaver.big",
+            "select id, sum(v) over (order by id) from weaver.big",
+        ] {
+            let err = rows(client, query).expect_err("sum should overflow");
+            assert_eq!(err.to_string(), "Integer overflow", "{query}");
+        }
+        assert_eq!(rows(client, "select avg(v) > 0 from weaver.big")?, ["true"]);
+
+        Ok(())
+    })?;
+
+    Ok(())
+}